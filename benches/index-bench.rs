@@ -111,6 +111,13 @@ pub fn hdfs_index_benchmark(c: &mut Criterion) {
         schema_builder.add_text_field("severity", FAST);
         schema_builder.build()
     };
+    let schema_only_stored = {
+        let mut schema_builder = tantivy::schema::SchemaBuilder::new();
+        schema_builder.add_u64_field("timestamp", STORED);
+        schema_builder.add_text_field("body", STORED);
+        schema_builder.add_text_field("severity", STORED);
+        schema_builder.build()
+    };
     let _schema_with_store = {
         let mut schema_builder = tantivy::schema::SchemaBuilder::new();
         schema_builder.add_u64_field("timestamp", INDEXED | STORED);
@@ -132,6 +139,7 @@ pub fn hdfs_index_benchmark(c: &mut Criterion) {
         ("only-indexed-".to_string(), schema, false),
         //("stored-".to_string(), _schema_with_store, false),
         ("only-fast-".to_string(), schema_only_fast, false),
+        ("only-stored-".to_string(), schema_only_stored, false),
         ("dynamic-".to_string(), dynamic_schema, true),
     ];
 
@@ -6,7 +6,7 @@ use std::io::Write;
 use std::ops::{Deref, Range, RangeInclusive};
 use std::sync::Arc;
 
-use common::BinarySerializable;
+use common::{BinarySerializable, ReadOnlyBitSet};
 pub use dictionary_encoded::{BytesColumn, StrColumn};
 pub use serialize::{
     open_column_bytes, open_column_str, open_column_u128, open_column_u64,
@@ -18,6 +18,9 @@ use crate::column_values::monotonic_mapping::StrictlyMonotonicMappingToInternal;
 use crate::column_values::{monotonic_map_column, ColumnValues};
 use crate::{Cardinality, DocId, EmptyColumnValues, MonotonicallyMappableToU64, RowId};
 
+/// Number of docs in the blocks of [`Column::for_each_block`].
+const SCAN_BLOCK_LEN: usize = 1_024;
+
 #[derive(Clone)]
 pub struct Column<T = u64> {
     pub index: ColumnIndex,
@@ -147,6 +150,66 @@ impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> Column<T> {
     }
 }
 
+impl<T: PartialOrd + Copy + Debug + Send + Sync + Default + 'static> Column<T> {
+    /// Scans the values of the column, block by block.
+    ///
+    /// `callback` is called with the docs of the block and their values, on the same index.
+    /// A doc with several values appears once per value, and the docs without values do not
+    /// appear at all. If `filter` is given, only its docs are scanned, e.g. the alive docs of a
+    /// segment, or the docs matching a query.
+    ///
+    /// This is much faster than fetching the values doc by doc, and is meant to compute custom
+    /// aggregates over large parts of a segment.
+    pub fn for_each_block(
+        &self,
+        filter: Option<&ReadOnlyBitSet>,
+        mut callback: impl FnMut(&[DocId], &[T]),
+    ) {
+        let num_docs = self.num_docs();
+        let mut docs: Vec<DocId> = Vec::with_capacity(SCAN_BLOCK_LEN);
+        let mut vals: Vec<T> = Vec::with_capacity(SCAN_BLOCK_LEN);
+        if filter.is_none() && matches!(self.index, ColumnIndex::Full) {
+            // Docs and rows are the same, the values can be read as ranges.
+            for block_start in (0..num_docs).step_by(SCAN_BLOCK_LEN) {
+                let block_end = (block_start + SCAN_BLOCK_LEN as u32).min(num_docs);
+                docs.clear();
+                docs.extend(block_start..block_end);
+                vals.resize(docs.len(), T::default());
+                self.values.get_range(block_start as u64, &mut vals);
+                callback(&docs, &vals);
+            }
+            return;
+        }
+        let mut block_docs: Vec<DocId> = Vec::with_capacity(SCAN_BLOCK_LEN);
+        let mut row_ids: Vec<RowId> = Vec::with_capacity(SCAN_BLOCK_LEN);
+        let mut scan_block = |block_docs: &[DocId]| {
+            docs.clear();
+            row_ids.clear();
+            self.row_ids_for_docs(block_docs, &mut docs, &mut row_ids);
+            if row_ids.is_empty() {
+                return;
+            }
+            vals.resize(row_ids.len(), T::default());
+            self.values.get_vals(&row_ids, &mut vals);
+            callback(&docs, &vals);
+        };
+        let doc_it: Box<dyn Iterator<Item = DocId>> = match filter {
+            Some(filter) => Box::new(filter.iter().take_while(|doc| *doc < num_docs)),
+            None => Box::new(0..num_docs),
+        };
+        for doc in doc_it {
+            block_docs.push(doc);
+            if block_docs.len() == SCAN_BLOCK_LEN {
+                scan_block(&block_docs);
+                block_docs.clear();
+            }
+        }
+        if !block_docs.is_empty() {
+            scan_block(&block_docs);
+        }
+    }
+}
+
 impl<T> Deref for Column<T> {
     type Target = ColumnIndex;
 
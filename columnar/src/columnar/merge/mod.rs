@@ -80,6 +80,28 @@ pub fn merge_columnar(
     required_columns: &[(String, ColumnType)],
     merge_row_order: MergeRowOrder,
     output: &mut impl io::Write,
+) -> io::Result<()> {
+    merge_columnar_with_overrides(
+        columnar_readers,
+        required_columns,
+        merge_row_order,
+        &|_columnar_id, _column_name, column| column,
+        output,
+    )
+}
+
+/// Merge several columnar table together, like [`merge_columnar`], after
+/// transforming their columns.
+///
+/// `override_column` is called with the index of the columnar in `columnar_readers`, the
+/// column name and the column as it was read. The column it returns is merged instead. It makes
+/// it possible to override some of the values of the input columnars.
+pub fn merge_columnar_with_overrides(
+    columnar_readers: &[&ColumnarReader],
+    required_columns: &[(String, ColumnType)],
+    merge_row_order: MergeRowOrder,
+    override_column: &dyn Fn(usize, &str, DynamicColumn) -> DynamicColumn,
+    output: &mut impl io::Write,
 ) -> io::Result<()> {
     let mut serializer = ColumnarSerializer::new(output);
     let num_rows_per_columnar = columnar_readers
@@ -91,7 +113,8 @@ pub fn merge_columnar(
         group_columns_for_merge(columnar_readers, required_columns, &merge_row_order)?;
     for res in columns_to_merge {
         let ((column_name, _column_type_category), grouped_columns) = res;
-        let grouped_columns = grouped_columns.open(&merge_row_order)?;
+        let grouped_columns =
+            grouped_columns.open(&column_name, &merge_row_order, override_column)?;
         if grouped_columns.is_empty() {
             continue;
         }
@@ -273,11 +296,16 @@ impl GroupedColumnsHandle {
             columns: vec![None; num_columnars],
         }
     }
-    fn open(self, merge_row_order: &MergeRowOrder) -> io::Result<GroupedColumns> {
+    fn open(
+        self,
+        column_name: &str,
+        merge_row_order: &MergeRowOrder,
+        override_column: &dyn Fn(usize, &str, DynamicColumn) -> DynamicColumn,
+    ) -> io::Result<GroupedColumns> {
         let mut columns: Vec<Option<DynamicColumn>> = Vec::new();
         for (columnar_id, column) in self.columns.iter().enumerate() {
             if let Some(column) = column {
-                let column = column.open()?;
+                let column = override_column(columnar_id, column_name, column.open()?);
                 // We skip columns that end up with 0 documents.
                 // That way, we make sure they don't end up influencing the merge type or
                 // creating empty columns.
@@ -47,8 +47,8 @@ pub use column_values::{
     ColumnValues, EmptyColumnValues, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
 };
 pub use columnar::{
-    merge_columnar, ColumnType, ColumnarReader, ColumnarWriter, HasAssociatedColumnType,
-    MergeRowOrder, ShuffleMergeOrder, StackMergeOrder,
+    merge_columnar, merge_columnar_with_overrides, ColumnType, ColumnarReader, ColumnarWriter,
+    HasAssociatedColumnType, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder,
 };
 use sstable::VoidSSTable;
 pub use value::{NumericalType, NumericalValue};
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::net::Ipv6Addr;
 
-use common::DateTime;
+use common::{BitSet, DateTime, ReadOnlyBitSet};
 use proptest::prelude::*;
 use proptest::sample::subsequence;
 
@@ -91,6 +91,56 @@ fn test_dataframe_writer_u64_multivalued() {
     assert_eq!(divisor_col.num_docs(), 7);
 }
 
+fn scan_column(column: &Column<i64>, filter: Option<&ReadOnlyBitSet>) -> Vec<(u32, i64)> {
+    let mut doc_vals = Vec::new();
+    column.for_each_block(filter, |docs, vals| {
+        assert_eq!(docs.len(), vals.len());
+        doc_vals.extend(docs.iter().copied().zip(vals.iter().copied()));
+    });
+    doc_vals
+}
+
+#[test]
+fn test_column_for_each_block() {
+    let num_docs = 3_000u32;
+    let mut dataframe_writer = ColumnarWriter::default();
+    for doc in 0..num_docs {
+        dataframe_writer.record_numerical(doc, "full", doc as i64);
+        if doc % 3 == 0 {
+            dataframe_writer.record_numerical(doc, "optional", doc as i64);
+        }
+        if doc % 5 == 0 {
+            dataframe_writer.record_numerical(doc, "multi", doc as i64);
+            dataframe_writer.record_numerical(doc, "multi", -(doc as i64));
+        }
+    }
+    let mut buffer: Vec<u8> = Vec::new();
+    dataframe_writer
+        .serialize(num_docs, None, &mut buffer)
+        .unwrap();
+    let columnar = ColumnarReader::open(buffer).unwrap();
+    let mut filter = BitSet::with_max_value(num_docs);
+    for doc in (0..num_docs).step_by(7) {
+        filter.insert(doc);
+    }
+    let filter = ReadOnlyBitSet::from(&filter);
+    for column_name in ["full", "optional", "multi"] {
+        let cols: Vec<DynamicColumnHandle> = columnar.read_columns(column_name).unwrap();
+        let DynamicColumn::I64(column) = cols[0].open().unwrap() else {
+            panic!();
+        };
+        let expected: Vec<(u32, i64)> = (0..num_docs)
+            .flat_map(|doc| column.values_for_doc(doc).map(move |val| (doc, val)))
+            .collect();
+        assert_eq!(scan_column(&column, None), expected);
+        let expected_filtered: Vec<(u32, i64)> = expected
+            .into_iter()
+            .filter(|(doc, _)| doc % 7 == 0)
+            .collect();
+        assert_eq!(scan_column(&column, Some(&filter)), expected_filtered);
+    }
+}
+
 #[test]
 fn test_dataframe_writer_ip_addr() {
     let mut dataframe_writer = ColumnarWriter::default();
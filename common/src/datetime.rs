@@ -9,13 +9,25 @@ use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
 
 use crate::BinarySerializable;
 
-/// Precision with which datetimes are truncated when stored in fast fields. This setting is only
-/// relevant for fast fields. In the docstore, datetimes are always saved with nanosecond precision.
+const NANOS_PER_MINUTE: i64 = 60 * 1_000_000_000;
+const NANOS_PER_HOUR: i64 = 60 * NANOS_PER_MINUTE;
+const NANOS_PER_DAY: i64 = 24 * NANOS_PER_HOUR;
+
+/// Precision with which datetimes are truncated when stored in fast fields or indexed. In the
+/// docstore, datetimes are always saved with nanosecond precision.
+///
+/// Precisions are ordered from the coarsest to the finest.
 #[derive(
     Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default,
 )]
 #[serde(rename_all = "lowercase")]
 pub enum DateTimePrecision {
+    /// Day precision.
+    Days,
+    /// Hour precision.
+    Hours,
+    /// Minute precision.
+    Minutes,
     /// Second precision.
     #[default]
     Seconds,
@@ -150,6 +162,17 @@ impl DateTime {
     /// Truncates the microseconds value to the corresponding precision.
     pub fn truncate(self, precision: DateTimePrecision) -> Self {
         let truncated_timestamp_micros = match precision {
+            // Coarse precisions round towards the past, so that dates before the epoch land in
+            // their own minute, hour or day.
+            DateTimePrecision::Days => {
+                self.timestamp_nanos.div_euclid(NANOS_PER_DAY) * NANOS_PER_DAY
+            }
+            DateTimePrecision::Hours => {
+                self.timestamp_nanos.div_euclid(NANOS_PER_HOUR) * NANOS_PER_HOUR
+            }
+            DateTimePrecision::Minutes => {
+                self.timestamp_nanos.div_euclid(NANOS_PER_MINUTE) * NANOS_PER_MINUTE
+            }
             DateTimePrecision::Seconds => (self.timestamp_nanos / 1_000_000_000) * 1_000_000_000,
             DateTimePrecision::Milliseconds => (self.timestamp_nanos / 1_000_000) * 1_000_000,
             DateTimePrecision::Microseconds => (self.timestamp_nanos / 1_000) * 1_000,
@@ -0,0 +1,230 @@
+// # HTTP search server
+//
+// This example serves an existing index over HTTP, with nothing but the
+// standard library. It is a starting point for a real search service:
+// - `GET /search?q=<query>&limit=<n>` runs the query through the `QueryParser` and returns the best
+//   hits as found by the `TopDocs` collector.
+// - `GET /doc?segment_ord=<ord>&doc_id=<id>` returns the stored fields of a hit.
+//
+// An index can be created and filled with the `tantivy` command line tool
+// (see the `cli` feature), and served with:
+//
+// ```
+// cargo run --example http_server -- <index_dir> [<address>]
+// ```
+//
+// The server keeps a single `IndexReader` around, which picks up new commits
+// on its own. Note that doc addresses are only valid for the searcher that
+// produced them, so `/doc` may return another document after a commit.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+use serde_json::json;
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::QueryParser;
+use tantivy::schema::{Document, FieldType, TantivyDocument};
+use tantivy::{DocAddress, Index, IndexReader, ReloadPolicy};
+
+const DEFAULT_LIMIT: usize = 10;
+
+fn main() -> tantivy::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let index_dir = args
+        .next()
+        .expect("Usage: http_server <index_dir> [<address>]");
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:3000".to_string());
+
+    let index = Index::open_in_dir(index_dir)?;
+    // Unqualified terms of a query target all of the text fields.
+    let schema = index.schema();
+    let default_fields = schema
+        .fields()
+        .filter(|(_, field_entry)| {
+            field_entry.is_indexed() && matches!(field_entry.field_type(), FieldType::Str(_))
+        })
+        .map(|(field, _)| field)
+        .collect();
+    let query_parser = QueryParser::for_index(&index, default_fields);
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::OnCommitWithDelay)
+        .try_into()?;
+
+    let listener = TcpListener::bind(&address)?;
+    println!("Listening on http://{address}");
+    let query_parser = Arc::new(query_parser);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Failed to accept connection: {err}");
+                continue;
+            }
+        };
+        let (index, reader, query_parser) = (index.clone(), reader.clone(), query_parser.clone());
+        thread::spawn(move || {
+            if let Err(err) = handle_connection(stream, &index, &reader, &query_parser) {
+                eprintln!("Failed to handle request: {err}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Reads a request, and writes back a JSON response.
+///
+/// Only the request line matters to us: the headers are skipped, and there is no body.
+fn handle_connection(
+    stream: TcpStream,
+    index: &Index,
+    reader: &IndexReader,
+    query_parser: &QueryParser,
+) -> io::Result<()> {
+    let mut stream_reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    stream_reader.read_line(&mut request_line)?;
+    let mut header_line = String::new();
+    while stream_reader.read_line(&mut header_line)? > 2 {
+        header_line.clear();
+    }
+
+    let (status, body) = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", target, _version] => {
+            let (path, query_string) = target.split_once('?').unwrap_or((target, ""));
+            let params = parse_query_string(query_string);
+            match path {
+                "/search" => search(index, reader, query_parser, &params),
+                "/doc" => doc(index, reader, &params),
+                _ => (404, json!({ "error": "not found" })),
+            }
+        }
+        _ => (400, json!({ "error": "only GET requests are supported" })),
+    };
+
+    let body = body.to_string();
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    };
+    write!(
+        &stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: \
+         {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+fn search(
+    index: &Index,
+    reader: &IndexReader,
+    query_parser: &QueryParser,
+    params: &HashMap<String, String>,
+) -> (u16, serde_json::Value) {
+    let Some(query) = params.get("q") else {
+        return (400, json!({ "error": "missing parameter `q`" }));
+    };
+    let limit = match params.get("limit").map(|limit| limit.parse::<usize>()) {
+        None => DEFAULT_LIMIT,
+        Some(Ok(limit)) => limit,
+        Some(Err(_)) => return (400, json!({ "error": "invalid parameter `limit`" })),
+    };
+    let query = match query_parser.parse_query(query) {
+        Ok(query) => query,
+        Err(err) => return (400, json!({ "error": err.to_string() })),
+    };
+
+    let searcher = reader.searcher();
+    let schema = index.schema();
+    let result = searcher
+        .search(&query, &(TopDocs::with_limit(limit), Count))
+        .and_then(|(top_docs, count)| {
+            let hits = top_docs
+                .into_iter()
+                .map(|(score, doc_address)| {
+                    let doc: TantivyDocument = searcher.doc(doc_address)?;
+                    Ok(json!({
+                        "score": score,
+                        "segment_ord": doc_address.segment_ord,
+                        "doc_id": doc_address.doc_id,
+                        "doc": doc.to_named_doc(&schema),
+                    }))
+                })
+                .collect::<tantivy::Result<Vec<_>>>()?;
+            Ok(json!({ "count": count, "hits": hits }))
+        });
+    match result {
+        Ok(body) => (200, body),
+        Err(err) => (500, json!({ "error": err.to_string() })),
+    }
+}
+
+fn doc(
+    index: &Index,
+    reader: &IndexReader,
+    params: &HashMap<String, String>,
+) -> (u16, serde_json::Value) {
+    let parse_param = |name: &str| params.get(name).and_then(|val| val.parse::<u32>().ok());
+    let (Some(segment_ord), Some(doc_id)) = (parse_param("segment_ord"), parse_param("doc_id"))
+    else {
+        return (
+            400,
+            json!({ "error": "expected numeric parameters `segment_ord` and `doc_id`" }),
+        );
+    };
+
+    let searcher = reader.searcher();
+    let is_valid_address = searcher
+        .segment_readers()
+        .get(segment_ord as usize)
+        .map_or(false, |segment_reader| doc_id < segment_reader.max_doc());
+    if !is_valid_address {
+        return (404, json!({ "error": "no such document" }));
+    }
+    match searcher.doc::<TantivyDocument>(DocAddress::new(segment_ord, doc_id)) {
+        Ok(doc) => (200, json!(doc.to_named_doc(&index.schema()))),
+        Err(err) => (500, json!({ "error": err.to_string() })),
+    }
+}
+
+/// Parses the `key=value` pairs of a query string, decoding `+` and `%XX` escapes.
+fn parse_query_string(query_string: &str) -> HashMap<String, String> {
+    query_string
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(encoded: &str) -> String {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut encoded_bytes = encoded.bytes();
+    while let Some(byte) = encoded_bytes.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = encoded_bytes.clone().take(2).collect();
+                let decoded = std::str::from_utf8(&hex)
+                    .ok()
+                    .filter(|hex| hex.len() == 2)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(decoded) = decoded {
+                    bytes.push(decoded);
+                    encoded_bytes.nth(1);
+                } else {
+                    bytes.push(b'%');
+                }
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -31,7 +31,8 @@ use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 
 use super::bucket::{
-    DateHistogramAggregationReq, HistogramAggregation, RangeAggregation, TermsAggregation,
+    DateHistogramAggregationReq, GeohashGridAggregation, HistogramAggregation, RangeAggregation,
+    TermsAggregation,
 };
 use super::metric::{
     AverageAggregation, CountAggregation, MaxAggregation, MinAggregation,
@@ -123,6 +124,9 @@ pub enum AggregationVariants {
     /// Put data into buckets of terms.
     #[serde(rename = "terms")]
     Terms(TermsAggregation),
+    /// Put geo points into buckets of geohash cells.
+    #[serde(rename = "geohash_grid")]
+    GeohashGrid(GeohashGridAggregation),
 
     // Metric aggregation types
     /// Computes the average of the extracted values.
@@ -157,6 +161,7 @@ impl AggregationVariants {
             AggregationVariants::Range(range) => range.field.as_str(),
             AggregationVariants::Histogram(histogram) => histogram.field.as_str(),
             AggregationVariants::DateHistogram(histogram) => histogram.field.as_str(),
+            AggregationVariants::GeohashGrid(geohash_grid) => geohash_grid.field.as_str(),
             AggregationVariants::Average(avg) => avg.field_name(),
             AggregationVariants::Count(count) => count.field_name(),
             AggregationVariants::Max(max) => max.field_name(),
@@ -189,6 +194,13 @@ impl AggregationVariants {
         }
     }
 
+    pub(crate) fn as_geohash_grid(&self) -> Option<&GeohashGridAggregation> {
+        match &self {
+            AggregationVariants::GeohashGrid(geohash_grid) => Some(geohash_grid),
+            _ => None,
+        }
+    }
+
     pub(crate) fn as_percentile(&self) -> Option<&PercentilesAggregationReq> {
         match &self {
             AggregationVariants::Percentiles(percentile_req) => Some(percentile_req),
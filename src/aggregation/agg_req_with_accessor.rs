@@ -5,7 +5,8 @@ use columnar::{Column, ColumnBlockAccessor, ColumnType, StrColumn};
 use super::agg_limits::ResourceLimitGuard;
 use super::agg_req::{Aggregation, AggregationVariants, Aggregations};
 use super::bucket::{
-    DateHistogramAggregationReq, HistogramAggregation, RangeAggregation, TermsAggregation,
+    DateHistogramAggregationReq, GeohashGridAggregation, HistogramAggregation, RangeAggregation,
+    TermsAggregation,
 };
 use super::metric::{
     AverageAggregation, CountAggregation, MaxAggregation, MinAggregation, StatsAggregation,
@@ -107,6 +108,14 @@ impl AggregationWithAccessor {
                     get_ff_reader(reader, field_name, Some(&[ColumnType::DateTime]))?;
                 add_agg_with_accessor(accessor, column_type, &mut res)?;
             }
+            GeohashGrid(GeohashGridAggregation {
+                field: field_name, ..
+            }) => {
+                let (accessor, column_type) =
+                    // Geo points are encoded in u64 fast fields
+                    get_ff_reader(reader, field_name, Some(&[ColumnType::U64]))?;
+                add_agg_with_accessor(accessor, column_type, &mut res)?;
+            }
             Terms(TermsAggregation {
                 field: field_name,
                 missing,
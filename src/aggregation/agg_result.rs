@@ -142,6 +142,13 @@ pub enum BucketResult {
         /// The upper bound error for the doc count of each term.
         doc_count_error_upper_bound: Option<u64>,
     },
+    /// This is the geohash grid result
+    GeohashGrid {
+        /// The buckets, keyed by geohash.
+        ///
+        /// See [`GeohashGridAggregation`](super::bucket::GeohashGridAggregation)
+        buckets: Vec<BucketEntry>,
+    },
 }
 
 impl BucketResult {
@@ -158,6 +165,9 @@ impl BucketResult {
                 sum_other_doc_count: _,
                 doc_count_error_upper_bound: _,
             } => buckets.iter().map(|bucket| bucket.get_bucket_count()).sum(),
+            BucketResult::GeohashGrid { buckets } => {
+                buckets.iter().map(|bucket| bucket.get_bucket_count()).sum()
+            }
         }
     }
 }
@@ -0,0 +1,325 @@
+use std::fmt::Debug;
+
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregation::agg_limits::MemoryConsumption;
+use crate::aggregation::agg_req::Aggregations;
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::agg_result::{BucketEntry, BucketResult};
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
+    IntermediateGeohashGridBucketResult, IntermediateTermBucketEntry,
+};
+use crate::aggregation::segment_agg_result::{
+    build_segment_agg_collector, AggregationLimits, SegmentAggregationCollector,
+};
+use crate::aggregation::Key;
+use crate::schema::geo_point::{geohash_cell_to_string, MAX_GEOHASH_PRECISION};
+use crate::schema::GeoPoint;
+use crate::TantivyError;
+
+/// Groups the geo points of a field by geohash cell.
+///
+/// The field needs to be a `u64` fast field holding points encoded with
+/// [`GeoPoint::to_u64`]. Each bucket is keyed by the geohash of its cell, and holds the
+/// number of points within the cell. This is typically used to render heatmaps.
+///
+/// The buckets are sorted by decreasing doc count, and by geohash in case of a tie.
+///
+/// Result type is [`BucketResult`](crate::aggregation::agg_result::BucketResult) with
+/// [`BucketEntry`](crate::aggregation::agg_result::BucketEntry) on the
+/// `AggregationCollector`.
+///
+/// # Limitations/Compatibility
+/// Documents holding several points are counted once per point.
+///
+/// # Request JSON Format
+/// ```json
+/// {
+///     "grid": {
+///         "geohash_grid": {
+///             "field": "location",
+///             "precision": 3
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeohashGridAggregation {
+    /// The field to aggregate on.
+    pub field: String,
+    /// The length of the geohashes of the cells, within `[1, 12]`. Defaults to 5, i.e.
+    /// cells of about 5km x 5km.
+    #[serde(default = "default_precision")]
+    pub precision: u8,
+    /// The maximum number of buckets returned. Defaults to 10000.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub size: Option<u32>,
+}
+
+fn default_precision() -> u8 {
+    5
+}
+
+impl GeohashGridAggregation {
+    fn validate(&self) -> crate::Result<()> {
+        if !(1..=MAX_GEOHASH_PRECISION).contains(&self.precision) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "precision must be within [1, {MAX_GEOHASH_PRECISION}], got {}",
+                self.precision
+            )));
+        }
+        if self.size == Some(0) {
+            return Err(TantivyError::InvalidArgument(
+                "size must be a positive value".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The collector puts the points from the fast field into the bucket of their cell.
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentGeohashGridCollector {
+    /// The doc counts, by geohash cell.
+    buckets: FxHashMap<u64, u32>,
+    sub_aggregations: FxHashMap<u64, Box<dyn SegmentAggregationCollector>>,
+    sub_aggregation_blueprint: Option<Box<dyn SegmentAggregationCollector>>,
+    precision: u8,
+    accessor_idx: usize,
+}
+
+impl SegmentAggregationCollector for SegmentGeohashGridCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let sub_agg = &agg_with_accessor.aggs.values[self.accessor_idx].sub_aggregation;
+
+        let mut sub_aggregations = self.sub_aggregations;
+        let mut buckets = FxHashMap::default();
+        for (cell, doc_count) in self.buckets {
+            let mut sub_aggregation = IntermediateAggregationResults::default();
+            if let Some(sub_agg_collector) = sub_aggregations.remove(&cell) {
+                sub_agg_collector
+                    .add_intermediate_aggregation_result(sub_agg, &mut sub_aggregation)?;
+            }
+            buckets.insert(
+                geohash_cell_to_string(cell, self.precision),
+                IntermediateTermBucketEntry {
+                    doc_count,
+                    sub_aggregation,
+                },
+            );
+        }
+
+        let bucket =
+            IntermediateBucketResult::GeohashGrid(IntermediateGeohashGridBucketResult { buckets });
+        results.push(name, IntermediateAggregationResult::Bucket(bucket))?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: crate::DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.collect_block(&[doc], agg_with_accessor)
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[crate::DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let bucket_agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+
+        let mem_pre = self.get_memory_consumption();
+
+        bucket_agg_accessor
+            .column_block_accessor
+            .fetch_block(docs, &bucket_agg_accessor.accessor);
+
+        for (doc, val) in bucket_agg_accessor.column_block_accessor.iter_docid_vals() {
+            let cell = GeoPoint::from_u64(val).geohash_cell(self.precision);
+            *self.buckets.entry(cell).or_default() += 1;
+            if let Some(sub_aggregation_blueprint) = self.sub_aggregation_blueprint.as_ref() {
+                self.sub_aggregations
+                    .entry(cell)
+                    .or_insert_with(|| sub_aggregation_blueprint.clone())
+                    .collect(doc, &mut bucket_agg_accessor.sub_aggregation)?;
+            }
+        }
+
+        let mem_delta = self.get_memory_consumption() - mem_pre;
+        bucket_agg_accessor
+            .limits
+            .add_memory_consumed(mem_delta as u64)?;
+
+        Ok(())
+    }
+
+    fn flush(&mut self, agg_with_accessor: &mut AggregationsWithAccessor) -> crate::Result<()> {
+        let sub_aggregation_accessor =
+            &mut agg_with_accessor.aggs.values[self.accessor_idx].sub_aggregation;
+
+        for sub_aggregation in self.sub_aggregations.values_mut() {
+            sub_aggregation.flush(sub_aggregation_accessor)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SegmentGeohashGridCollector {
+    fn get_memory_consumption(&self) -> usize {
+        let self_mem = std::mem::size_of::<Self>();
+        let sub_aggs_mem = self.sub_aggregations.memory_consumption();
+        let buckets_mem = self.buckets.memory_consumption();
+        self_mem + sub_aggs_mem + buckets_mem
+    }
+
+    pub(crate) fn from_req_and_validate(
+        req: &GeohashGridAggregation,
+        sub_aggregation: &mut AggregationsWithAccessor,
+        accessor_idx: usize,
+    ) -> crate::Result<Self> {
+        req.validate()?;
+
+        let sub_aggregation_blueprint = if sub_aggregation.is_empty() {
+            None
+        } else {
+            Some(build_segment_agg_collector(sub_aggregation)?)
+        };
+
+        Ok(SegmentGeohashGridCollector {
+            buckets: Default::default(),
+            sub_aggregations: Default::default(),
+            sub_aggregation_blueprint,
+            precision: req.precision,
+            accessor_idx,
+        })
+    }
+}
+
+impl IntermediateGeohashGridBucketResult {
+    pub(crate) fn into_final_result(
+        self,
+        req: &GeohashGridAggregation,
+        sub_aggregation_req: &Aggregations,
+        limits: &AggregationLimits,
+    ) -> crate::Result<BucketResult> {
+        let mut buckets: Vec<(String, IntermediateTermBucketEntry)> =
+            self.buckets.into_iter().collect();
+        buckets.sort_unstable_by(|(left_key, left), (right_key, right)| {
+            right
+                .doc_count
+                .cmp(&left.doc_count)
+                .then_with(|| left_key.cmp(right_key))
+        });
+        buckets.truncate(req.size.unwrap_or(10_000) as usize);
+
+        let buckets = buckets
+            .into_iter()
+            .map(|(key, entry)| {
+                Ok(BucketEntry {
+                    key_as_string: None,
+                    key: Key::Str(key),
+                    doc_count: entry.doc_count as u64,
+                    sub_aggregation: entry
+                        .sub_aggregation
+                        .into_final_result_internal(sub_aggregation_req, limits)?,
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+
+        Ok(BucketResult::GeohashGrid { buckets })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::exec_request;
+    use crate::schema::{GeoPoint, Schema, FAST};
+    use crate::{Index, IndexWriter};
+
+    fn get_test_index(points: &[(f64, f64)]) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_u64_field("location", FAST);
+        let score = schema_builder.add_f64_field("score", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for (doc_ord, &(lat, lon)) in points.iter().enumerate() {
+            index_writer.add_document(doc!(
+                location => GeoPoint::new(lat, lon),
+                score => doc_ord as f64,
+            ))?;
+            // Spread the documents over two segments.
+            if doc_ord == points.len() / 2 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn geohash_grid_test() -> crate::Result<()> {
+        let index = get_test_index(&[
+            (48.8566, 2.3522),    // Paris
+            (48.8600, 2.3500),    // Paris
+            (48.8530, 2.3499),    // Paris
+            (51.5074, -0.1278),   // London
+            (51.5080, -0.1281),   // London
+            (-33.8688, 151.2093), // Sydney
+        ])?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "grid": {
+                "geohash_grid": { "field": "location", "precision": 3 },
+                "aggs": { "max_score": { "max": { "field": "score" } } }
+            }
+        }))
+        .unwrap();
+
+        let res = exec_request(agg_req, &index)?;
+        assert_eq!(
+            res["grid"]["buckets"],
+            json!([
+                { "key": "u09", "doc_count": 3, "max_score": { "value": 2.0 } },
+                { "key": "gcp", "doc_count": 2, "max_score": { "value": 4.0 } },
+                { "key": "r3g", "doc_count": 1, "max_score": { "value": 5.0 } },
+            ])
+        );
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "grid": { "geohash_grid": { "field": "location", "precision": 1, "size": 1 } }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+        assert_eq!(
+            res["grid"]["buckets"],
+            json!([{ "key": "u", "doc_count": 3 }])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn geohash_grid_invalid_precision_test() -> crate::Result<()> {
+        let index = get_test_index(&[(48.8566, 2.3522)])?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "grid": { "geohash_grid": { "field": "location", "precision": 13 } }
+        }))
+        .unwrap();
+        assert!(exec_request(agg_req, &index).is_err());
+        Ok(())
+    }
+}
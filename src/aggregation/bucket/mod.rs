@@ -17,11 +17,13 @@
 //! [`IntermediateBucketResult`](super::intermediate_agg_result::IntermediateBucketResult)
 //!
 //! ## Supported Bucket Aggregations
+//! - [GeohashGrid](GeohashGridAggregation)
 //! - [Histogram](HistogramAggregation)
 //! - [DateHistogram](DateHistogramAggregationReq)
 //! - [Range](RangeAggregation)
 //! - [Terms](TermsAggregation)
 
+mod geohash_grid;
 mod histogram;
 mod range;
 mod term_agg;
@@ -29,6 +31,7 @@ mod term_missing_agg;
 
 use std::collections::HashMap;
 
+pub use geohash_grid::*;
 pub use histogram::*;
 pub use range::*;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
@@ -184,6 +184,9 @@ pub(crate) fn empty_from_req(req: &Aggregation) -> IntermediateAggregationResult
                 is_date_agg: true,
             })
         }
+        GeohashGrid(_) => IntermediateAggregationResult::Bucket(
+            IntermediateBucketResult::GeohashGrid(Default::default()),
+        ),
         Average(_) => IntermediateAggregationResult::Metric(IntermediateMetricResult::Average(
             IntermediateAverage::default(),
         )),
@@ -356,6 +359,8 @@ pub enum IntermediateBucketResult {
     },
     /// Term aggregation
     Terms(IntermediateTermBucketResult),
+    /// Geohash grid aggregation
+    GeohashGrid(IntermediateGeohashGridBucketResult),
 }
 
 impl IntermediateBucketResult {
@@ -439,6 +444,13 @@ impl IntermediateBucketResult {
                 req.sub_aggregation(),
                 limits,
             ),
+            IntermediateBucketResult::GeohashGrid(geohash_grid) => geohash_grid.into_final_result(
+                req.agg
+                    .as_geohash_grid()
+                    .expect("unexpected aggregation, expected geohash grid aggregation"),
+                req.sub_aggregation(),
+                limits,
+            ),
         }
     }
 
@@ -488,6 +500,12 @@ impl IntermediateBucketResult {
 
                 *buckets_left = buckets?;
             }
+            (
+                IntermediateBucketResult::GeohashGrid(geohash_grid_left),
+                IntermediateBucketResult::GeohashGrid(geohash_grid_right),
+            ) => {
+                merge_maps(&mut geohash_grid_left.buckets, geohash_grid_right.buckets)?;
+            }
             (IntermediateBucketResult::Range(_), _) => {
                 panic!("try merge on different types")
             }
@@ -497,6 +515,9 @@ impl IntermediateBucketResult {
             (IntermediateBucketResult::Terms { .. }, _) => {
                 panic!("try merge on different types")
             }
+            (IntermediateBucketResult::GeohashGrid(_), _) => {
+                panic!("try merge on different types")
+            }
         }
         Ok(())
     }
@@ -517,6 +538,12 @@ pub struct IntermediateTermBucketResult {
     pub(crate) doc_count_error_upper_bound: u64,
 }
 
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Geohash grid aggregation, with the buckets keyed by geohash
+pub struct IntermediateGeohashGridBucketResult {
+    pub(crate) buckets: FxHashMap<String, IntermediateTermBucketEntry>,
+}
+
 impl IntermediateTermBucketResult {
     pub(crate) fn into_final_result(
         self,
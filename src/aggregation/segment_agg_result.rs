@@ -8,7 +8,10 @@ use std::fmt::Debug;
 pub(crate) use super::agg_limits::AggregationLimits;
 use super::agg_req::AggregationVariants;
 use super::agg_req_with_accessor::{AggregationWithAccessor, AggregationsWithAccessor};
-use super::bucket::{SegmentHistogramCollector, SegmentRangeCollector, SegmentTermCollector};
+use super::bucket::{
+    SegmentGeohashGridCollector, SegmentHistogramCollector, SegmentRangeCollector,
+    SegmentTermCollector,
+};
 use super::intermediate_agg_result::IntermediateAggregationResults;
 use super::metric::{
     AverageAggregation, CountAggregation, MaxAggregation, MinAggregation,
@@ -115,6 +118,13 @@ pub(crate) fn build_single_agg_segment_collector(
             req.field_type,
             accessor_idx,
         )?)),
+        GeohashGrid(geohash_grid) => Ok(Box::new(
+            SegmentGeohashGridCollector::from_req_and_validate(
+                geohash_grid,
+                &mut req.sub_aggregation,
+                accessor_idx,
+            )?,
+        )),
         Average(AverageAggregation { missing, .. }) => {
             Ok(Box::new(SegmentStatsCollector::from_req(
                 req.field_type,
@@ -0,0 +1,249 @@
+//! A minimal command line tool to try tantivy out without writing any Rust.
+//!
+//! ```text
+//! tantivy new --index <dir> --schema <schema.json>
+//! tantivy index --index <dir> < documents.jsonl
+//! tantivy search --index <dir> --query <query> [--limit <n>]
+//! tantivy inspect --index <dir> [--field <field> [--term <term>]]
+//! ```
+//!
+//! The schema file holds a schema as serialized by tantivy, i.e. the content of the
+//! `schema` entry of the `meta.json` file of an index. Documents are read from the standard
+//! input as JSON objects, one per line. Search prints the matching documents as JSON objects,
+//! one per line, by decreasing score. Inspect prints the segments of the index, the terms of a
+//! field, or the postings of a term, see the [`tantivy::inspect`] module.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use serde_json::json;
+use tantivy::collector::TopDocs;
+use tantivy::inspect::{field_terms, term_postings};
+use tantivy::query::QueryParser;
+use tantivy::schema::{Document, FieldType, Schema, TantivyDocument, Term};
+use tantivy::{Index, IndexWriter};
+
+const USAGE: &str = "Usage:
+    tantivy new --index <dir> --schema <schema.json>
+        Creates an empty index in <dir>, with the schema read from <schema.json>.
+    tantivy index --index <dir> [--memory-budget <bytes>]
+        Indexes the JSON documents read from stdin, one per line, and commits them.
+    tantivy search --index <dir> --query <query> [--limit <n>]
+        Prints the best documents matching <query>, one JSON object per line.
+    tantivy inspect --index <dir> [--field <field> [--term <term>]]
+        Prints the metadata of the segments, the terms of <field> with their document
+        frequency, or the postings of <term> in <field>, one JSON object per line.";
+
+const DEFAULT_MEMORY_BUDGET: usize = 100_000_000;
+const DEFAULT_LIMIT: usize = 10;
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, options) = match args.split_first() {
+        Some(command_and_options) => command_and_options,
+        None => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let options = match parse_options(options) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let res = match command.as_str() {
+        "new" => run_new(&options),
+        "index" => run_index(&options),
+        "search" => run_search(&options),
+        "inspect" => run_inspect(&options),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        _ => {
+            eprintln!("Unknown command {command:?}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The `--name value` options given after the command.
+struct Options(HashMap<String, String>);
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = HashMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let name = arg
+            .strip_prefix("--")
+            .ok_or_else(|| format!("Unexpected argument {arg:?}"))?;
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for option --{name}"))?;
+        options.insert(name.to_string(), value.to_string());
+    }
+    Ok(Options(options))
+}
+
+impl Options {
+    fn required(&self, name: &str) -> Result<&str, String> {
+        self.0
+            .get(name)
+            .map(String::as_str)
+            .ok_or_else(|| format!("Missing option --{name}"))
+    }
+
+    fn optional(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    fn parsed_or<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.0.get(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("Invalid value {value:?} for option --{name}")),
+            None => Ok(default),
+        }
+    }
+}
+
+fn run_new(options: &Options) -> CliResult {
+    let index_path = Path::new(options.required("index")?);
+    let schema_file = File::open(options.required("schema")?)?;
+    let schema: Schema = serde_json::from_reader(BufReader::new(schema_file))?;
+    std::fs::create_dir_all(index_path)?;
+    Index::create_in_dir(index_path, schema)?;
+    eprintln!("Created index in {}", index_path.display());
+    Ok(())
+}
+
+fn run_index(options: &Options) -> CliResult {
+    let index = Index::open_in_dir(options.required("index")?)?;
+    let memory_budget = options.parsed_or("memory-budget", DEFAULT_MEMORY_BUDGET)?;
+    let schema = index.schema();
+    let mut index_writer: IndexWriter = index.writer(memory_budget)?;
+    let mut num_docs = 0u64;
+    for (line_ord, line) in io::stdin().lock().lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let doc = TantivyDocument::parse_json(&schema, &line)
+            .map_err(|err| format!("Invalid document on line {}: {err}", line_ord + 1))?;
+        index_writer.add_document(doc)?;
+        num_docs += 1;
+    }
+    index_writer.commit()?;
+    index_writer.wait_merging_threads()?;
+    eprintln!("Indexed {num_docs} documents");
+    Ok(())
+}
+
+fn run_search(options: &Options) -> CliResult {
+    let index = Index::open_in_dir(options.required("index")?)?;
+    let query = options.required("query")?;
+    let limit = options.parsed_or("limit", DEFAULT_LIMIT)?;
+    let schema = index.schema();
+    // Terms without a field target all of the text fields.
+    let default_fields = schema
+        .fields()
+        .filter(|(_, field_entry)| {
+            field_entry.is_indexed()
+                && matches!(
+                    field_entry.field_type(),
+                    FieldType::Str(_) | FieldType::JsonObject(_)
+                )
+        })
+        .map(|(field, _)| field)
+        .collect();
+    let query = QueryParser::for_index(&index, default_fields).parse_query(query)?;
+    let searcher = index.reader()?.searcher();
+    let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    for (_score, doc_address) in top_docs {
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        writeln!(stdout, "{}", doc.to_json(&schema))?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+fn run_inspect(options: &Options) -> CliResult {
+    let index = Index::open_in_dir(options.required("index")?)?;
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let field_name = match options.optional("field") {
+        Some(field_name) => field_name,
+        None => {
+            for segment_meta in index.searchable_segment_metas()? {
+                writeln!(stdout, "{}", serde_json::to_string(&segment_meta)?)?;
+            }
+            stdout.flush()?;
+            return Ok(());
+        }
+    };
+    let field = index.schema().get_field(field_name)?;
+    let searcher = index.reader()?.searcher();
+    match options.optional("term") {
+        None => {
+            for segment_reader in searcher.segment_readers() {
+                for term_entry in field_terms(segment_reader, field)? {
+                    let line = json!({
+                        "segment_id": segment_reader.segment_id(),
+                        "term": term_to_string(&term_entry.term),
+                        "doc_freq": term_entry.doc_freq,
+                    });
+                    writeln!(stdout, "{line}")?;
+                }
+            }
+        }
+        Some(term_text) => {
+            // The query parser takes care of tokenizing the term, or of parsing it into a
+            // number, a date, etc. depending on the field type.
+            let escaped_term_text = term_text.replace('\\', "\\\\").replace('"', "\\\"");
+            let query = QueryParser::for_index(&index, Vec::new())
+                .parse_query(&format!("{field_name}:\"{escaped_term_text}\""))?;
+            let mut terms: Vec<Term> = Vec::new();
+            query.query_terms(&mut |term, _| terms.push(term.clone()));
+            for segment_reader in searcher.segment_readers() {
+                for term in &terms {
+                    for posting_entry in term_postings(segment_reader, term)? {
+                        let line = json!({
+                            "segment_id": segment_reader.segment_id(),
+                            "term": term_to_string(term),
+                            "doc": posting_entry.doc,
+                            "is_deleted": posting_entry.is_deleted,
+                            "term_freq": posting_entry.term_freq,
+                            "positions": posting_entry.positions,
+                        });
+                        writeln!(stdout, "{line}")?;
+                    }
+                }
+            }
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Text terms are printed as is, other terms with their type.
+fn term_to_string(term: &Term) -> String {
+    match term.value().as_str() {
+        Some(text) => text.to_string(),
+        None => format!("{term:?}"),
+    }
+}
@@ -33,6 +33,7 @@ use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
 ///
 /// assert_eq!(count, 2);
 /// ```
+#[derive(Debug)]
 pub struct Count;
 
 impl Collector for Count {
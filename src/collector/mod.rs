@@ -114,6 +114,11 @@ pub use self::docset_collector::DocSetCollector;
 mod filter_collector_wrapper;
 pub use self::filter_collector_wrapper::{BytesFilterCollector, FilterCollector};
 
+mod near_duplicate_collector;
+pub use self::near_duplicate_collector::{
+    NearDuplicateCollector, NearDuplicateHit, NearDuplicateSegmentCollector,
+};
+
 /// `Fruit` is the type for the result of our collection.
 /// e.g. `usize` for the `Count` collector.
 pub trait Fruit: Send + downcast_rs::Downcast {}
@@ -155,6 +160,15 @@ pub trait Collector: Sync + Send {
     /// Returns true iff the collector requires to compute scores for documents.
     fn requires_scoring(&self) -> bool;
 
+    /// Returns true iff the collector requires the clauses of the query matched by the
+    /// documents, which are then pushed with
+    /// [`SegmentCollector::collect_with_matched_clauses()`].
+    ///
+    /// See [`Weight::for_each_with_matched_clauses()`].
+    fn requires_matched_clauses(&self) -> bool {
+        false
+    }
+
     /// Combines the fruit associated with the collection of each segments
     /// into one fruit.
     fn merge_fruits(
@@ -171,6 +185,16 @@ pub trait Collector: Sync + Send {
     ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
         let mut segment_collector = self.for_segment(segment_ord, reader)?;
 
+        if self.requires_matched_clauses() {
+            let alive_bitset = reader.alive_bitset();
+            weight.for_each_with_matched_clauses(reader, &mut |doc, score, matched_clauses| {
+                if alive_bitset.map_or(true, |alive_bitset| alive_bitset.is_alive(doc)) {
+                    segment_collector.collect_with_matched_clauses(doc, score, matched_clauses);
+                }
+            })?;
+            return Ok(segment_collector.harvest());
+        }
+
         match (reader.alive_bitset(), self.requires_scoring()) {
             (Some(alive_bitset), true) => {
                 weight.for_each(reader, &mut |doc, score| {
@@ -272,6 +296,13 @@ pub trait SegmentCollector: 'static {
     /// The query pushes the scored document to the collector via this method.
     fn collect(&mut self, doc: DocId, score: Score);
 
+    /// The query pushes the scored document, and the clauses of the query it matches, to the
+    /// collector via this method if the collector
+    /// [requires them](Collector::requires_matched_clauses).
+    fn collect_with_matched_clauses(&mut self, doc: DocId, score: Score, _matched_clauses: u64) {
+        self.collect(doc, score);
+    }
+
     /// The query pushes the scored document to the collector via this method.
     fn collect_block(&mut self, docs: &[DocId]) {
         for doc in docs {
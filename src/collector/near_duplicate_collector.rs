@@ -0,0 +1,278 @@
+use std::cmp::Ordering;
+
+use columnar::BytesColumn;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// The `NearDuplicateCollector` collects the top documents, dropping the near-duplicates of
+/// the documents with a higher score.
+///
+/// The documents are compared using a hash stored in a bytes fast field, typically a simhash
+/// of their content computed when they are indexed. Two documents are near-duplicates if the
+/// Hamming distance between their hashes, in bits, is at most `max_distance`. Hashes of
+/// different lengths are never near-duplicates, and the documents without a hash are always
+/// kept.
+///
+/// Every hit is retained until the end of the collection of its segment, so this collector is
+/// more expensive than [`TopDocs`](crate::collector::TopDocs) for queries matching a lot of
+/// documents.
+///
+/// ```rust
+/// use tantivy::collector::NearDuplicateCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let simhash = schema_builder.add_bytes_field("simhash", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib", simhash => &[0b1010_1010u8][..]))?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib.", simhash => &[0b1010_1011u8][..]))?;
+/// index_writer.add_document(doc!(title => "A Dairy Cow", simhash => &[0b0101_0101u8][..]))?;
+/// index_writer.commit()?;
+///
+/// let reader = index.reader()?;
+/// let searcher = reader.searcher();
+///
+/// let query_parser = QueryParser::for_index(&index, vec![title]);
+/// let query = query_parser.parse_query("diary")?;
+/// let collector = NearDuplicateCollector::new("simhash".to_string(), 1, 10);
+/// let top_docs = searcher.search(&query, &collector)?;
+///
+/// assert_eq!(top_docs.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct NearDuplicateCollector {
+    field: String,
+    max_distance: u32,
+    limit: usize,
+}
+
+impl NearDuplicateCollector {
+    /// Create a new `NearDuplicateCollector` returning at most `limit` documents, none of them
+    /// within `max_distance` bits of each other.
+    pub fn new(field: String, max_distance: u32, limit: usize) -> NearDuplicateCollector {
+        NearDuplicateCollector {
+            field,
+            max_distance,
+            limit,
+        }
+    }
+}
+
+/// A hit, with the hash of its document if it has one.
+pub struct NearDuplicateHit {
+    score: Score,
+    doc_address: DocAddress,
+    hash: Option<Vec<u8>>,
+}
+
+impl Collector for NearDuplicateCollector {
+    type Fruit = Vec<(Score, DocAddress)>;
+
+    type Child = NearDuplicateSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<NearDuplicateSegmentCollector> {
+        let column_opt = segment_reader.fast_fields().bytes(&self.field)?;
+        Ok(NearDuplicateSegmentCollector {
+            segment_local_id,
+            column_opt,
+            max_distance: self.max_distance,
+            limit: self.limit,
+            hits: Vec::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<NearDuplicateHit>>,
+    ) -> crate::Result<Vec<(Score, DocAddress)>> {
+        let hits = segment_fruits.into_iter().flatten().collect();
+        Ok(remove_near_duplicates(hits, self.max_distance, self.limit)
+            .into_iter()
+            .map(|hit| (hit.score, hit.doc_address))
+            .collect())
+    }
+}
+
+/// Segment collector of a [`NearDuplicateCollector`].
+pub struct NearDuplicateSegmentCollector {
+    segment_local_id: SegmentOrdinal,
+    column_opt: Option<BytesColumn>,
+    max_distance: u32,
+    limit: usize,
+    // The hits, with the term ordinal of their hash.
+    hits: Vec<(Score, DocId, Option<u64>)>,
+}
+
+impl SegmentCollector for NearDuplicateSegmentCollector {
+    type Fruit = Vec<NearDuplicateHit>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let hash_ord = self
+            .column_opt
+            .as_ref()
+            .and_then(|column| column.term_ords(doc).next());
+        self.hits.push((score, doc, hash_ord));
+    }
+
+    fn harvest(self) -> Vec<NearDuplicateHit> {
+        let mut buffer = Vec::new();
+        let hits = self
+            .hits
+            .into_iter()
+            .map(|(score, doc, hash_ord)| {
+                let hash = hash_ord
+                    .zip(self.column_opt.as_ref())
+                    .and_then(|(ord, column)| {
+                        buffer.clear();
+                        let found = column.ord_to_bytes(ord, &mut buffer).unwrap_or(false);
+                        found.then(|| buffer.clone())
+                    });
+                NearDuplicateHit {
+                    score,
+                    doc_address: DocAddress::new(self.segment_local_id, doc),
+                    hash,
+                }
+            })
+            .collect();
+        remove_near_duplicates(hits, self.max_distance, self.limit)
+    }
+}
+
+/// Returns the `limit` best hits, skipping the near-duplicates of the hits already returned.
+fn remove_near_duplicates(
+    mut hits: Vec<NearDuplicateHit>,
+    max_distance: u32,
+    limit: usize,
+) -> Vec<NearDuplicateHit> {
+    hits.sort_by(|left, right| {
+        right
+            .score
+            .partial_cmp(&left.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| left.doc_address.cmp(&right.doc_address))
+    });
+    let mut kept_hits: Vec<NearDuplicateHit> = Vec::new();
+    for hit in hits {
+        if kept_hits.len() == limit {
+            break;
+        }
+        let is_near_duplicate = hit.hash.as_ref().map_or(false, |hash| {
+            kept_hits.iter().any(|kept_hit| {
+                kept_hit.hash.as_ref().map_or(false, |kept_hash| {
+                    hamming_distance(hash, kept_hash)
+                        .map_or(false, |distance| distance <= max_distance)
+                })
+            })
+        });
+        if !is_near_duplicate {
+            kept_hits.push(hit);
+        }
+    }
+    kept_hits
+}
+
+/// Returns the number of bits differing between two hashes of the same length.
+fn hamming_distance(left: &[u8], right: &[u8]) -> Option<u32> {
+    if left.len() != right.len() {
+        return None;
+    }
+    Some(
+        left.iter()
+            .zip(right)
+            .map(|(left_byte, right_byte)| (left_byte ^ right_byte).count_ones())
+            .sum(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hamming_distance, NearDuplicateCollector};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(hamming_distance(&[0b1010], &[0b1010]), Some(0));
+        assert_eq!(hamming_distance(&[0b1010, 0], &[0b0110, 1]), Some(3));
+        assert_eq!(hamming_distance(&[0b1010], &[0b1010, 0]), None);
+    }
+
+    #[test]
+    fn test_near_duplicate_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let id = schema_builder.add_u64_field("id", FAST);
+        let simhash = schema_builder.add_bytes_field("simhash", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer
+            .add_document(doc!(id => 0u64, text => "a", simhash => vec![0b0000_0000u8, 0]))?;
+        index_writer
+            .add_document(doc!(id => 1u64, text => "a a", simhash => vec![0b0000_0011u8, 0]))?;
+        index_writer
+            .add_document(doc!(id => 2u64, text => "a", simhash => vec![0b1111_0000u8, 0]))?;
+        index_writer.commit()?;
+        index_writer
+            .add_document(doc!(id => 3u64, text => "a a a", simhash => vec![0b0000_0001u8, 0]))?;
+        index_writer.add_document(doc!(id => 4u64, text => "a"))?;
+        index_writer.add_document(doc!(id => 5u64, text => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let ids = |max_distance: u32, limit: usize| -> crate::Result<Vec<u64>> {
+            let query = TermQuery::new(
+                Term::from_field_text(text, "a"),
+                IndexRecordOption::WithFreqs,
+            );
+            let collector = NearDuplicateCollector::new("simhash".to_string(), max_distance, limit);
+            searcher
+                .search(&query, &collector)?
+                .into_iter()
+                .map(|(_, doc_address)| {
+                    let id_column = searcher
+                        .segment_reader(doc_address.segment_ord)
+                        .fast_fields()
+                        .u64("id")?;
+                    Ok(id_column.first(doc_address.doc_id).unwrap())
+                })
+                .collect()
+        };
+        // Documents 0 and 1 are near-duplicates of document 3, which has the highest score.
+        let mut kept_ids = ids(2, 10)?;
+        assert_eq!(kept_ids[0], 3);
+        kept_ids.sort();
+        assert_eq!(kept_ids, vec![2, 3, 4, 5]);
+        let mut kept_ids = ids(0, 10)?;
+        kept_ids.sort();
+        assert_eq!(kept_ids, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(ids(2, 2)?.len(), 2);
+        assert_eq!(
+            searcher
+                .search(
+                    &AllQuery,
+                    &NearDuplicateCollector::new("simhash".to_string(), 8, 10)
+                )?
+                .len(),
+            3
+        );
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
-use columnar::ColumnValues;
+use columnar::{Column, ColumnValues};
 
 use super::Collector;
 use crate::collector::custom_score_top_collector::CustomScoreTopCollector;
@@ -13,6 +13,7 @@ use crate::collector::{
 };
 use crate::fastfield::{FastFieldNotAvailableError, FastValue};
 use crate::query::Weight;
+use crate::schema::{FieldType, GeoPoint};
 use crate::{DocAddress, DocId, Order, Score, SegmentOrdinal, SegmentReader, TantivyError};
 
 struct FastFieldConvertCollector<
@@ -82,6 +83,40 @@ where
     }
 }
 
+/// Converts the negated distances used to rank the documents back into distances.
+struct GeoDistanceConvertCollector {
+    collector: CustomScoreTopCollector<ScorerByGeoDistance, f64>,
+}
+
+impl Collector for GeoDistanceConvertCollector {
+    type Fruit = Vec<(f64, DocAddress)>;
+
+    type Child = <CustomScoreTopCollector<ScorerByGeoDistance, f64> as Collector>::Child;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        self.collector.for_segment(segment_local_id, segment)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<Self::Fruit> {
+        let raw_result = self.collector.merge_fruits(segment_fruits)?;
+        Ok(raw_result
+            .into_iter()
+            .map(|(neg_distance, doc_address)| (-neg_distance, doc_address))
+            .collect())
+    }
+}
+
 /// The `TopDocs` collector keeps track of the top `K` documents
 /// sorted by their score.
 ///
@@ -184,6 +219,56 @@ impl CustomScorer<u64> for ScorerByField {
     }
 }
 
+struct ScorerByGeoDistanceReader {
+    column_opt: Option<Column<u64>>,
+    origin: GeoPoint,
+}
+
+impl CustomSegmentScorer<f64> for ScorerByGeoDistanceReader {
+    fn score(&mut self, doc: DocId) -> f64 {
+        // The top-K collector keeps the highest scores: we use the negated distance
+        // as the score, so that the closest documents come first.
+        let Some(column) = self.column_opt.as_ref() else {
+            return f64::NEG_INFINITY;
+        };
+        let origin = self.origin;
+        let distance = column
+            .values_for_doc(doc)
+            .map(|val| origin.distance(&GeoPoint::from_u64(val)))
+            .fold(f64::INFINITY, f64::min);
+        -distance
+    }
+}
+
+struct ScorerByGeoDistance {
+    field: String,
+    origin: GeoPoint,
+}
+
+impl CustomScorer<f64> for ScorerByGeoDistance {
+    type Child = ScorerByGeoDistanceReader;
+
+    fn segment_scorer(&self, segment_reader: &SegmentReader) -> crate::Result<Self::Child> {
+        let schema = segment_reader.schema();
+        let field = schema.get_field(&self.field)?;
+        match schema.get_field_entry(field).field_type() {
+            FieldType::U64(options) if options.is_fast() => {}
+            _ => {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is not a u64 fast field, and cannot hold geo points.",
+                    self.field
+                )));
+            }
+        }
+        Ok(ScorerByGeoDistanceReader {
+            column_opt: segment_reader
+                .fast_fields()
+                .column_opt::<u64>(&self.field)?,
+            origin: self.origin,
+        })
+    }
+}
+
 impl TopDocs {
     /// Creates a top score collector, with a number of documents equal to "limit".
     ///
@@ -409,6 +494,65 @@ impl TopDocs {
         }
     }
 
+    /// Set top-K to rank documents by their distance to a point, closest first.
+    ///
+    /// The field needs to be a `u64` fast field holding geo points encoded with
+    /// [`GeoPoint::to_u64`]. The distance of each document, in meters, is returned
+    /// along with its address. If a document holds several points, the closest one is
+    /// considered. Documents without any point in the field come last, with an
+    /// infinite distance: they can be filtered out with a
+    /// [`GeoDistanceQuery`](crate::query::GeoDistanceQuery).
+    ///
+    /// If the field is not a `u64` fast field, an explicit error will be returned at the
+    /// moment of collection.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::GeoDistanceQuery;
+    /// use tantivy::schema::{GeoPoint, Schema, FAST};
+    /// use tantivy::{doc, DocAddress, Index};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let location = schema_builder.add_u64_field("location", FAST);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// index_writer.add_document(doc!(location => GeoPoint::new(52.52, 13.405)))?;
+    /// index_writer.add_document(doc!(location => GeoPoint::new(51.5074, -0.1278)))?;
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let paris = GeoPoint::new(48.8566, 2.3522);
+    /// let query = GeoDistanceQuery::new("location".to_string(), paris, 1_000_000.0);
+    /// let top_docs = searcher.search(
+    ///     &query,
+    ///     &TopDocs::with_limit(10).order_by_geo_distance("location", paris),
+    /// )?;
+    /// assert_eq!(top_docs.len(), 2);
+    /// // London is closer to Paris than Berlin.
+    /// assert_eq!(top_docs[0].1, DocAddress::new(0, 1));
+    /// assert!(top_docs[0].0 < top_docs[1].0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn order_by_geo_distance(
+        self,
+        field: impl ToString,
+        origin: GeoPoint,
+    ) -> impl Collector<Fruit = Vec<(f64, DocAddress)>> {
+        GeoDistanceConvertCollector {
+            collector: CustomScoreTopCollector::new(
+                ScorerByGeoDistance {
+                    field: field.to_string(),
+                    origin,
+                },
+                self.0.into_tscore(),
+            ),
+        }
+    }
+
     /// Ranks the documents using a custom score.
     ///
     /// This method offers a convenient way to tweak or replace
@@ -800,7 +944,7 @@ mod tests {
     use crate::collector::top_collector::ComparableDoc;
     use crate::collector::Collector;
     use crate::query::{AllQuery, Query, QueryParser};
-    use crate::schema::{Field, Schema, FAST, STORED, TEXT};
+    use crate::schema::{Field, GeoPoint, Schema, FAST, STORED, TEXT};
     use crate::time::format_description::well_known::Rfc3339;
     use crate::time::OffsetDateTime;
     use crate::{DateTime, DocAddress, DocId, Index, IndexWriter, Order, Score, SegmentReader};
@@ -1152,6 +1296,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_top_field_collector_geo_distance() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let name = schema_builder.add_text_field("name", TEXT);
+        let location = schema_builder.add_u64_field("location", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            name => "berlin",
+            location => GeoPoint::new(52.52, 13.405),
+        ))?;
+        index_writer.add_document(doc!(name => "nowhere"))?;
+        index_writer.add_document(doc!(
+            name => "london",
+            location => GeoPoint::new(51.5074, -0.1278),
+        ))?;
+        index_writer.add_document(doc!(
+            name => "new york and paris",
+            location => GeoPoint::new(40.7128, -74.006),
+            location => GeoPoint::new(48.86, 2.35),
+        ))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let paris = GeoPoint::new(48.8566, 2.3522);
+        let top_collector = TopDocs::with_limit(4).order_by_geo_distance("location", paris);
+        let top_docs: Vec<(f64, DocAddress)> = searcher.search(&AllQuery, &top_collector)?;
+        let doc_ids: Vec<DocId> = top_docs
+            .iter()
+            .map(|(_, doc_address)| doc_address.doc_id)
+            .collect();
+        assert_eq!(doc_ids, vec![3, 2, 0, 1]);
+        assert!(top_docs[0].0 < 1_000.0);
+        let london = GeoPoint::new(51.5074, -0.1278);
+        assert!((top_docs[1].0 - paris.distance(&london)).abs() < 1.0);
+        assert_eq!(top_docs[3].0, f64::INFINITY);
+
+        let not_fast = TopDocs::with_limit(4).order_by_geo_distance("name", paris);
+        assert!(searcher.search(&AllQuery, &not_fast).is_err());
+        Ok(())
+    }
+
     #[test]
     #[should_panic]
     fn test_field_does_not_exist() {
@@ -0,0 +1,181 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::query::{Explanation, Scorer, Weight};
+use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, TERMINATED};
+
+/// Number of documents a cancellable scorer goes through between two checks of its token.
+const NUM_DOCS_BETWEEN_CHECKS: u32 = 4_096;
+
+/// A token used to cancel long operations, like searches and merges, while they are running.
+///
+/// The token is shared by cloning it: the operation periodically checks it, and stops with a
+/// [`TantivyError::Cancelled`] error as soon as [`CancelToken::cancel()`] was called on any of
+/// its clones, e.g. when the client of a search disconnected or the application is shutting
+/// down.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Creates a new token, which is not cancelled.
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Cancels the operations using this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns a [`TantivyError::Cancelled`] error if the token was cancelled.
+    pub(crate) fn check(&self) -> crate::Result<()> {
+        if self.is_cancelled() {
+            Err(TantivyError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A weight whose scorers stop early when a token is cancelled.
+///
+/// The scorers it returns hide the actual scorers, which disables some optimizations of
+/// compound queries (e.g. block WAND).
+pub(crate) struct CancellableWeight {
+    weight: Box<dyn Weight>,
+    cancel_token: CancelToken,
+}
+
+impl CancellableWeight {
+    pub(crate) fn new(weight: Box<dyn Weight>, cancel_token: CancelToken) -> CancellableWeight {
+        CancellableWeight {
+            weight,
+            cancel_token,
+        }
+    }
+}
+
+impl Weight for CancellableWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        self.cancel_token.check()?;
+        let scorer = self.weight.scorer(reader, boost)?;
+        Ok(Box::new(CancellableScorer {
+            scorer,
+            cancel_token: self.cancel_token.clone(),
+            num_docs_until_check: NUM_DOCS_BETWEEN_CHECKS,
+            cancelled: false,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        self.weight.explain(reader, doc)
+    }
+}
+
+/// A scorer behaving as if it was exhausted once its token is cancelled.
+struct CancellableScorer {
+    scorer: Box<dyn Scorer>,
+    cancel_token: CancelToken,
+    num_docs_until_check: u32,
+    cancelled: bool,
+}
+
+impl CancellableScorer {
+    /// Returns true if the token was cancelled, checking it every `NUM_DOCS_BETWEEN_CHECKS`
+    /// calls.
+    fn is_cancelled(&mut self) -> bool {
+        if !self.cancelled {
+            self.num_docs_until_check -= 1;
+            if self.num_docs_until_check == 0 {
+                self.num_docs_until_check = NUM_DOCS_BETWEEN_CHECKS;
+                self.cancelled = self.cancel_token.is_cancelled();
+            }
+        }
+        self.cancelled
+    }
+}
+
+impl DocSet for CancellableScorer {
+    fn advance(&mut self) -> DocId {
+        if self.is_cancelled() {
+            return TERMINATED;
+        }
+        self.scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.is_cancelled() {
+            return TERMINATED;
+        }
+        self.scorer.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        if self.cancelled {
+            return TERMINATED;
+        }
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl Scorer for CancellableScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CancelToken, CancellableWeight};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{AllQuery, EnableScoring, Query, Weight};
+    use crate::schema::{Schema, STRING};
+    use crate::{DocSet, Index, IndexWriter, TantivyError, TERMINATED};
+
+    #[test]
+    fn test_cancel_search() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for _ in 0..10_000 {
+            index_writer.add_document(doc!(tag => "a"))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let cancel_token = CancelToken::new();
+        let count = searcher.search_with_cancel_token(&AllQuery, &Count, &cancel_token)?;
+        assert_eq!(count, 10_000);
+
+        // Cancelling the token stops the scorers midway.
+        let weight = CancellableWeight::new(
+            AllQuery.weight(EnableScoring::disabled_from_searcher(&searcher))?,
+            cancel_token.clone(),
+        );
+        let mut scorer = weight.scorer(searcher.segment_reader(0), 1.0)?;
+        for _ in 0..100 {
+            scorer.advance();
+        }
+        cancel_token.clone().cancel();
+        assert!(cancel_token.is_cancelled());
+        assert!(scorer.count_including_deleted() < 9_900);
+        assert_eq!(scorer.doc(), TERMINATED);
+
+        let result =
+            searcher.search_with_cancel_token(&AllQuery, &TopDocs::with_limit(10), &cancel_token);
+        assert!(matches!(result, Err(TantivyError::Cancelled)));
+        Ok(())
+    }
+}
@@ -1,7 +1,20 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use once_cell::sync::Lazy;
 use rayon::{ThreadPool, ThreadPoolBuilder};
 
 use crate::TantivyError;
 
+/// Thread pool running the background tasks of the single thread executors, so that they never
+/// block the caller.
+static BACKGROUND_POOL: Lazy<ThreadPool> = Lazy::new(|| {
+    ThreadPoolBuilder::new()
+        .num_threads(num_cpus::get())
+        .thread_name(|num| format!("tantivy-background-search-{num}"))
+        .build()
+        .expect("Failed to create the background search thread pool")
+});
+
 /// Search executor whether search request are single thread or multithread.
 ///
 /// We don't expose Rayon thread pool directly here for several reasons.
@@ -91,6 +104,23 @@ impl Executor {
             }
         }
     }
+
+    /// Runs a task in the background.
+    ///
+    /// The `ThreadPool` variant runs the task in its own thread pool, while the `SingleThread`
+    /// variant runs it in a thread pool shared by the process. Panics in the task are caught
+    /// and logged.
+    pub(crate) fn spawn<F: FnOnce() + Send + 'static>(&self, task: F) {
+        let task = move || {
+            if catch_unwind(AssertUnwindSafe(task)).is_err() {
+                error!("A background search task panicked.");
+            }
+        };
+        match self {
+            Executor::SingleThread => BACKGROUND_POOL.spawn(task),
+            Executor::ThreadPool(pool) => pool.spawn(task),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -125,6 +155,19 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_spawn() {
+        for executor in [
+            Executor::single_thread(),
+            Executor::multi_thread(2, "search-test").unwrap(),
+        ] {
+            let (sender, receiver) = crossbeam_channel::bounded(1);
+            executor.spawn(move || panic!("panics are caught"));
+            executor.spawn(move || sender.send(2).unwrap());
+            assert_eq!(receiver.recv().unwrap(), 2);
+        }
+    }
+
     #[test]
     fn test_map_singlethread() {
         let result: Vec<usize> = Executor::single_thread()
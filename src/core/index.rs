@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 use std::fmt;
+use std::io::Write;
 #[cfg(feature = "mmap")]
 use std::path::Path;
 use std::path::PathBuf;
@@ -7,9 +8,12 @@ use std::sync::Arc;
 
 use super::segment::Segment;
 use super::IndexSettings;
+use crate::core::index_validation::validate_index;
+use crate::core::metrics::{IndexMetrics, MetricsCounters};
 use crate::core::single_segment_index_writer::SingleSegmentIndexWriter;
 use crate::core::{
-    Executor, IndexMeta, SegmentId, SegmentMeta, SegmentMetaInventory, META_FILEPATH,
+    Executor, IndexMeta, IndexValidationReport, SegmentId, SegmentMeta, SegmentMetaInventory,
+    META_FILEPATH,
 };
 use crate::directory::error::OpenReadError;
 #[cfg(feature = "mmap")]
@@ -18,10 +22,13 @@ use crate::directory::{Directory, ManagedDirectory, RamDirectory, INDEX_WRITER_L
 use crate::error::{DataCorruption, TantivyError};
 use crate::indexer::index_writer::{MAX_NUM_THREAD, MEMORY_BUDGET_NUM_BYTES_MIN};
 use crate::indexer::segment_updater::save_metas;
-use crate::indexer::IndexWriter;
-use crate::reader::{IndexReader, IndexReaderBuilder};
+use crate::indexer::{IndexWriter, IndexWriterOptions};
+use crate::query::{Bm25Similarity, StaticRank};
+use crate::reader::{IndexReader, IndexReaderBuilder, ReloadPolicy};
 use crate::schema::document::Document;
-use crate::schema::{Field, FieldType, Schema};
+use crate::schema::{Field, FieldType, Schema, TantivyDocument};
+use crate::space_usage::SearcherSpaceUsage;
+use crate::store::{StoredValueTransform, StoredValueTransforms};
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
 use crate::{merge_field_meta_data, FieldMetadata, SegmentReader};
 
@@ -37,6 +44,7 @@ fn load_metas(
             "Meta file does not contain valid utf8 file.".to_string(),
         )
     })?;
+    IndexMeta::check_compatibility(&meta_string).map_err(TantivyError::IncompatibleIndex)?;
     IndexMeta::deserialize(&meta_string, inventory)
         .map_err(|e| {
             DataCorruption::new(
@@ -253,6 +261,20 @@ impl IndexBuilder {
                     )));
                 }
             }
+            if let Some(expiry_field) = self.index_settings.expiry_field.as_ref() {
+                let schema_field = schema.get_field(expiry_field).map_err(|_| {
+                    TantivyError::InvalidArgument(format!(
+                        "Expiry field {expiry_field} not found in schema"
+                    ))
+                })?;
+                let entry = schema.get_field_entry(schema_field);
+                if !matches!(entry.field_type(), FieldType::Date(options) if options.is_fast()) {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Field {expiry_field} needs to be a date fast field to be used as expiry \
+                         field"
+                    )));
+                }
+            }
             Ok(())
         } else {
             Err(TantivyError::InvalidArgument(
@@ -291,7 +313,11 @@ pub struct Index {
     executor: Arc<Executor>,
     tokenizers: TokenizerManager,
     fast_field_tokenizers: TokenizerManager,
+    static_rank: Option<StaticRank>,
+    bm25_similarity: Bm25Similarity,
+    stored_value_transforms: StoredValueTransforms,
     inventory: SegmentMetaInventory,
+    metrics: Arc<MetricsCounters>,
 }
 
 impl Index {
@@ -401,14 +427,28 @@ impl Index {
         Index {
             settings: metas.index_settings.clone(),
             directory,
+            stored_value_transforms: StoredValueTransforms::new(schema.clone()),
             schema,
             tokenizers: TokenizerManager::default(),
             fast_field_tokenizers: TokenizerManager::default(),
+            static_rank: None,
+            bm25_similarity: Bm25Similarity::default(),
             executor: Arc::new(Executor::single_thread()),
             inventory,
+            metrics: Arc::default(),
         }
     }
 
+    /// Returns a snapshot of the activity counters of the index, e.g. to export them to a
+    /// monitoring system.
+    pub fn metrics(&self) -> IndexMetrics {
+        self.metrics.snapshot()
+    }
+
+    pub(crate) fn metrics_counters(&self) -> &Arc<MetricsCounters> {
+        &self.metrics
+    }
+
     /// Setter for the tokenizer manager.
     pub fn set_tokenizers(&mut self, tokenizers: TokenizerManager) {
         self.tokenizers = tokenizers;
@@ -429,6 +469,74 @@ impl Index {
         &self.fast_field_tokenizers
     }
 
+    /// Sets the static rank blended into the scores of the searches.
+    ///
+    /// It applies to the searchers of the readers created afterwards.
+    /// Returns an error if the static rank field is not a numerical fast field.
+    pub fn set_static_rank(&mut self, static_rank: StaticRank) -> crate::Result<()> {
+        static_rank.validate(&self.schema)?;
+        self.static_rank = Some(static_rank);
+        Ok(())
+    }
+
+    /// Removes the static rank of the index.
+    pub fn clear_static_rank(&mut self) {
+        self.static_rank = None;
+    }
+
+    /// Accessor for the static rank blended into the scores of the searches, if any.
+    pub fn static_rank(&self) -> Option<&StaticRank> {
+        self.static_rank.as_ref()
+    }
+
+    /// Sets the parameters of the BM25 formula scoring the searches.
+    ///
+    /// It applies to the searchers of the readers created afterwards.
+    /// Returns an error if `k1` is negative, or if `b` is not between 0 and 1.
+    ///
+    /// The block max scores stored in the postings are computed with the default parameters,
+    /// so top-k searches skip fewer blocks with other parameters.
+    pub fn set_bm25_similarity(&mut self, bm25_similarity: Bm25Similarity) -> crate::Result<()> {
+        bm25_similarity.validate()?;
+        self.bm25_similarity = bm25_similarity;
+        Ok(())
+    }
+
+    /// Accessor for the parameters of the BM25 formula scoring the searches.
+    pub fn bm25_similarity(&self) -> Bm25Similarity {
+        self.bm25_similarity
+    }
+
+    /// Sets the hook transforming the values of the stored field `field`.
+    ///
+    /// It applies to the documents added by the writers, and read by the searchers of the
+    /// readers, created afterwards.
+    /// Returns an error if the field is not stored.
+    pub fn set_stored_value_transform<T: StoredValueTransform>(
+        &mut self,
+        field: Field,
+        transform: T,
+    ) -> crate::Result<()> {
+        let field_entry = self.schema.get_field_entry(field);
+        if !field_entry.is_stored() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not stored.",
+                field_entry.name()
+            )));
+        }
+        self.stored_value_transforms.set(field, Arc::new(transform));
+        Ok(())
+    }
+
+    /// Removes the hook transforming the values of the stored field `field`, if any.
+    pub fn clear_stored_value_transform(&mut self, field: Field) {
+        self.stored_value_transforms.remove(field);
+    }
+
+    pub(crate) fn stored_value_transforms(&self) -> &StoredValueTransforms {
+        &self.stored_value_transforms
+    }
+
     /// Get the tokenizer associated with a specific field.
     pub fn tokenizer_for_field(&self, field: Field) -> crate::Result<TextAnalyzer> {
         let field_entry = self.schema.get_field_entry(field);
@@ -559,6 +667,22 @@ impl Index {
         &self,
         num_threads: usize,
         overall_memory_budget_in_bytes: usize,
+    ) -> crate::Result<IndexWriter<D>> {
+        let options = IndexWriterOptions::default()
+            .num_worker_threads(num_threads)
+            .memory_budget_per_thread(overall_memory_budget_in_bytes / num_threads);
+        self.writer_with_options(options)
+    }
+
+    /// Open a new index writer with the given options. Attempts to acquire a lockfile.
+    ///
+    /// # Errors
+    /// If the lockfile already exists, returns `Error::DirectoryLockBusy` or an `Error::IoError`.
+    /// If the options are invalid, e.g. if the memory arena per thread is too small or too
+    /// big, returns `TantivyError::InvalidArgument`
+    pub fn writer_with_options<D: Document>(
+        &self,
+        options: IndexWriterOptions,
     ) -> crate::Result<IndexWriter<D>> {
         let directory_lock = self
             .directory
@@ -574,13 +698,7 @@ impl Index {
                     ),
                 )
             })?;
-        let memory_arena_in_bytes_per_thread = overall_memory_budget_in_bytes / num_threads;
-        IndexWriter::new(
-            self,
-            num_threads,
-            memory_arena_in_bytes_per_thread,
-            directory_lock,
-        )
+        IndexWriter::new(self, options, directory_lock)
     }
 
     /// Helper to create an index writer for tests.
@@ -698,6 +816,73 @@ impl Index {
         }
         Ok(damaged_files)
     }
+
+    /// Computes the space usage of the searchable segments of the index, broken down
+    /// by segment, by component, and by field.
+    ///
+    /// This is the same report as [`Searcher::space_usage`](crate::Searcher::space_usage),
+    /// without having to open an `IndexReader`.
+    pub fn space_usage(&self) -> crate::Result<SearcherSpaceUsage> {
+        let mut space_usage = SearcherSpaceUsage::new();
+        for segment in self.searchable_segments()? {
+            let segment_reader = SegmentReader::open(&segment)?;
+            space_usage.add_segment(segment_reader.space_usage()?);
+        }
+        Ok(space_usage)
+    }
+
+    /// Checks the consistency of the searchable segments of the index.
+    ///
+    /// For each segment, this verifies that all of its files are present and that their
+    /// checksums are valid. It then opens the segment and checks that the doc store,
+    /// fast fields, fieldnorms and alive bitset hold the number of documents recorded in
+    /// the segment meta, and that every postings list matches its term dictionary entry.
+    ///
+    /// This reads the entire index, and is meant to be used before trusting an index of
+    /// unknown integrity, e.g. one restored from a backup.
+    pub fn validate(&self) -> crate::Result<IndexValidationReport> {
+        validate_index(self)
+    }
+
+    /// Writes the stored fields of all of the live documents of the index to `writer`, as JSON
+    /// lines.
+    ///
+    /// Each line holds a document, encoded as with [`Document::to_json`]. If `fields` is not
+    /// empty, only the given fields are exported. Fields that are not stored cannot be
+    /// exported.
+    ///
+    /// This makes it possible to reindex the documents with another schema, or to move them
+    /// to another system, without keeping the original data around.
+    ///
+    /// Returns the number of exported documents.
+    pub fn export_json<W: Write>(&self, mut writer: W, fields: &[Field]) -> crate::Result<u64> {
+        let schema = self.schema();
+        let reader: IndexReader = self
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+        let mut num_exported_docs = 0;
+        let mut doc_json = String::new();
+        for segment_reader in searcher.segment_readers() {
+            let store_reader = segment_reader.get_store_reader(1)?;
+            for doc_res in store_reader.iter::<TantivyDocument>(segment_reader.alive_bitset()) {
+                let mut doc = doc_res?;
+                if !fields.is_empty() {
+                    doc = doc
+                        .into_iter()
+                        .filter(|field_value| fields.contains(&field_value.field()))
+                        .collect::<Vec<_>>()
+                        .into();
+                }
+                doc.to_json_into(&schema, &mut doc_json);
+                writeln!(writer, "{doc_json}")?;
+                num_exported_docs += 1;
+            }
+        }
+        writer.flush()?;
+        Ok(num_exported_docs)
+    }
 }
 
 impl fmt::Debug for Index {
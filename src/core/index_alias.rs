@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::directory::MmapDirectory;
+use crate::schema::Schema;
+use crate::{Directory, Index, IndexReader, Searcher, TantivyError};
+
+static ALIAS_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new("alias.json"));
+
+#[derive(Serialize, Deserialize)]
+struct AliasTarget {
+    index: String,
+}
+
+/// A stable name for an index that can be atomically replaced by another one.
+///
+/// The indexes of an alias live in the subdirectories of its root directory, along with an
+/// `alias.json` file naming the current one. A full reindex typically consists in building a
+/// new index with [`IndexAlias::create_index()`], and then making it current with
+/// [`IndexAlias::swap()`]. Readers created with [`IndexAlias::reader()`] move to the new index
+/// on their next [`IndexAliasReader::reload()`], while the searches running on the previous
+/// index complete undisturbed.
+#[derive(Clone)]
+pub struct IndexAlias {
+    root_path: PathBuf,
+    directory: MmapDirectory,
+}
+
+impl IndexAlias {
+    /// Opens the alias rooted at `root_path`, creating the directory if it does not exist.
+    pub fn open<P: AsRef<Path>>(root_path: P) -> crate::Result<IndexAlias> {
+        let root_path = root_path.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root_path)?;
+        let directory = MmapDirectory::open(&root_path)?;
+        Ok(IndexAlias {
+            root_path,
+            directory,
+        })
+    }
+
+    /// Returns the name of the current index, or `None` if no index was swapped in yet.
+    pub fn current(&self) -> crate::Result<Option<String>> {
+        if !self.directory.exists(&ALIAS_FILEPATH)? {
+            return Ok(None);
+        }
+        let alias_data = self.directory.atomic_read(&ALIAS_FILEPATH)?;
+        let alias_target: AliasTarget = serde_json::from_slice(&alias_data)?;
+        Ok(Some(alias_target.index))
+    }
+
+    /// Creates a new index named `name`, which can be populated before being swapped in.
+    pub fn create_index(&self, name: &str, schema: Schema) -> crate::Result<Index> {
+        let index_path = self.index_path(name)?;
+        std::fs::create_dir(&index_path)?;
+        Index::create_in_dir(index_path, schema)
+    }
+
+    /// Opens the index named `name`.
+    pub fn open_index(&self, name: &str) -> crate::Result<Index> {
+        Index::open_in_dir(self.index_path(name)?)
+    }
+
+    /// Opens the current index.
+    pub fn open_current(&self) -> crate::Result<Index> {
+        let name = self.current()?.ok_or_else(|| self.no_current_index())?;
+        self.open_index(&name)
+    }
+
+    /// Atomically makes the index named `name` the current index, and returns the name of the
+    /// previous one.
+    ///
+    /// The previous index is left untouched, and can be removed with
+    /// [`IndexAlias::delete_index()`] once the readers moved to the new one.
+    pub fn swap(&self, name: &str) -> crate::Result<Option<String>> {
+        // Makes sure the index is complete before exposing it.
+        self.open_index(name)?;
+        let previous = self.current()?;
+        let alias_data = serde_json::to_vec(&AliasTarget {
+            index: name.to_string(),
+        })?;
+        self.directory.atomic_write(&ALIAS_FILEPATH, &alias_data)?;
+        self.directory.sync_directory()?;
+        Ok(previous)
+    }
+
+    /// Deletes the index named `name`, which must not be the current index.
+    pub fn delete_index(&self, name: &str) -> crate::Result<()> {
+        if self.current()?.as_deref() == Some(name) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The index {name:?} is the current index of the alias"
+            )));
+        }
+        std::fs::remove_dir_all(self.index_path(name)?)?;
+        Ok(())
+    }
+
+    /// Creates a reader following the current index of the alias.
+    pub fn reader(&self) -> crate::Result<IndexAliasReader> {
+        let current = self.open_current_reader()?;
+        Ok(IndexAliasReader {
+            alias: self.clone(),
+            current: ArcSwap::from_pointee(current),
+        })
+    }
+
+    fn open_current_reader(&self) -> crate::Result<CurrentIndex> {
+        let name = self.current()?.ok_or_else(|| self.no_current_index())?;
+        let reader = self.open_index(&name)?.reader()?;
+        Ok(CurrentIndex { name, reader })
+    }
+
+    fn index_path(&self, name: &str) -> crate::Result<PathBuf> {
+        let is_valid = !name.is_empty()
+            && name != "."
+            && name != ".."
+            && !name.contains(|c: char| std::path::is_separator(c));
+        if !is_valid {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Invalid index name {name:?}"
+            )));
+        }
+        Ok(self.root_path.join(name))
+    }
+
+    fn no_current_index(&self) -> TantivyError {
+        TantivyError::InvalidArgument(format!(
+            "The alias {:?} has no current index",
+            self.root_path
+        ))
+    }
+}
+
+struct CurrentIndex {
+    name: String,
+    reader: IndexReader,
+}
+
+/// A reader over the current index of an [`IndexAlias`].
+///
+/// The reader follows the commits of the current index according to the default reload policy,
+/// but only moves to a newly swapped in index when [`IndexAliasReader::reload()`] is called.
+pub struct IndexAliasReader {
+    alias: IndexAlias,
+    current: ArcSwap<CurrentIndex>,
+}
+
+impl IndexAliasReader {
+    /// Returns the name of the index the reader is currently reading.
+    pub fn current(&self) -> String {
+        self.current.load().name.clone()
+    }
+
+    /// Moves to the current index of the alias if it was swapped, or reloads the index
+    /// otherwise.
+    pub fn reload(&self) -> crate::Result<()> {
+        let current = self.current.load();
+        if self.alias.current()?.as_deref() == Some(current.name.as_str()) {
+            return current.reader.reload();
+        }
+        self.current
+            .store(Arc::new(self.alias.open_current_reader()?));
+        Ok(())
+    }
+
+    /// Returns a searcher over the last loaded state of the current index.
+    pub fn searcher(&self) -> Searcher {
+        self.current.load().reader.searcher()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexAlias;
+    use crate::schema::{Schema, STRING};
+    use crate::IndexWriter;
+
+    #[test]
+    fn test_index_alias_swap() -> crate::Result<()> {
+        let tempdir = tempfile::TempDir::new()?;
+        let alias = IndexAlias::open(tempdir.path().join("products"))?;
+        assert_eq!(alias.current()?, None);
+        assert!(alias.reader().is_err());
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STRING);
+        let schema = schema_builder.build();
+        let index = alias.create_index("v1", schema.clone())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "a"))?;
+        index_writer.commit()?;
+        assert_eq!(alias.swap("v1")?, None);
+        let reader = alias.reader()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+
+        let index = alias.create_index("v2", schema)?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "a"))?;
+        index_writer.add_document(doc!(title => "b"))?;
+        index_writer.commit()?;
+        drop(index_writer);
+        // The swap is only visible after a reload.
+        let previous_searcher = reader.searcher();
+        assert_eq!(alias.swap("v2")?, Some("v1".to_string()));
+        assert_eq!(reader.current(), "v1");
+        reader.reload()?;
+        assert_eq!(reader.current(), "v2");
+        assert_eq!(reader.searcher().num_docs(), 2);
+        assert_eq!(previous_searcher.num_docs(), 1);
+
+        assert!(alias.delete_index("v2").is_err());
+        alias.delete_index("v1")?;
+        assert!(alias.open_index("v1").is_err());
+        assert!(alias
+            .create_index("../v3", Schema::builder().build())
+            .is_err());
+        Ok(())
+    }
+}
@@ -1,21 +1,43 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
 use super::SegmentComponent;
 use crate::core::SegmentId;
+use crate::directory::error::Incompatibility;
 use crate::schema::Schema;
 use crate::store::Compressor;
-use crate::{Inventory, Opstamp, TrackedObject};
+use crate::{DocId, Inventory, Opstamp, TrackedObject, Version};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct DeleteMeta {
     num_deleted_docs: u32,
     opstamp: Opstamp,
+    /// Opstamps of the earlier delete files, oldest first, kept with soft deletes.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    previous_opstamps: Vec<Opstamp>,
+    /// The deleted documents an undelete can restore, kept with soft deletes.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    restorable_deletes: Vec<RestorableDelete>,
+}
+
+/// Deleted documents of a segment that an undelete of `term` can restore.
+///
+/// They were deleted by a `delete_term` of `term` at `opstamp`, and by no other delete.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RestorableDelete {
+    /// The serialized deleted term.
+    pub term: Vec<u8>,
+    /// The opstamp of the delete.
+    pub opstamp: Opstamp,
+    /// The deleted documents, sorted.
+    pub docs: Vec<DocId>,
 }
 
 #[derive(Clone, Default)]
@@ -39,6 +61,8 @@ impl SegmentMetaInventory {
             max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: None,
+            fast_field_updates: None,
+            attributes: BTreeMap::new(),
         };
         SegmentMeta::from(self.inventory.track(inner))
     }
@@ -111,7 +135,7 @@ impl SegmentMeta {
     /// is by removing all files that have been created by tantivy
     /// and are not used by any segment anymore.
     pub fn list_files(&self) -> HashSet<PathBuf> {
-        if self
+        let mut files = if self
             .tracked
             .include_temp_doc_store
             .load(std::sync::atomic::Ordering::Relaxed)
@@ -124,7 +148,13 @@ impl SegmentMeta {
                 .filter(|comp| *comp != &SegmentComponent::TempStore)
                 .map(|component| self.relative_path(*component))
                 .collect::<HashSet<PathBuf>>()
-        }
+        };
+        files.extend(
+            self.previous_delete_opstamps()
+                .iter()
+                .map(|&opstamp| self.delete_file_path(opstamp)),
+        );
+        files
     }
 
     /// Returns the relative path of a component of our segment.
@@ -141,11 +171,20 @@ impl SegmentMeta {
             SegmentComponent::TempStore => ".store.temp".to_string(),
             SegmentComponent::FastFields => ".fast".to_string(),
             SegmentComponent::FieldNorms => ".fieldnorm".to_string(),
+            SegmentComponent::TermVectors => ".tv".to_string(),
             SegmentComponent::Delete => format!(".{}.del", self.delete_opstamp().unwrap_or(0)),
+            SegmentComponent::FastFieldUpdates => {
+                format!(".{}.upd", self.fast_field_updates_opstamp().unwrap_or(0))
+            }
         });
         PathBuf::from(path)
     }
 
+    /// Returns the relative path of the delete file written at `opstamp`.
+    pub(crate) fn delete_file_path(&self, opstamp: Opstamp) -> PathBuf {
+        PathBuf::from(format!("{}.{}.del", self.id().uuid_string(), opstamp))
+    }
+
     /// Return the highest doc id + 1
     ///
     /// If there are no deletes, then num_docs = max_docs
@@ -169,12 +208,45 @@ impl SegmentMeta {
             .map(|delete_meta| delete_meta.opstamp)
     }
 
+    /// Returns the `Opstamp`s of the earlier delete files of the segment, oldest first.
+    ///
+    /// They are only kept by the `IndexWriter`s with soft deletes, so that merges can tell
+    /// when the deleted documents were deleted.
+    pub fn previous_delete_opstamps(&self) -> &[Opstamp] {
+        self.tracked
+            .deletes
+            .as_ref()
+            .map(|delete_meta| &delete_meta.previous_opstamps[..])
+            .unwrap_or(&[])
+    }
+
+    /// Returns the deleted documents of the segment that an undelete can restore.
+    pub(crate) fn restorable_deletes(&self) -> &[RestorableDelete] {
+        self.tracked
+            .deletes
+            .as_ref()
+            .map(|delete_meta| &delete_meta.restorable_deletes[..])
+            .unwrap_or(&[])
+    }
+
     /// Returns true iff the segment meta contains
     /// delete information.
     pub fn has_deletes(&self) -> bool {
         self.num_deleted_docs() > 0
     }
 
+    /// Returns the `Opstamp` of the fast field updates file of the segment,
+    /// if some fast field values of the segment have been updated.
+    pub fn fast_field_updates_opstamp(&self) -> Option<Opstamp> {
+        self.tracked.fast_field_updates
+    }
+
+    /// Returns true iff some fast field values of the segment have been updated
+    /// since it was written.
+    pub fn has_fast_field_updates(&self) -> bool {
+        self.tracked.fast_field_updates.is_some()
+    }
+
     /// Updates the max_doc value from the `SegmentMeta`.
     ///
     /// This method is only used when updating `max_doc` from 0
@@ -186,7 +258,47 @@ impl SegmentMeta {
             segment_id: inner_meta.segment_id,
             max_doc,
             deletes: None,
+            fast_field_updates: inner_meta.fast_field_updates,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            attributes: inner_meta.attributes.clone(),
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the custom attributes of the segment.
+    ///
+    /// They are computed by the
+    /// [`SegmentAttributesProvider`](crate::indexer::SegmentAttributesProvider) of the
+    /// `IndexWriter` when the segment gets written, and are empty otherwise.
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.tracked.attributes
+    }
+
+    /// Sets the custom attributes of the segment.
+    #[must_use]
+    pub(crate) fn with_attributes(self, attributes: BTreeMap<String, String>) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            fast_field_updates: inner_meta.fast_field_updates,
+            include_temp_doc_store: inner_meta.include_temp_doc_store.clone(),
+            attributes,
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Records that the fast field updates of the segment are stored in the file
+    /// associated with the given opstamp.
+    #[must_use]
+    pub(crate) fn with_fast_field_updates(self, opstamp: Opstamp) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            fast_field_updates: Some(opstamp),
+            include_temp_doc_store: inner_meta.include_temp_doc_store.clone(),
+            attributes: inner_meta.attributes.clone(),
         });
         SegmentMeta { tracked }
     }
@@ -194,6 +306,18 @@ impl SegmentMeta {
     #[doc(hidden)]
     #[must_use]
     pub fn with_delete_meta(self, num_deleted_docs: u32, opstamp: Opstamp) -> SegmentMeta {
+        self.with_delete_generations(num_deleted_docs, opstamp, Vec::new())
+    }
+
+    /// Records the delete file of `opstamp`, keeping the earlier delete files of
+    /// `previous_opstamps`.
+    #[must_use]
+    pub(crate) fn with_delete_generations(
+        self,
+        num_deleted_docs: u32,
+        opstamp: Opstamp,
+        previous_opstamps: Vec<Opstamp>,
+    ) -> SegmentMeta {
         assert!(
             num_deleted_docs <= self.max_doc(),
             "There cannot be more deleted docs than there are docs."
@@ -201,12 +325,69 @@ impl SegmentMeta {
         let delete_meta = DeleteMeta {
             num_deleted_docs,
             opstamp,
+            previous_opstamps,
+            restorable_deletes: Vec::new(),
         };
         let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
             segment_id: inner_meta.segment_id,
             max_doc: inner_meta.max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: Some(delete_meta),
+            fast_field_updates: inner_meta.fast_field_updates,
+            attributes: inner_meta.attributes.clone(),
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Records a new delete file written at `opstamp`, keeping the current one as an earlier
+    /// delete generation.
+    #[must_use]
+    pub(crate) fn with_new_delete_generation(
+        self,
+        num_deleted_docs: u32,
+        opstamp: Opstamp,
+    ) -> SegmentMeta {
+        let mut previous_opstamps = self.previous_delete_opstamps().to_vec();
+        previous_opstamps.extend(self.delete_opstamp());
+        self.with_delete_generations(num_deleted_docs, opstamp, previous_opstamps)
+    }
+
+    /// Records the deleted documents of the segment that an undelete can restore.
+    ///
+    /// The segment is required to have a delete file, unless `restorable_deletes` is empty.
+    #[must_use]
+    pub(crate) fn with_restorable_deletes(
+        self,
+        restorable_deletes: Vec<RestorableDelete>,
+    ) -> SegmentMeta {
+        assert!(
+            restorable_deletes.is_empty() || self.tracked.deletes.is_some(),
+            "Restorable deletes require a delete file."
+        );
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone().map(|delete_meta| DeleteMeta {
+                restorable_deletes,
+                ..delete_meta
+            }),
+            fast_field_updates: inner_meta.fast_field_updates,
+            include_temp_doc_store: inner_meta.include_temp_doc_store.clone(),
+            attributes: inner_meta.attributes.clone(),
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the meta of the segment without its deletes, to read its deleted documents.
+    #[must_use]
+    pub(crate) fn without_deletes(self) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: None,
+            fast_field_updates: inner_meta.fast_field_updates,
+            include_temp_doc_store: inner_meta.include_temp_doc_store.clone(),
+            attributes: inner_meta.attributes.clone(),
         });
         SegmentMeta { tracked }
     }
@@ -217,11 +398,18 @@ struct InnerSegmentMeta {
     segment_id: SegmentId,
     max_doc: u32,
     deletes: Option<DeleteMeta>,
+    /// Opstamp of the file holding the updated fast field values, if any.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fast_field_updates: Option<Opstamp>,
     /// If you want to avoid the SegmentComponent::TempStore file to be covered by
     /// garbage collection and deleted, set this to true. This is used during merge.
     #[serde(skip)]
     #[serde(default = "default_temp_store")]
     pub(crate) include_temp_doc_store: Arc<AtomicBool>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, String>,
 }
 fn default_temp_store() -> Arc<AtomicBool> {
     Arc::new(AtomicBool::new(false))
@@ -265,6 +453,15 @@ pub struct IndexSettings {
     #[serde(default = "default_docstore_blocksize")]
     /// The size of each block that will be compressed and written to disk
     pub docstore_blocksize: usize,
+    /// Name of a date fast field holding the expiry date of the documents.
+    ///
+    /// Documents whose expiry date is reached are filtered out of searchers, as if they
+    /// were deleted, and are physically removed when their segment gets merged.
+    /// Expiry is evaluated when the segments are opened: searchers only filter out the
+    /// documents expired at the time they were loaded.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry_field: Option<String>,
 }
 
 /// Must be a function to be compatible with serde defaults
@@ -279,6 +476,7 @@ impl Default for IndexSettings {
             docstore_compression: Compressor::default(),
             docstore_blocksize: default_docstore_blocksize(),
             docstore_compress_dedicated_thread: true,
+            expiry_field: None,
         }
     }
 }
@@ -321,10 +519,12 @@ impl Order {
 /// * the searchable segments,
 /// * the index `docstamp`
 /// * the schema
-#[derive(Clone, Serialize)]
+///
+/// The version of tantivy that wrote the file is serialized alongside, so that
+/// an index written in a newer, unsupported format is refused with a clear error.
+#[derive(Clone)]
 pub struct IndexMeta {
     /// `IndexSettings` to configure index options.
-    #[serde(default)]
     pub index_settings: IndexSettings,
     /// List of `SegmentMeta` information associated with each finalized segment of the index.
     pub segments: Vec<SegmentMeta>,
@@ -337,10 +537,44 @@ pub struct IndexMeta {
     /// Upon commit, clients can optionally add a small `String` payload to their commit
     /// to help identify this commit.
     /// This payload is entirely unused by tantivy.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub payload: Option<String>,
 }
 
+impl Serialize for IndexMeta {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct SerializableIndexMeta<'a> {
+            index_settings: &'a IndexSettings,
+            segments: &'a [SegmentMeta],
+            schema: &'a Schema,
+            opstamp: Opstamp,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            payload: Option<&'a str>,
+            version: &'a Version,
+        }
+        SerializableIndexMeta {
+            index_settings: &self.index_settings,
+            segments: &self.segments,
+            schema: &self.schema,
+            opstamp: self.opstamp,
+            payload: self.payload.as_deref(),
+            version: crate::version(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Only the version of a `meta.json` file.
+///
+/// It is read before the rest of the file, which may not be deserializable
+/// if it was written in a newer format.
+#[derive(Deserialize)]
+struct IndexMetaVersion {
+    // `meta.json` files written before tantivy 0.22 do not have a version.
+    #[serde(default)]
+    version: Option<Version>,
+}
+
 #[derive(Deserialize, Debug)]
 struct UntrackedIndexMeta {
     pub segments: Vec<InnerSegmentMeta>,
@@ -391,6 +625,19 @@ impl IndexMeta {
         let untracked_meta_json: UntrackedIndexMeta = serde_json::from_str(meta_json)?;
         Ok(untracked_meta_json.track(inventory))
     }
+
+    /// Checks that the `meta.json` content was written in an index format
+    /// supported by this version of tantivy.
+    ///
+    /// Malformed content is not reported here, but when deserializing the `IndexMeta`.
+    pub(crate) fn check_compatibility(meta_json: &str) -> Result<(), Incompatibility> {
+        match serde_json::from_str::<IndexMetaVersion>(meta_json) {
+            Ok(IndexMetaVersion {
+                version: Some(version),
+            }) => version.check_compatibility(),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl fmt::Debug for IndexMeta {
@@ -438,7 +685,9 @@ mod tests {
         let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
         assert_eq!(
             json,
-            r#"{"index_settings":{"sort_by_field":{"field":"text","order":"Asc"},"docstore_compression":"lz4","docstore_blocksize":16384},"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","fieldnorms":true,"tokenizer":"default"},"stored":false,"fast":false}}],"opstamp":0}"#
+            r#"{"index_settings":{"sort_by_field":{"field":"text","order":"Asc"},"docstore_compression":"lz4","docstore_blocksize":16384},"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","fieldnorms":true,"tokenizer":"default"},"stored":false,"fast":false}}],"opstamp":0,"version":"#.to_string()
+                + &serde_json::to_string(crate::version()).unwrap()
+                + "}"
         );
 
         let deser_meta: UntrackedIndexMeta = serde_json::from_str(&json).unwrap();
@@ -466,6 +715,7 @@ mod tests {
                 }),
                 docstore_blocksize: 1_000_000,
                 docstore_compress_dedicated_thread: true,
+                expiry_field: None,
             },
             segments: Vec::new(),
             schema,
@@ -475,7 +725,9 @@ mod tests {
         let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
         assert_eq!(
             json,
-            r#"{"index_settings":{"sort_by_field":{"field":"text","order":"Asc"},"docstore_compression":"zstd(compression_level=4)","docstore_blocksize":1000000},"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","fieldnorms":true,"tokenizer":"default"},"stored":false,"fast":false}}],"opstamp":0}"#
+            r#"{"index_settings":{"sort_by_field":{"field":"text","order":"Asc"},"docstore_compression":"zstd(compression_level=4)","docstore_blocksize":1000000},"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","fieldnorms":true,"tokenizer":"default"},"stored":false,"fast":false}}],"opstamp":0,"version":"#.to_string()
+                + &serde_json::to_string(crate::version()).unwrap()
+                + "}"
         );
 
         let deser_meta: UntrackedIndexMeta = serde_json::from_str(&json).unwrap();
@@ -530,7 +782,8 @@ mod tests {
                 sort_by_field: None,
                 docstore_compression: Compressor::default(),
                 docstore_compress_dedicated_thread: true,
-                docstore_blocksize: 16_384
+                docstore_blocksize: 16_384,
+                expiry_field: None,
             }
         );
         {
@@ -562,4 +815,67 @@ mod tests {
             assert_eq!(index_settings_deser, index_settings);
         }
     }
+
+    #[test]
+    fn test_index_meta_newer_format_version_is_refused() {
+        use crate::core::META_FILEPATH;
+        use crate::directory::error::Incompatibility;
+        use crate::directory::{Directory, RamDirectory};
+        use crate::{Index, TantivyError, Version};
+
+        let directory = RamDirectory::create();
+        Index::create(
+            directory.clone(),
+            Schema::builder().build(),
+            IndexSettings::default(),
+        )
+        .unwrap();
+        let meta_json = String::from_utf8(directory.atomic_read(&META_FILEPATH).unwrap()).unwrap();
+        let mut meta_value: serde_json::Value = serde_json::from_str(&meta_json).unwrap();
+        assert_eq!(
+            meta_value["version"]["index_format_version"],
+            crate::INDEX_FORMAT_VERSION
+        );
+        meta_value["version"]["index_format_version"] = (crate::INDEX_FORMAT_VERSION + 1).into();
+        directory
+            .atomic_write(&META_FILEPATH, meta_value.to_string().as_bytes())
+            .unwrap();
+        let Err(err) = Index::open(directory.clone()) else {
+            panic!("an index with a newer format should not be opened");
+        };
+        assert!(matches!(
+            err,
+            TantivyError::IncompatibleIndex(Incompatibility::IndexMismatch { .. })
+        ));
+
+        // A reader limited to format 6 refuses the `meta.json` written in format 7.
+        meta_value["version"]["index_format_version"] = 7.into();
+        let meta_version: Version = serde_json::from_value(meta_value["version"].clone()).unwrap();
+        assert!(matches!(
+            meta_version.check_compatibility_with(4..=6),
+            Err(Incompatibility::IndexMismatch { .. })
+        ));
+
+        // The formats from the oldest supported one to format 6 are still opened.
+        for index_format_version in crate::INDEX_FORMAT_OLDEST_SUPPORTED_VERSION..=6 {
+            meta_value["version"]["index_format_version"] = index_format_version.into();
+            directory
+                .atomic_write(&META_FILEPATH, meta_value.to_string().as_bytes())
+                .unwrap();
+            assert!(Index::open(directory.clone()).is_ok());
+        }
+        meta_value["version"]["index_format_version"] =
+            (crate::INDEX_FORMAT_OLDEST_SUPPORTED_VERSION - 1).into();
+        directory
+            .atomic_write(&META_FILEPATH, meta_value.to_string().as_bytes())
+            .unwrap();
+        assert!(Index::open(directory.clone()).is_err());
+
+        // `meta.json` files written by older versions do not have a version.
+        meta_value.as_object_mut().unwrap().remove("version");
+        directory
+            .atomic_write(&META_FILEPATH, meta_value.to_string().as_bytes())
+            .unwrap();
+        assert!(Index::open(directory).is_ok());
+    }
 }
@@ -0,0 +1,411 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::core::{Index, SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
+use crate::directory::Directory;
+use crate::docset::{DocSet, TERMINATED};
+use crate::schema::{Field, IndexRecordOption};
+use crate::termvector::fields_with_term_vectors;
+use crate::DocId;
+
+/// A problem found while validating an index with [`Index::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// A file of the segment is missing from the directory.
+    MissingFile(PathBuf),
+    /// The checksum recorded in the footer of a file does not match its content.
+    ChecksumMismatch(PathBuf),
+    /// A file of the segment could not be read.
+    UnreadableFile {
+        /// Path of the file.
+        path: PathBuf,
+        /// Description of the error.
+        error: String,
+    },
+    /// The segment could not be opened.
+    CannotOpenSegment(String),
+    /// A component of the segment does not have as many documents as the segment meta states.
+    DocCountMismatch {
+        /// The component holding the wrong number of documents.
+        component: SegmentComponent,
+        /// The field concerned, for per-field data such as fieldnorms.
+        field: Option<Field>,
+        /// Number of documents stated in the segment meta.
+        expected: u32,
+        /// Number of documents found in the component.
+        actual: u32,
+    },
+    /// The postings list of a term is inconsistent with the term dictionary
+    /// or with the number of documents in the segment.
+    InvalidPostings {
+        /// The field of the term.
+        field: Field,
+        /// The term bytes, as stored in the term dictionary.
+        term: Vec<u8>,
+        /// Description of the inconsistency.
+        reason: String,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::MissingFile(path) => write!(f, "Missing file {path:?}"),
+            ValidationIssue::ChecksumMismatch(path) => write!(f, "Checksum mismatch in {path:?}"),
+            ValidationIssue::UnreadableFile { path, error } => {
+                write!(f, "Cannot read file {path:?}: {error}")
+            }
+            ValidationIssue::CannotOpenSegment(error) => write!(f, "Cannot open segment: {error}"),
+            ValidationIssue::DocCountMismatch {
+                component,
+                field,
+                expected,
+                actual,
+            } => {
+                write!(f, "{component:?}")?;
+                if let Some(field) = field {
+                    write!(f, " (field {})", field.field_id())?;
+                }
+                write!(f, " holds {actual} docs, expected {expected}")
+            }
+            ValidationIssue::InvalidPostings {
+                field,
+                term,
+                reason,
+            } => write!(
+                f,
+                "Invalid postings for term {term:?} of field {}: {reason}",
+                field.field_id()
+            ),
+        }
+    }
+}
+
+/// Result of the validation of a single segment.
+#[derive(Clone, Debug)]
+pub struct SegmentValidationReport {
+    /// Id of the segment.
+    pub segment_id: SegmentId,
+    /// Problems found in the segment. Empty if the segment is valid.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl SegmentValidationReport {
+    /// Returns true if no problem was found in the segment.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Result of [`Index::validate`], with one entry per searchable segment.
+#[derive(Clone, Debug, Default)]
+pub struct IndexValidationReport {
+    /// Per segment reports, in the order of the index meta.
+    pub segments: Vec<SegmentValidationReport>,
+}
+
+impl IndexValidationReport {
+    /// Returns true if no problem was found in any of the segments.
+    pub fn is_valid(&self) -> bool {
+        self.segments.iter().all(SegmentValidationReport::is_valid)
+    }
+
+    /// Iterates over all of the problems found, together with the segment they belong to.
+    pub fn issues(&self) -> impl Iterator<Item = (SegmentId, &ValidationIssue)> + '_ {
+        self.segments.iter().flat_map(|segment_report| {
+            segment_report
+                .issues
+                .iter()
+                .map(move |issue| (segment_report.segment_id, issue))
+        })
+    }
+}
+
+pub(crate) fn validate_index(index: &Index) -> crate::Result<IndexValidationReport> {
+    let segments = index
+        .searchable_segment_metas()?
+        .into_iter()
+        .map(|segment_meta| validate_segment(index, segment_meta))
+        .collect();
+    Ok(IndexValidationReport { segments })
+}
+
+fn validate_segment(index: &Index, segment_meta: SegmentMeta) -> SegmentValidationReport {
+    let segment_id = segment_meta.id();
+    let mut issues = validate_segment_files(index, &segment_meta);
+    // Opening a segment with missing or corrupted files may fail in arbitrary ways.
+    if issues.is_empty() {
+        match SegmentReader::open(&index.segment(segment_meta.clone())) {
+            Ok(segment_reader) => {
+                validate_doc_counts(&segment_meta, &segment_reader, &mut issues);
+                validate_postings(&segment_reader, &mut issues);
+            }
+            Err(err) => issues.push(ValidationIssue::CannotOpenSegment(err.to_string())),
+        }
+    }
+    SegmentValidationReport { segment_id, issues }
+}
+
+fn validate_segment_files(index: &Index, segment_meta: &SegmentMeta) -> Vec<ValidationIssue> {
+    let directory = index.directory();
+    // Segments written before term vectors existed have no term vectors file: it is only
+    // required when some field actually has term vectors.
+    let has_term_vectors = !fields_with_term_vectors(&index.schema()).is_empty();
+    let components = SegmentComponent::iterator().filter(|component| match component {
+        SegmentComponent::TempStore => false,
+        SegmentComponent::TermVectors => has_term_vectors,
+        SegmentComponent::Delete => segment_meta.has_deletes(),
+        SegmentComponent::FastFieldUpdates => segment_meta.has_fast_field_updates(),
+        _ => true,
+    });
+    let mut issues = Vec::new();
+    for &component in components {
+        let path = segment_meta.relative_path(component);
+        match directory.exists(&path) {
+            Ok(true) => {}
+            Ok(false) => {
+                issues.push(ValidationIssue::MissingFile(path));
+                continue;
+            }
+            Err(err) => {
+                issues.push(ValidationIssue::UnreadableFile {
+                    path,
+                    error: err.to_string(),
+                });
+                continue;
+            }
+        }
+        match directory.validate_checksum(&path) {
+            Ok(true) => {}
+            Ok(false) => issues.push(ValidationIssue::ChecksumMismatch(path)),
+            Err(err) => issues.push(ValidationIssue::UnreadableFile {
+                path,
+                error: err.to_string(),
+            }),
+        }
+    }
+    issues
+}
+
+fn validate_doc_counts(
+    segment_meta: &SegmentMeta,
+    segment_reader: &SegmentReader,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let max_doc = segment_meta.max_doc();
+    match segment_reader.get_store_reader(0) {
+        Ok(store_reader) => {
+            let num_stored_docs = store_reader
+                .block_checkpoints()
+                .last()
+                .map(|checkpoint| checkpoint.doc_range.end)
+                .unwrap_or(0);
+            check_doc_count(
+                issues,
+                SegmentComponent::Store,
+                None,
+                max_doc,
+                num_stored_docs,
+            );
+        }
+        Err(err) => issues.push(ValidationIssue::UnreadableFile {
+            path: segment_meta.relative_path(SegmentComponent::Store),
+            error: err.to_string(),
+        }),
+    }
+    check_doc_count(
+        issues,
+        SegmentComponent::FastFields,
+        None,
+        max_doc,
+        segment_reader.fast_fields().columnar().num_rows(),
+    );
+    for (field, field_entry) in segment_reader.schema().fields() {
+        if !field_entry.has_fieldnorms() {
+            continue;
+        }
+        match segment_reader.fieldnorms_readers().get_field(field) {
+            Ok(Some(fieldnorm_reader)) => check_doc_count(
+                issues,
+                SegmentComponent::FieldNorms,
+                Some(field),
+                max_doc,
+                fieldnorm_reader.num_docs(),
+            ),
+            Ok(None) => {}
+            Err(err) => issues.push(ValidationIssue::UnreadableFile {
+                path: segment_meta.relative_path(SegmentComponent::FieldNorms),
+                error: err.to_string(),
+            }),
+        }
+    }
+    check_doc_count(
+        issues,
+        SegmentComponent::Delete,
+        None,
+        segment_meta.num_deleted_docs(),
+        segment_reader.num_deleted_docs(),
+    );
+}
+
+fn check_doc_count(
+    issues: &mut Vec<ValidationIssue>,
+    component: SegmentComponent,
+    field: Option<Field>,
+    expected: u32,
+    actual: u32,
+) {
+    if actual != expected {
+        issues.push(ValidationIssue::DocCountMismatch {
+            component,
+            field,
+            expected,
+            actual,
+        });
+    }
+}
+
+fn validate_postings(segment_reader: &SegmentReader, issues: &mut Vec<ValidationIssue>) {
+    let max_doc = segment_reader.max_doc();
+    for (field, field_entry) in segment_reader.schema().fields() {
+        if !field_entry.is_indexed() {
+            continue;
+        }
+        let mut invalid_postings = |term: &[u8], reason: String| {
+            issues.push(ValidationIssue::InvalidPostings {
+                field,
+                term: term.to_vec(),
+                reason,
+            });
+        };
+        let inverted_index = match segment_reader.inverted_index(field) {
+            Ok(inverted_index) => inverted_index,
+            Err(err) => {
+                invalid_postings(&[], err.to_string());
+                continue;
+            }
+        };
+        let mut term_stream = match inverted_index.terms().stream() {
+            Ok(term_stream) => term_stream,
+            Err(err) => {
+                invalid_postings(&[], err.to_string());
+                continue;
+            }
+        };
+        while term_stream.advance() {
+            let term_info = term_stream.value();
+            let mut postings = match inverted_index
+                .read_postings_from_terminfo(term_info, IndexRecordOption::Basic)
+            {
+                Ok(postings) => postings,
+                Err(err) => {
+                    invalid_postings(term_stream.key(), err.to_string());
+                    continue;
+                }
+            };
+            let mut num_docs = 0u32;
+            let mut last_doc: Option<DocId> = None;
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if doc >= max_doc || last_doc.map(|last_doc| doc <= last_doc).unwrap_or(false) {
+                    break;
+                }
+                last_doc = Some(doc);
+                num_docs += 1;
+                doc = postings.advance();
+            }
+            if doc != TERMINATED {
+                invalid_postings(
+                    term_stream.key(),
+                    format!("doc {doc} out of order or beyond max doc {max_doc}"),
+                );
+            } else if num_docs != term_info.doc_freq {
+                invalid_postings(
+                    term_stream.key(),
+                    format!(
+                        "{num_docs} docs in postings, expected {}",
+                        term_info.doc_freq
+                    ),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidationIssue;
+    use crate::core::SegmentComponent;
+    use crate::directory::{Directory, RamDirectory};
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, FAST, STORED, STRING, TEXT};
+    use crate::{Index, IndexSettings, IndexWriter, Term};
+
+    #[test]
+    fn test_validate_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST | STORED);
+        let key = schema_builder.add_text_field("key", STRING);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0u64..20 {
+            index_writer.add_document(doc!(
+                id => i,
+                key => format!("key{i}"),
+                body => "hello happy tax payer",
+            ))?;
+            if i == 9 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.delete_term(Term::from_field_text(key, "key3"));
+        index_writer.commit()?;
+
+        let report = index.validate()?;
+        assert_eq!(report.segments.len(), 2);
+        assert!(report.is_valid());
+
+        let segment_meta = &index.searchable_segment_metas()?[0];
+        let store_path = segment_meta.relative_path(SegmentComponent::Store);
+        index.directory().delete(&store_path).unwrap();
+        let report = index.validate()?;
+        assert!(!report.is_valid());
+        let issues: Vec<_> = report.issues().collect();
+        assert_eq!(
+            issues,
+            vec![(segment_meta.id(), &ValidationIssue::MissingFile(store_path))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_index_checksum_mismatch() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let directory = RamDirectory::create();
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(body => "hello"))?;
+        index_writer.commit()?;
+
+        let segment_meta = &index.searchable_segment_metas()?[0];
+        let postings_path = segment_meta.relative_path(SegmentComponent::Postings);
+        let mut data = directory.atomic_read(&postings_path).unwrap();
+        data[0] ^= 0xFF;
+        directory.atomic_write(&postings_path, &data)?;
+
+        let report = index.validate()?;
+        let issues: Vec<_> = report.issues().map(|(_, issue)| issue.clone()).collect();
+        assert_eq!(
+            issues,
+            vec![ValidationIssue::ChecksumMismatch(postings_path)]
+        );
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@ use crate::directory::FileSlice;
 use crate::positions::PositionReader;
 use crate::postings::{BlockSegmentPostings, SegmentPostings, TermInfo};
 use crate::schema::{IndexRecordOption, Term, Type, JSON_END_OF_PATH};
-use crate::termdict::TermDictionary;
+use crate::termdict::{TermBloomFilter, TermDictionary};
 
 /// The inverted index reader is in charge of accessing
 /// the inverted index associated with a specific field.
@@ -27,6 +27,7 @@ pub struct InvertedIndexReader {
     positions_file_slice: FileSlice,
     record_option: IndexRecordOption,
     total_num_tokens: u64,
+    bloom_filter_opt: Option<TermBloomFilter>,
 }
 
 impl InvertedIndexReader {
@@ -36,6 +37,7 @@ impl InvertedIndexReader {
         postings_file_slice: FileSlice,
         positions_file_slice: FileSlice,
         record_option: IndexRecordOption,
+        bloom_filter_opt: Option<TermBloomFilter>,
     ) -> io::Result<InvertedIndexReader> {
         let (total_num_tokens_slice, postings_body) = postings_file_slice.split(8);
         let total_num_tokens = u64::deserialize(&mut total_num_tokens_slice.read_bytes()?)?;
@@ -45,6 +47,7 @@ impl InvertedIndexReader {
             positions_file_slice,
             record_option,
             total_num_tokens,
+            bloom_filter_opt,
         })
     }
 
@@ -57,11 +60,27 @@ impl InvertedIndexReader {
             positions_file_slice: FileSlice::empty(),
             record_option,
             total_num_tokens: 0u64,
+            bloom_filter_opt: None,
         }
     }
 
+    /// Returns false if the term is definitely absent from this inverted index,
+    /// without looking it up in the term dictionary.
+    ///
+    /// This relies on the bloom filter written for `raw` tokenized text fields.
+    /// For other fields, this always returns true.
+    pub fn may_contain_term(&self, term: &Term) -> bool {
+        self.bloom_filter_opt
+            .as_ref()
+            .map(|bloom_filter| bloom_filter.may_contain(term.serialized_value_bytes()))
+            .unwrap_or(true)
+    }
+
     /// Returns the term info associated with the term.
     pub fn get_term_info(&self, term: &Term) -> io::Result<Option<TermInfo>> {
+        if !self.may_contain_term(term) {
+            return Ok(None);
+        }
         self.termdict.get(term.serialized_value_bytes())
     }
 
@@ -221,11 +240,40 @@ impl InvertedIndexReader {
             .map(|term_info| term_info.doc_freq)
             .unwrap_or(0u32))
     }
+
+    /// Returns the total number of occurrences of the term, summed over all of the
+    /// documents containing it (including deleted documents).
+    ///
+    /// This requires decoding the term frequencies of the entire posting list.
+    /// If the field was indexed without frequencies, each document accounts for
+    /// a single occurrence.
+    pub fn total_term_freq(&self, term: &Term) -> io::Result<u64> {
+        let Some(mut block_postings) =
+            self.read_block_postings(term, IndexRecordOption::WithFreqs)?
+        else {
+            return Ok(0u64);
+        };
+        let mut total_term_freq = 0u64;
+        loop {
+            let block_len = block_postings.block_len();
+            if block_len == 0 {
+                break;
+            }
+            total_term_freq += (0..block_len)
+                .map(|idx| u64::from(block_postings.freq(idx)))
+                .sum::<u64>();
+            block_postings.advance();
+        }
+        Ok(total_term_freq)
+    }
 }
 
 #[cfg(feature = "quickwit")]
 impl InvertedIndexReader {
     pub(crate) async fn get_term_info_async(&self, term: &Term) -> io::Result<Option<TermInfo>> {
+        if !self.may_contain_term(term) {
+            return Ok(None);
+        }
         self.termdict.get_async(term.serialized_value_bytes()).await
     }
 
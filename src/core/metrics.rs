@@ -0,0 +1,110 @@
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use common::{AntiCallToken, TerminatingWrite};
+use serde::Serialize;
+
+/// A snapshot of the activity of an [`Index`](crate::Index), as returned by
+/// [`Index::metrics()`](crate::Index::metrics).
+///
+/// The counters are cumulative since the index was opened, and are shared by the clones of the
+/// `Index` object, as well as by the writers, readers and searchers created from it. They are
+/// not persisted, and opening the same index twice gives two independent sets of counters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct IndexMetrics {
+    /// Number of documents written into new segments by the index writers.
+    pub num_docs_indexed: u64,
+    /// Number of successful commits.
+    pub num_commits: u64,
+    /// Number of successful merges.
+    pub num_merges: u64,
+    /// Number of bytes written to segment files, by indexing and merging.
+    pub num_bytes_written: u64,
+    /// Number of queries executed by the searchers.
+    pub num_searches: u64,
+    /// Number of document fetches served from the doc store block cache.
+    pub doc_store_cache_hits: u64,
+    /// Number of document fetches that required to read and decompress a doc store block.
+    pub doc_store_cache_misses: u64,
+}
+
+impl IndexMetrics {
+    /// Returns the ratio of the document fetches served from the doc store cache, or `None` if
+    /// no document was fetched yet.
+    pub fn doc_store_cache_hit_rate(&self) -> Option<f64> {
+        let num_fetches = self.doc_store_cache_hits + self.doc_store_cache_misses;
+        if num_fetches == 0 {
+            return None;
+        }
+        Some(self.doc_store_cache_hits as f64 / num_fetches as f64)
+    }
+}
+
+/// The live counters behind [`IndexMetrics`].
+#[derive(Default)]
+pub(crate) struct MetricsCounters {
+    pub num_docs_indexed: AtomicU64,
+    pub num_commits: AtomicU64,
+    pub num_merges: AtomicU64,
+    pub num_bytes_written: AtomicU64,
+    pub num_searches: AtomicU64,
+    pub doc_store_cache_hits: AtomicU64,
+    pub doc_store_cache_misses: AtomicU64,
+}
+
+impl MetricsCounters {
+    pub fn snapshot(&self) -> IndexMetrics {
+        IndexMetrics {
+            num_docs_indexed: self.num_docs_indexed.load(Ordering::Relaxed),
+            num_commits: self.num_commits.load(Ordering::Relaxed),
+            num_merges: self.num_merges.load(Ordering::Relaxed),
+            num_bytes_written: self.num_bytes_written.load(Ordering::Relaxed),
+            num_searches: self.num_searches.load(Ordering::Relaxed),
+            doc_store_cache_hits: self.doc_store_cache_hits.load(Ordering::Relaxed),
+            doc_store_cache_misses: self.doc_store_cache_misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Increments `counter` by `val`.
+pub(crate) fn incr(counter: &AtomicU64, val: u64) {
+    counter.fetch_add(val, Ordering::Relaxed);
+}
+
+/// A writer counting the bytes written through it in
+/// [`MetricsCounters::num_bytes_written`].
+pub(crate) struct CountingTerminatingWrite {
+    underlying: Box<dyn TerminatingWrite>,
+    metrics: Arc<MetricsCounters>,
+}
+
+impl CountingTerminatingWrite {
+    pub fn new(
+        underlying: Box<dyn TerminatingWrite>,
+        metrics: Arc<MetricsCounters>,
+    ) -> CountingTerminatingWrite {
+        CountingTerminatingWrite {
+            underlying,
+            metrics,
+        }
+    }
+}
+
+impl Write for CountingTerminatingWrite {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.underlying.write(buf)?;
+        incr(&self.metrics.num_bytes_written, num_bytes as u64);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl TerminatingWrite for CountingTerminatingWrite {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.underlying.terminate_ref(token)
+    }
+}
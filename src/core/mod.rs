@@ -1,31 +1,54 @@
+mod cancel_token;
 mod executor;
 pub mod index;
+#[cfg(feature = "mmap")]
+mod index_alias;
 mod index_meta;
+mod index_validation;
 mod inverted_index_reader;
 #[doc(hidden)]
 pub mod json_utils;
+pub(crate) mod metrics;
+mod multi_searcher;
+mod query_result_cache;
 pub mod searcher;
 mod segment;
 mod segment_component;
+mod segment_filter;
 mod segment_id;
 mod segment_reader;
+mod sharded_index;
 mod single_segment_index_writer;
 
 use std::path::Path;
 
 use once_cell::sync::Lazy;
 
+pub use self::cancel_token::CancelToken;
+pub(crate) use self::cancel_token::CancellableWeight;
 pub use self::executor::Executor;
 pub use self::index::{Index, IndexBuilder};
+#[cfg(feature = "mmap")]
+pub use self::index_alias::{IndexAlias, IndexAliasReader};
+pub(crate) use self::index_meta::RestorableDelete;
 pub use self::index_meta::{
     IndexMeta, IndexSettings, IndexSortByField, Order, SegmentMeta, SegmentMetaInventory,
 };
+pub use self::index_validation::{IndexValidationReport, SegmentValidationReport, ValidationIssue};
 pub use self::inverted_index_reader::InvertedIndexReader;
-pub use self::searcher::{Searcher, SearcherGeneration};
+pub use self::metrics::IndexMetrics;
+pub use self::multi_searcher::MultiSearcher;
+pub use self::query_result_cache::QueryResultCache;
+pub use self::searcher::{
+    Completion, FieldStatistics, Searcher, SearcherGeneration, SearcherMemoryUsage, Suggestion,
+    VocabularyStatistics,
+};
 pub use self::segment::Segment;
 pub use self::segment_component::SegmentComponent;
+pub use self::segment_filter::SegmentFilter;
 pub use self::segment_id::SegmentId;
 pub use self::segment_reader::{merge_field_meta_data, FieldMetadata, SegmentReader};
+pub use self::sharded_index::{ShardedIndex, ShardedIndexReader, ShardedIndexWriter};
 pub use self::single_segment_index_writer::SingleSegmentIndexWriter;
 
 /// The meta file contains all the information about the list of segments and the schema
@@ -0,0 +1,284 @@
+use crate::collector::Collector;
+use crate::query::{
+    with_static_rank, Bm25Similarity, Bm25StatisticsProvider, EnableScoring, Query,
+};
+use crate::schema::document::DocumentDeserialize;
+use crate::schema::{Field, Schema, Term};
+use crate::{DocAddress, Searcher, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Searches several indexes sharing the same schema as if they were a single one.
+///
+/// This is typically useful for time-partitioned deployments, e.g. one index per day.
+///
+/// The segments of all of the searchers are seen as a single list: the segment ordinals of the
+/// [`DocAddress`] returned by collectors are relative to this list, and can be passed to
+/// [`MultiSearcher::doc`]. Scoring relies on the statistics of all of the indexes, so that
+/// scores are consistent across indexes, and identical to those of a single index holding all
+/// of the documents.
+#[derive(Clone)]
+pub struct MultiSearcher {
+    searchers: Vec<Searcher>,
+    // Ordinal of the first segment of each searcher, in the flattened list of segments.
+    segment_ord_offsets: Vec<SegmentOrdinal>,
+}
+
+impl MultiSearcher {
+    /// Creates a `MultiSearcher` over the given searchers.
+    ///
+    /// Returns an error if the searchers do not all share the same schema and BM25 parameters.
+    pub fn new(searchers: Vec<Searcher>) -> crate::Result<MultiSearcher> {
+        if let Some((first, others)) = searchers.split_first() {
+            if others
+                .iter()
+                .any(|searcher| searcher.schema() != first.schema())
+            {
+                return Err(TantivyError::SchemaError(
+                    "All of the searchers of a MultiSearcher must share the same schema."
+                        .to_string(),
+                ));
+            }
+            if others
+                .iter()
+                .any(|searcher| searcher.bm25_similarity() != first.bm25_similarity())
+            {
+                return Err(TantivyError::InvalidArgument(
+                    "All of the searchers of a MultiSearcher must share the same BM25 parameters."
+                        .to_string(),
+                ));
+            }
+        }
+        let mut segment_ord_offsets = Vec::with_capacity(searchers.len());
+        let mut num_segments = 0;
+        for searcher in &searchers {
+            segment_ord_offsets.push(num_segments);
+            num_segments += searcher.segment_readers().len() as SegmentOrdinal;
+        }
+        Ok(MultiSearcher {
+            searchers,
+            segment_ord_offsets,
+        })
+    }
+
+    /// Returns the underlying searchers.
+    pub fn searchers(&self) -> &[Searcher] {
+        &self.searchers
+    }
+
+    /// Returns the schema shared by the searchers, or `None` if there are no searchers.
+    pub fn schema(&self) -> Option<&Schema> {
+        self.searchers.first().map(Searcher::schema)
+    }
+
+    /// Returns the overall number of documents, over all of the searchers.
+    pub fn num_docs(&self) -> u64 {
+        self.searchers.iter().map(Searcher::num_docs).sum()
+    }
+
+    /// Returns the segment readers of all of the searchers, in the order of the
+    /// segment ordinals used by the `MultiSearcher`.
+    pub fn segment_readers(&self) -> impl Iterator<Item = &SegmentReader> + '_ {
+        self.searchers
+            .iter()
+            .flat_map(|searcher| searcher.segment_readers().iter())
+    }
+
+    /// Fetches a document given a [`DocAddress`] returned by [`MultiSearcher::search`].
+    pub fn doc<D: DocumentDeserialize>(&self, doc_address: DocAddress) -> crate::Result<D> {
+        let (searcher, local_doc_address) = self.resolve(doc_address)?;
+        searcher.doc(local_doc_address)
+    }
+
+    /// Converts a [`DocAddress`] returned by the `MultiSearcher` into the searcher holding
+    /// the document and the address of the document within this searcher.
+    pub fn resolve(&self, doc_address: DocAddress) -> crate::Result<(&Searcher, DocAddress)> {
+        let searcher_ord = self
+            .segment_ord_offsets
+            .partition_point(|&offset| offset <= doc_address.segment_ord)
+            .checked_sub(1)
+            .filter(|&searcher_ord| {
+                doc_address.segment_ord - self.segment_ord_offsets[searcher_ord]
+                    < self.searchers[searcher_ord].segment_readers().len() as SegmentOrdinal
+            })
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "Segment ordinal {} is out of range",
+                    doc_address.segment_ord
+                ))
+            })?;
+        let local_doc_address = DocAddress::new(
+            doc_address.segment_ord - self.segment_ord_offsets[searcher_ord],
+            doc_address.doc_id,
+        );
+        Ok((&self.searchers[searcher_ord], local_doc_address))
+    }
+
+    /// Runs a query over all of the searchers, and merges the results of the collector
+    /// as if all of the segments belonged to a single index.
+    pub fn search<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<C::Fruit> {
+        let mut fruits = Vec::new();
+        for (searcher, &segment_ord_offset) in self.searchers.iter().zip(&self.segment_ord_offsets)
+        {
+            let enabled_scoring = if collector.requires_scoring() {
+                EnableScoring::enabled_from_statistics_provider(self, searcher)
+            } else {
+                EnableScoring::disabled_from_searcher(searcher)
+            };
+            let weight = with_static_rank(
+                searcher.index(),
+                enabled_scoring,
+                query.weight(enabled_scoring)?,
+            );
+            let segment_fruits = searcher.index().search_executor().map(
+                |(segment_ord, segment_reader)| {
+                    collector.collect_segment(
+                        weight.as_ref(),
+                        segment_ord_offset + segment_ord as SegmentOrdinal,
+                        segment_reader,
+                    )
+                },
+                searcher.segment_readers().iter().enumerate(),
+            )?;
+            fruits.extend(segment_fruits);
+        }
+        collector.merge_fruits(fruits)
+    }
+}
+
+impl Bm25StatisticsProvider for MultiSearcher {
+    fn total_num_tokens(&self, field: Field) -> crate::Result<u64> {
+        let mut total_num_tokens = 0u64;
+        for searcher in &self.searchers {
+            total_num_tokens += searcher.total_num_tokens(field)?;
+        }
+        Ok(total_num_tokens)
+    }
+
+    fn total_num_docs(&self) -> crate::Result<u64> {
+        let mut total_num_docs = 0u64;
+        for searcher in &self.searchers {
+            total_num_docs += searcher.total_num_docs()?;
+        }
+        Ok(total_num_docs)
+    }
+
+    fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
+        let mut doc_freq = 0u64;
+        for searcher in &self.searchers {
+            doc_freq += searcher.doc_freq(term)?;
+        }
+        Ok(doc_freq)
+    }
+
+    fn bm25_similarity(&self) -> Bm25Similarity {
+        self.searchers
+            .first()
+            .map(Searcher::bm25_similarity)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiSearcher;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{Bm25Similarity, Bm25StatisticsProvider, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, Value, STORED, TEXT};
+    use crate::{Index, IndexWriter, Searcher, TantivyDocument, TantivyError, Term};
+
+    #[test]
+    fn test_multi_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let texts = ["a b c", "a a", "b c d", "a d d d", "c", "a b"];
+        let all_index = Index::create_in_ram(schema.clone());
+        let mut all_writer: IndexWriter = all_index.writer_for_tests()?;
+        let mut searchers = Vec::new();
+        for chunk in texts.chunks(2) {
+            let index = Index::create_in_ram(schema.clone());
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            for &text_value in chunk {
+                index_writer.add_document(doc!(text => text_value))?;
+                all_writer.add_document(doc!(text => text_value))?;
+            }
+            index_writer.commit()?;
+            searchers.push(index.reader()?.searcher());
+        }
+        all_writer.commit()?;
+        let all_searcher = all_index.reader()?.searcher();
+        let multi_searcher = MultiSearcher::new(searchers)?;
+        assert_eq!(multi_searcher.num_docs(), 6);
+
+        let query = TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        assert_eq!(multi_searcher.search(&query, &Count)?, 4);
+        let top_docs = multi_searcher.search(&query, &TopDocs::with_limit(4))?;
+        let all_top_docs = all_searcher.search(&query, &TopDocs::with_limit(4))?;
+        assert_eq!(top_docs.len(), 4);
+        for ((score, doc_address), (all_score, all_doc_address)) in
+            top_docs.iter().zip(all_top_docs.iter())
+        {
+            assert_eq!(score, all_score);
+            let doc: TantivyDocument = multi_searcher.doc(*doc_address)?;
+            let all_doc: TantivyDocument = all_searcher.doc(*all_doc_address)?;
+            assert_eq!(
+                doc.get_first(text).and_then(|value| value.as_str()),
+                all_doc.get_first(text).and_then(|value| value.as_str())
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_searcher_schema_mismatch() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let other_index = Index::create_in_ram(schema_builder.build());
+        let searchers = vec![index.reader()?.searcher(), other_index.reader()?.searcher()];
+        assert!(MultiSearcher::new(searchers).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_searcher_bm25_similarity() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let similarity = Bm25Similarity { k1: 2.0, b: 0.5 };
+        let mut indexes = Vec::new();
+        for _ in 0..2 {
+            let mut index = Index::create_in_ram(schema.clone());
+            index.set_bm25_similarity(similarity)?;
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text => "a b"))?;
+            index_writer.commit()?;
+            indexes.push(index);
+        }
+        let searchers: Vec<Searcher> = indexes
+            .iter()
+            .map(|index| Ok(index.reader()?.searcher()))
+            .collect::<crate::Result<_>>()?;
+        let multi_searcher = MultiSearcher::new(searchers)?;
+        assert_eq!(multi_searcher.bm25_similarity(), similarity);
+
+        let other_index = Index::create_in_ram(schema);
+        let searchers = vec![
+            indexes[0].reader()?.searcher(),
+            other_index.reader()?.searcher(),
+        ];
+        assert!(matches!(
+            MultiSearcher::new(searchers),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+}
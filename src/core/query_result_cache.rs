@@ -0,0 +1,182 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use lru::LruCache;
+
+use crate::collector::Collector;
+use crate::query::Query;
+use crate::{Opstamp, Searcher, SegmentId};
+
+/// The key of a cached result: the collector, and the query, as identified by their `Debug`
+/// representation.
+type CacheKey = (String, String);
+
+struct InnerCache {
+    // The segments, and their delete opstamps, of the searcher the cached results come from.
+    segments: Option<BTreeMap<SegmentId, Option<Opstamp>>>,
+    results: LruCache<CacheKey, Box<dyn Any + Send>>,
+}
+
+/// An LRU cache of search results, for queries that get executed over and over again, like the
+/// counts or the first page of results of a dashboard.
+///
+/// The results are only valid for the state of the index the searcher was opened on: the cache
+/// is cleared as soon as it is used with a searcher over a different set of segments, or
+/// deletes, typically after a reload following a commit.
+///
+/// Queries and collectors are identified by their `Debug` representation, which needs to
+/// reflect everything that affects their results. This is the case for the queries and
+/// collectors of tantivy, but custom ones may need some care. Since every result is kept, the
+/// cache is meant for small results, like counts or top-k lists, rather than large ones.
+pub struct QueryResultCache {
+    inner: Mutex<InnerCache>,
+    num_hits: AtomicU64,
+    num_misses: AtomicU64,
+}
+
+impl QueryResultCache {
+    /// Creates a cache holding up to `capacity` results.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is `0`.
+    pub fn new(capacity: usize) -> QueryResultCache {
+        let capacity =
+            NonZeroUsize::new(capacity).expect("The capacity of the cache must be positive");
+        QueryResultCache {
+            inner: Mutex::new(InnerCache {
+                segments: None,
+                results: LruCache::new(capacity),
+            }),
+            num_hits: AtomicU64::default(),
+            num_misses: AtomicU64::default(),
+        }
+    }
+
+    /// Runs `query` with `collector` on `searcher`, like [`Searcher::search()`], unless its
+    /// result is already in the cache.
+    pub fn search<C>(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<C::Fruit>
+    where
+        C: Collector + fmt::Debug,
+        C::Fruit: Clone,
+    {
+        let key = (
+            format!("{}{collector:?}", std::any::type_name::<C>()),
+            format!("{query:?}"),
+        );
+        let segments = searcher.generation().segments();
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.segments.as_ref() != Some(segments) {
+                inner.results.clear();
+                inner.segments = Some(segments.clone());
+            }
+            let cached_fruit = inner
+                .results
+                .get(&key)
+                .and_then(|fruit| fruit.downcast_ref::<C::Fruit>());
+            if let Some(fruit) = cached_fruit {
+                self.num_hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(fruit.clone());
+            }
+        }
+        self.num_misses.fetch_add(1, Ordering::Relaxed);
+        let fruit = searcher.search(query, collector)?;
+        let mut inner = self.inner.lock().unwrap();
+        // The cache may have moved to another searcher in the meantime.
+        if inner.segments.as_ref() == Some(segments) {
+            inner.results.put(key, Box::new(fruit.clone()));
+        }
+        Ok(fruit)
+    }
+
+    /// Returns the number of results currently in the cache.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().results.len()
+    }
+
+    /// Returns true if the cache holds no result.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all of the results from the cache.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.results.clear();
+        inner.segments = None;
+    }
+
+    /// Returns the number of searches served from the cache.
+    pub fn num_hits(&self) -> u64 {
+        self.num_hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of searches that had to be executed.
+    pub fn num_misses(&self) -> u64 {
+        self.num_misses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryResultCache;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_query_result_cache() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.add_document(doc!(tag => "b"))?;
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let cache = QueryResultCache::new(10);
+        let query_a = TermQuery::new(Term::from_field_text(tag, "a"), IndexRecordOption::Basic);
+        let query_b = TermQuery::new(Term::from_field_text(tag, "b"), IndexRecordOption::Basic);
+
+        let searcher = reader.searcher();
+        assert_eq!(cache.search(&searcher, &query_a, &Count)?, 2);
+        assert_eq!(cache.search(&searcher, &query_a, &Count)?, 2);
+        assert_eq!(cache.search(&searcher, &query_b, &Count)?, 1);
+        assert_eq!(
+            cache
+                .search(&searcher, &query_a, &TopDocs::with_limit(1))?
+                .len(),
+            1
+        );
+        assert_eq!(
+            cache
+                .search(&searcher, &query_a, &TopDocs::with_limit(2))?
+                .len(),
+            2
+        );
+        assert_eq!(cache.num_hits(), 1);
+        assert_eq!(cache.num_misses(), 4);
+        assert_eq!(cache.len(), 4);
+
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(cache.search(&searcher, &query_a, &Count)?, 3);
+        assert_eq!(cache.num_misses(), 5);
+        assert_eq!(cache.len(), 1);
+        Ok(())
+    }
+}
@@ -1,15 +1,34 @@
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{fmt, io};
 
+use columnar::{Column, StrColumn};
+use common::{BitSet, ReadOnlyBitSet};
+use levenshtein_automata::Distance;
+
 use crate::collector::Collector;
-use crate::core::{Executor, SegmentReader};
-use crate::query::{Bm25StatisticsProvider, EnableScoring, Query};
+use crate::core::{
+    metrics, CancelToken, CancellableWeight, Executor, SegmentFilter, SegmentMeta, SegmentReader,
+};
+use crate::fastfield::AliveBitSet;
+use crate::query::profile::profiled_weight;
+use crate::query::{
+    levenshtein_automaton_builder, with_static_rank, Bm25StatisticsProvider, DfaWrapper,
+    EnableScoring, Query, SearchProfile, Weight,
+};
 use crate::schema::document::DocumentDeserialize;
-use crate::schema::{Schema, Term};
+use crate::schema::{Field, FieldType, OwnedValue, Schema, Term, TextFieldIndexing};
 use crate::space_usage::SearcherSpaceUsage;
-use crate::store::{CacheStats, StoreReader};
-use crate::{DocAddress, Index, Opstamp, SegmentId, TrackedObject};
+use crate::store::{CacheStats, StoreReader, StoredDocument, DOCSTORE_CACHE_CAPACITY};
+use crate::termdict::TermMerger;
+use crate::termvector::TermVector;
+use crate::{
+    DocAddress, DocId, FutureResult, Index, Opstamp, SegmentId, SegmentOrdinal, StableDocAddress,
+    TantivyError, TrackedObject,
+};
 
 /// Identifies the searcher generation accessed by a [`Searcher`].
 ///
@@ -60,10 +79,90 @@ impl SearcherGeneration {
     }
 }
 
+/// Statistics of a field over all of the segments of a [`Searcher`],
+/// as returned by [`Searcher::field_statistics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FieldStatistics {
+    /// Number of documents with at least one token in the field.
+    pub num_docs_with_field: u64,
+    /// Total number of tokens in the field.
+    pub total_num_tokens: u64,
+}
+
+impl FieldStatistics {
+    /// Average number of tokens in the field, over the documents having the field.
+    ///
+    /// Returns 0 if no document has the field.
+    pub fn average_field_length(&self) -> f32 {
+        if self.num_docs_with_field == 0 {
+            return 0.0;
+        }
+        self.total_num_tokens as f32 / self.num_docs_with_field as f32
+    }
+}
+
+/// Vocabulary statistics of a field, as returned by [`Searcher::vocabulary_statistics()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VocabularyStatistics {
+    /// Number of distinct terms of the field, over all of the segments.
+    pub num_unique_terms: u64,
+    /// Number of terms by document frequency: the bucket `i` counts the terms with a document
+    /// frequency in `[2^i, 2^(i+1))`.
+    pub doc_freq_histogram: Vec<u64>,
+    /// The terms with the highest document frequencies, along with their document frequency,
+    /// by decreasing document frequency.
+    pub top_terms: Vec<(Term, u64)>,
+}
+
+/// A term of the dictionary close to a misspelled term, as returned by [`Searcher::suggest()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The suggested term.
+    pub term: Term,
+    /// Levenshtein distance between the suggested term and the misspelled term.
+    pub distance: u8,
+    /// Number of documents containing the suggested term, deleted documents included.
+    pub doc_freq: u64,
+}
+
+/// A term completing a prefix, as returned by [`Searcher::complete()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    /// The completed term.
+    pub term: Term,
+    /// Number of documents containing the term, deleted documents included.
+    pub doc_freq: u64,
+}
+
+/// Memory held by a [`Searcher`], as returned by [`Searcher::memory_usage()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearcherMemoryUsage {
+    /// Number of bytes of the decompressed blocks held by the doc store caches.
+    pub doc_store_caches: usize,
+    /// Number of bytes of the files of the segments, which are either mapped in memory or
+    /// loaded in memory, depending on the directory.
+    ///
+    /// With a `MmapDirectory`, this is an upper bound of the resident memory: the operating
+    /// system only loads the pages that are accessed, and may evict them under memory pressure.
+    pub segment_files: usize,
+}
+
+impl SearcherMemoryUsage {
+    /// Returns the total number of bytes.
+    pub fn total(&self) -> usize {
+        self.doc_store_caches + self.segment_files
+    }
+}
+
 /// Holds a list of `SegmentReader`s ready for search.
 ///
 /// It guarantees that the `Segment` will not be removed before
-/// the destruction of the `Searcher`.
+/// the destruction of the `Searcher`: the files of its segments are protected from
+/// garbage collection until the `Searcher` and all of its clones are dropped.
+///
+/// A `Searcher` is therefore a point-in-time snapshot of the index. Keeping it around,
+/// e.g. keyed by its [`SearcherGeneration::generation_id`], makes it possible to serve
+/// consistent pagination across several requests while commits and merges proceed.
 #[derive(Clone)]
 pub struct Searcher {
     inner: Arc<SearcherInner>,
@@ -75,6 +174,11 @@ impl Searcher {
         &self.inner.index
     }
 
+    /// Returns the opstamp of the commit this searcher was loaded from.
+    pub(crate) fn commit_opstamp(&self) -> Opstamp {
+        self.inner.opstamp
+    }
+
     /// [`SearcherGeneration`] which identifies the version of the snapshot held by this `Searcher`.
     pub fn generation(&self) -> &SearcherGeneration {
         self.inner.generation.as_ref()
@@ -89,6 +193,69 @@ impl Searcher {
         store_reader.get(doc_address.doc_id)
     }
 
+    /// Fetches several documents from tantivy's store, returned in the order of `doc_addresses`.
+    ///
+    /// The documents are grouped by segment and by block of the doc store, so that each block
+    /// is decompressed once. This is faster than calling [`Searcher::doc`] for every document,
+    /// e.g. to fetch a page of results.
+    pub fn docs<D: DocumentDeserialize>(
+        &self,
+        doc_addresses: &[DocAddress],
+    ) -> crate::Result<Vec<D>> {
+        // For each segment, the positions of its documents in `doc_addresses` and their ids.
+        let mut segment_docs: BTreeMap<SegmentOrdinal, (Vec<usize>, Vec<DocId>)> = BTreeMap::new();
+        for (doc_ord, doc_address) in doc_addresses.iter().enumerate() {
+            let (doc_ords, doc_ids) = segment_docs.entry(doc_address.segment_ord).or_default();
+            doc_ords.push(doc_ord);
+            doc_ids.push(doc_address.doc_id);
+        }
+        let mut docs: Vec<Option<D>> = (0..doc_addresses.len()).map(|_| None).collect();
+        for (segment_ord, (doc_ords, doc_ids)) in segment_docs {
+            let store_reader = &self.inner.store_readers[segment_ord as usize];
+            for (doc_ord, doc) in doc_ords.into_iter().zip(store_reader.get_many(&doc_ids)?) {
+                docs[doc_ord] = Some(doc);
+            }
+        }
+        // Every document has been read at this point.
+        Ok(docs.into_iter().flatten().collect())
+    }
+
+    /// Fetches a document from tantivy's store given a [`DocAddress`], without copying its
+    /// values.
+    ///
+    /// See [`StoredDocument`] for more information.
+    pub fn stored_doc(&self, doc_address: DocAddress) -> crate::Result<StoredDocument> {
+        let store_reader = &self.inner.store_readers[doc_address.segment_ord as usize];
+        store_reader.get_stored_document(doc_address.doc_id)
+    }
+
+    /// Returns the term vector of a text field of the document at the given [`DocAddress`].
+    ///
+    /// The term vector is the one recorded when the document was indexed. It is empty if the
+    /// document has no terms in the field.
+    ///
+    /// Returns a [`TantivyError::SchemaError`] if the field is not a text field indexed with
+    /// [term vectors](crate::schema::TextFieldIndexing::set_term_vectors).
+    pub fn term_vector(&self, doc_address: DocAddress, field: Field) -> crate::Result<TermVector> {
+        let field_entry = self.schema().get_field_entry(field);
+        let has_term_vectors = match field_entry.field_type() {
+            FieldType::Str(text_options) => text_options
+                .get_indexing_options()
+                .map_or(false, TextFieldIndexing::term_vectors),
+            _ => false,
+        };
+        if !has_term_vectors {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not indexed with term vectors.",
+                field_entry.name()
+            )));
+        }
+        let segment_reader = self.segment_reader(doc_address.segment_ord);
+        Ok(segment_reader
+            .term_vectors()
+            .term_vector(doc_address.doc_id, field)?)
+    }
+
     /// The cache stats for the underlying store reader.
     ///
     /// Aggregates the sum for each segment store reader.
@@ -138,6 +305,242 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Return the overall number of occurrences of the given term,
+    /// summed over all of the documents containing it.
+    ///
+    /// Contrary to [`Searcher::doc_freq`], this requires decoding the posting
+    /// lists of the term in every segment.
+    pub fn total_term_freq(&self, term: &Term) -> crate::Result<u64> {
+        let mut total_term_freq = 0;
+        for segment_reader in &self.inner.segment_readers {
+            let inverted_index = segment_reader.inverted_index(term.field())?;
+            total_term_freq += inverted_index.total_term_freq(term)?;
+        }
+        Ok(total_term_freq)
+    }
+
+    /// Computes collection level statistics for a field indexed with fieldnorms.
+    ///
+    /// Like the other statistics used for scoring, they include deleted documents.
+    /// Returns an error if the field does not record fieldnorms.
+    pub fn field_statistics(&self, field: Field) -> crate::Result<FieldStatistics> {
+        let mut field_statistics = FieldStatistics::default();
+        for segment_reader in &self.inner.segment_readers {
+            let fieldnorm_reader = segment_reader.get_fieldnorms_reader(field)?;
+            field_statistics.num_docs_with_field += (0..segment_reader.max_doc())
+                .filter(|&doc| fieldnorm_reader.fieldnorm_id(doc) != 0)
+                .count() as u64;
+            let inverted_index = segment_reader.inverted_index(field)?;
+            field_statistics.total_num_tokens += inverted_index.total_num_tokens();
+        }
+        Ok(field_statistics)
+    }
+
+    /// Computes the vocabulary statistics of an indexed field, with its `top_n` terms of highest
+    /// document frequency.
+    ///
+    /// This goes through the term dictionaries of the field in all of the segments. Like the
+    /// other statistics used for scoring, the document frequencies include deleted documents.
+    /// Returns an error if the field is not indexed.
+    pub fn vocabulary_statistics(
+        &self,
+        field: Field,
+        top_n: usize,
+    ) -> crate::Result<VocabularyStatistics> {
+        let field_entry = self.schema().get_field_entry(field);
+        if !field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not indexed.",
+                field_entry.name()
+            )));
+        }
+        let typ = field_entry.field_type().value_type();
+        let inverted_indexes = self
+            .inner
+            .segment_readers
+            .iter()
+            .map(|segment_reader| segment_reader.inverted_index(field))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let term_streams = inverted_indexes
+            .iter()
+            .map(|inverted_index| inverted_index.terms().stream())
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut term_merger = TermMerger::new(term_streams);
+        let mut vocabulary_statistics = VocabularyStatistics::default();
+        // Min-heap of the top terms, which pops the highest term among equal document
+        // frequencies first, so that the lowest terms are kept.
+        let mut top_terms: BinaryHeap<Reverse<(u64, Reverse<Vec<u8>>)>> = BinaryHeap::new();
+        while term_merger.advance() {
+            let doc_freq: u64 = term_merger
+                .current_segment_ords_and_term_infos()
+                .map(|(_, term_info)| u64::from(term_info.doc_freq))
+                .sum();
+            if doc_freq == 0 {
+                continue;
+            }
+            vocabulary_statistics.num_unique_terms += 1;
+            let bucket = (u64::BITS - 1 - doc_freq.leading_zeros()) as usize;
+            if vocabulary_statistics.doc_freq_histogram.len() <= bucket {
+                vocabulary_statistics
+                    .doc_freq_histogram
+                    .resize(bucket + 1, 0);
+            }
+            vocabulary_statistics.doc_freq_histogram[bucket] += 1;
+            if top_n == 0 {
+                continue;
+            }
+            if top_terms.len() == top_n {
+                let Some(Reverse((lowest_doc_freq, _))) = top_terms.peek() else {
+                    continue;
+                };
+                if doc_freq <= *lowest_doc_freq {
+                    continue;
+                }
+                top_terms.pop();
+            }
+            top_terms.push(Reverse((doc_freq, Reverse(term_merger.key().to_vec()))));
+        }
+        vocabulary_statistics.top_terms = top_terms
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((doc_freq, Reverse(term_bytes)))| {
+                let mut term = Term::with_type_and_field(typ, field);
+                term.append_bytes(&term_bytes);
+                (term, doc_freq)
+            })
+            .collect();
+        Ok(vocabulary_statistics)
+    }
+
+    /// Returns the `k` most frequent terms of a text field starting with `prefix`, to offer
+    /// completions as the user types a query.
+    ///
+    /// The completions come from the term dictionaries of the segments, weighted by their
+    /// document frequency summed over all of the segments, so they are up to date with the
+    /// index as of the last reload. As with the query parser, `prefix` is compared to the terms
+    /// as they are indexed.
+    ///
+    /// There is no dedicated suggester structure, such as an FST weighted by frequencies: each
+    /// call streams all of the terms starting with `prefix` from the term dictionary of every
+    /// segment, and keeps the `k` most frequent ones. Its cost is thus linear in the number of
+    /// terms matching `prefix`, whatever `k`, which can be most of the dictionary for a short
+    /// prefix. Requiring a minimum prefix length bounds it.
+    ///
+    /// The completions are sorted by decreasing document frequency, then by term.
+    pub fn complete(&self, prefix: &str, field: Field, k: usize) -> crate::Result<Vec<Completion>> {
+        let field_entry = self.schema().get_field_entry(field);
+        if !matches!(field_entry.field_type(), FieldType::Str(_)) || !field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not an indexed text field.",
+                field_entry.name()
+            )));
+        }
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let inverted_indexes = self
+            .inner
+            .segment_readers
+            .iter()
+            .map(|segment_reader| segment_reader.inverted_index(field))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let term_streams = inverted_indexes
+            .iter()
+            .map(|inverted_index| inverted_index.terms().prefix_range(prefix).into_stream())
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut term_merger = TermMerger::new(term_streams);
+        // Min-heap of the completions, see `vocabulary_statistics`.
+        let mut completions: BinaryHeap<Reverse<(u64, Reverse<Vec<u8>>)>> = BinaryHeap::new();
+        while term_merger.advance() {
+            let doc_freq: u64 = term_merger
+                .current_segment_ords_and_term_infos()
+                .map(|(_, term_info)| u64::from(term_info.doc_freq))
+                .sum();
+            if completions.len() == k {
+                let Some(Reverse((lowest_doc_freq, _))) = completions.peek() else {
+                    continue;
+                };
+                if doc_freq <= *lowest_doc_freq {
+                    continue;
+                }
+                completions.pop();
+            }
+            completions.push(Reverse((doc_freq, Reverse(term_merger.key().to_vec()))));
+        }
+        Ok(completions
+            .into_sorted_vec()
+            .into_iter()
+            .filter_map(|Reverse((doc_freq, Reverse(term_bytes)))| {
+                let term_text = std::str::from_utf8(&term_bytes).ok()?;
+                Some(Completion {
+                    term: Term::from_field_text(field, term_text),
+                    doc_freq,
+                })
+            })
+            .collect())
+    }
+
+    /// Returns the terms of a text field within `max_edits` of `text`, to offer "did you mean"
+    /// corrections.
+    ///
+    /// `text` is compared to the terms as they are indexed, so it typically needs to go through
+    /// the tokenizer of the field first. Transpositions count as a single edit, and `max_edits`
+    /// can be at most 2.
+    ///
+    /// The suggestions are sorted by increasing distance, then by decreasing document frequency.
+    /// If `text` is itself a term of the field, it is the first suggestion, with a distance of 0.
+    pub fn suggest(
+        &self,
+        text: &str,
+        field: Field,
+        max_edits: u8,
+    ) -> crate::Result<Vec<Suggestion>> {
+        let field_entry = self.schema().get_field_entry(field);
+        if !matches!(field_entry.field_type(), FieldType::Str(_)) || !field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not an indexed text field.",
+                field_entry.name()
+            )));
+        }
+        let automaton_builder = levenshtein_automaton_builder(max_edits, true)?;
+        let mut doc_freqs: HashMap<Vec<u8>, u64> = HashMap::new();
+        for segment_reader in &self.inner.segment_readers {
+            let inverted_index = segment_reader.inverted_index(field)?;
+            let automaton = DfaWrapper(automaton_builder.build_dfa(text));
+            let mut term_stream = inverted_index.terms().search(automaton).into_stream()?;
+            while term_stream.advance() {
+                *doc_freqs.entry(term_stream.key().to_vec()).or_default() +=
+                    u64::from(term_stream.value().doc_freq);
+            }
+        }
+        let dfa = automaton_builder.build_dfa(text);
+        let mut suggestions: Vec<Suggestion> = doc_freqs
+            .into_iter()
+            .filter_map(|(term_bytes, doc_freq)| {
+                let Distance::Exact(distance) = dfa.eval(&term_bytes) else {
+                    return None;
+                };
+                let term_text = std::str::from_utf8(&term_bytes).ok()?;
+                Some(Suggestion {
+                    term: Term::from_field_text(field, term_text),
+                    distance,
+                    doc_freq,
+                })
+            })
+            .collect();
+        suggestions.sort_by(|left, right| {
+            left.distance
+                .cmp(&right.distance)
+                .then_with(|| right.doc_freq.cmp(&left.doc_freq))
+                .then_with(|| {
+                    left.term
+                        .serialized_value_bytes()
+                        .cmp(right.term.serialized_value_bytes())
+                })
+        });
+        Ok(suggestions)
+    }
+
     /// Return the overall number of documents containing
     /// the given term in an asynchronous manner.
     #[cfg(feature = "quickwit")]
@@ -151,11 +554,126 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Converts a [`DocAddress`] obtained from this searcher into a [`StableDocAddress`],
+    /// which remains meaningful after the searcher is dropped or the reader reloaded.
+    pub fn stable_doc_address(&self, doc_address: DocAddress) -> StableDocAddress {
+        StableDocAddress {
+            segment_id: self.segment_reader(doc_address.segment_ord).segment_id(),
+            doc_id: doc_address.doc_id,
+        }
+    }
+
+    /// Resolves a [`StableDocAddress`] into a [`DocAddress`] valid for this searcher.
+    ///
+    /// Returns `None` if the document is not visible in this searcher anymore: either
+    /// its segment was merged into another one, or the document was deleted.
+    /// In that case, the application should run its query again.
+    pub fn resolve_doc_address(&self, stable_doc_address: StableDocAddress) -> Option<DocAddress> {
+        let segment_ord = self.segment_readers().iter().position(|segment_reader| {
+            segment_reader.segment_id() == stable_doc_address.segment_id
+        })?;
+        let segment_reader = self.segment_reader(segment_ord as SegmentOrdinal);
+        if stable_doc_address.doc_id >= segment_reader.max_doc()
+            || segment_reader.is_deleted(stable_doc_address.doc_id)
+        {
+            return None;
+        }
+        Some(DocAddress::new(
+            segment_ord as SegmentOrdinal,
+            stable_doc_address.doc_id,
+        ))
+    }
+
+    /// Maps a batch of [`DocAddress`]es to the primary keys of their documents, i.e. the first
+    /// value of `id_field`.
+    ///
+    /// The id field is read from its fast field if it is a fast text, `u64` or `i64` field, and
+    /// from the doc store otherwise. In both cases, the documents are accessed per segment in
+    /// the order of their doc ids, which is a lot cheaper than looking them up in the order of
+    /// the results.
+    ///
+    /// The keys are returned in the order of `doc_addresses`, with `None` for the documents
+    /// without a value for `id_field`.
+    pub fn primary_keys(
+        &self,
+        id_field: Field,
+        doc_addresses: &[DocAddress],
+    ) -> crate::Result<Vec<Option<OwnedValue>>> {
+        let field_entry = self.schema().get_field_entry(id_field);
+        if !field_entry.is_fast() && !field_entry.is_stored() {
+            return Err(TantivyError::SchemaError(format!(
+                "The id field {:?} is neither fast nor stored",
+                field_entry.name()
+            )));
+        }
+        let mut order: Vec<usize> = (0..doc_addresses.len()).collect();
+        order.sort_unstable_by_key(|&i| doc_addresses[i]);
+        let mut primary_keys = vec![None; doc_addresses.len()];
+        let mut current_reader: Option<(SegmentOrdinal, PrimaryKeyReader)> = None;
+        for i in order {
+            let doc_address = doc_addresses[i];
+            let primary_key_reader = match &mut current_reader {
+                Some((segment_ord, primary_key_reader))
+                    if *segment_ord == doc_address.segment_ord =>
+                {
+                    primary_key_reader
+                }
+                _ => {
+                    let primary_key_reader = PrimaryKeyReader::open(
+                        self.segment_reader(doc_address.segment_ord),
+                        &self.inner.store_readers[doc_address.segment_ord as usize],
+                        id_field,
+                    )?;
+                    &mut current_reader
+                        .insert((doc_address.segment_ord, primary_key_reader))
+                        .1
+                }
+            };
+            primary_keys[i] = primary_key_reader.primary_key(doc_address.doc_id)?;
+        }
+        Ok(primary_keys)
+    }
+
     /// Return the list of segment readers
     pub fn segment_readers(&self) -> &[SegmentReader] {
         &self.inner.segment_readers
     }
 
+    /// Returns a searcher over the same commit, where the documents matching one of `queries`
+    /// are deleted.
+    pub(crate) fn without_docs_matching(&self, queries: &[&dyn Query]) -> crate::Result<Searcher> {
+        if queries.is_empty() {
+            return Ok(self.clone());
+        }
+        let weights = queries
+            .iter()
+            .map(|query| query.weight(EnableScoring::disabled_from_searcher(self)))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let mut segment_readers = Vec::with_capacity(self.segment_readers().len());
+        for segment_reader in self.segment_readers() {
+            let mut alive_bitset = BitSet::with_max_value_and_full(segment_reader.max_doc());
+            for weight in &weights {
+                weight.for_each_no_score(segment_reader, &mut |docs| {
+                    for &doc in docs {
+                        alive_bitset.remove(doc);
+                    }
+                })?;
+            }
+            let alive_bitset = AliveBitSet::from(ReadOnlyBitSet::from(&alive_bitset));
+            segment_readers.push(segment_reader.with_alive_bitset(alive_bitset));
+        }
+        let inner = SearcherInner::new(
+            self.inner.schema.clone(),
+            self.inner.index.clone(),
+            self.inner.opstamp,
+            self.inner._segment_metas.clone(),
+            segment_readers,
+            self.inner.generation.clone(),
+            DOCSTORE_CACHE_CAPACITY,
+        )?;
+        Ok(Searcher::from(Arc::new(inner)))
+    }
+
     /// Returns the segment_reader associated with the given segment_ord
     pub fn segment_reader(&self, segment_ord: u32) -> &SegmentReader {
         &self.inner.segment_readers[segment_ord as usize]
@@ -183,6 +701,26 @@ impl Searcher {
         self.search_with_statistics_provider(query, collector, self)
     }
 
+    /// Same as [`search(...)`](Searcher::search), but runs the search in the background and
+    /// returns a [`FutureResult`] that can be awaited.
+    ///
+    /// The search runs on the search executor of the index if it is multithreaded, or in a
+    /// thread pool shared by the process otherwise, so that async callers do not need to
+    /// dedicate a blocking thread to each search.
+    pub fn search_async<Q, C>(&self, query: Q, collector: C) -> FutureResult<C::Fruit>
+    where
+        Q: Query + 'static,
+        C: Collector + 'static,
+    {
+        let (future_result, sender) =
+            FutureResult::create("The search was interrupted before completing");
+        let searcher = self.clone();
+        self.inner.index.search_executor().spawn(move || {
+            let _ = sender.send(searcher.search(&query, &collector));
+        });
+        future_result
+    }
+
     /// Same as [`search(...)`](Searcher::search) but allows specifying
     /// a [Bm25StatisticsProvider].
     ///
@@ -203,6 +741,83 @@ impl Searcher {
         self.search_with_executor(query, collector, executor, enabled_scoring)
     }
 
+    /// Same as [`search(...)`](Searcher::search), but stops with a
+    /// [`TantivyError::Cancelled`](crate::TantivyError::Cancelled) error as soon as possible
+    /// after `cancel_token` gets cancelled.
+    ///
+    /// The token is checked every few thousand documents. Checking it disables some
+    /// optimizations of compound queries (e.g. block WAND).
+    pub fn search_with_cancel_token<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        cancel_token: &CancelToken,
+    ) -> crate::Result<C::Fruit> {
+        cancel_token.check()?;
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(self)
+        } else {
+            EnableScoring::disabled_from_searcher(self)
+        };
+        metrics::incr(&self.inner.index.metrics_counters().num_searches, 1);
+        let weight = CancellableWeight::new(
+            self.search_weight(query, enabled_scoring)?,
+            cancel_token.clone(),
+        );
+        let fruits = self.inner.index.search_executor().map(
+            |(segment_ord, segment_reader)| {
+                let fruit = collector.collect_segment(&weight, segment_ord as u32, segment_reader);
+                // A cancelled scorer looks exhausted, the fruit is then incomplete.
+                cancel_token.check()?;
+                fruit
+            },
+            self.segment_readers().iter().enumerate(),
+        )?;
+        collector.merge_fruits(fruits)
+    }
+
+    /// Same as [`search(...)`](Searcher::search), but only goes through the segments accepted
+    /// by `segment_filter`.
+    ///
+    /// The rejected segments are skipped before any of their postings is read: the collector
+    /// does not see them at all.
+    pub fn search_with_segment_filter<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        segment_filter: &dyn SegmentFilter,
+    ) -> crate::Result<C::Fruit> {
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(self)
+        } else {
+            EnableScoring::disabled_from_searcher(self)
+        };
+        metrics::incr(&self.inner.index.metrics_counters().num_searches, 1);
+        let weight = self.search_weight(query, enabled_scoring)?;
+        let accepted_segments = self
+            .segment_readers()
+            .iter()
+            .enumerate()
+            .filter(|(_, segment_reader)| segment_filter.accept(segment_reader));
+        let fruits = self.inner.index.search_executor().map(
+            |(segment_ord, segment_reader)| {
+                collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
+            },
+            accepted_segments,
+        )?;
+        collector.merge_fruits(fruits)
+    }
+
+    /// Builds the weight of `query`, with the static rank of the index blended into its scores.
+    fn search_weight(
+        &self,
+        query: &dyn Query,
+        enabled_scoring: EnableScoring,
+    ) -> crate::Result<Box<dyn Weight>> {
+        let weight = query.weight(enabled_scoring)?;
+        Ok(with_static_rank(&self.inner.index, enabled_scoring, weight))
+    }
+
     /// Same as [`search(...)`](Searcher::search) but multithreaded.
     ///
     /// The current implementation is rather naive :
@@ -222,10 +837,27 @@ impl Searcher {
         executor: &Executor,
         enabled_scoring: EnableScoring,
     ) -> crate::Result<C::Fruit> {
-        let weight = query.weight(enabled_scoring)?;
+        metrics::incr(&self.inner.index.metrics_counters().num_searches, 1);
         let segment_readers = self.segment_readers();
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "search",
+            num_segments = segment_readers.len(),
+            num_docs = self.num_docs(),
+        )
+        .entered();
+        let weight = self.search_weight(query, enabled_scoring)?;
         let fruits = executor.map(
             |(segment_ord, segment_reader)| {
+                // Segments may be searched on other threads, the span is hence passed explicitly.
+                #[cfg(feature = "tracing")]
+                let _span = tracing::info_span!(
+                    parent: &*span,
+                    "collect_segment",
+                    segment_ord,
+                    max_doc = segment_reader.max_doc(),
+                )
+                .entered();
                 collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
             },
             segment_readers.iter().enumerate(),
@@ -233,6 +865,52 @@ impl Searcher {
         collector.merge_fruits(fruits)
     }
 
+    /// Same as [`search(...)`](Searcher::search), but also returns a profile of the search.
+    ///
+    /// The profile gives, for each node of the query, the time spent creating its scorers,
+    /// advancing them and scoring, as well as the number of documents they went through. This
+    /// makes it possible to pinpoint the clauses making a search slow.
+    ///
+    /// Profiling has a cost, and disables some optimizations: the timings are meant to be
+    /// compared with each other, rather than with the duration of a regular search.
+    pub fn search_with_profile<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<(C::Fruit, SearchProfile)> {
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(self)
+        } else {
+            EnableScoring::disabled_from_searcher(self)
+        };
+        metrics::incr(&self.inner.index.metrics_counters().num_searches, 1);
+        let start = Instant::now();
+        let (weight, profile_node) = profiled_weight(query, enabled_scoring)?;
+        let weight = with_static_rank(&self.inner.index, enabled_scoring, weight);
+        let weight_creation = start.elapsed();
+        let collect_nanos = AtomicU64::new(0);
+        let fruits = self.inner.index.search_executor().map(
+            |(segment_ord, segment_reader)| {
+                let start = Instant::now();
+                let fruit =
+                    collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader);
+                collect_nanos.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                fruit
+            },
+            self.segment_readers().iter().enumerate(),
+        )?;
+        let fruit = collector.merge_fruits(fruits)?;
+        // The time spent in the scorers is accounted for in the query profile.
+        let collect = Duration::from_nanos(collect_nanos.load(Ordering::Relaxed))
+            .saturating_sub(profile_node.total_time());
+        let search_profile = SearchProfile {
+            weight_creation,
+            collect,
+            query: profile_node.to_profile(),
+        };
+        Ok((fruit, search_profile))
+    }
+
     /// Summarize total space usage of this searcher.
     pub fn space_usage(&self) -> io::Result<SearcherSpaceUsage> {
         let mut space_usage = SearcherSpaceUsage::new();
@@ -241,6 +919,72 @@ impl Searcher {
         }
         Ok(space_usage)
     }
+
+    /// Returns the memory held by the searcher.
+    ///
+    /// See [`SearcherMemoryUsage`] for the meaning of each of its parts.
+    pub fn memory_usage(&self) -> io::Result<SearcherMemoryUsage> {
+        Ok(SearcherMemoryUsage {
+            doc_store_caches: self.doc_store_cache_stats().num_bytes,
+            segment_files: self.space_usage()?.total().get_bytes() as usize,
+        })
+    }
+}
+
+/// Reads the primary keys of the documents of a segment, see [`Searcher::primary_keys()`].
+enum PrimaryKeyReader<'a> {
+    Str(Option<StrColumn>, String),
+    U64(Column<u64>),
+    I64(Column<i64>),
+    Stored(&'a StoreReader, Field),
+}
+
+impl<'a> PrimaryKeyReader<'a> {
+    fn open(
+        segment_reader: &SegmentReader,
+        store_reader: &'a StoreReader,
+        id_field: Field,
+    ) -> crate::Result<PrimaryKeyReader<'a>> {
+        let field_entry = segment_reader.schema().get_field_entry(id_field);
+        let fast_fields = segment_reader.fast_fields();
+        let field_name = field_entry.name();
+        let primary_key_reader = match field_entry.field_type() {
+            FieldType::Str(_) if field_entry.is_fast() => {
+                PrimaryKeyReader::Str(fast_fields.str(field_name)?, String::new())
+            }
+            FieldType::U64(_) if field_entry.is_fast() => {
+                PrimaryKeyReader::U64(fast_fields.u64(field_name)?)
+            }
+            FieldType::I64(_) if field_entry.is_fast() => {
+                PrimaryKeyReader::I64(fast_fields.i64(field_name)?)
+            }
+            _ => PrimaryKeyReader::Stored(store_reader, id_field),
+        };
+        Ok(primary_key_reader)
+    }
+
+    fn primary_key(&mut self, doc: DocId) -> crate::Result<Option<OwnedValue>> {
+        let primary_key = match self {
+            PrimaryKeyReader::Str(str_column_opt, buffer) => {
+                let Some(str_column) = str_column_opt else {
+                    return Ok(None);
+                };
+                let Some(term_ord) = str_column.term_ords(doc).next() else {
+                    return Ok(None);
+                };
+                buffer.clear();
+                str_column.ord_to_str(term_ord, buffer)?;
+                Some(OwnedValue::Str(buffer.clone()))
+            }
+            PrimaryKeyReader::U64(column) => column.first(doc).map(OwnedValue::U64),
+            PrimaryKeyReader::I64(column) => column.first(doc).map(OwnedValue::I64),
+            PrimaryKeyReader::Stored(store_reader, field) => store_reader
+                .get_stored_document(doc)?
+                .get_first(*field)
+                .map(|value| value.to_owned_value()),
+        };
+        Ok(primary_key)
+    }
 }
 
 impl From<Arc<SearcherInner>> for Searcher {
@@ -256,6 +1000,11 @@ impl From<Arc<SearcherInner>> for Searcher {
 pub(crate) struct SearcherInner {
     schema: Schema,
     index: Index,
+    // Opstamp of the commit the segments were loaded from.
+    opstamp: Opstamp,
+    // Tracked in the index inventory, the segment metas protect the
+    // segment files from garbage collection.
+    _segment_metas: Vec<SegmentMeta>,
     segment_readers: Vec<SegmentReader>,
     store_readers: Vec<StoreReader>,
     generation: TrackedObject<SearcherGeneration>,
@@ -266,6 +1015,8 @@ impl SearcherInner {
     pub(crate) fn new(
         schema: Schema,
         index: Index,
+        opstamp: Opstamp,
+        segment_metas: Vec<SegmentMeta>,
         segment_readers: Vec<SegmentReader>,
         generation: TrackedObject<SearcherGeneration>,
         doc_store_cache_num_blocks: usize,
@@ -280,12 +1031,19 @@ impl SearcherInner {
         );
         let store_readers: Vec<StoreReader> = segment_readers
             .iter()
-            .map(|segment_reader| segment_reader.get_store_reader(doc_store_cache_num_blocks))
+            .map(|segment_reader| {
+                let store_reader = segment_reader.get_store_reader(doc_store_cache_num_blocks)?;
+                Ok(store_reader
+                    .with_metrics(index.metrics_counters().clone())
+                    .with_stored_value_transforms(index.stored_value_transforms().clone()))
+            })
             .collect::<io::Result<Vec<_>>>()?;
 
         Ok(SearcherInner {
             schema,
             index,
+            opstamp,
+            _segment_metas: segment_metas,
             segment_readers,
             store_readers,
             generation,
@@ -1,8 +1,11 @@
+use std::collections::BTreeMap;
 use std::fmt;
+use std::io::BufWriter;
 use std::path::PathBuf;
 
 use super::SegmentComponent;
-use crate::core::{Index, SegmentId, SegmentMeta};
+use crate::core::metrics::CountingTerminatingWrite;
+use crate::core::{Index, RestorableDelete, SegmentId, SegmentMeta};
 use crate::directory::error::{OpenReadError, OpenWriteError};
 use crate::directory::{Directory, FileSlice, WritePtr};
 use crate::schema::Schema;
@@ -53,6 +56,14 @@ impl Segment {
         }
     }
 
+    /// Sets the custom attributes of the segment.
+    pub(crate) fn with_attributes(self, attributes: BTreeMap<String, String>) -> Segment {
+        Segment {
+            index: self.index,
+            meta: self.meta.with_attributes(attributes),
+        }
+    }
+
     #[doc(hidden)]
     #[must_use]
     pub fn with_delete_meta(self, num_deleted_docs: u32, opstamp: Opstamp) -> Segment {
@@ -62,6 +73,57 @@ impl Segment {
         }
     }
 
+    /// Records a new delete file written at `opstamp`, keeping the current one as an earlier
+    /// delete generation.
+    pub(crate) fn with_new_delete_generation(
+        self,
+        num_deleted_docs: u32,
+        opstamp: Opstamp,
+    ) -> Segment {
+        Segment {
+            index: self.index,
+            meta: self
+                .meta
+                .with_new_delete_generation(num_deleted_docs, opstamp),
+        }
+    }
+
+    /// Records the delete file of `opstamp`, along with the earlier delete files of
+    /// `previous_opstamps`.
+    pub(crate) fn with_delete_generations(
+        self,
+        num_deleted_docs: u32,
+        opstamp: Opstamp,
+        previous_opstamps: Vec<Opstamp>,
+    ) -> Segment {
+        Segment {
+            index: self.index,
+            meta: self
+                .meta
+                .with_delete_generations(num_deleted_docs, opstamp, previous_opstamps),
+        }
+    }
+
+    /// Records the deleted documents of the segment that an undelete can restore.
+    pub(crate) fn with_restorable_deletes(
+        self,
+        restorable_deletes: Vec<RestorableDelete>,
+    ) -> Segment {
+        Segment {
+            index: self.index,
+            meta: self.meta.with_restorable_deletes(restorable_deletes),
+        }
+    }
+
+    /// Records that the fast field updates of the segment are stored in the file
+    /// associated with the given opstamp.
+    pub(crate) fn with_fast_field_updates(self, opstamp: Opstamp) -> Segment {
+        Segment {
+            index: self.index,
+            meta: self.meta.with_fast_field_updates(opstamp),
+        }
+    }
+
     /// Returns the segment's id.
     pub fn id(&self) -> SegmentId {
         self.meta.id()
@@ -81,10 +143,40 @@ impl Segment {
         self.index.directory().open_read(&path)
     }
 
+    /// Opens the delete file written at `opstamp`, which may be an earlier delete generation
+    /// of the segment.
+    pub(crate) fn open_read_deletes(&self, opstamp: Opstamp) -> Result<FileSlice, OpenReadError> {
+        let path = self.meta.delete_file_path(opstamp);
+        self.index.directory().open_read(&path)
+    }
+
     /// Open one of the component file for *regular* write.
     pub fn open_write(&mut self, component: SegmentComponent) -> Result<WritePtr, OpenWriteError> {
         let path = self.relative_path(component);
-        let write = self.index.directory_mut().open_write(&path)?;
-        Ok(write)
+        self.open_write_path(path)
+    }
+
+    /// Opens the delete file written at `opstamp` for write, to record an earlier delete
+    /// generation of the segment.
+    pub(crate) fn open_write_deletes(
+        &mut self,
+        opstamp: Opstamp,
+    ) -> Result<WritePtr, OpenWriteError> {
+        let path = self.meta.delete_file_path(opstamp);
+        self.open_write_path(path)
+    }
+
+    fn open_write_path(&mut self, path: PathBuf) -> Result<WritePtr, OpenWriteError> {
+        let write = self
+            .index
+            .directory_mut()
+            .open_write(&path)?
+            .into_inner()
+            .map_err(|_| ())
+            .expect("buffer should be empty");
+        Ok(BufWriter::new(Box::new(CountingTerminatingWrite::new(
+            write,
+            self.index.metrics_counters().clone(),
+        ))))
     }
 }
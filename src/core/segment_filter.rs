@@ -0,0 +1,71 @@
+use crate::SegmentReader;
+
+/// Decides which segments a search goes through, see
+/// [`Searcher::search_with_segment_filter()`](crate::Searcher::search_with_segment_filter).
+///
+/// A filter rejects whole segments before any of their postings is read, typically based on
+/// their [custom attributes](SegmentReader::segment_attributes), e.g. to skip the segments
+/// whose documents are out of the time range of the query.
+pub trait SegmentFilter: Sync {
+    /// Returns true if the segment needs to be searched.
+    fn accept(&self, segment_reader: &SegmentReader) -> bool;
+}
+
+impl<F> SegmentFilter for F
+where F: Fn(&SegmentReader) -> bool + Sync
+{
+    fn accept(&self, segment_reader: &SegmentReader) -> bool {
+        self(segment_reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::collector::Count;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST};
+    use crate::{Index, IndexWriter, SegmentReader};
+
+    #[test]
+    fn test_search_with_segment_filter() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_u64_field("timestamp", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.set_segment_attributes_provider(Box::new(
+            |segment_reader: &SegmentReader| -> crate::Result<BTreeMap<String, String>> {
+                let column = segment_reader.fast_fields().u64("timestamp")?;
+                Ok(BTreeMap::from([(
+                    "max".to_string(),
+                    column.max_value().to_string(),
+                )]))
+            },
+        ));
+        index_writer.add_document(doc!(timestamp => 10u64))?;
+        index_writer.add_document(doc!(timestamp => 20u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(timestamp => 30u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let recent_segments = |segment_reader: &SegmentReader| {
+            segment_reader.segment_attributes()["max"]
+                .parse::<u64>()
+                .unwrap()
+                >= 25
+        };
+        assert_eq!(
+            searcher.search_with_segment_filter(&AllQuery, &Count, &recent_segments)?,
+            1
+        );
+        assert_eq!(
+            searcher.search_with_segment_filter(&AllQuery, &Count, &|_: &SegmentReader| true)?,
+            3
+        );
+        Ok(())
+    }
+}
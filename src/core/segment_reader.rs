@@ -1,22 +1,26 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::BitOrAssign;
 use std::sync::{Arc, RwLock};
 use std::{fmt, io};
 
+use common::{BitSet, ReadOnlyBitSet};
 use fnv::FnvHashMap;
 use itertools::Itertools;
 
 use crate::core::{InvertedIndexReader, Segment, SegmentComponent, SegmentId};
 use crate::directory::{CompositeFile, FileSlice};
 use crate::error::DataCorruption;
-use crate::fastfield::{intersect_alive_bitsets, AliveBitSet, FacetReader, FastFieldReaders};
+use crate::fastfield::{
+    intersect_alive_bitsets, AliveBitSet, FacetReader, FastFieldReaders, FastFieldUpdates,
+};
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders};
 use crate::json_utils::json_path_sep_to_dot;
 use crate::schema::{Field, IndexRecordOption, Schema, Type};
 use crate::space_usage::SegmentSpaceUsage;
 use crate::store::StoreReader;
-use crate::termdict::TermDictionary;
-use crate::{DocId, Opstamp};
+use crate::termdict::{TermBloomFilter, TermDictionary, BLOOM_FILTER_IDX};
+use crate::termvector::TermVectorsReader;
+use crate::{DateTime, DocId, Opstamp};
 
 /// Entry point to access all of the datastructures of the `Segment`
 ///
@@ -34,6 +38,7 @@ pub struct SegmentReader {
 
     segment_id: SegmentId,
     delete_opstamp: Option<Opstamp>,
+    segment_attributes: BTreeMap<String, String>,
 
     max_doc: DocId,
     num_docs: DocId,
@@ -43,6 +48,7 @@ pub struct SegmentReader {
     positions_composite: CompositeFile,
     fast_fields_readers: FastFieldReaders,
     fieldnorm_readers: FieldNormReaders,
+    term_vectors_reader: TermVectorsReader,
 
     store_file: FileSlice,
     alive_bitset_opt: Option<AliveBitSet>,
@@ -131,6 +137,11 @@ impl SegmentReader {
         &self.fieldnorm_readers
     }
 
+    /// Accessor to the segment's [`TermVectorsReader`].
+    pub fn term_vectors(&self) -> &TermVectorsReader {
+        &self.term_vectors_reader
+    }
+
     /// Accessor to the segment's [`StoreReader`](crate::store::StoreReader).
     ///
     /// `cache_num_blocks` sets the number of decompressed blocks to be cached in an LRU.
@@ -170,10 +181,20 @@ impl SegmentReader {
         let schema = segment.schema();
 
         let fast_fields_data = segment.open_read(SegmentComponent::FastFields)?;
-        let fast_fields_readers = FastFieldReaders::open(fast_fields_data, schema.clone())?;
+        let fast_field_updates = FastFieldUpdates::open(segment)?;
+        let fast_fields_readers = FastFieldReaders::open(fast_fields_data, schema.clone())?
+            .with_updates(Arc::new(fast_field_updates));
         let fieldnorm_data = segment.open_read(SegmentComponent::FieldNorms)?;
         let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
 
+        let term_vectors_reader = {
+            if let Ok(term_vectors_file) = segment.open_read(SegmentComponent::TermVectors) {
+                TermVectorsReader::open(term_vectors_file)?
+            } else {
+                TermVectorsReader::empty()
+            }
+        };
+
         let original_bitset = if segment.meta().has_deletes() {
             let alive_doc_file_slice = segment.open_read(SegmentComponent::Delete)?;
             let alive_doc_data = alive_doc_file_slice.read_bytes()?;
@@ -198,8 +219,10 @@ impl SegmentReader {
             postings_composite,
             fast_fields_readers,
             fieldnorm_readers,
+            term_vectors_reader,
             segment_id: segment.id(),
             delete_opstamp: segment.meta().delete_opstamp(),
+            segment_attributes: segment.meta().attributes().clone(),
             store_file,
             alive_bitset_opt,
             positions_composite,
@@ -207,6 +230,51 @@ impl SegmentReader {
         })
     }
 
+    /// Open a new segment for reading. If `expiry` is given as `(expiry_field, now)`, the
+    /// documents whose expiry date, held by the `expiry_field` fast field, is not after `now`
+    /// are considered as deleted.
+    pub(crate) fn open_excluding_expired(
+        segment: &Segment,
+        custom_bitset: Option<AliveBitSet>,
+        expiry: Option<(&str, DateTime)>,
+    ) -> crate::Result<SegmentReader> {
+        let mut segment_reader = Self::open_with_custom_alive_set(segment, custom_bitset)?;
+        let Some((expiry_field, now)) = expiry else {
+            return Ok(segment_reader);
+        };
+        let Some(expiry_column) = segment_reader
+            .fast_fields()
+            .column_opt::<DateTime>(expiry_field)?
+        else {
+            return Ok(segment_reader);
+        };
+        let max_doc = segment_reader.max_doc();
+        let mut expired_docs = Vec::new();
+        expiry_column.get_docids_for_value_range(
+            DateTime::MIN..=now,
+            0..max_doc,
+            &mut expired_docs,
+        );
+        if expired_docs.is_empty() {
+            return Ok(segment_reader);
+        }
+        let mut unexpired_bitset = BitSet::with_max_value_and_full(max_doc);
+        for doc in expired_docs {
+            unexpired_bitset.remove(doc);
+        }
+        let unexpired_bitset = AliveBitSet::from(ReadOnlyBitSet::from(&unexpired_bitset));
+        let alive_bitset_opt = intersect_alive_bitset(
+            segment_reader.alive_bitset_opt.take(),
+            Some(unexpired_bitset),
+        );
+        segment_reader.num_docs = alive_bitset_opt
+            .as_ref()
+            .map(|alive_bitset| alive_bitset.num_alive_docs() as u32)
+            .unwrap_or(max_doc);
+        segment_reader.alive_bitset_opt = alive_bitset_opt;
+        Ok(segment_reader)
+    }
+
     /// Returns a field reader associated with the field given in argument.
     /// If the field was not present in the index during indexing time,
     /// the InvertedIndexReader is empty.
@@ -268,11 +336,18 @@ impl SegmentReader {
             DataCorruption::comment_only(error_msg)
         })?;
 
+        let bloom_filter_opt = self
+            .termdict_composite
+            .open_read_with_idx(field, BLOOM_FILTER_IDX)
+            .map(TermBloomFilter::open)
+            .transpose()?;
+
         let inv_idx_reader = Arc::new(InvertedIndexReader::new(
             TermDictionary::open(termdict_file)?,
             postings_file,
             positions_file,
             record_option,
+            bloom_filter_opt,
         )?);
 
         // by releasing the lock in between, we may end up opening the inverting index
@@ -387,6 +462,12 @@ impl SegmentReader {
         self.segment_id
     }
 
+    /// Returns the custom attributes of the segment, see
+    /// [`SegmentAttributesProvider`](crate::SegmentAttributesProvider).
+    pub fn segment_attributes(&self) -> &BTreeMap<String, String> {
+        &self.segment_attributes
+    }
+
     /// Returns the delete opstamp
     pub fn delete_opstamp(&self) -> Option<Opstamp> {
         self.delete_opstamp
@@ -397,6 +478,22 @@ impl SegmentReader {
         self.alive_bitset_opt.as_ref()
     }
 
+    /// Returns a reader of the same segment, where the documents missing from `alive_bitset`
+    /// are deleted too.
+    pub(crate) fn with_alive_bitset(&self, alive_bitset: AliveBitSet) -> SegmentReader {
+        let alive_bitset_opt =
+            intersect_alive_bitset(self.alive_bitset_opt.clone(), Some(alive_bitset));
+        let num_docs = alive_bitset_opt
+            .as_ref()
+            .map(|alive_bitset| alive_bitset.num_alive_docs() as u32)
+            .unwrap_or(self.max_doc);
+        SegmentReader {
+            alive_bitset_opt,
+            num_docs,
+            ..self.clone()
+        }
+    }
+
     /// Returns true if the `doc` is marked
     /// as deleted.
     pub fn is_deleted(&self, doc: DocId) -> bool {
@@ -424,6 +521,7 @@ impl SegmentReader {
             self.fast_fields_readers.space_usage(self.schema())?,
             self.fieldnorm_readers.space_usage(),
             self.get_store_reader(0)?.space_usage(),
+            self.term_vectors_reader.space_usage(),
             self.alive_bitset_opt
                 .as_ref()
                 .map(AliveBitSet::space_usage)
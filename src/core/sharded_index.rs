@@ -0,0 +1,317 @@
+use std::hash::Hasher;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+
+use fnv::FnvHasher;
+
+use crate::schema::document::{Document, Value};
+use crate::schema::{Field, Schema, TantivyDocument, Term};
+use crate::{Index, IndexReader, IndexWriter, MultiSearcher, Opstamp, TantivyError};
+
+/// A set of indexes sharing the same schema, the shards, used as a single index.
+///
+/// Documents are assigned to a shard according to the hash of the value of their shard key
+/// field, so that all of the documents with a given key end up in the same shard. Searches go
+/// through a [`MultiSearcher`], which queries all of the shards and merges their results, with
+/// scores computed from the statistics of all of the shards.
+///
+/// The shard of a document only depends on its key and on the number of shards: the number of
+/// shards of an index cannot be changed without reindexing it.
+#[derive(Clone)]
+pub struct ShardedIndex {
+    shards: Vec<Index>,
+    shard_key: Field,
+}
+
+impl ShardedIndex {
+    /// Creates a `ShardedIndex` from existing indexes.
+    ///
+    /// The shards need to share the same schema, and to be given in the same order every time,
+    /// since a document is assigned to a shard according to its position.
+    pub fn new(shards: Vec<Index>, shard_key: Field) -> crate::Result<ShardedIndex> {
+        let Some((first, others)) = shards.split_first() else {
+            return Err(TantivyError::InvalidArgument(
+                "A ShardedIndex needs at least one shard.".to_string(),
+            ));
+        };
+        let schema = first.schema();
+        if others.iter().any(|shard| shard.schema() != schema) {
+            return Err(TantivyError::SchemaError(
+                "All of the shards of a ShardedIndex must share the same schema.".to_string(),
+            ));
+        }
+        if !schema.fields().any(|(field, _)| field == shard_key) {
+            return Err(TantivyError::FieldNotFound(format!("{shard_key:?}")));
+        }
+        Ok(ShardedIndex { shards, shard_key })
+    }
+
+    /// Creates a `ShardedIndex` of `num_shards` indexes in memory.
+    pub fn create_in_ram(
+        schema: Schema,
+        num_shards: usize,
+        shard_key: Field,
+    ) -> crate::Result<ShardedIndex> {
+        let shards = (0..num_shards)
+            .map(|_| Index::create_in_ram(schema.clone()))
+            .collect();
+        ShardedIndex::new(shards, shard_key)
+    }
+
+    /// Creates a `ShardedIndex` of `num_shards` indexes, in the `shard-<ord>` subdirectories
+    /// of `directory_path`.
+    #[cfg(feature = "mmap")]
+    pub fn create_in_dir<P: AsRef<Path>>(
+        directory_path: P,
+        schema: Schema,
+        num_shards: usize,
+        shard_key: Field,
+    ) -> crate::Result<ShardedIndex> {
+        let mut shards = Vec::with_capacity(num_shards);
+        for shard_ord in 0..num_shards {
+            let shard_path = shard_path(directory_path.as_ref(), shard_ord);
+            std::fs::create_dir_all(&shard_path)?;
+            shards.push(Index::create_in_dir(shard_path, schema.clone())?);
+        }
+        ShardedIndex::new(shards, shard_key)
+    }
+
+    /// Opens a `ShardedIndex` created with [`ShardedIndex::create_in_dir()`].
+    #[cfg(feature = "mmap")]
+    pub fn open_in_dir<P: AsRef<Path>>(
+        directory_path: P,
+        shard_key: Field,
+    ) -> crate::Result<ShardedIndex> {
+        let mut shards = Vec::new();
+        loop {
+            let shard_path = shard_path(directory_path.as_ref(), shards.len());
+            if !shard_path.exists() {
+                break;
+            }
+            shards.push(Index::open_in_dir(shard_path)?);
+        }
+        ShardedIndex::new(shards, shard_key)
+    }
+
+    /// Returns the shards.
+    pub fn shards(&self) -> &[Index] {
+        &self.shards
+    }
+
+    /// Returns the field whose value assigns documents to shards.
+    pub fn shard_key(&self) -> Field {
+        self.shard_key
+    }
+
+    /// Returns the schema shared by the shards.
+    pub fn schema(&self) -> Schema {
+        self.shards[0].schema()
+    }
+
+    /// Returns the ordinal of the shard holding the documents whose shard key is the value of
+    /// `term`.
+    pub fn shard_ord(&self, term: &Term) -> usize {
+        let mut hasher = FnvHasher::default();
+        hasher.write(term.serialized_value_bytes());
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    /// Creates a writer for each of the shards, with `memory_budget_per_shard` bytes each.
+    pub fn writer<D: Document>(
+        &self,
+        memory_budget_per_shard: usize,
+    ) -> crate::Result<ShardedIndexWriter<D>> {
+        let writers = self
+            .shards
+            .iter()
+            .map(|shard| shard.writer(memory_budget_per_shard))
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(ShardedIndexWriter {
+            sharded_index: self.clone(),
+            writers,
+        })
+    }
+
+    /// Creates a reader for each of the shards, with the default settings.
+    pub fn reader(&self) -> crate::Result<ShardedIndexReader> {
+        let readers = self
+            .shards
+            .iter()
+            .map(Index::reader)
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(ShardedIndexReader { readers })
+    }
+}
+
+#[cfg(feature = "mmap")]
+fn shard_path(directory_path: &Path, shard_ord: usize) -> std::path::PathBuf {
+    directory_path.join(format!("shard-{shard_ord}"))
+}
+
+/// Writes documents to the shards of a [`ShardedIndex`].
+///
+/// Each shard has its own [`IndexWriter`], and commits are not atomic across shards: if a
+/// commit fails, some of the shards may have committed while others did not.
+pub struct ShardedIndexWriter<D: Document = TantivyDocument> {
+    sharded_index: ShardedIndex,
+    writers: Vec<IndexWriter<D>>,
+}
+
+impl<D: Document> ShardedIndexWriter<D> {
+    /// Returns the writers of the shards.
+    pub fn shard_writers(&self) -> &[IndexWriter<D>] {
+        &self.writers
+    }
+
+    /// Adds a document to the shard assigned to its shard key, and returns the ordinal of this
+    /// shard.
+    ///
+    /// Returns an error if the document has no value for the shard key, or if the value is not
+    /// a string, an integer or bytes.
+    pub fn add_document(&self, document: D) -> crate::Result<usize> {
+        let term = shard_key_term(&document, self.sharded_index.shard_key)?;
+        let shard_ord = self.sharded_index.shard_ord(&term);
+        self.writers[shard_ord].add_document(document)?;
+        Ok(shard_ord)
+    }
+
+    /// Deletes the documents containing `term`.
+    ///
+    /// Terms of the shard key field only target the shard assigned to them, other terms are
+    /// deleted from all of the shards.
+    pub fn delete_term(&self, term: Term) {
+        if term.field() == self.sharded_index.shard_key {
+            let shard_ord = self.sharded_index.shard_ord(&term);
+            self.writers[shard_ord].delete_term(term);
+        } else {
+            for writer in &self.writers {
+                writer.delete_term(term.clone());
+            }
+        }
+    }
+
+    /// Commits all of the shards, and returns their opstamps.
+    pub fn commit(&mut self) -> crate::Result<Vec<Opstamp>> {
+        self.writers.iter_mut().map(IndexWriter::commit).collect()
+    }
+
+    /// Rolls all of the shards back to their last commit, and returns their opstamps.
+    pub fn rollback(&mut self) -> crate::Result<Vec<Opstamp>> {
+        self.writers.iter_mut().map(IndexWriter::rollback).collect()
+    }
+}
+
+/// Returns the term of the shard key of `document`.
+fn shard_key_term<D: Document>(document: &D, shard_key: Field) -> crate::Result<Term> {
+    let Some((_, value)) = document
+        .iter_fields_and_values()
+        .find(|(field, _)| *field == shard_key)
+    else {
+        return Err(TantivyError::InvalidArgument(format!(
+            "The document has no value for the shard key {shard_key:?}"
+        )));
+    };
+    if let Some(text) = value.as_str() {
+        Ok(Term::from_field_text(shard_key, text))
+    } else if let Some(val) = value.as_u64() {
+        Ok(Term::from_field_u64(shard_key, val))
+    } else if let Some(val) = value.as_i64() {
+        Ok(Term::from_field_i64(shard_key, val))
+    } else if let Some(bytes) = value.as_bytes() {
+        Ok(Term::from_field_bytes(shard_key, bytes))
+    } else {
+        Err(TantivyError::InvalidArgument(format!(
+            "The shard key {shard_key:?} must be a string, an integer or bytes"
+        )))
+    }
+}
+
+/// Gives access to searchers over all of the shards of a [`ShardedIndex`].
+#[derive(Clone)]
+pub struct ShardedIndexReader {
+    readers: Vec<IndexReader>,
+}
+
+impl ShardedIndexReader {
+    /// Returns the readers of the shards.
+    pub fn shard_readers(&self) -> &[IndexReader] {
+        &self.readers
+    }
+
+    /// Reloads the readers of all of the shards.
+    pub fn reload(&self) -> crate::Result<()> {
+        self.readers.iter().try_for_each(IndexReader::reload)
+    }
+
+    /// Returns a searcher over the last loaded state of all of the shards.
+    pub fn searcher(&self) -> MultiSearcher {
+        let searchers = self.readers.iter().map(IndexReader::searcher).collect();
+        MultiSearcher::new(searchers).expect("the shards share the same schema")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShardedIndex;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_sharded_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let sharded_index = ShardedIndex::create_in_ram(schema.clone(), 3, id)?;
+        let mut sharded_writer = sharded_index.writer::<TantivyDocument>(15_000_000)?;
+        let single_index = Index::create_in_ram(schema);
+        let mut single_writer: IndexWriter = single_index.writer_for_tests()?;
+        let texts = ["a b c", "a a", "b c d", "a d d d", "c", "a b", "d a", "b"];
+        let mut shard_ords = Vec::new();
+        for (doc_id, &text_value) in texts.iter().enumerate() {
+            let id_value = format!("doc-{doc_id}");
+            shard_ords.push(
+                sharded_writer.add_document(doc!(id => id_value.clone(), text => text_value))?,
+            );
+            single_writer.add_document(doc!(id => id_value, text => text_value))?;
+        }
+        sharded_writer.commit()?;
+        single_writer.commit()?;
+        // The shard of a document only depends on its key.
+        assert_eq!(
+            sharded_index.shard_ord(&Term::from_field_text(id, "doc-3")),
+            shard_ords[3]
+        );
+        assert!(shard_ords
+            .iter()
+            .any(|&shard_ord| shard_ord != shard_ords[0]));
+
+        let reader = sharded_index.reader()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 8);
+        let query = TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        let single_top_docs = single_index
+            .reader()?
+            .searcher()
+            .search(&query, &TopDocs::with_limit(3))?;
+        let scores =
+            |top_docs: &[(f32, _)]| top_docs.iter().map(|(score, _)| *score).collect::<Vec<_>>();
+        assert_eq!(scores(&top_docs), scores(&single_top_docs));
+
+        sharded_writer.delete_term(Term::from_field_text(id, "doc-1"));
+        sharded_writer.delete_term(Term::from_field_text(text, "c"));
+        sharded_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().search(&query, &Count)?, 3);
+
+        assert!(sharded_writer.add_document(doc!(text => "e")).is_err());
+        Ok(())
+    }
+}
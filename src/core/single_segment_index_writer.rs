@@ -4,7 +4,7 @@ use crate::indexer::operation::AddOperation;
 use crate::indexer::segment_updater::save_metas;
 use crate::indexer::SegmentWriter;
 use crate::schema::document::Document;
-use crate::{Directory, Index, IndexMeta, Opstamp, Segment, TantivyDocument};
+use crate::{Directory, Index, IndexMeta, Opstamp, Score, Segment, TantivyDocument};
 
 #[doc(hidden)]
 pub struct SingleSegmentIndexWriter<D: Document = TantivyDocument> {
@@ -31,10 +31,17 @@ impl<D: Document> SingleSegmentIndexWriter<D> {
     }
 
     pub fn add_document(&mut self, document: D) -> crate::Result<()> {
+        self.add_document_with_boost(document, 1.0)
+    }
+
+    pub fn add_document_with_boost(&mut self, document: D, boost: Score) -> crate::Result<()> {
         let opstamp = self.opstamp;
         self.opstamp += 1;
-        self.segment_writer
-            .add_document(AddOperation { opstamp, document })
+        self.segment_writer.add_document(AddOperation {
+            opstamp,
+            document,
+            boost,
+        })
     }
 
     pub fn finalize(self) -> crate::Result<Index> {
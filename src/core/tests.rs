@@ -1,13 +1,18 @@
-use crate::collector::Count;
+use std::path::PathBuf;
+
+use crate::collector::{Count, TopDocs};
 use crate::directory::{RamDirectory, WatchCallback};
 use crate::indexer::{LogMergePolicy, NoMergePolicy};
 use crate::json_utils::JsonTermWriter;
-use crate::query::TermQuery;
-use crate::schema::{Field, IndexRecordOption, Schema, Type, INDEXED, STRING, TEXT};
+use crate::query::{AllQuery, TermQuery};
+use crate::schema::{
+    Field, IndexRecordOption, OwnedValue, Schema, Type, FAST, INDEXED, STORED, STRING, TEXT,
+};
+use crate::time::{Duration, OffsetDateTime};
 use crate::tokenizer::TokenizerManager;
 use crate::{
-    Directory, DocSet, Index, IndexBuilder, IndexReader, IndexSettings, IndexWriter, Postings,
-    ReloadPolicy, SegmentId, TantivyDocument, Term,
+    DateTime, Directory, DocAddress, DocSet, Index, IndexBuilder, IndexMetrics, IndexReader,
+    IndexSettings, IndexWriter, Postings, ReloadPolicy, SegmentId, TantivyDocument, Term,
 };
 
 #[test]
@@ -262,9 +267,14 @@ fn garbage_collect_works_as_intended() -> crate::Result<()> {
 
     writer.wait_merging_threads()?;
 
+    // The files of the merged segments are only garbage collected
+    // once the searcher using them is released.
+    reader.reload().unwrap();
+    let writer: IndexWriter = index.writer_with_num_threads(1, 32_000_000).unwrap();
+    writer.garbage_collect_files().wait()?;
+
     let mem_right_after_merge_finished = directory.total_mem_usage();
 
-    reader.reload().unwrap();
     let searcher = reader.searcher();
     assert_eq!(searcher.segment_readers().len(), 1);
     assert_eq!(searcher.num_docs(), 8_000);
@@ -346,6 +356,97 @@ fn test_merging_segment_update_docfreq() {
     assert_eq!(term_info.doc_freq, 12);
 }
 
+#[test]
+fn test_string_field_bloom_filter() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text_field = schema_builder.add_text_field("text", TEXT);
+    let id_field = schema_builder.add_text_field("id", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut writer: IndexWriter = index.writer_for_tests()?;
+    writer.set_merge_policy(Box::new(NoMergePolicy));
+    for i in 0..100 {
+        writer.add_document(doc!(text_field=>"hello", id_field=>format!("a{i}")))?;
+    }
+    writer.commit()?;
+    for i in 0..100 {
+        writer.add_document(doc!(text_field=>"hello", id_field=>format!("b{i}")))?;
+    }
+    writer.commit()?;
+    writer.delete_term(Term::from_field_text(id_field, "a3"));
+    writer.delete_term(Term::from_field_text(id_field, "b5"));
+    writer.commit()?;
+
+    let check_segment_readers = |searcher: &crate::Searcher| -> crate::Result<()> {
+        for segment_reader in searcher.segment_readers() {
+            let inv_index = segment_reader.inverted_index(id_field)?;
+            let mut num_absent_terms_skipped = 0;
+            for i in 0..100 {
+                for prefix in ["a", "b"] {
+                    let term = Term::from_field_text(id_field, &format!("{prefix}{i}"));
+                    let doc_freq = inv_index.doc_freq(&term)?;
+                    if doc_freq > 0 {
+                        assert!(inv_index.may_contain_term(&term));
+                    } else if !inv_index.may_contain_term(&term) {
+                        num_absent_terms_skipped += 1;
+                    }
+                }
+            }
+            assert!(num_absent_terms_skipped > 0);
+            // Text fields do not have a bloom filter.
+            let text_inv_index = segment_reader.inverted_index(text_field)?;
+            assert!(text_inv_index.may_contain_term(&Term::from_field_text(text_field, "absent")));
+        }
+        Ok(())
+    };
+
+    let reader = index.reader()?;
+    let searcher = reader.searcher();
+    assert_eq!(searcher.num_docs(), 198);
+    assert_eq!(searcher.segment_readers().len(), 2);
+    check_segment_readers(&searcher)?;
+
+    let segment_ids = index.searchable_segment_ids()?;
+    writer.merge(&segment_ids).wait()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    assert_eq!(searcher.segment_readers().len(), 1);
+    assert_eq!(searcher.num_docs(), 198);
+    check_segment_readers(&searcher)?;
+    let term = Term::from_field_text(id_field, "b7");
+    let term_query = TermQuery::new(term, IndexRecordOption::Basic);
+    assert_eq!(searcher.search(&term_query, &Count)?, 1);
+    Ok(())
+}
+
+#[test]
+fn test_string_field_bloom_filter_is_refused_by_format_version_6() -> crate::Result<()> {
+    use crate::core::SegmentComponent;
+    use crate::directory::error::Incompatibility;
+    use crate::directory::Footer;
+
+    let mut schema_builder = Schema::builder();
+    let id_field = schema_builder.add_text_field("id", STRING);
+    let directory = RamDirectory::create();
+    let index = Index::create(
+        directory.clone(),
+        schema_builder.build(),
+        IndexSettings::default(),
+    )?;
+    let mut writer: IndexWriter = index.writer_for_tests()?;
+    writer.add_document(doc!(id_field=>"a"))?;
+    writer.commit()?;
+
+    let segment_metas = index.searchable_segment_metas()?;
+    let terms_path = segment_metas[0].relative_path(SegmentComponent::Terms);
+    let (footer, _) = Footer::extract_footer(directory.open_read(&terms_path)?)?;
+    assert!(footer.is_compatible().is_ok());
+    assert!(matches!(
+        footer.version.check_compatibility_with(4..=6),
+        Err(Incompatibility::IndexMismatch { .. })
+    ));
+    Ok(())
+}
+
 // motivated by https://github.com/quickwit-oss/quickwit/issues/4130
 #[test]
 fn test_positions_merge_bug_non_text_json_vint() {
@@ -474,3 +575,381 @@ fn test_non_text_json_term_freq_bitpacked() {
         assert_eq!(postings.term_freq(), 1u32);
     }
 }
+
+#[test]
+fn test_stable_doc_address() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id_field = schema_builder.add_text_field("id", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(id_field => "a"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(id_field => "b"))?;
+    index_writer.add_document(doc!(id_field => "c"))?;
+    index_writer.commit()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    let segment_ord = searcher
+        .segment_readers()
+        .iter()
+        .position(|segment_reader| segment_reader.max_doc() == 2)
+        .unwrap() as u32;
+    let doc_address = DocAddress::new(segment_ord, 1);
+    let stable_doc_address = searcher.stable_doc_address(doc_address);
+    assert_eq!(
+        stable_doc_address.segment_id,
+        searcher.segment_reader(segment_ord).segment_id()
+    );
+    assert_eq!(
+        searcher.resolve_doc_address(stable_doc_address),
+        Some(doc_address)
+    );
+
+    // Deleting another document keeps the address valid.
+    index_writer.delete_term(Term::from_field_text(id_field, "a"));
+    index_writer.commit()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    let resolved_doc_address = searcher.resolve_doc_address(stable_doc_address).unwrap();
+    assert_eq!(
+        searcher
+            .segment_reader(resolved_doc_address.segment_ord)
+            .segment_id(),
+        stable_doc_address.segment_id
+    );
+    assert_eq!(resolved_doc_address.doc_id, 1);
+
+    // Once the segment is merged away, the address cannot be resolved anymore.
+    let segment_ids = index.searchable_segment_ids()?;
+    index_writer.merge(&segment_ids).wait()?;
+    reader.reload()?;
+    assert_eq!(
+        reader.searcher().resolve_doc_address(stable_doc_address),
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn test_primary_keys() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let fast_id = schema_builder.add_text_field("fast_id", STRING | FAST);
+    let stored_id = schema_builder.add_text_field("stored_id", STRING | STORED);
+    let num_id = schema_builder.add_u64_field("num_id", FAST);
+    let title = schema_builder.add_text_field("title", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(fast_id => "a", stored_id => "a", num_id => 1u64))?;
+    index_writer.add_document(doc!(title => "no id"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(fast_id => "c", stored_id => "c", num_id => 3u64))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let segment_ord = |max_doc: u32| {
+        searcher
+            .segment_readers()
+            .iter()
+            .position(|segment_reader| segment_reader.max_doc() == max_doc)
+            .unwrap() as u32
+    };
+    let doc_addresses = [
+        DocAddress::new(segment_ord(1), 0),
+        DocAddress::new(segment_ord(2), 1),
+        DocAddress::new(segment_ord(2), 0),
+    ];
+    let text_keys = vec![
+        Some(OwnedValue::Str("c".to_string())),
+        None,
+        Some(OwnedValue::Str("a".to_string())),
+    ];
+    assert_eq!(searcher.primary_keys(fast_id, &doc_addresses)?, text_keys);
+    assert_eq!(searcher.primary_keys(stored_id, &doc_addresses)?, text_keys);
+    assert_eq!(
+        searcher.primary_keys(num_id, &doc_addresses)?,
+        vec![Some(OwnedValue::U64(3)), None, Some(OwnedValue::U64(1))]
+    );
+    assert!(searcher.primary_keys(title, &doc_addresses).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_searcher_docs() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id_field = schema_builder.add_text_field("id", STRING | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(id_field => "a"))?;
+    index_writer.add_document(doc!(id_field => "b"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(id_field => "c"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let doc_address = |id: &str| -> crate::Result<DocAddress> {
+        let query = TermQuery::new(
+            Term::from_field_text(id_field, id),
+            IndexRecordOption::Basic,
+        );
+        Ok(searcher.search(&query, &TopDocs::with_limit(1))?[0].1)
+    };
+    let doc_addresses = [
+        doc_address("c")?,
+        doc_address("a")?,
+        doc_address("b")?,
+        doc_address("c")?,
+    ];
+    let ids: Vec<Option<OwnedValue>> = searcher
+        .docs::<TantivyDocument>(&doc_addresses)?
+        .iter()
+        .map(|doc| doc.get_first(id_field).cloned())
+        .collect();
+    let expected_ids: Vec<Option<OwnedValue>> = ["c", "a", "b", "c"]
+        .iter()
+        .map(|id| Some(OwnedValue::Str(id.to_string())))
+        .collect();
+    assert_eq!(ids, expected_ids);
+    assert!(searcher.docs::<TantivyDocument>(&[])?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_searcher_protects_segment_files_from_gc() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id_field = schema_builder.add_text_field("id", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(id_field => "a"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(id_field => "b"))?;
+    index_writer.commit()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    let segment_files: Vec<PathBuf> = index
+        .searchable_segment_metas()?
+        .iter()
+        .flat_map(|segment_meta| segment_meta.list_files())
+        .filter(|path| index.directory().exists(path).unwrap())
+        .collect();
+    assert!(!segment_files.is_empty());
+
+    let segment_ids = index.searchable_segment_ids()?;
+    assert_eq!(segment_ids.len(), 2);
+    index_writer.merge(&segment_ids).wait()?;
+    index_writer.garbage_collect_files().wait()?;
+    for path in &segment_files {
+        assert!(index.directory().exists(path)?);
+    }
+    assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+
+    drop(searcher);
+    reader.reload()?;
+    index_writer.garbage_collect_files().wait()?;
+    for path in &segment_files {
+        assert!(!index.directory().exists(path)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_expired_documents_are_filtered_and_merged_away() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id_field = schema_builder.add_text_field("id", STRING);
+    let expires_at = schema_builder.add_date_field("expires_at", FAST);
+    let index = Index::builder()
+        .schema(schema_builder.build())
+        .settings(IndexSettings {
+            expiry_field: Some("expires_at".to_string()),
+            ..Default::default()
+        })
+        .create_in_ram()?;
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let now = OffsetDateTime::now_utc();
+    let past = DateTime::from_utc(now - Duration::hours(1));
+    let future = DateTime::from_utc(now + Duration::hours(1));
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(id_field => "expired", expires_at => past))?;
+    index_writer.add_document(doc!(id_field => "alive", expires_at => future))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(id_field => "forever"))?;
+    index_writer.add_document(doc!(id_field => "expired", expires_at => past))?;
+    index_writer.commit()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    assert_eq!(searcher.num_docs(), 2);
+    assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+    let expired_query = TermQuery::new(
+        Term::from_field_text(id_field, "expired"),
+        IndexRecordOption::Basic,
+    );
+    assert_eq!(searcher.search(&expired_query, &Count)?, 0);
+
+    let segment_ids = index.searchable_segment_ids()?;
+    index_writer.merge(&segment_ids).wait()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    assert_eq!(searcher.segment_readers().len(), 1);
+    assert_eq!(searcher.segment_reader(0).max_doc(), 2);
+    assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+    Ok(())
+}
+
+#[test]
+fn test_expiry_field_needs_to_be_a_date_fast_field() {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field("id", STRING);
+    let index_res = Index::builder()
+        .schema(schema_builder.build())
+        .settings(IndexSettings {
+            expiry_field: Some("id".to_string()),
+            ..Default::default()
+        })
+        .create_in_ram();
+    assert!(index_res.is_err());
+}
+
+#[test]
+fn test_export_json() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id_field = schema_builder.add_text_field("id", STRING | STORED);
+    let title_field = schema_builder.add_text_field("title", TEXT | STORED);
+    let body_field = schema_builder.add_text_field("body", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.add_document(doc!(id_field => "1", title_field => "first", body_field => "a"))?;
+    index_writer.add_document(doc!(id_field => "2", title_field => "second"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(id_field => "3", title_field => "third"))?;
+    index_writer.delete_term(Term::from_field_text(id_field, "2"));
+    index_writer.commit()?;
+
+    let mut exported = Vec::new();
+    assert_eq!(index.export_json(&mut exported, &[])?, 2);
+    let mut lines: Vec<&str> = std::str::from_utf8(&exported).unwrap().lines().collect();
+    lines.sort_unstable();
+    assert_eq!(
+        lines,
+        [
+            r#"{"id":["1"],"title":["first"]}"#,
+            r#"{"id":["3"],"title":["third"]}"#
+        ]
+    );
+
+    let mut exported = Vec::new();
+    assert_eq!(index.export_json(&mut exported, &[title_field])?, 2);
+    let mut lines: Vec<&str> = std::str::from_utf8(&exported).unwrap().lines().collect();
+    lines.sort_unstable();
+    assert_eq!(lines, [r#"{"title":["first"]}"#, r#"{"title":["third"]}"#]);
+    Ok(())
+}
+
+#[test]
+fn test_index_metrics() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title_field = schema_builder.add_text_field("title", TEXT | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    assert_eq!(index.metrics(), IndexMetrics::default());
+    assert_eq!(index.metrics().doc_store_cache_hit_rate(), None);
+
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(title_field => "first"))?;
+    index_writer.add_document(doc!(title_field => "second"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(title_field => "third"))?;
+    index_writer.commit()?;
+    let metrics = index.metrics();
+    assert_eq!(metrics.num_docs_indexed, 3);
+    assert_eq!(metrics.num_commits, 2);
+    assert_eq!(metrics.num_merges, 0);
+    assert!(metrics.num_bytes_written > 0);
+
+    let segment_ids = index.searchable_segment_ids()?;
+    index_writer.merge(&segment_ids).wait()?;
+    let metrics_after_merge = index.metrics();
+    assert_eq!(metrics_after_merge.num_merges, 1);
+    assert!(metrics_after_merge.num_bytes_written > metrics.num_bytes_written);
+
+    let reader = index.reader()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    assert_eq!(searcher.search(&AllQuery, &Count)?, 3);
+    let doc_address = DocAddress::new(0, 0);
+    searcher.doc::<TantivyDocument>(doc_address)?;
+    searcher.doc::<TantivyDocument>(doc_address)?;
+    let metrics = index.metrics();
+    assert_eq!(metrics.num_searches, 1);
+    assert_eq!(metrics.doc_store_cache_hits, 1);
+    assert_eq!(metrics.doc_store_cache_misses, 1);
+    assert_eq!(metrics.doc_store_cache_hit_rate(), Some(0.5));
+    Ok(())
+}
+
+#[test]
+fn test_search_async() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let tag = schema_builder.add_text_field("tag", STRING);
+    let mut index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.add_document(doc!(tag => "a"))?;
+    index_writer.add_document(doc!(tag => "b"))?;
+    index_writer.add_document(doc!(tag => "a"))?;
+    index_writer.commit()?;
+    let query = TermQuery::new(Term::from_field_text(tag, "a"), IndexRecordOption::Basic);
+    let searcher = index.reader()?.searcher();
+    let count = futures::executor::block_on(searcher.search_async(query.clone(), Count))?;
+    assert_eq!(count, 2);
+
+    index.set_multithread_executor(2)?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.search_async(query, Count).wait()?, 2);
+    Ok(())
+}
+
+#[test]
+fn test_memory_usage() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.add_document(doc!(title => "hello happy tax payer"))?;
+    // The memory usage is updated once the indexing thread processed the document.
+    let mut num_attempts = 0;
+    while index_writer.memory_usage().segments == 0 {
+        num_attempts += 1;
+        assert!(num_attempts < 1_000, "the memory usage was not updated");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    let memory_usage = index_writer.memory_usage();
+    assert!(memory_usage.term_hashmaps > 0);
+    assert!(memory_usage.term_hashmaps <= memory_usage.segments);
+    index_writer.commit()?;
+    assert_eq!(index_writer.memory_usage(), Default::default());
+
+    let searcher = index.reader()?.searcher();
+    let memory_usage = searcher.memory_usage()?;
+    assert_eq!(memory_usage.doc_store_caches, 0);
+    assert!(memory_usage.segment_files > 0);
+    searcher.doc::<TantivyDocument>(DocAddress::new(0, 0))?;
+    let memory_usage = searcher.memory_usage()?;
+    assert!(memory_usage.doc_store_caches > 0);
+    assert_eq!(
+        memory_usage.total(),
+        memory_usage.doc_store_caches + memory_usage.segment_files
+    );
+    Ok(())
+}
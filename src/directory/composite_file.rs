@@ -169,13 +169,14 @@ impl CompositeFile {
     }
 
     pub fn space_usage(&self) -> PerFieldSpaceUsage {
-        let mut fields = Vec::new();
+        let mut fields: HashMap<Field, FieldUsage> = HashMap::new();
         for (&field_addr, byte_range) in &self.offsets_index {
-            let mut field_usage = FieldUsage::empty(field_addr.field);
-            field_usage.add_field_idx(field_addr.idx, byte_range.len().into());
-            fields.push(field_usage);
+            fields
+                .entry(field_addr.field)
+                .or_insert_with(|| FieldUsage::empty(field_addr.field))
+                .add_field_idx(field_addr.idx, byte_range.len().into());
         }
-        PerFieldSpaceUsage::new(fields)
+        PerFieldSpaceUsage::new(fields.into_values().collect())
     }
 }
 
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use common::{AntiCallToken, TerminatingWrite};
+
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    Directory, DirectoryLock, FileHandle, Lock, WatchCallback, WatchHandle, WritePtr,
+};
+
+/// An operation of a [`FailpointDirectory`] that can be made to fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DirectoryOperation {
+    /// A write to a file opened with [`Directory::open_write()`], as it reaches the directory
+    /// once buffered.
+    Write,
+    /// A flush of a file opened with [`Directory::open_write()`].
+    Flush,
+    /// A call to [`Directory::atomic_write()`].
+    AtomicWrite,
+    /// A call to [`Directory::sync_directory()`].
+    SyncDirectory,
+}
+
+/// How a [`FailpointDirectory`] fails an operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InjectedFailure {
+    /// The operation returns an error without doing anything.
+    Error,
+    /// The operation only writes the first half of its data, and then returns an error, like a
+    /// process crashing in the middle of a write.
+    ///
+    /// For [`DirectoryOperation::AtomicWrite`], this simulates a storage without atomic writes,
+    /// and leaves the file truncated. Flushes and syncs have no data, and just return an error.
+    Truncate,
+}
+
+#[derive(Debug, Default)]
+struct FailpointState {
+    num_calls: HashMap<DirectoryOperation, usize>,
+    // The failures to inject, by operation and index of the call.
+    failpoints: HashMap<(DirectoryOperation, usize), InjectedFailure>,
+    num_injected_failures: usize,
+}
+
+impl FailpointState {
+    /// Counts a call to `operation`, and returns the failure to inject, if any.
+    fn call(&mut self, operation: DirectoryOperation) -> Option<InjectedFailure> {
+        let num_calls = self.num_calls.entry(operation).or_default();
+        let call = *num_calls;
+        *num_calls += 1;
+        let failure = self.failpoints.remove(&(operation, call))?;
+        self.num_injected_failures += 1;
+        Some(failure)
+    }
+}
+
+/// A directory wrapper injecting failures in the writes of the wrapped directory, to test
+/// that a crash at any point leaves an index that can be opened.
+///
+/// The failures are armed with [`FailpointDirectory::fail_nth()`]. The clones of a
+/// `FailpointDirectory` share their failpoints and counters, so that the directory can be
+/// configured after an [`Index`](crate::Index) was created on top of it.
+///
+/// A typical test counts the operations of a commit with
+/// [`FailpointDirectory::num_calls()`], and then replays the commit once for each of them,
+/// failing that operation, and checks that the index can still be opened.
+#[derive(Clone, Debug)]
+pub struct FailpointDirectory {
+    underlying: Box<dyn Directory>,
+    state: Arc<Mutex<FailpointState>>,
+}
+
+impl FailpointDirectory {
+    /// Wraps `directory`, without any failure armed.
+    pub fn new<D: Into<Box<dyn Directory>>>(directory: D) -> FailpointDirectory {
+        FailpointDirectory {
+            underlying: directory.into(),
+            state: Arc::default(),
+        }
+    }
+
+    /// Makes the `n`-th next call to `operation` fail, counting from 0.
+    pub fn fail_nth(&self, operation: DirectoryOperation, n: usize, failure: InjectedFailure) {
+        let mut state = self.state.lock().unwrap();
+        let call = state.num_calls.get(&operation).copied().unwrap_or(0) + n;
+        state.failpoints.insert((operation, call), failure);
+    }
+
+    /// Disarms all of the failures that were not injected yet.
+    pub fn clear_failpoints(&self) {
+        self.state.lock().unwrap().failpoints.clear();
+    }
+
+    /// Returns the number of calls to `operation` since the directory was created.
+    pub fn num_calls(&self, operation: DirectoryOperation) -> usize {
+        let state = self.state.lock().unwrap();
+        state.num_calls.get(&operation).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of failures injected since the directory was created.
+    pub fn num_injected_failures(&self) -> usize {
+        self.state.lock().unwrap().num_injected_failures
+    }
+
+    fn call(&self, operation: DirectoryOperation) -> Option<InjectedFailure> {
+        self.state.lock().unwrap().call(operation)
+    }
+}
+
+fn injected_error(operation: DirectoryOperation) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        format!("Injected failure of {operation:?}"),
+    )
+}
+
+impl Directory for FailpointDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        self.underlying.get_file_handle(path)
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.underlying.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.underlying.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let writer = self.underlying.open_write(path)?;
+        Ok(BufWriter::new(Box::new(FailpointWriter {
+            underlying: writer,
+            state: self.state.clone(),
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.underlying.atomic_read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        match self.call(DirectoryOperation::AtomicWrite) {
+            None => self.underlying.atomic_write(path, data),
+            Some(InjectedFailure::Error) => Err(injected_error(DirectoryOperation::AtomicWrite)),
+            Some(InjectedFailure::Truncate) => {
+                self.underlying
+                    .atomic_write(path, &data[..data.len() / 2])?;
+                Err(injected_error(DirectoryOperation::AtomicWrite))
+            }
+        }
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        if self.call(DirectoryOperation::SyncDirectory).is_some() {
+            return Err(injected_error(DirectoryOperation::SyncDirectory));
+        }
+        self.underlying.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.underlying.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.underlying.watch(watch_callback)
+    }
+}
+
+/// A writer of a [`FailpointDirectory`].
+struct FailpointWriter {
+    underlying: WritePtr,
+    state: Arc<Mutex<FailpointState>>,
+}
+
+impl Write for FailpointWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let failure = self.state.lock().unwrap().call(DirectoryOperation::Write);
+        match failure {
+            None => self.underlying.write(buf),
+            Some(InjectedFailure::Error) => Err(injected_error(DirectoryOperation::Write)),
+            Some(InjectedFailure::Truncate) => {
+                self.underlying.write_all(&buf[..buf.len() / 2])?;
+                self.underlying.flush()?;
+                Err(injected_error(DirectoryOperation::Write))
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let failure = self.state.lock().unwrap().call(DirectoryOperation::Flush);
+        if failure.is_some() {
+            return Err(injected_error(DirectoryOperation::Flush));
+        }
+        self.underlying.flush()
+    }
+}
+
+impl TerminatingWrite for FailpointWriter {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        // The buffered writer wrapping this one flushed it already.
+        self.underlying.terminate_ref(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DirectoryOperation, FailpointDirectory, InjectedFailure};
+    use crate::directory::RamDirectory;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexSettings, IndexWriter, TantivyError};
+
+    #[test]
+    fn test_failpoint_directory_commit() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let operations = [
+            DirectoryOperation::Write,
+            DirectoryOperation::Flush,
+            DirectoryOperation::AtomicWrite,
+            DirectoryOperation::SyncDirectory,
+        ];
+        for operation in operations {
+            for failure in [InjectedFailure::Error, InjectedFailure::Truncate] {
+                // Without atomic writes, a truncated `meta.json` or `.managed.json` cannot be
+                // recovered from: the index has to fail to open with a data corruption error,
+                // instead of panicking.
+                let is_truncated_meta = operation == DirectoryOperation::AtomicWrite
+                    && failure == InjectedFailure::Truncate;
+                for n in 0.. {
+                    let directory = FailpointDirectory::new(RamDirectory::create());
+                    let index =
+                        Index::create(directory.clone(), schema.clone(), IndexSettings::default())?;
+                    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+                    index_writer.add_document(doc!(text => "first"))?;
+                    index_writer.commit()?;
+                    index_writer.add_document(doc!(text => "second"))?;
+                    directory.fail_nth(operation, n, failure);
+                    let commit_result = index_writer.commit();
+                    drop(index_writer);
+                    if directory.num_injected_failures() == 0 {
+                        // All of the calls of the commit were tried.
+                        assert!(commit_result.is_ok());
+                        assert!(n > 0 || operation == DirectoryOperation::SyncDirectory);
+                        break;
+                    }
+                    directory.clear_failpoints();
+                    let num_docs = match Index::open(directory) {
+                        Ok(index) => index.reader()?.searcher().num_docs(),
+                        Err(TantivyError::DataCorruption(_)) if is_truncated_meta => continue,
+                        Err(err) => return Err(err),
+                    };
+                    if commit_result.is_err() {
+                        assert_eq!(num_docs, 1);
+                    } else {
+                        assert_eq!(num_docs, 2);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -128,18 +128,18 @@ mod tests {
         assert_eq!(counter.load(Ordering::SeqCst), 0);
         assert_eq!(state.load(Ordering::SeqCst), 1);
 
-        atomic_write(&tmp_file, b"foo")?;
+        atomic_write(&tmp_file, b"foo", true)?;
         assert_eq!(rx.recv_timeout(timeout), Ok(1));
 
-        atomic_write(&tmp_file, b"foo")?;
+        atomic_write(&tmp_file, b"foo", true)?;
         assert!(rx.recv_timeout(timeout).is_err());
 
-        atomic_write(&tmp_file, b"bar")?;
+        atomic_write(&tmp_file, b"bar", true)?;
         assert_eq!(rx.recv_timeout(timeout), Ok(2));
 
         mem::drop(watcher);
 
-        atomic_write(&tmp_file, b"qux")?;
+        atomic_write(&tmp_file, b"qux", true)?;
         thread::sleep(Duration::from_millis(10));
         assert_eq!(counter.load(Ordering::SeqCst), 2);
         assert_eq!(state.load(Ordering::SeqCst), 2);
@@ -171,12 +171,12 @@ mod tests {
         assert_eq!(counter.load(Ordering::SeqCst), 0);
         assert_eq!(state.load(Ordering::SeqCst), 1);
 
-        atomic_write(&tmp_file, b"foo")?;
+        atomic_write(&tmp_file, b"foo", true)?;
         assert_eq!(rx.recv_timeout(timeout), Ok(1));
 
         mem::drop(handle);
 
-        atomic_write(&tmp_file, b"qux")?;
+        atomic_write(&tmp_file, b"qux", true)?;
         assert_eq!(counter.load(Ordering::SeqCst), 1);
         assert_eq!(state.load(Ordering::SeqCst), 1);
 
@@ -256,6 +256,14 @@ impl ManagedDirectory {
         Ok(footer.crc() == crc)
     }
 
+    /// Opens a file as it is stored in the underlying directory, footer included.
+    pub(crate) fn open_read_with_footer(
+        &self,
+        path: &Path,
+    ) -> result::Result<FileSlice, OpenReadError> {
+        self.directory.open_read(path)
+    }
+
     /// List all managed files
     pub fn list_managed_files(&self) -> HashSet<PathBuf> {
         let managed_paths = self
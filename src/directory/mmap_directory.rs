@@ -61,6 +61,32 @@ fn open_mmap(full_path: &Path) -> Result<Option<Mmap>, OpenReadError> {
     Ok(mmap_opt)
 }
 
+/// Reads one byte of every page of `data`, so that the pages get loaded in memory.
+fn prefault(data: &[u8]) {
+    const PAGE_SIZE: usize = 4096;
+    for offset in (0..data.len()).step_by(PAGE_SIZE) {
+        // The volatile read prevents the compiler from optimizing the access away.
+        unsafe {
+            std::ptr::read_volatile(data.as_ptr().add(offset));
+        }
+    }
+}
+
+/// Returns true if the extension of `path` is one of `extensions`.
+///
+/// The extension of a file is everything after the first dot of its name, so that
+/// `"store.temp"` or `"del"` can be matched as well as `"idx"`.
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    let Some(file_name) = path.file_name().and_then(|file_name| file_name.to_str()) else {
+        return false;
+    };
+    extensions.iter().any(|extension| {
+        file_name
+            .strip_suffix(extension.as_str())
+            .map_or(false, |prefix| prefix.ends_with('.'))
+    })
+}
+
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct CacheCounters {
     /// Number of time the cache prevents to call `mmap`
@@ -81,6 +107,7 @@ struct MmapCache {
     cache: HashMap<PathBuf, WeakArcBytes>,
     #[cfg(unix)]
     madvice_opt: Option<Advice>,
+    prefault_extensions: Vec<String>,
 }
 
 impl MmapCache {
@@ -90,6 +117,7 @@ impl MmapCache {
             cache: HashMap::default(),
             #[cfg(unix)]
             madvice_opt: None,
+            prefault_extensions: Vec::new(),
         }
     }
 
@@ -106,6 +134,10 @@ impl MmapCache {
         }
     }
 
+    fn mmapped(&self) -> Vec<ArcBytes> {
+        self.cache.values().filter_map(Weak::upgrade).collect()
+    }
+
     fn remove_weak_ref(&mut self) {
         let keys_to_remove: Vec<PathBuf> = self
             .cache
@@ -125,6 +157,11 @@ impl MmapCache {
             // We ignore madvise errors.
             let _ = mmap.advise(madvice);
         }
+        if let Some(mmap) = mmap_opt.as_ref() {
+            if has_extension(full_path, &self.prefault_extensions) {
+                prefault(mmap);
+            }
+        }
         Ok(mmap_opt)
     }
 
@@ -165,9 +202,30 @@ pub struct MmapDirectory {
     inner: Arc<MmapDirectoryInner>,
 }
 
+/// Defines which files an [`MmapDirectory`] fsyncs, trading durability for write speed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CommitPolicy {
+    /// Every file is fsynced, as well as the directory, so that a commit survives a crash
+    /// of the machine.
+    #[default]
+    Full,
+    /// Only the files written atomically, like `meta.json`, and the directory are fsynced.
+    ///
+    /// The segment files are left to the OS to flush: after a crash of the machine, the last
+    /// commits may point to incomplete segments, and the index may need to be rebuilt.
+    MetadataOnly,
+    /// Nothing is fsynced.
+    ///
+    /// This is meant for bulk loads on ephemeral machines, where the index gets rebuilt
+    /// anyway after a crash of the machine. Crashes of the process itself do not lose
+    /// committed data, since the OS still flushes the files.
+    NoSync,
+}
+
 struct MmapDirectoryInner {
     root_path: PathBuf,
     mmap_cache: RwLock<MmapCache>,
+    commit_policy: RwLock<CommitPolicy>,
     _temp_directory: Option<TempDir>,
     watcher: FileWatcher,
 }
@@ -176,6 +234,7 @@ impl MmapDirectoryInner {
     fn new(root_path: PathBuf, temp_directory: Option<TempDir>) -> MmapDirectoryInner {
         MmapDirectoryInner {
             mmap_cache: RwLock::new(MmapCache::new()),
+            commit_policy: RwLock::new(CommitPolicy::default()),
             _temp_directory: temp_directory,
             watcher: FileWatcher::new(&root_path.join(*META_FILEPATH)),
             root_path,
@@ -268,6 +327,60 @@ impl MmapDirectory {
         Ok(MmapDirectory::new(canonical_path, None))
     }
 
+    /// Prefaults the pages of the files whose extension is one of `extensions` (e.g. `"idx"`
+    /// or `"term"`, see [`SegmentMeta::relative_path()`](crate::SegmentMeta::relative_path)),
+    /// as soon as they are mapped.
+    ///
+    /// Opening an index, or reloading its reader, then reads these files entirely from disk, so
+    /// that the first queries do not have to wait for the page faults. This only makes sense if
+    /// these files fit in memory.
+    pub fn set_prefault_extensions(&self, extensions: &[&str]) {
+        self.inner
+            .mmap_cache
+            .write()
+            .expect("Mmap cache lock is poisoned.")
+            .prefault_extensions = extensions
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect();
+    }
+
+    /// Prefaults the pages of all of the files that are currently mapped.
+    ///
+    /// This is typically called after opening an index reader, to load the whole index in
+    /// memory ahead of the first queries.
+    pub fn warm_all(&self) {
+        let mmapped = self
+            .inner
+            .mmap_cache
+            .read()
+            .expect("Mmap cache lock is poisoned.")
+            .mmapped();
+        for mmap in mmapped {
+            prefault(&mmap);
+        }
+    }
+
+    /// Sets the files fsynced by the directory, i.e. how durable the commits written to it are.
+    ///
+    /// Defaults to [`CommitPolicy::Full`].
+    pub fn set_commit_policy(&self, commit_policy: CommitPolicy) {
+        *self
+            .inner
+            .commit_policy
+            .write()
+            .expect("Commit policy lock is poisoned.") = commit_policy;
+    }
+
+    /// Returns the files fsynced by the directory.
+    pub fn commit_policy(&self) -> CommitPolicy {
+        *self
+            .inner
+            .commit_policy
+            .read()
+            .expect("Commit policy lock is poisoned.")
+    }
+
     /// Joins a relative_path to the directory `root_path`
     /// to create a proper complete `filepath`.
     fn resolve_path(&self, relative_path: &Path) -> PathBuf {
@@ -309,18 +422,21 @@ impl Drop for ReleaseLockFile {
 }
 
 /// This Write wraps a File, but has the specificity of
-/// call `sync_all` on flush.
-struct SafeFileWriter(File);
+/// call `sync_data` on terminate, unless `sync` is false.
+struct SafeFileWriter {
+    file: File,
+    sync: bool,
+}
 
 impl SafeFileWriter {
-    fn new(file: File) -> SafeFileWriter {
-        SafeFileWriter(file)
+    fn new(file: File, sync: bool) -> SafeFileWriter {
+        SafeFileWriter { file, sync }
     }
 }
 
 impl Write for SafeFileWriter {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.0.write(buf)
+        self.file.write(buf)
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -330,8 +446,10 @@ impl Write for SafeFileWriter {
 
 impl TerminatingWrite for SafeFileWriter {
     fn terminate_ref(&mut self, _: AntiCallToken) -> io::Result<()> {
-        self.0.flush()?;
-        self.0.sync_data()?;
+        self.file.flush()?;
+        if self.sync {
+            self.file.sync_data()?;
+        }
         Ok(())
     }
 }
@@ -348,8 +466,9 @@ impl Deref for MmapArc {
 }
 unsafe impl StableDeref for MmapArc {}
 
-/// Writes a file in an atomic manner.
-pub(crate) fn atomic_write(path: &Path, content: &[u8]) -> io::Result<()> {
+/// Writes a file in an atomic manner, fsyncing its content before exposing it if `sync` is
+/// true.
+pub(crate) fn atomic_write(path: &Path, content: &[u8], sync: bool) -> io::Result<()> {
     // We create the temporary file in the same directory as the target file.
     // Indeed the canonical temp directory and the target file might sit in different
     // filesystem, in which case the atomic write may actually not work.
@@ -362,7 +481,9 @@ pub(crate) fn atomic_write(path: &Path, content: &[u8]) -> io::Result<()> {
     let mut tempfile = tempfile::Builder::new().tempfile_in(parent_path)?;
     tempfile.write_all(content)?;
     tempfile.flush()?;
-    tempfile.as_file_mut().sync_data()?;
+    if sync {
+        tempfile.as_file_mut().sync_data()?;
+    }
     tempfile.into_temp_path().persist(path)?;
     Ok(())
 }
@@ -442,7 +563,7 @@ impl Directory for MmapDirectory {
         // The file will only be durably written after we terminate AND
         // sync_directory() is called.
 
-        let writer = SafeFileWriter::new(file);
+        let writer = SafeFileWriter::new(file, self.commit_policy() == CommitPolicy::Full);
         Ok(BufWriter::new(Box::new(writer)))
     }
 
@@ -469,7 +590,8 @@ impl Directory for MmapDirectory {
     fn atomic_write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
         debug!("Atomic Write {:?}", path);
         let full_path = self.resolve_path(path);
-        atomic_write(&full_path, content)?;
+        let sync = self.commit_policy() != CommitPolicy::NoSync;
+        atomic_write(&full_path, content, sync)?;
         Ok(())
     }
 
@@ -508,6 +630,9 @@ impl Directory for MmapDirectory {
 
     #[cfg(not(windows))]
     fn sync_directory(&self) -> Result<(), io::Error> {
+        if self.commit_policy() == CommitPolicy::NoSync {
+            return Ok(());
+        }
         let mut open_opts = OpenOptions::new();
 
         // Linux needs read to be set, otherwise returns EINVAL
@@ -622,6 +747,61 @@ mod tests {
         assert_eq!(mmap_directory.get_cache_info().mmapped.len(), 0);
     }
 
+    #[test]
+    fn test_prefault_extensions() -> crate::Result<()> {
+        let extensions = ["idx".to_string(), "store.temp".to_string()];
+        assert!(has_extension(Path::new("abc.idx"), &extensions));
+        assert!(has_extension(Path::new("abc.store.temp"), &extensions));
+        assert!(!has_extension(Path::new("abc.store"), &extensions));
+        assert!(!has_extension(Path::new("abcidx"), &extensions));
+
+        let mmap_directory = MmapDirectory::create_from_tempdir()?;
+        mmap_directory.set_prefault_extensions(&["idx", "term"]);
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create(
+            mmap_directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"abc"))?;
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        mmap_directory.warm_all();
+        assert_eq!(reader.searcher().num_docs(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_policy() -> crate::Result<()> {
+        for commit_policy in [
+            CommitPolicy::Full,
+            CommitPolicy::MetadataOnly,
+            CommitPolicy::NoSync,
+        ] {
+            let tempdir = TempDir::new()?;
+            let mmap_directory = MmapDirectory::open(tempdir.path())?;
+            assert_eq!(mmap_directory.commit_policy(), CommitPolicy::Full);
+            mmap_directory.set_commit_policy(commit_policy);
+            assert_eq!(mmap_directory.commit_policy(), commit_policy);
+            let mut schema_builder = Schema::builder();
+            let text_field = schema_builder.add_text_field("text", TEXT);
+            let index = Index::create(
+                mmap_directory,
+                schema_builder.build(),
+                IndexSettings::default(),
+            )?;
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text_field=>"abc"))?;
+            index_writer.commit()?;
+            drop(index_writer);
+            let index = Index::open_in_dir(tempdir.path())?;
+            assert_eq!(index.reader()?.searcher().num_docs(), 1);
+        }
+        Ok(())
+    }
+
     fn assert_eventually<P: Fn() -> Option<String>>(predicate: P) {
         for _ in 0..30 {
             if predicate().is_none() {
@@ -673,7 +853,7 @@ mod tests {
             let num_segments = reader.searcher().segment_readers().len();
             assert!(num_segments <= 4);
             let num_components_except_deletes_and_tempstore =
-                crate::core::SegmentComponent::iterator().len() - 2;
+                crate::core::SegmentComponent::iterator().len() - 3;
             let max_num_mmapped = num_components_except_deletes_and_tempstore * num_segments;
             assert_eventually(|| {
                 let num_mmapped = mmap_directory.get_cache_info().mmapped.len();
@@ -5,6 +5,8 @@ mod mmap_directory;
 
 mod directory;
 mod directory_lock;
+mod failpoint_directory;
+#[cfg(feature = "mmap")]
 mod file_watcher;
 mod footer;
 mod managed_directory;
@@ -25,6 +27,8 @@ pub use common::{AntiCallToken, OwnedBytes, TerminatingWrite};
 pub(crate) use self::composite_file::{CompositeFile, CompositeWrite};
 pub use self::directory::{Directory, DirectoryClone, DirectoryLock};
 pub use self::directory_lock::{Lock, INDEX_WRITER_LOCK, META_LOCK};
+pub use self::failpoint_directory::{DirectoryOperation, FailpointDirectory, InjectedFailure};
+pub(crate) use self::footer::Footer;
 pub use self::ram_directory::RamDirectory;
 pub use self::watch_event_router::{WatchCallback, WatchCallbackList, WatchHandle};
 
@@ -47,7 +51,7 @@ pub use memmap2::Advice;
 
 pub use self::managed_directory::ManagedDirectory;
 #[cfg(feature = "mmap")]
-pub use self::mmap_directory::MmapDirectory;
+pub use self::mmap_directory::{CommitPolicy, MmapDirectory};
 
 /// Write object for Directory.
 ///
@@ -110,6 +110,17 @@ pub enum TantivyError {
     #[error("Deserialize error: {0}")]
     /// An error occurred while attempting to deserialize a document.
     DeserializeError(DeserializeError),
+    /// The document does not comply with the schema, see
+    /// [`Schema::validate()`](crate::schema::Schema::validate).
+    #[error("Invalid document: {0:?}")]
+    InvalidDocument(Vec<schema::DocumentViolation>),
+    /// The operation was cancelled through its [`CancelToken`](crate::CancelToken).
+    #[error("The operation was cancelled")]
+    Cancelled,
+    /// The document was rejected because its unique key already exists in the index, see
+    /// [`IndexWriterOptions::unique_key()`](crate::IndexWriterOptions::unique_key).
+    #[error("Duplicate key: {0:?}")]
+    DuplicateKey(schema::Term),
 }
 
 impl From<io::Error> for TantivyError {
@@ -165,7 +176,7 @@ impl From<time::error::ComponentRange> for TantivyError {
 
 impl From<schema::DocParsingError> for TantivyError {
     fn from(error: schema::DocParsingError) -> TantivyError {
-        TantivyError::InvalidArgument(format!("Failed to parse document {error:?}"))
+        TantivyError::InvalidArgument(format!("Failed to parse document: {error}"))
     }
 }
 
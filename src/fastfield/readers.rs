@@ -8,6 +8,7 @@ use columnar::{
 };
 use common::ByteCount;
 
+use super::updates::FastFieldUpdates;
 use crate::core::json_utils::encode_column_name;
 use crate::directory::FileSlice;
 use crate::schema::{Field, FieldEntry, FieldType, Schema};
@@ -22,12 +23,26 @@ use crate::TantivyError;
 pub struct FastFieldReaders {
     columnar: Arc<ColumnarReader>,
     schema: Schema,
+    updates: Arc<FastFieldUpdates>,
 }
 
 impl FastFieldReaders {
     pub(crate) fn open(fast_field_file: FileSlice, schema: Schema) -> io::Result<FastFieldReaders> {
         let columnar = Arc::new(ColumnarReader::open(fast_field_file)?);
-        Ok(FastFieldReaders { columnar, schema })
+        Ok(FastFieldReaders {
+            columnar,
+            schema,
+            updates: Arc::default(),
+        })
+    }
+
+    /// Overrides the values of the columns with the updated values of the segment.
+    pub(crate) fn with_updates(self, updates: Arc<FastFieldUpdates>) -> FastFieldReaders {
+        FastFieldReaders { updates, ..self }
+    }
+
+    pub(crate) fn updates(&self) -> &FastFieldUpdates {
+        &self.updates
     }
 
     fn resolve_field(&self, column_name: &str) -> crate::Result<Option<String>> {
@@ -125,7 +140,14 @@ impl FastFieldReaders {
         else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
+        let mut dynamic_column = dynamic_column_handle.open()?;
+        if !self.updates.is_empty() {
+            if let Some(resolved_field_name) = self.resolve_field(field_name)? {
+                dynamic_column = self
+                    .updates
+                    .apply_to_dynamic_column(&resolved_field_name, dynamic_column);
+            }
+        }
         Ok(dynamic_column.into())
     }
 
@@ -285,6 +307,9 @@ impl FastFieldReaders {
                 }
             }
             if let Some(col_u64) = col.open_u64_lenient()? {
+                let col_u64 = self
+                    .updates
+                    .apply_to_u64_column(&resolved_field_name, col_u64);
                 return Ok(Some((col_u64, col.column_type())));
             }
         }
@@ -313,6 +338,9 @@ impl FastFieldReaders {
                 }
             }
             if let Some(col_u64) = col.open_u64_lenient()? {
+                let col_u64 = self
+                    .updates
+                    .apply_to_u64_column(&resolved_field_name, col_u64);
                 columns_and_types.push((col_u64, col.column_type()));
             }
         }
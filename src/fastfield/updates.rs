@@ -0,0 +1,180 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use columnar::{Column, ColumnValues, DynamicColumn, MonotonicallyMappableToU64, RowId};
+use common::BinarySerializable;
+
+use crate::core::{Segment, SegmentComponent};
+use crate::directory::TerminatingWrite;
+use crate::DocId;
+
+/// Fast field values updated after their segment was written, with
+/// [`IndexWriter::update_fast_field`](crate::IndexWriter::update_fast_field).
+///
+/// They are stored in a file of their own, next to the segment, and override the values of
+/// the fast field columns when those are read. Merges carry them over to the merged segment.
+///
+/// Only documents with exactly one value for the field can be updated.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FastFieldUpdates {
+    /// For each field name, the updated values of the documents, in the `u64` representation
+    /// of their column.
+    fields: BTreeMap<String, BTreeMap<DocId, u64>>,
+    modified: bool,
+}
+
+impl FastFieldUpdates {
+    /// Loads the fast field updates of a segment, if it has any.
+    pub(crate) fn open(segment: &Segment) -> crate::Result<FastFieldUpdates> {
+        if !segment.meta().has_fast_field_updates() {
+            return Ok(FastFieldUpdates::default());
+        }
+        let data = segment
+            .open_read(SegmentComponent::FastFieldUpdates)?
+            .read_bytes()?;
+        let fields: Vec<(String, Vec<(DocId, u64)>)> =
+            BinarySerializable::deserialize(&mut data.as_slice())?;
+        Ok(FastFieldUpdates {
+            fields: fields
+                .into_iter()
+                .map(|(field_name, values)| (field_name, values.into_iter().collect()))
+                .collect(),
+            modified: false,
+        })
+    }
+
+    /// Writes the fast field updates to the file of the segment.
+    ///
+    /// The segment meta is expected to point to a file that does not exist yet.
+    pub(crate) fn write(&self, segment: &mut Segment) -> crate::Result<()> {
+        let fields: Vec<(String, Vec<(DocId, u64)>)> = self
+            .fields
+            .iter()
+            .map(|(field_name, values)| {
+                let values = values.iter().map(|(&doc, &value)| (doc, value)).collect();
+                (field_name.clone(), values)
+            })
+            .collect();
+        let mut wrt = segment.open_write(SegmentComponent::FastFieldUpdates)?;
+        fields.serialize(&mut wrt)?;
+        wrt.terminate()?;
+        Ok(())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns true if values were set since the updates were loaded.
+    pub(crate) fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Sets the value of the field `field_name` of the document `doc`, given in the `u64`
+    /// representation of its column.
+    pub(crate) fn set(&mut self, field_name: &str, doc: DocId, value: u64) {
+        if !self.fields.contains_key(field_name) {
+            self.fields.insert(field_name.to_string(), BTreeMap::new());
+        }
+        let values = self.fields.get_mut(field_name).unwrap();
+        values.insert(doc, value);
+        self.modified = true;
+    }
+
+    /// Overrides the values of a column of the field `field_name` with the updated ones.
+    ///
+    /// Only numerical and boolean columns can hold updated values.
+    pub(crate) fn apply_to_dynamic_column(
+        &self,
+        field_name: &str,
+        dynamic_column: DynamicColumn,
+    ) -> DynamicColumn {
+        let Some(updates) = self.fields.get(field_name) else {
+            return dynamic_column;
+        };
+        match dynamic_column {
+            DynamicColumn::Bool(column) => DynamicColumn::Bool(apply_to_column(column, updates)),
+            DynamicColumn::I64(column) => DynamicColumn::I64(apply_to_column(column, updates)),
+            DynamicColumn::U64(column) => DynamicColumn::U64(apply_to_column(column, updates)),
+            DynamicColumn::F64(column) => DynamicColumn::F64(apply_to_column(column, updates)),
+            dynamic_column => dynamic_column,
+        }
+    }
+
+    /// Overrides the values of a column of the field `field_name`, opened in its `u64`
+    /// representation, with the updated ones.
+    pub(crate) fn apply_to_u64_column(&self, field_name: &str, column: Column<u64>) -> Column<u64> {
+        let Some(updates) = self.fields.get(field_name) else {
+            return column;
+        };
+        apply_to_column(column, updates)
+    }
+}
+
+fn apply_to_column<T>(column: Column<T>, updates: &BTreeMap<DocId, u64>) -> Column<T>
+where T: MonotonicallyMappableToU64 + PartialOrd + Copy + Send + Sync + 'static {
+    let mut row_updates: HashMap<RowId, T> = HashMap::new();
+    for (&doc, &value) in updates {
+        let row_ids = column.index.value_row_ids(doc);
+        if row_ids.len() == 1 {
+            row_updates.insert(row_ids.start, T::from_u64(value));
+        }
+    }
+    if row_updates.is_empty() {
+        return column;
+    }
+    Column {
+        index: column.index,
+        values: Arc::new(UpdatedColumnValues::new(column.values, row_updates)),
+    }
+}
+
+/// Column values, some of which are overridden.
+struct UpdatedColumnValues<T> {
+    values: Arc<dyn ColumnValues<T>>,
+    updates: HashMap<RowId, T>,
+    min_value: T,
+    max_value: T,
+}
+
+impl<T: PartialOrd + Copy> UpdatedColumnValues<T> {
+    fn new(values: Arc<dyn ColumnValues<T>>, updates: HashMap<RowId, T>) -> Self {
+        let mut min_value = values.min_value();
+        let mut max_value = values.max_value();
+        for &value in updates.values() {
+            if value < min_value {
+                min_value = value;
+            }
+            if value > max_value {
+                max_value = value;
+            }
+        }
+        UpdatedColumnValues {
+            values,
+            updates,
+            min_value,
+            max_value,
+        }
+    }
+}
+
+impl<T: PartialOrd + Copy + Send + Sync> ColumnValues<T> for UpdatedColumnValues<T> {
+    fn get_val(&self, idx: u32) -> T {
+        match self.updates.get(&idx) {
+            Some(&value) => value,
+            None => self.values.get_val(idx),
+        }
+    }
+
+    fn min_value(&self) -> T {
+        self.min_value
+    }
+
+    fn max_value(&self) -> T {
+        self.max_value
+    }
+
+    fn num_vals(&self) -> u32 {
+        self.values.num_vals()
+    }
+}
@@ -6,7 +6,7 @@ use tokenizer_api::Token;
 
 use crate::indexer::doc_id_mapping::DocIdMapping;
 use crate::schema::document::{Document, ReferenceValue, ReferenceValueLeaf, Value};
-use crate::schema::{value_type_to_column_type, Field, FieldType, Schema, Type};
+use crate::schema::{value_type_to_column_type, Field, FieldType, ScaledF64Options, Schema, Type};
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
 use crate::{DateTimePrecision, DocId, TantivyError};
 
@@ -20,6 +20,7 @@ pub struct FastFieldsWriter {
     fast_field_names: Vec<Option<String>>, //< TODO see if we can hash the field name hash too.
     per_field_tokenizer: Vec<Option<TextAnalyzer>>,
     date_precisions: Vec<DateTimePrecision>,
+    scaled_f64_options: Vec<Option<ScaledF64Options>>,
     expand_dots: Vec<bool>,
     num_docs: DocId,
     // Buffer that we recycle to avoid allocation.
@@ -45,11 +46,12 @@ impl FastFieldsWriter {
             std::iter::repeat_with(DateTimePrecision::default)
                 .take(schema.num_fields())
                 .collect();
+        let mut scaled_f64_options: Vec<Option<ScaledF64Options>> = vec![None; schema.num_fields()];
         let mut expand_dots = vec![false; schema.num_fields()];
         let mut per_field_tokenizer: Vec<Option<TextAnalyzer>> = vec![None; schema.num_fields()];
         // TODO see other types
         for (field_id, field_entry) in schema.fields() {
-            if !field_entry.field_type().is_fast() {
+            if !field_entry.field_type().is_fast() || field_entry.is_ignored() {
                 continue;
             }
             fast_field_names[field_id.field_id() as usize] = Some(field_entry.name().to_string());
@@ -57,6 +59,9 @@ impl FastFieldsWriter {
             if let FieldType::Date(date_options) = field_entry.field_type() {
                 date_precisions[field_id.field_id() as usize] = date_options.get_precision();
             }
+            if let FieldType::ScaledF64(options) = field_entry.field_type() {
+                scaled_f64_options[field_id.field_id() as usize] = Some(options.clone());
+            }
             if let FieldType::JsonObject(json_object_options) = field_entry.field_type() {
                 if let Some(tokenizer_name) = json_object_options.get_fast_field_tokenizer_name() {
                     let text_analyzer = tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
@@ -96,6 +101,7 @@ impl FastFieldsWriter {
             per_field_tokenizer,
             num_docs: 0u32,
             date_precisions,
+            scaled_f64_options,
             expand_dots,
             json_path_buffer: JsonPathWriter::default(),
         })
@@ -170,11 +176,13 @@ impl FastFieldsWriter {
                     );
                 }
                 ReferenceValueLeaf::F64(val) => {
-                    self.columnar_writer.record_numerical(
-                        doc_id,
-                        field_name,
-                        NumericalValue::from(val),
-                    );
+                    let numerical_value = match &self.scaled_f64_options[field.field_id() as usize]
+                    {
+                        Some(scaled_options) => NumericalValue::from(scaled_options.scale(val)),
+                        None => NumericalValue::from(val),
+                    };
+                    self.columnar_writer
+                        .record_numerical(doc_id, field_name, numerical_value);
                 }
                 ReferenceValueLeaf::Date(val) => {
                     let date_precision = self.date_precisions[field.field_id() as usize];
@@ -0,0 +1,342 @@
+//! A small C API, for bindings to other languages.
+//!
+//! The API only exchanges C strings, and an opaque [`TantivyIndex`] handle, so that bindings
+//! do not depend on the internal types of tantivy:
+//! - schemas are given in their JSON serialization, as found in the `meta.json` file of an index,
+//! - documents are given as JSON objects, as accepted by [`TantivyDocument::parse_json`],
+//! - search results are returned as a JSON object, holding the number of matching documents and the
+//!   best hits, e.g. `{"count": 12, "hits": [{"score": 1.5, "doc": {...}}]}`.
+//!
+//! Functions returning a pointer return a null pointer on error, and functions returning an
+//! `c_int` return `0` on success, and `-1` on error. The message of the last error of the
+//! calling thread can then be retrieved with [`tantivy_last_error`].
+//!
+//! Strings returned by the API are owned by the caller, and need to be released with
+//! [`tantivy_string_free`].
+//!
+//! In order to get a shared or static library out of it, the crate needs to be built as a
+//! `cdylib` or `staticlib` with the `ffi` feature, e.g. with
+//! `cargo rustc --release --features ffi --crate-type cdylib`.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use serde_json::json;
+
+use crate::collector::{Count, TopDocs};
+use crate::query::QueryParser;
+use crate::schema::{Document, FieldType, Schema, TantivyDocument};
+use crate::{Index, IndexReader, IndexWriter, ReloadPolicy, TantivyError};
+
+/// Memory budget of the index writers created through the C API.
+const WRITER_MEMORY_BUDGET: usize = 50_000_000;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// An opened index, along with the writer and reader used to add documents and search.
+pub struct TantivyIndex {
+    index: Index,
+    writer: Option<IndexWriter>,
+    reader: IndexReader,
+}
+
+impl TantivyIndex {
+    fn new(index: Index) -> crate::Result<TantivyIndex> {
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(TantivyIndex {
+            index,
+            writer: None,
+            reader,
+        })
+    }
+
+    fn writer(&mut self) -> crate::Result<&mut IndexWriter> {
+        if self.writer.is_none() {
+            self.writer = Some(self.index.writer(WRITER_MEMORY_BUDGET)?);
+        }
+        Ok(self.writer.as_mut().unwrap())
+    }
+
+    fn search(&self, query: &str, limit: usize) -> crate::Result<serde_json::Value> {
+        let schema = self.index.schema();
+        // Terms without a field target all of the text fields.
+        let default_fields = schema
+            .fields()
+            .filter(|(_, field_entry)| {
+                field_entry.is_indexed() && matches!(field_entry.field_type(), FieldType::Str(_))
+            })
+            .map(|(field, _)| field)
+            .collect();
+        let query = QueryParser::for_index(&self.index, default_fields).parse_query(query)?;
+        let searcher = self.reader.searcher();
+        let (top_docs, count) = searcher.search(&query, &(TopDocs::with_limit(limit), Count))?;
+        let hits = top_docs
+            .into_iter()
+            .map(|(score, doc_address)| {
+                let doc: TantivyDocument = searcher.doc(doc_address)?;
+                Ok(json!({ "score": score, "doc": doc.to_named_doc(&schema) }))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(json!({ "count": count, "hits": hits }))
+    }
+}
+
+/// Runs `f`, recording its error, or its panic, as the last error of the thread.
+fn ffi_call<T>(f: impl FnOnce() -> crate::Result<T>) -> Option<T> {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = None);
+    let res = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(res) => res.map_err(|err| err.to_string()),
+        Err(_) => Err("tantivy panicked".to_string()),
+    };
+    match res {
+        Ok(val) => Some(val),
+        Err(message) => {
+            let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+            LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+            None
+        }
+    }
+}
+
+/// # Safety
+///
+/// `ptr` must be null or point to a nul-terminated string.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> crate::Result<&'a str> {
+    if ptr.is_null() {
+        return Err(TantivyError::InvalidArgument(format!("{name} is null")));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| TantivyError::InvalidArgument(format!("{name} is not valid UTF-8")))
+}
+
+/// # Safety
+///
+/// `index` must be null or a pointer returned by one of the `tantivy_index_*` constructors.
+unsafe fn index_arg<'a>(index: *mut TantivyIndex) -> crate::Result<&'a mut TantivyIndex> {
+    index
+        .as_mut()
+        .ok_or_else(|| TantivyError::InvalidArgument("index is null".to_string()))
+}
+
+fn into_handle(index: Index) -> crate::Result<*mut TantivyIndex> {
+    Ok(Box::into_raw(Box::new(TantivyIndex::new(index)?)))
+}
+
+/// Returns the message of the error of the last call to the API in the calling thread, or null
+/// if it succeeded.
+///
+/// The returned string needs to be released with [`tantivy_string_free`].
+#[no_mangle]
+pub extern "C" fn tantivy_last_error() -> *mut c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(std::ptr::null_mut(), |message| message.clone().into_raw())
+    })
+}
+
+/// Releases a string returned by the API.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by the API, that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Creates an index in memory, with the given JSON schema.
+///
+/// # Safety
+///
+/// `schema_json` must be a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_create_in_ram(
+    schema_json: *const c_char,
+) -> *mut TantivyIndex {
+    ffi_call(|| {
+        let schema = Schema::from_json(str_arg(schema_json, "schema_json")?)?;
+        into_handle(Index::create_in_ram(schema))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Creates an index in the existing, empty directory `path`, with the given JSON schema.
+///
+/// # Safety
+///
+/// `path` and `schema_json` must be nul-terminated strings.
+#[cfg(feature = "mmap")]
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_create_in_dir(
+    path: *const c_char,
+    schema_json: *const c_char,
+) -> *mut TantivyIndex {
+    ffi_call(|| {
+        let path = str_arg(path, "path")?;
+        let schema = Schema::from_json(str_arg(schema_json, "schema_json")?)?;
+        into_handle(Index::create_in_dir(path, schema)?)
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Opens the index stored in the directory `path`.
+///
+/// # Safety
+///
+/// `path` must be a nul-terminated string.
+#[cfg(feature = "mmap")]
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_open_in_dir(path: *const c_char) -> *mut TantivyIndex {
+    ffi_call(|| into_handle(Index::open_in_dir(str_arg(path, "path")?)?))
+        .unwrap_or(std::ptr::null_mut())
+}
+
+/// Closes an index, discarding the documents added since the last commit.
+///
+/// # Safety
+///
+/// `index` must be null or a pointer returned by one of the `tantivy_index_*` constructors,
+/// that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_free(index: *mut TantivyIndex) {
+    if !index.is_null() {
+        drop(Box::from_raw(index));
+    }
+}
+
+/// Adds a document, given as a JSON object.
+///
+/// The document only becomes searchable after [`tantivy_index_commit`].
+///
+/// # Safety
+///
+/// `index` must be a valid index handle, not used concurrently, and `doc_json` a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_add_document(
+    index: *mut TantivyIndex,
+    doc_json: *const c_char,
+) -> c_int {
+    ffi_call(|| {
+        let index = index_arg(index)?;
+        let doc =
+            TantivyDocument::parse_json(&index.index.schema(), str_arg(doc_json, "doc_json")?)?;
+        index.writer()?.add_document(doc)?;
+        Ok(())
+    })
+    .map_or(-1, |_| 0)
+}
+
+/// Commits the documents added so far, and makes them searchable.
+///
+/// # Safety
+///
+/// `index` must be a valid index handle, not used concurrently.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_commit(index: *mut TantivyIndex) -> c_int {
+    ffi_call(|| {
+        let index = index_arg(index)?;
+        index.writer()?.commit()?;
+        index.reader.reload()
+    })
+    .map_or(-1, |_| 0)
+}
+
+/// Runs a query, in the query parser syntax, and returns the `limit` best hits as JSON.
+/// `limit` has to be strictly positive.
+///
+/// Terms that do not target a specific field are searched in all of the text fields.
+///
+/// # Safety
+///
+/// `index` must be a valid index handle, not used concurrently, and `query` a nul-terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_search(
+    index: *mut TantivyIndex,
+    query: *const c_char,
+    limit: usize,
+) -> *mut c_char {
+    ffi_call(|| {
+        if limit == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "limit has to be strictly positive".to_string(),
+            ));
+        }
+        let hits = index_arg(index)?.search(str_arg(query, "query")?, limit)?;
+        Ok(CString::new(hits.to_string())
+            .expect("JSON strings do not contain nul bytes")
+            .into_raw())
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString};
+
+    use super::*;
+
+    unsafe fn take_string(string: *mut c_char) -> String {
+        assert!(!string.is_null());
+        let owned = CStr::from_ptr(string).to_str().unwrap().to_string();
+        tantivy_string_free(string);
+        owned
+    }
+
+    #[test]
+    fn test_ffi_index_and_search() {
+        let schema_json = CString::new(
+            r#"[{"name": "title", "type": "text", "options": {"indexing": {"record": "position",
+            "fieldnorms": true, "tokenizer": "default"}, "stored": true}}]"#,
+        )
+        .unwrap();
+        unsafe {
+            let index = tantivy_index_create_in_ram(schema_json.as_ptr());
+            assert!(!index.is_null());
+            for doc in [
+                r#"{"title": "Of Mice and Men"}"#,
+                r#"{"title": "Frankenstein"}"#,
+            ] {
+                let doc = CString::new(doc).unwrap();
+                assert_eq!(tantivy_index_add_document(index, doc.as_ptr()), 0);
+            }
+            assert_eq!(tantivy_index_commit(index), 0);
+
+            let query = CString::new("mice").unwrap();
+            let hits: serde_json::Value = serde_json::from_str(&take_string(tantivy_index_search(
+                index,
+                query.as_ptr(),
+                10,
+            )))
+            .unwrap();
+            assert_eq!(hits["count"], 1);
+            assert_eq!(hits["hits"][0]["doc"]["title"][0], "Of Mice and Men");
+
+            let invalid_doc = CString::new(r#"{"title": 3}"#).unwrap();
+            assert_eq!(tantivy_index_add_document(index, invalid_doc.as_ptr()), -1);
+            assert!(take_string(tantivy_last_error()).contains("title"));
+            assert!(tantivy_index_search(index, std::ptr::null(), 10).is_null());
+            assert!(take_string(tantivy_last_error()).contains("query is null"));
+            assert!(tantivy_index_search(index, query.as_ptr(), 0).is_null());
+            assert!(take_string(tantivy_last_error()).contains("limit"));
+            // A successful call clears the error of the previous one.
+            assert_eq!(tantivy_index_commit(index), 0);
+            assert!(tantivy_last_error().is_null());
+
+            tantivy_index_free(index);
+        }
+    }
+}
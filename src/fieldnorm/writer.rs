@@ -1,10 +1,10 @@
 use std::cmp::Ordering;
 use std::{io, iter};
 
-use super::{fieldnorm_to_id, FieldNormsSerializer};
+use super::{fieldnorm_to_id, id_to_fieldnorm, FieldNormsSerializer};
 use crate::indexer::doc_id_mapping::DocIdMapping;
 use crate::schema::{Field, Schema};
-use crate::DocId;
+use crate::{DocId, Score};
 
 /// The `FieldNormsWriter` is in charge of tracking the fieldnorm byte
 /// of each document for each field with field norms.
@@ -91,6 +91,21 @@ impl FieldNormsWriter {
         }
     }
 
+    /// Divides the fieldnorms recorded for the document `doc` by `boost`.
+    ///
+    /// Since BM25 favors short fields, this boosts the score of the document for the queries
+    /// on these fields, without having to change the queries. The result is rounded to an
+    /// integer number of tokens, so a boost that does not change the rounded length of a field
+    /// has no effect on it.
+    pub fn boost_doc(&mut self, doc: DocId, boost: Score) {
+        for fieldnorm_buffer in self.fieldnorms_buffers.iter_mut().flatten() {
+            if let Some(fieldnorm_id) = fieldnorm_buffer.get_mut(doc as usize) {
+                let fieldnorm = id_to_fieldnorm(*fieldnorm_id) as Score / boost;
+                *fieldnorm_id = fieldnorm_to_id(fieldnorm.round() as u32);
+            }
+        }
+    }
+
     /// Serialize the seen fieldnorm values to the serializer for all fields.
     pub fn serialize(
         &self,
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, HashMap};
+
+use common::{BitSet, ReadOnlyBitSet};
+
+use crate::core::{RestorableDelete, Segment, SegmentId, SegmentMeta};
+use crate::directory::TerminatingWrite;
+use crate::fastfield::{write_alive_bitset, AliveBitSet};
+use crate::{DocId, Opstamp, SegmentComponent};
+
+/// The delete generations of a segment: the alive bitsets of its delete files, oldest first.
+///
+/// With soft deletes, each delete file of a segment is kept along with the opstamp it was
+/// written at, so that merges can tell when each of the deleted documents was deleted, and
+/// only purge the ones deleted before the undelete window.
+pub(crate) struct DeleteGenerations {
+    max_doc: DocId,
+    generations: Vec<(Opstamp, AliveBitSet)>,
+    restorable_deletes: Vec<RestorableDelete>,
+}
+
+impl DeleteGenerations {
+    pub(crate) fn open(segment: &Segment) -> crate::Result<DeleteGenerations> {
+        let segment_meta = segment.meta();
+        let opstamps = segment_meta
+            .previous_delete_opstamps()
+            .iter()
+            .copied()
+            .chain(segment_meta.delete_opstamp());
+        let mut generations = Vec::new();
+        for opstamp in opstamps {
+            let alive_bitset_data = segment.open_read_deletes(opstamp)?.read_bytes()?;
+            generations.push((opstamp, AliveBitSet::open(alive_bitset_data)));
+        }
+        Ok(DeleteGenerations {
+            max_doc: segment_meta.max_doc(),
+            generations,
+            restorable_deletes: segment_meta.restorable_deletes().to_vec(),
+        })
+    }
+
+    /// Returns the alive bitset of the segment as of `opstamp`, or `None` if none of its
+    /// documents were deleted yet.
+    fn alive_bitset_at(&self, opstamp: Opstamp) -> Option<&AliveBitSet> {
+        self.generations
+            .iter()
+            .rev()
+            .find(|(generation_opstamp, _)| *generation_opstamp <= opstamp)
+            .map(|(_, alive_bitset)| alive_bitset)
+    }
+
+    fn current_alive_bitset(&self) -> Option<&AliveBitSet> {
+        self.generations
+            .last()
+            .map(|(_, alive_bitset)| alive_bitset)
+    }
+
+    /// Returns the documents a merge purging the deletes up to `purge_opstamp` keeps: the
+    /// alive documents, and the ones deleted after `purge_opstamp`, which can still be
+    /// restored.
+    pub(crate) fn kept_docs(&self, purge_opstamp: Option<Opstamp>) -> AliveBitSet {
+        let alive_bitset = self.current_alive_bitset();
+        let purged_alive_bitset = purge_opstamp.and_then(|opstamp| self.alive_bitset_at(opstamp));
+        let mut kept_docs = BitSet::with_max_value(self.max_doc);
+        for doc in 0..self.max_doc {
+            if is_alive(alive_bitset, doc) || is_alive(purged_alive_bitset, doc) {
+                kept_docs.insert(doc);
+            }
+        }
+        AliveBitSet::from(ReadOnlyBitSet::from(&kept_docs))
+    }
+}
+
+fn is_alive(alive_bitset_opt: Option<&AliveBitSet>, doc: DocId) -> bool {
+    alive_bitset_opt.map_or(true, |alive_bitset| alive_bitset.is_alive(doc))
+}
+
+/// Returns true if a merge purging the deletes up to `purge_opstamp` would drop some of the
+/// deleted documents of the segment.
+pub(crate) fn has_purgeable_deletes(
+    segment_meta: &SegmentMeta,
+    purge_opstamp: Option<Opstamp>,
+) -> bool {
+    let Some(purge_opstamp) = purge_opstamp else {
+        return false;
+    };
+    let oldest_delete_opstamp = segment_meta
+        .previous_delete_opstamps()
+        .first()
+        .copied()
+        .or_else(|| segment_meta.delete_opstamp());
+    segment_meta.has_deletes()
+        && oldest_delete_opstamp.map_or(false, |opstamp| opstamp <= purge_opstamp)
+}
+
+/// Writes the delete files of a segment merged from segments with soft deletes, for the
+/// deleted documents the merge kept, and returns the merged segment recording them.
+///
+/// `doc_addresses` gives the segment and the doc id in that segment of each document of the
+/// merged segment. The merged segment gets the delete generations of the merged segments
+/// written after `purge_opstamp`, and the restorable deletes of the documents it kept.
+pub(crate) fn write_merged_deletes(
+    mut merged_segment: Segment,
+    doc_addresses: &[(SegmentId, DocId)],
+    delete_generations: &HashMap<SegmentId, DeleteGenerations>,
+    purge_opstamp: Option<Opstamp>,
+) -> crate::Result<Segment> {
+    // The alive bitset of the merged segment as of `opstamp`, or currently if `None`.
+    let merged_alive_bitset = |opstamp_opt: Option<Opstamp>| -> BitSet {
+        let mut alive_bitset = BitSet::with_max_value(doc_addresses.len() as DocId);
+        for (doc, (segment_id, segment_doc)) in doc_addresses.iter().enumerate() {
+            let generations = &delete_generations[segment_id];
+            let segment_alive_bitset = match opstamp_opt {
+                Some(opstamp) => generations.alive_bitset_at(opstamp),
+                None => generations.current_alive_bitset(),
+            };
+            if is_alive(segment_alive_bitset, *segment_doc) {
+                alive_bitset.insert(doc as DocId);
+            }
+        }
+        alive_bitset
+    };
+    let alive_bitset = merged_alive_bitset(None);
+    let num_deleted_docs = (doc_addresses.len() - alive_bitset.len()) as u32;
+    if num_deleted_docs == 0 {
+        return Ok(merged_segment);
+    }
+    let mut previous_opstamps: Vec<Opstamp> = delete_generations
+        .values()
+        .flat_map(|generations| generations.generations.iter().map(|(opstamp, _)| *opstamp))
+        .filter(|&opstamp| purge_opstamp.map_or(true, |purge_opstamp| opstamp > purge_opstamp))
+        .collect();
+    previous_opstamps.sort_unstable();
+    previous_opstamps.dedup();
+    // The kept deleted documents were deleted after `purge_opstamp`, by the last of these
+    // generations at the latest.
+    let opstamp = previous_opstamps
+        .pop()
+        .expect("Deleted documents are only kept for the generations after the purge opstamp.");
+    for &previous_opstamp in &previous_opstamps {
+        let mut alive_doc_file = merged_segment.open_write_deletes(previous_opstamp)?;
+        write_alive_bitset(
+            &merged_alive_bitset(Some(previous_opstamp)),
+            &mut alive_doc_file,
+        )?;
+        alive_doc_file.terminate()?;
+    }
+    let mut merged_segment = merged_segment
+        .with_delete_generations(num_deleted_docs, opstamp, previous_opstamps)
+        .with_restorable_deletes(merged_restorable_deletes(doc_addresses, delete_generations));
+    let mut alive_doc_file = merged_segment.open_write(SegmentComponent::Delete)?;
+    write_alive_bitset(&alive_bitset, &mut alive_doc_file)?;
+    alive_doc_file.terminate()?;
+    Ok(merged_segment)
+}
+
+/// Returns the restorable deletes of the merged segments, with the doc ids of the documents
+/// the merge kept in the merged segment.
+fn merged_restorable_deletes(
+    doc_addresses: &[(SegmentId, DocId)],
+    delete_generations: &HashMap<SegmentId, DeleteGenerations>,
+) -> Vec<RestorableDelete> {
+    if delete_generations
+        .values()
+        .all(|generations| generations.restorable_deletes.is_empty())
+    {
+        return Vec::new();
+    }
+    let merged_docs: HashMap<(SegmentId, DocId), DocId> = doc_addresses
+        .iter()
+        .enumerate()
+        .map(|(doc, doc_address)| (*doc_address, doc as DocId))
+        .collect();
+    let mut merged_restorable_deletes = Vec::new();
+    for (segment_id, generations) in delete_generations {
+        for restorable_delete in &generations.restorable_deletes {
+            let mut docs: Vec<DocId> = restorable_delete
+                .docs
+                .iter()
+                .filter_map(|doc| merged_docs.get(&(*segment_id, *doc)).copied())
+                .collect();
+            if docs.is_empty() {
+                continue;
+            }
+            docs.sort_unstable();
+            merged_restorable_deletes.push(RestorableDelete {
+                term: restorable_delete.term.clone(),
+                opstamp: restorable_delete.opstamp,
+                docs,
+            });
+        }
+    }
+    merged_restorable_deletes.sort_by_key(|restorable_delete| restorable_delete.opstamp);
+    merged_restorable_deletes
+}
+
+/// The deleted documents of a segment that an undelete can restore, tracked while the delete
+/// operations are applied to the segment.
+///
+/// A deleted document can only be restored by an undelete of the term of the `delete_term`
+/// that deleted it, and only if no other delete matched it: the deletes of replaced documents,
+/// the deletes by query, and the deletes of other terms are final.
+#[derive(Default)]
+pub(crate) struct RestorableDeletes {
+    /// The deleted terms, serialized, along with the opstamp of their delete.
+    terms: Vec<(Vec<u8>, Opstamp)>,
+    /// The restorable documents, with the index in `terms` of the term that deleted them.
+    docs: HashMap<DocId, usize>,
+}
+
+impl RestorableDeletes {
+    pub(crate) fn new(restorable_deletes: &[RestorableDelete]) -> RestorableDeletes {
+        let mut terms = Vec::with_capacity(restorable_deletes.len());
+        let mut docs = HashMap::new();
+        for (term_ord, restorable_delete) in restorable_deletes.iter().enumerate() {
+            terms.push((restorable_delete.term.clone(), restorable_delete.opstamp));
+            for &doc in &restorable_delete.docs {
+                docs.insert(doc, term_ord);
+            }
+        }
+        RestorableDeletes { terms, docs }
+    }
+
+    /// Records that the delete of `term` at `opstamp` matched `doc`, which was still alive if
+    /// `was_alive`.
+    pub(crate) fn delete_term(
+        &mut self,
+        doc: DocId,
+        was_alive: bool,
+        term: &[u8],
+        opstamp: Opstamp,
+    ) {
+        if was_alive {
+            if self
+                .terms
+                .last()
+                .map(|(last_term, last_opstamp)| (&last_term[..], *last_opstamp))
+                != Some((term, opstamp))
+            {
+                self.terms.push((term.to_vec(), opstamp));
+            }
+            self.docs.insert(doc, self.terms.len() - 1);
+        } else if let Some(&term_ord) = self.docs.get(&doc) {
+            // The document was already deleted, it stays restorable by the same term only.
+            if self.terms[term_ord].0 != term {
+                self.docs.remove(&doc);
+            }
+        }
+    }
+
+    /// Records that a delete that cannot be undone matched `doc`.
+    pub(crate) fn delete_for_good(&mut self, doc: DocId) {
+        self.docs.remove(&doc);
+    }
+
+    /// Returns the documents an undelete of `term` restores, sorted, and forgets them.
+    pub(crate) fn undelete_term(&mut self, term: &[u8]) -> Vec<DocId> {
+        let terms = &self.terms;
+        let mut restored_docs: Vec<DocId> = self
+            .docs
+            .iter()
+            .filter(|(_, &term_ord)| terms[term_ord].0 == term)
+            .map(|(&doc, _)| doc)
+            .collect();
+        for doc in &restored_docs {
+            self.docs.remove(doc);
+        }
+        restored_docs.sort_unstable();
+        restored_docs
+    }
+
+    pub(crate) fn into_restorable_deletes(self) -> Vec<RestorableDelete> {
+        let mut docs_per_term: BTreeMap<usize, Vec<DocId>> = BTreeMap::new();
+        for (doc, term_ord) in self.docs {
+            docs_per_term.entry(term_ord).or_default().push(doc);
+        }
+        docs_per_term
+            .into_iter()
+            .map(|(term_ord, mut docs)| {
+                docs.sort_unstable();
+                let (term, opstamp) = self.terms[term_ord].clone();
+                RestorableDelete {
+                    term,
+                    opstamp,
+                    docs,
+                }
+            })
+            .collect()
+    }
+}
@@ -246,6 +246,7 @@ impl DeleteCursor {
 mod tests {
 
     use super::{DeleteOperation, DeleteQueue};
+    use crate::indexer::operation::DeleteTarget;
     use crate::query::{Explanation, Scorer, Weight};
     use crate::{DocId, Score, SegmentReader};
 
@@ -266,7 +267,7 @@ mod tests {
 
         let make_op = |i: usize| DeleteOperation {
             opstamp: i as u64,
-            target: Box::new(DummyWeight),
+            target: DeleteTarget::Query(Box::new(DummyWeight)),
         };
 
         delete_queue.push(make_op(1));
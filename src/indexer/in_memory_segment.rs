@@ -0,0 +1,333 @@
+use std::sync::Mutex;
+
+use super::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
+use crate::directory::RamDirectory;
+use crate::query::{AllQuery, Query, TermQuery};
+use crate::schema::document::{Document, Value};
+use crate::schema::{IndexRecordOption, OwnedValue, TantivyDocument, Term};
+use crate::{
+    Index, IndexReader, IndexWriter, Opstamp, ReloadPolicy, Score, Searcher, TantivyError,
+};
+
+/// The documents added to an [`IndexWriter`] since its last commit, indexed in RAM so that
+/// they can be searched before they are committed.
+///
+/// The documents are indexed as they are added, by the writer of an index in a
+/// [`RamDirectory`], which also gets the deletes. This RAM index is committed when a searcher
+/// is requested, so that each request only indexes the documents added since the previous one.
+///
+/// The deletes are also kept, as queries, to hide the documents they delete from the searchers
+/// of the earlier RAM indexes and of the committed documents, until their commit is visible.
+///
+/// Preparing a commit seals the RAM index with the opstamp of the commit, and later documents
+/// go to a new RAM index. A sealed RAM index stays searchable until the searcher it is combined
+/// with was loaded from its commit, or a later one, so that its documents remain visible while
+/// the commit is in progress and until the readers are reloaded.
+///
+/// The RAM indexes are limited to the memory budget of the writer: once they exceed it, the
+/// documents are not copied anymore, and the in-memory searchers fail until the next commit
+/// is visible.
+pub(crate) struct InMemorySegment {
+    index: Index,
+    memory_budget: usize,
+    state: Mutex<InMemoryState>,
+}
+
+#[derive(Default)]
+struct InMemoryState {
+    // The prepared commits, oldest first.
+    sealed: Vec<SealedCommit>,
+    // The RAM index of the documents added since the last prepared commit, created with the
+    // first of them.
+    open: Option<RamIndex>,
+    // The deletes since the last prepared commit.
+    open_deletes: Vec<Box<dyn Query>>,
+    // Whether the documents added since the last prepared commit exceeded the memory budget.
+    open_overflowed: bool,
+}
+
+/// The documents added and the deletes of a prepared commit.
+struct SealedCommit {
+    opstamp: Opstamp,
+    // `None` if no documents were added, or if they exceeded the memory budget.
+    ram_index: Option<RamIndex>,
+    overflowed: bool,
+    deletes: Vec<Box<dyn Query>>,
+}
+
+impl InMemoryState {
+    fn memory_usage(&self) -> usize {
+        self.sealed
+            .iter()
+            .flat_map(|sealed_commit| &sealed_commit.ram_index)
+            .chain(&self.open)
+            .map(RamIndex::memory_usage)
+            .sum()
+    }
+}
+
+struct RamIndex {
+    directory: RamDirectory,
+    // Dropped once the RAM index is sealed. Boxed, as an `IndexWriter` holds an
+    // `InMemorySegment`.
+    writer: Option<Box<IndexWriter>>,
+    reader: IndexReader,
+    has_uncommitted_changes: bool,
+}
+
+impl RamIndex {
+    fn create(index: &Index) -> crate::Result<RamIndex> {
+        let directory = RamDirectory::create();
+        let mut ram_index = Index::builder()
+            .schema(index.schema())
+            .settings(index.settings().clone())
+            .tokenizers(index.tokenizers().clone())
+            .fast_field_tokenizers(index.fast_field_tokenizer().clone())
+            .open_or_create(directory.clone())?;
+        ram_index.set_bm25_similarity(index.bm25_similarity())?;
+        if let Some(static_rank) = index.static_rank() {
+            ram_index.set_static_rank(static_rank.clone())?;
+        }
+        let writer = ram_index.writer_with_num_threads(1, MEMORY_BUDGET_NUM_BYTES_MIN)?;
+        let reader = ram_index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(RamIndex {
+            directory,
+            writer: Some(Box::new(writer)),
+            reader,
+            has_uncommitted_changes: false,
+        })
+    }
+
+    fn memory_usage(&self) -> usize {
+        let writer_memory_usage = self
+            .writer
+            .as_ref()
+            .map(|writer| writer.memory_usage().segments)
+            .unwrap_or(0);
+        self.directory.total_mem_usage() + writer_memory_usage
+    }
+
+    /// Commits the changes of the RAM index, and returns a searcher over its documents.
+    fn searcher(&mut self) -> crate::Result<Searcher> {
+        if self.has_uncommitted_changes {
+            if let Some(writer) = self.writer.as_mut() {
+                writer.commit()?;
+            }
+            self.reader.reload()?;
+            self.has_uncommitted_changes = false;
+        }
+        Ok(self.reader.searcher())
+    }
+
+    /// Commits the changes of the RAM index, and drops its writer.
+    fn seal(mut self) -> crate::Result<RamIndex> {
+        self.searcher()?;
+        if let Some(writer) = self.writer.take() {
+            writer.wait_merging_threads()?;
+        }
+        Ok(self)
+    }
+}
+
+fn memory_budget_exceeded() -> TantivyError {
+    TantivyError::InvalidArgument(
+        "The uncommitted documents exceed the memory budget of the in-memory segment, they are \
+         only searchable once committed."
+            .to_string(),
+    )
+}
+
+impl InMemorySegment {
+    pub(crate) fn new(index: &Index, memory_budget: usize) -> InMemorySegment {
+        InMemorySegment {
+            index: index.clone(),
+            memory_budget,
+            state: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn add_document<D: Document>(
+        &self,
+        document: &D,
+        boost: Score,
+    ) -> crate::Result<()> {
+        let mut doc = TantivyDocument::new();
+        for (field, value) in document.iter_fields_and_values() {
+            doc.add_field_value(field, OwnedValue::from(value.as_value()));
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.open_overflowed {
+            return Ok(());
+        }
+        if state.open.is_none() {
+            state.open = Some(RamIndex::create(&self.index)?);
+        }
+        let open = state.open.as_mut().unwrap();
+        if let Some(writer) = open.writer.as_ref() {
+            writer.add_document_with_boost(doc, boost)?;
+            open.has_uncommitted_changes = true;
+        }
+        if state.memory_usage() > self.memory_budget {
+            state.open = None;
+            state.open_overflowed = true;
+        }
+        Ok(())
+    }
+
+    /// Returns the writer of the RAM index of the documents added since the last prepared
+    /// commit, if there are some, and marks it as changed.
+    fn open_writer(state: &mut InMemoryState) -> Option<&IndexWriter> {
+        let open = state.open.as_mut()?;
+        open.has_uncommitted_changes = true;
+        open.writer.as_deref()
+    }
+
+    /// Deletes the documents containing `term`.
+    pub(crate) fn delete_term(&self, term: &Term) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(writer) = Self::open_writer(&mut state) {
+            writer.delete_term(term.clone());
+        }
+        state.open_deletes.push(Box::new(TermQuery::new(
+            term.clone(),
+            IndexRecordOption::Basic,
+        )));
+    }
+
+    /// Deletes the documents matching `query`.
+    pub(crate) fn delete_query(&self, query: &dyn Query) -> crate::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(writer) = Self::open_writer(&mut state) {
+            writer.delete_query(query.box_clone())?;
+        }
+        state.open_deletes.push(query.box_clone());
+        Ok(())
+    }
+
+    /// Drops all of the documents, and deletes the committed ones.
+    pub(crate) fn delete_all_documents(&self) {
+        *self.state.lock().unwrap() = InMemoryState {
+            open_deletes: vec![Box::new(AllQuery)],
+            ..InMemoryState::default()
+        };
+    }
+
+    /// Seals the documents added so far, once they are part of the commit `commit_opstamp`.
+    ///
+    /// The RAM indexes sealed by the commits before `committed_opstamp`, the last completed
+    /// commit, are dropped.
+    pub(crate) fn prepare_commit(
+        &self,
+        commit_opstamp: Opstamp,
+        committed_opstamp: Opstamp,
+    ) -> crate::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state
+            .sealed
+            .retain(|sealed_commit| sealed_commit.opstamp >= committed_opstamp);
+        let overflowed = std::mem::take(&mut state.open_overflowed);
+        let ram_index = if overflowed {
+            None
+        } else {
+            state.open.take().map(RamIndex::seal).transpose()?
+        };
+        let deletes = std::mem::take(&mut state.open_deletes);
+        if ram_index.is_some() || overflowed || !deletes.is_empty() {
+            state.sealed.push(SealedCommit {
+                opstamp: commit_opstamp,
+                ram_index,
+                overflowed,
+                deletes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns searchers over the documents that are not part of the commit
+    /// `visible_opstamp`, or of an earlier one, preceded by `committed_searcher` if given.
+    ///
+    /// The searchers exclude the documents deleted after they were added: the deletes that are
+    /// not part of the commit `visible_opstamp` apply to `committed_searcher`, which is expected
+    /// to be loaded from that commit.
+    ///
+    /// The RAM indexes of these commits are dropped if `drop_visible` is true.
+    pub(crate) fn searchers(
+        &self,
+        committed_searcher: Option<Searcher>,
+        visible_opstamp: Opstamp,
+        drop_visible: bool,
+    ) -> crate::Result<Vec<Searcher>> {
+        let mut state = self.state.lock().unwrap();
+        if drop_visible {
+            state
+                .sealed
+                .retain(|sealed_commit| sealed_commit.opstamp > visible_opstamp);
+        }
+        if state.open_overflowed {
+            return Err(memory_budget_exceeded());
+        }
+        let state = &mut *state;
+        let mut searchers = Vec::new();
+        if let Some(open) = state.open.as_mut() {
+            searchers.push(open.searcher()?);
+        }
+        // The commits are visited latest first, so that the deletes of the later commits apply
+        // to the documents of the earlier ones.
+        let mut later_deletes: Vec<&dyn Query> =
+            state.open_deletes.iter().map(Box::as_ref).collect();
+        for sealed_commit in state.sealed.iter_mut().rev() {
+            if sealed_commit.opstamp <= visible_opstamp {
+                break;
+            }
+            if sealed_commit.overflowed {
+                return Err(memory_budget_exceeded());
+            }
+            if let Some(ram_index) = sealed_commit.ram_index.as_mut() {
+                let searcher = ram_index.searcher()?;
+                searchers.push(searcher.without_docs_matching(&later_deletes)?);
+            }
+            later_deletes.extend(sealed_commit.deletes.iter().map(Box::as_ref));
+        }
+        if let Some(committed_searcher) = committed_searcher {
+            searchers.push(committed_searcher.without_docs_matching(&later_deletes)?);
+        }
+        searchers.reverse();
+        Ok(searchers)
+    }
+
+    /// Returns the number of bytes held by the RAM indexes.
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.state.lock().unwrap().memory_usage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InMemorySegment;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, TantivyError};
+
+    #[test]
+    fn test_in_memory_segment_memory_budget() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let in_memory_segment = InMemorySegment::new(&index, 0);
+        in_memory_segment.add_document(&doc!(text_field => "hello"), 1.0)?;
+        assert_eq!(in_memory_segment.memory_usage(), 0);
+        assert!(matches!(
+            in_memory_segment.searchers(None, 0, false),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        // The documents are searchable again once the commit holding them is visible.
+        in_memory_segment.prepare_commit(1, 0)?;
+        assert!(in_memory_segment.searchers(None, 0, false).is_err());
+        assert!(in_memory_segment.searchers(None, 1, true)?.is_empty());
+        in_memory_segment.add_document(&doc!(text_field => "hello"), 1.0)?;
+        assert!(in_memory_segment.searchers(None, 1, false).is_err());
+        Ok(())
+    }
+}
@@ -1,28 +1,37 @@
 use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
 
+use columnar::MonotonicallyMappableToU64;
 use common::BitSet;
 use smallvec::smallvec;
 
 use super::operation::{AddOperation, UserOperation};
 use super::segment_updater::SegmentUpdater;
 use super::{AddBatch, AddBatchReceiver, AddBatchSender, PreparedCommit};
-use crate::core::{Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
+use crate::core::{
+    metrics, CancelToken, Index, InvertedIndexReader, MultiSearcher, RestorableDelete, Segment,
+    SegmentComponent, SegmentId, SegmentMeta, SegmentReader,
+};
 use crate::directory::{DirectoryLock, GarbageCollectionResult, TerminatingWrite};
 use crate::error::TantivyError;
-use crate::fastfield::write_alive_bitset;
+use crate::fastfield::{write_alive_bitset, FastFieldUpdates};
+use crate::indexer::delete_generations::RestorableDeletes;
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
+use crate::indexer::in_memory_segment::InMemorySegment;
 use crate::indexer::index_writer_status::IndexWriterStatus;
-use crate::indexer::operation::DeleteOperation;
+use crate::indexer::operation::{DeleteOperation, DeleteTarget};
+use crate::indexer::segment_attributes::{with_segment_attributes, SegmentAttributesProvider};
 use crate::indexer::stamper::Stamper;
-use crate::indexer::{MergePolicy, SegmentEntry, SegmentWriter};
+use crate::indexer::unique_key::{DuplicateKeyPolicy, UniqueKeys};
+use crate::indexer::{MergePolicy, MergeSimulation, SegmentEntry, SegmentWriter};
 use crate::query::{EnableScoring, Query, TermQuery};
 use crate::schema::document::Document;
-use crate::schema::{IndexRecordOption, TantivyDocument, Term};
-use crate::{FutureResult, Opstamp};
+use crate::schema::{Field, FieldType, IndexRecordOption, OwnedValue, TantivyDocument, Term};
+use crate::{DocSet, FutureResult, IndexReader, Opstamp, Score, TERMINATED};
 
 // Size of the margin for the `memory_arena`. A segment is closed when the remaining memory
 // in the `memory_arena` goes below MARGIN_IN_BYTES.
@@ -35,6 +44,9 @@ pub const MEMORY_BUDGET_NUM_BYTES_MAX: usize = u32::MAX as usize - MARGIN_IN_BYT
 // We impose the number of index writer threads to be at most this.
 pub const MAX_NUM_THREAD: usize = 8;
 
+// Default number of merges that can run at the same time.
+pub const DEFAULT_NUM_MERGE_THREADS: usize = 4;
+
 // Add document will block if the number of docs waiting in the queue to be indexed
 // reaches `PIPELINE_MAX_SIZE_IN_DOCS`
 const PIPELINE_MAX_SIZE_IN_DOCS: usize = 10_000;
@@ -45,6 +57,183 @@ fn error_in_index_worker_thread(context: &str) -> TantivyError {
     ))
 }
 
+/// Options of an [`IndexWriter`], for
+/// [`Index::writer_with_options()`](crate::Index::writer_with_options).
+#[derive(Clone, Debug)]
+pub struct IndexWriterOptions {
+    memory_budget_per_thread: usize,
+    num_worker_threads: usize,
+    num_merge_threads: usize,
+    validate_documents: bool,
+    in_memory_segment: bool,
+    soft_deletes: bool,
+    undelete_window: Opstamp,
+    unique_key: Option<(Field, DuplicateKeyPolicy)>,
+}
+
+impl Default for IndexWriterOptions {
+    fn default() -> Self {
+        IndexWriterOptions {
+            memory_budget_per_thread: MEMORY_BUDGET_NUM_BYTES_MIN,
+            num_worker_threads: 1,
+            num_merge_threads: DEFAULT_NUM_MERGE_THREADS,
+            validate_documents: false,
+            in_memory_segment: false,
+            soft_deletes: false,
+            undelete_window: Opstamp::MAX,
+            unique_key: None,
+        }
+    }
+}
+
+impl IndexWriterOptions {
+    /// Sets the memory budget of each indexing thread, after which it flushes its segment.
+    ///
+    /// It needs to be at least 15 MB.
+    #[must_use]
+    pub fn memory_budget_per_thread(mut self, memory_budget_per_thread: usize) -> Self {
+        self.memory_budget_per_thread = memory_budget_per_thread;
+        self
+    }
+
+    /// Sets the number of indexing threads.
+    #[must_use]
+    pub fn num_worker_threads(mut self, num_worker_threads: usize) -> Self {
+        self.num_worker_threads = num_worker_threads;
+        self
+    }
+
+    /// Sets the number of merges that can run at the same time, defaults to 4.
+    ///
+    /// Merges run on their own threads, concurrently with indexing, commits and searches.
+    /// Merges scheduled while all of the merge threads are busy wait for one of them to be
+    /// available.
+    #[must_use]
+    pub fn num_merge_threads(mut self, num_merge_threads: usize) -> Self {
+        self.num_merge_threads = num_merge_threads;
+        self
+    }
+
+    /// Sets whether the added documents are checked with
+    /// [`Schema::validate()`](crate::schema::Schema::validate), defaults to false.
+    ///
+    /// Adding a document that does not comply with the schema then fails with a
+    /// [`TantivyError::InvalidDocument`] error.
+    #[must_use]
+    pub fn validate_documents(mut self, validate_documents: bool) -> Self {
+        self.validate_documents = validate_documents;
+        self
+    }
+
+    /// Sets whether the writer also indexes the uncommitted documents in RAM, so that they can
+    /// be searched before the next commit, defaults to false.
+    ///
+    /// The RAM index is limited to the overall memory budget of the indexing threads, on top
+    /// of it. See [`IndexWriter::in_memory_searcher()`].
+    #[must_use]
+    pub fn in_memory_segment(mut self, in_memory_segment: bool) -> Self {
+        self.in_memory_segment = in_memory_segment;
+        self
+    }
+
+    /// Sets whether deletes are reversible, defaults to false.
+    ///
+    /// With soft deletes, each commit deleting documents of a segment writes a new delete
+    /// generation, and keeps the previous ones. The deleted documents can be restored with
+    /// [`IndexWriter::undelete_term()`] until they are purged by a merge, which only happens
+    /// once they are older than the [undelete window](IndexWriterOptions::undelete_window()).
+    #[must_use]
+    pub fn soft_deletes(mut self, soft_deletes: bool) -> Self {
+        self.soft_deletes = soft_deletes;
+        self
+    }
+
+    /// Sets the number of operations during which soft deletes can be undone, defaults to no
+    /// limit.
+    ///
+    /// The merges keep the documents deleted by the delete generations of the last
+    /// `undelete_window` opstamps as deleted documents of the merged segment, so that they can
+    /// still be restored, and purge the ones deleted before. Without limit, deleted documents
+    /// are never purged. This has no effect without
+    /// [soft deletes](IndexWriterOptions::soft_deletes()).
+    #[must_use]
+    pub fn undelete_window(mut self, undelete_window: Opstamp) -> Self {
+        self.undelete_window = undelete_window;
+        self
+    }
+
+    /// Sets a field acting as a unique key, for which the writer detects duplicates.
+    ///
+    /// The added documents whose key already exists in the committed segments, or in the
+    /// documents added since the last commit, are handled according to `policy`. Deleting the
+    /// key with [`IndexWriter::delete_term()`] beforehand, as updates do, makes it available
+    /// again. The deletes by query are not taken into account.
+    ///
+    /// The field has to be an indexed `u64` or `i64` field, or a text field tokenized with the
+    /// `raw` tokenizer, whose bloom filters make checking the committed segments cheap.
+    #[must_use]
+    pub fn unique_key(mut self, field: Field, policy: DuplicateKeyPolicy) -> Self {
+        self.unique_key = Some((field, policy));
+        self
+    }
+}
+
+/// Memory held by an [`IndexWriter`], as returned by [`IndexWriter::memory_usage()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IndexWriterMemoryUsage {
+    /// Number of bytes held by the segments being built by the indexing threads, which are
+    /// flushed once they reach the memory budget of their thread.
+    pub segments: usize,
+    /// Number of bytes held by the term hashmaps of these segments, which is included in
+    /// `segments`.
+    pub term_hashmaps: usize,
+    /// Number of bytes held by the RAM index of the uncommitted documents, see
+    /// [`IndexWriterOptions::in_memory_segment()`].
+    pub in_memory_segment: usize,
+}
+
+/// The memory held by the segments being built, shared by the indexing threads.
+#[derive(Default)]
+struct IndexingMemoryUsage {
+    segments: AtomicUsize,
+    term_hashmaps: AtomicUsize,
+}
+
+/// The contribution of a segment writer to the `IndexingMemoryUsage`, which is removed once
+/// the segment is flushed.
+struct SegmentMemoryUsage {
+    memory_usage: Arc<IndexingMemoryUsage>,
+    segment: usize,
+    term_hashmap: usize,
+}
+
+impl SegmentMemoryUsage {
+    fn update(&mut self, segment_writer: &SegmentWriter) {
+        let segment = segment_writer.mem_usage();
+        let term_hashmap = segment_writer.term_hashmap_mem_usage();
+        self.memory_usage
+            .segments
+            .fetch_add(segment.wrapping_sub(self.segment), Ordering::Relaxed);
+        self.memory_usage.term_hashmaps.fetch_add(
+            term_hashmap.wrapping_sub(self.term_hashmap),
+            Ordering::Relaxed,
+        );
+        self.segment = segment;
+        self.term_hashmap = term_hashmap;
+    }
+}
+
+impl Drop for SegmentMemoryUsage {
+    fn drop(&mut self) {
+        self.memory_usage
+            .segments
+            .fetch_sub(self.segment, Ordering::Relaxed);
+        self.memory_usage
+            .term_hashmaps
+            .fetch_sub(self.term_hashmap, Ordering::Relaxed);
+    }
+}
+
 /// `IndexWriter` is the user entry-point to add document to an index.
 ///
 /// It manages a small number of indexing thread, as well as a shared
@@ -58,8 +247,7 @@ pub struct IndexWriter<D: Document = TantivyDocument> {
 
     index: Index,
 
-    // The memory budget per thread, after which a commit is triggered.
-    memory_budget_in_bytes_per_thread: usize,
+    options: IndexWriterOptions,
 
     workers_join_handle: Vec<JoinHandle<crate::Result<()>>>,
 
@@ -70,22 +258,44 @@ pub struct IndexWriter<D: Document = TantivyDocument> {
 
     worker_id: usize,
 
-    num_threads: usize,
-
     delete_queue: DeleteQueue,
 
     stamper: Stamper,
     committed_opstamp: Opstamp,
+
+    memory_usage: Arc<IndexingMemoryUsage>,
+
+    in_memory_segment: Option<InMemorySegment>,
+
+    unique_keys: Option<UniqueKeys>,
+}
+
+/// What [`compute_deleted_bitset`] did to the alive bitset.
+#[derive(Default)]
+struct ComputedDeletes {
+    /// Some documents may have been deleted.
+    might_have_changed: bool,
+    /// Some deleted documents were restored by an undelete.
+    has_undeletes: bool,
 }
 
+/// Applies the delete operations of `delete_cursor` up to `target_opstamp` to `alive_bitset`.
+///
+/// `restorable_deletes` tracks the deleted documents an undelete can restore, and is only
+/// required with soft deletes.
 fn compute_deleted_bitset(
     alive_bitset: &mut BitSet,
+    fast_field_updates: &mut FastFieldUpdates,
+    mut restorable_deletes: Option<&mut RestorableDeletes>,
     segment_reader: &SegmentReader,
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &DocToOpstampMapping,
     target_opstamp: Opstamp,
-) -> crate::Result<bool> {
-    let mut might_have_changed = false;
+) -> crate::Result<ComputedDeletes> {
+    let mut computed_deletes = ComputedDeletes::default();
+    // The term deletes are collected, to be applied together once the query deletes are done.
+    // Deletes only depend on the opstamps of the documents, so their order does not matter.
+    let mut term_deletes: Vec<TermDelete> = Vec::new();
     while let Some(delete_op) = delete_cursor.get() {
         if delete_op.opstamp > target_opstamp {
             break;
@@ -93,18 +303,168 @@ fn compute_deleted_bitset(
 
         // A delete operation should only affect
         // document that were inserted before it.
-        delete_op
-            .target
-            .for_each_no_score(segment_reader, &mut |docs_matching_delete_query| {
-                for doc_matching_delete_query in docs_matching_delete_query.iter().cloned() {
-                    if doc_opstamps.is_deleted(doc_matching_delete_query, delete_op.opstamp) {
-                        alive_bitset.remove(doc_matching_delete_query);
-                        might_have_changed = true;
+        match &delete_op.target {
+            DeleteTarget::Term(term) => term_deletes.push(TermDelete {
+                term: term.clone(),
+                opstamp: delete_op.opstamp,
+                restorable: true,
+            }),
+            DeleteTarget::Replace(term) => term_deletes.push(TermDelete {
+                term: term.clone(),
+                opstamp: delete_op.opstamp,
+                restorable: false,
+            }),
+            DeleteTarget::Query(weight) => {
+                weight.for_each_no_score(segment_reader, &mut |docs_matching_delete_query| {
+                    for doc_matching_delete_query in docs_matching_delete_query.iter().cloned() {
+                        if doc_opstamps.is_deleted(doc_matching_delete_query, delete_op.opstamp) {
+                            alive_bitset.remove(doc_matching_delete_query);
+                            computed_deletes.might_have_changed = true;
+                            if let Some(restorable_deletes) = restorable_deletes.as_deref_mut() {
+                                restorable_deletes.delete_for_good(doc_matching_delete_query);
+                            }
+                        }
                     }
+                })?;
+            }
+            DeleteTarget::Undelete(term) => {
+                // The pending term deletes have to be applied first, as the undelete may
+                // restore some of their documents.
+                if !term_deletes.is_empty() {
+                    computed_deletes.might_have_changed |= delete_terms(
+                        alive_bitset,
+                        restorable_deletes.as_deref_mut(),
+                        segment_reader,
+                        &mut term_deletes,
+                        doc_opstamps,
+                    )?;
+                    term_deletes.clear();
                 }
-            })?;
+                if let Some(restorable_deletes) = restorable_deletes.as_deref_mut() {
+                    for doc in restorable_deletes.undelete_term(term.serialized_term()) {
+                        alive_bitset.insert(doc);
+                        computed_deletes.has_undeletes = true;
+                    }
+                }
+            }
+            DeleteTarget::UpdateFastField {
+                term,
+                column_name,
+                value,
+            } => {
+                // Fast field updates are applied in order, so that the latest update wins.
+                let inverted_index = segment_reader.inverted_index(term.field())?;
+                if let Some(mut postings) =
+                    inverted_index.read_postings(term, IndexRecordOption::Basic)?
+                {
+                    let mut doc = postings.doc();
+                    while doc != TERMINATED {
+                        if doc_opstamps.is_deleted(doc, delete_op.opstamp) {
+                            fast_field_updates.set(column_name, doc, *value);
+                        }
+                        doc = postings.advance();
+                    }
+                }
+            }
+        }
         delete_cursor.advance();
     }
+    if !term_deletes.is_empty() {
+        computed_deletes.might_have_changed |= delete_terms(
+            alive_bitset,
+            restorable_deletes,
+            segment_reader,
+            &mut term_deletes,
+            doc_opstamps,
+        )?;
+    }
+    Ok(computed_deletes)
+}
+
+/// A delete of the documents containing a term.
+struct TermDelete {
+    term: Term,
+    opstamp: Opstamp,
+    /// False for the deletes of replaced documents, which an undelete cannot restore.
+    restorable: bool,
+}
+
+/// Removes the documents containing the terms of `term_deletes` from `alive_bitset`.
+///
+/// The terms are sorted, so that the deletes of a same term are applied once, and each of them
+/// is looked up in the term dictionary, unless the bloom filter of its field rules it out.
+fn delete_terms(
+    alive_bitset: &mut BitSet,
+    mut restorable_deletes: Option<&mut RestorableDeletes>,
+    segment_reader: &SegmentReader,
+    term_deletes: &mut [TermDelete],
+    doc_opstamps: &DocToOpstampMapping,
+) -> crate::Result<bool> {
+    let mut might_have_changed = false;
+    term_deletes.sort_unstable_by(|left, right| {
+        (left.term.field(), left.term.serialized_value_bytes())
+            .cmp(&(right.term.field(), right.term.serialized_value_bytes()))
+    });
+    let mut inverted_index_opt: Option<(Field, Arc<InvertedIndexReader>)> = None;
+    let mut remaining_term_deletes: &[TermDelete] = term_deletes;
+    while let Some(TermDelete { term, .. }) = remaining_term_deletes.first() {
+        let num_term_deletes = remaining_term_deletes
+            .iter()
+            .take_while(|other| other.term == *term)
+            .count();
+        let (same_term_deletes, others) = remaining_term_deletes.split_at(num_term_deletes);
+        remaining_term_deletes = others;
+        // The same term may be deleted several times, the latest delete wins. A document is
+        // restorable if all of the deletes matching it are.
+        let latest_opstamp = |restorable_opt: Option<bool>| {
+            same_term_deletes
+                .iter()
+                .filter(|term_delete| {
+                    restorable_opt.map_or(true, |restorable| term_delete.restorable == restorable)
+                })
+                .map(|term_delete| term_delete.opstamp)
+                .max()
+        };
+        let delete_opstamp =
+            latest_opstamp(None).expect("There is at least one delete of the term.");
+        let restorable_opstamp = latest_opstamp(Some(true));
+        let final_opstamp = latest_opstamp(Some(false));
+        let inverted_index = match &inverted_index_opt {
+            Some((field, inverted_index)) if *field == term.field() => inverted_index,
+            _ => {
+                let inverted_index = segment_reader.inverted_index(term.field())?;
+                &inverted_index_opt.insert((term.field(), inverted_index)).1
+            }
+        };
+        let Some(term_info) = inverted_index.get_term_info(term)? else {
+            continue;
+        };
+        let mut postings =
+            inverted_index.read_postings_from_terminfo(&term_info, IndexRecordOption::Basic)?;
+        let mut doc = postings.doc();
+        while doc != TERMINATED {
+            if doc_opstamps.is_deleted(doc, delete_opstamp) {
+                let was_alive = alive_bitset.contains(doc);
+                alive_bitset.remove(doc);
+                might_have_changed = true;
+                if let Some(restorable_deletes) = restorable_deletes.as_deref_mut() {
+                    match (final_opstamp, restorable_opstamp) {
+                        (Some(opstamp), _) if doc_opstamps.is_deleted(doc, opstamp) => {
+                            restorable_deletes.delete_for_good(doc);
+                        }
+                        (_, Some(opstamp)) => restorable_deletes.delete_term(
+                            doc,
+                            was_alive,
+                            term.serialized_term(),
+                            opstamp,
+                        ),
+                        _ => restorable_deletes.delete_for_good(doc),
+                    }
+                }
+            }
+            doc = postings.advance();
+        }
+    }
     Ok(might_have_changed)
 }
 
@@ -114,10 +474,14 @@ fn compute_deleted_bitset(
 /// is `==` target_opstamp.
 /// For instance, there was no delete operation between the state of the `segment_entry` and
 /// the `target_opstamp`, `segment_entry` is not updated.
+///
+/// With `soft_deletes`, the previous delete file of the segment is kept as an earlier delete
+/// generation when a new one is written.
 pub(crate) fn advance_deletes(
     mut segment: Segment,
     segment_entry: &mut SegmentEntry,
     target_opstamp: Opstamp,
+    soft_deletes: bool,
 ) -> crate::Result<()> {
     if segment_entry.meta().delete_opstamp() == Some(target_opstamp) {
         // We are already up-to-date here.
@@ -137,28 +501,54 @@ pub(crate) fn advance_deletes(
         None => BitSet::with_max_value_and_full(max_doc),
     };
 
+    // The deletes of the delete file are applied before the delete operations, so that
+    // undeletes can restore them.
+    if let Some(seg_alive_bitset) = segment_reader.alive_bitset() {
+        alive_bitset.intersect_update(seg_alive_bitset.bitset());
+    }
+
     let num_deleted_docs_before = segment.meta().num_deleted_docs();
+    let mut fast_field_updates = segment_reader.fast_fields().updates().clone();
+    let mut restorable_deletes = if soft_deletes {
+        let mut restorable_deletes = segment.meta().restorable_deletes().to_vec();
+        restorable_deletes.extend_from_slice(segment_entry.restorable_deletes());
+        Some(RestorableDeletes::new(&restorable_deletes))
+    } else {
+        None
+    };
 
-    compute_deleted_bitset(
+    let computed_deletes = compute_deleted_bitset(
         &mut alive_bitset,
+        &mut fast_field_updates,
+        restorable_deletes.as_mut(),
         &segment_reader,
         segment_entry.delete_cursor(),
         &DocToOpstampMapping::None,
         target_opstamp,
     )?;
 
-    if let Some(seg_alive_bitset) = segment_reader.alive_bitset() {
-        alive_bitset.intersect_update(seg_alive_bitset.bitset());
-    }
-
     let num_alive_docs: u32 = alive_bitset.len() as u32;
     let num_deleted_docs = max_doc - num_alive_docs;
-    if num_deleted_docs > num_deleted_docs_before {
-        // There are new deletes. We need to write a new delete file.
-        segment = segment.with_delete_meta(num_deleted_docs, target_opstamp);
+    if num_deleted_docs > num_deleted_docs_before || computed_deletes.has_undeletes {
+        // There are new deletes or undeletes. We need to write a new delete file.
+        segment = if soft_deletes {
+            segment.with_new_delete_generation(num_deleted_docs, target_opstamp)
+        } else {
+            segment.with_delete_meta(num_deleted_docs, target_opstamp)
+        };
         let mut alive_doc_file = segment.open_write(SegmentComponent::Delete)?;
         write_alive_bitset(&alive_bitset, &mut alive_doc_file)?;
         alive_doc_file.terminate()?;
+        // The delete file now holds the deletes of the in-memory bitset, which must not be
+        // applied again on top of later undeletes.
+        segment_entry.clear_alive_bitset();
+    }
+    if let Some(restorable_deletes) = restorable_deletes {
+        segment = segment.with_restorable_deletes(restorable_deletes.into_restorable_deletes());
+    }
+    if fast_field_updates.is_modified() {
+        segment = segment.with_fast_field_updates(target_opstamp);
+        fast_field_updates.write(&mut segment)?;
     }
 
     segment_entry.set_meta(segment.meta().clone());
@@ -171,12 +561,20 @@ fn index_documents<D: Document>(
     grouped_document_iterator: &mut dyn Iterator<Item = AddBatch<D>>,
     segment_updater: &SegmentUpdater,
     mut delete_cursor: DeleteCursor,
+    memory_usage: Arc<IndexingMemoryUsage>,
+    soft_deletes: bool,
 ) -> crate::Result<()> {
     let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?;
+    let mut segment_memory_usage = SegmentMemoryUsage {
+        memory_usage,
+        segment: 0,
+        term_hashmap: 0,
+    };
     for document_group in grouped_document_iterator {
         for doc in document_group {
             segment_writer.add_document(doc)?;
         }
+        segment_memory_usage.update(&segment_writer);
         let mem_usage = segment_writer.mem_usage();
         if mem_usage >= memory_budget - MARGIN_IN_BYTES {
             info!(
@@ -197,30 +595,55 @@ fn index_documents<D: Document>(
     // the worker thread.
     assert!(max_doc > 0);
 
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!(
+        "flush_segment",
+        segment_id = %segment.id(),
+        num_docs = max_doc,
+        mem_usage = segment_writer.mem_usage(),
+    )
+    .entered();
+
     let doc_opstamps: Vec<Opstamp> = segment_writer.finalize()?;
 
-    let segment_with_max_doc = segment.with_max_doc(max_doc);
+    let segment_with_max_doc = with_segment_attributes(
+        segment.with_max_doc(max_doc),
+        segment_updater.get_segment_attributes_provider().as_deref(),
+    )?;
 
-    let alive_bitset_opt = apply_deletes(&segment_with_max_doc, &mut delete_cursor, &doc_opstamps)?;
+    let (segment_with_max_doc, alive_bitset_opt, restorable_deletes) = apply_deletes(
+        segment_with_max_doc,
+        &mut delete_cursor,
+        &doc_opstamps,
+        soft_deletes,
+    )?;
 
     let meta = segment_with_max_doc.meta().clone();
     meta.untrack_temp_docstore();
     // update segment_updater inventory to remove tempstore
-    let segment_entry = SegmentEntry::new(meta, delete_cursor, alive_bitset_opt);
+    let mut segment_entry = SegmentEntry::new(meta, delete_cursor, alive_bitset_opt);
+    segment_entry.set_restorable_deletes(restorable_deletes);
     segment_updater.schedule_add_segment(segment_entry).wait()?;
+    let metrics_counters = segment_with_max_doc.index().metrics_counters();
+    metrics::incr(&metrics_counters.num_docs_indexed, max_doc as u64);
     Ok(())
 }
 
+/// Applies the delete operations, and the fast field updates, of the queue to a freshly
+/// written segment, and returns its alive bitset, along with the deleted documents an
+/// undelete can restore if `soft_deletes`.
+///
 /// `doc_opstamps` is required to be non-empty.
 fn apply_deletes(
-    segment: &Segment,
+    mut segment: Segment,
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &[Opstamp],
-) -> crate::Result<Option<BitSet>> {
+    soft_deletes: bool,
+) -> crate::Result<(Segment, Option<BitSet>, Vec<RestorableDelete>)> {
     if delete_cursor.get().is_none() {
         // if there are no delete operation in the queue, no need
         // to even open the segment.
-        return Ok(None);
+        return Ok((segment, None, Vec::new()));
     }
 
     let max_doc_opstamp: Opstamp = doc_opstamps
@@ -229,23 +652,35 @@ fn apply_deletes(
         .max()
         .expect("Empty DocOpstamp is forbidden");
 
-    let segment_reader = SegmentReader::open(segment)?;
+    let segment_reader = SegmentReader::open(&segment)?;
     let doc_to_opstamps = DocToOpstampMapping::WithMap(doc_opstamps);
 
     let max_doc = segment.meta().max_doc();
     let mut deleted_bitset = BitSet::with_max_value_and_full(max_doc);
-    let may_have_deletes = compute_deleted_bitset(
+    let mut fast_field_updates = FastFieldUpdates::default();
+    let mut restorable_deletes = soft_deletes.then(RestorableDeletes::default);
+    let computed_deletes = compute_deleted_bitset(
         &mut deleted_bitset,
+        &mut fast_field_updates,
+        restorable_deletes.as_mut(),
         &segment_reader,
         delete_cursor,
         &doc_to_opstamps,
         max_doc_opstamp,
     )?;
-    Ok(if may_have_deletes {
+    if fast_field_updates.is_modified() {
+        segment = segment.with_fast_field_updates(max_doc_opstamp);
+        fast_field_updates.write(&mut segment)?;
+    }
+    let alive_bitset_opt = if computed_deletes.might_have_changed {
         Some(deleted_bitset)
     } else {
         None
-    })
+    };
+    let restorable_deletes = restorable_deletes
+        .map(RestorableDeletes::into_restorable_deletes)
+        .unwrap_or_default();
+    Ok((segment, alive_bitset_opt, restorable_deletes))
 }
 
 impl<D: Document> IndexWriter<D> {
@@ -257,18 +692,16 @@ impl<D: Document> IndexWriter<D> {
     /// `IndexWriter` on the system is accessing the index directory,
     /// it is safe to manually delete the lockfile.
     ///
-    /// `num_threads` specifies the number of indexing workers that
-    /// should work at the same time.
     /// # Errors
     /// If the lockfile already exists, returns `Error::FileAlreadyExists`.
-    /// If the memory arena per thread is too small or too big, returns
-    /// `TantivyError::InvalidArgument`
+    /// If the memory arena per thread is too small or too big, or if there are no merge
+    /// threads, returns `TantivyError::InvalidArgument`
     pub(crate) fn new(
         index: &Index,
-        num_threads: usize,
-        memory_budget_in_bytes_per_thread: usize,
+        options: IndexWriterOptions,
         directory_lock: DirectoryLock,
     ) -> crate::Result<Self> {
+        let memory_budget_in_bytes_per_thread = options.memory_budget_per_thread;
         if memory_budget_in_bytes_per_thread < MEMORY_BUDGET_NUM_BYTES_MIN {
             let err_msg = format!(
                 "The memory arena in bytes per thread needs to be at least \
@@ -282,6 +715,11 @@ impl<D: Document> IndexWriter<D> {
             );
             return Err(TantivyError::InvalidArgument(err_msg));
         }
+        if options.num_merge_threads == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "The number of merge threads needs to be at least 1.".to_string(),
+            ));
+        }
         let (document_sender, document_receiver) =
             crossbeam_channel::bounded(PIPELINE_MAX_SIZE_IN_DOCS);
 
@@ -291,13 +729,18 @@ impl<D: Document> IndexWriter<D> {
 
         let stamper = Stamper::new(current_opstamp);
 
-        let segment_updater =
-            SegmentUpdater::create(index.clone(), stamper.clone(), &delete_queue.cursor())?;
+        let segment_updater = SegmentUpdater::create(
+            index.clone(),
+            stamper.clone(),
+            &delete_queue.cursor(),
+            options.num_merge_threads,
+            options.soft_deletes.then_some(options.undelete_window),
+        )?;
 
         let mut index_writer = Self {
             _directory_lock: Some(directory_lock),
 
-            memory_budget_in_bytes_per_thread,
+            options: options.clone(),
             index: index.clone(),
             index_writer_status: IndexWriterStatus::from(document_receiver),
             operation_sender: document_sender,
@@ -305,13 +748,26 @@ impl<D: Document> IndexWriter<D> {
             segment_updater,
 
             workers_join_handle: vec![],
-            num_threads,
 
             delete_queue,
 
             committed_opstamp: current_opstamp,
             stamper,
 
+            memory_usage: Arc::default(),
+
+            in_memory_segment: options.in_memory_segment.then(|| {
+                InMemorySegment::new(
+                    index,
+                    options.memory_budget_per_thread * options.num_worker_threads,
+                )
+            }),
+
+            unique_keys: options
+                .unique_key
+                .map(|(field, policy)| UniqueKeys::open(index, field, policy))
+                .transpose()?,
+
             worker_id: 0,
         };
         index_writer.start_workers()?;
@@ -328,6 +784,22 @@ impl<D: Document> IndexWriter<D> {
         &self.index
     }
 
+    /// Returns the memory currently held by the segments being built by the indexing threads.
+    ///
+    /// This is updated after each batch of documents, and does not include the documents
+    /// waiting in the indexing queue.
+    pub fn memory_usage(&self) -> IndexWriterMemoryUsage {
+        IndexWriterMemoryUsage {
+            segments: self.memory_usage.segments.load(Ordering::Relaxed),
+            term_hashmaps: self.memory_usage.term_hashmaps.load(Ordering::Relaxed),
+            in_memory_segment: self
+                .in_memory_segment
+                .as_ref()
+                .map(InMemorySegment::memory_usage)
+                .unwrap_or(0),
+        }
+    }
+
     /// If there are some merging threads, blocks until they all finish their work and
     /// then drop the `IndexWriter`.
     pub fn wait_merging_threads(mut self) -> crate::Result<()> {
@@ -398,8 +870,10 @@ impl<D: Document> IndexWriter<D> {
 
         let mut delete_cursor = self.delete_queue.cursor();
 
-        let mem_budget = self.memory_budget_in_bytes_per_thread;
+        let mem_budget = self.options.memory_budget_per_thread;
+        let soft_deletes = self.options.soft_deletes;
         let index = self.index.clone();
+        let memory_usage = self.memory_usage.clone();
         let join_handle: JoinHandle<crate::Result<()>> = thread::Builder::new()
             .name(format!("thrd-tantivy-index{}", self.worker_id))
             .spawn(move || {
@@ -432,6 +906,8 @@ impl<D: Document> IndexWriter<D> {
                         &mut document_iterator,
                         &segment_updater,
                         delete_cursor.clone(),
+                        memory_usage.clone(),
+                        soft_deletes,
                     )?;
                 }
             })?;
@@ -450,8 +926,29 @@ impl<D: Document> IndexWriter<D> {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Sets the provider computing the custom attributes of the segments written by the
+    /// writer, see [`SegmentAttributesProvider`].
+    pub fn set_segment_attributes_provider(
+        &self,
+        segment_attributes_provider: Box<dyn SegmentAttributesProvider>,
+    ) {
+        self.segment_updater
+            .set_segment_attributes_provider(segment_attributes_provider);
+    }
+
+    /// Returns the merges the current merge policy would start, without starting them.
+    ///
+    /// As for actual merges, committed and uncommitted segments are considered separately, and
+    /// segments being merged are left aside. This makes it possible to see what a merge policy
+    /// would do with an existing index, before setting it: a writer using the
+    /// [`NoMergePolicy`](crate::indexer::NoMergePolicy) can simulate other policies by switching
+    /// back and forth with [`IndexWriter::set_merge_policy()`].
+    pub fn simulate_merges(&self) -> crate::Result<Vec<MergeSimulation>> {
+        self.segment_updater.simulate_merges()
+    }
+
     fn start_workers(&mut self) -> crate::Result<()> {
-        for _ in 0..self.num_threads {
+        for _ in 0..self.options.num_worker_threads {
             self.add_indexing_worker()?;
         }
         Ok(())
@@ -501,6 +998,12 @@ impl<D: Document> IndexWriter<D> {
     pub fn delete_all_documents(&self) -> crate::Result<Opstamp> {
         // Delete segments
         self.segment_updater.remove_all_segments();
+        if let Some(unique_keys) = &self.unique_keys {
+            unique_keys.delete_all_documents(self.segment_updater.load_meta().opstamp)?;
+        }
+        if let Some(in_memory_segment) = &self.in_memory_segment {
+            in_memory_segment.delete_all_documents();
+        }
         // Return new stamp - reverted stamp
         self.stamper.revert(self.committed_opstamp);
         Ok(self.committed_opstamp)
@@ -512,9 +1015,48 @@ impl<D: Document> IndexWriter<D> {
     ///
     /// `segment_ids` is required to be non-empty.
     pub fn merge(&mut self, segment_ids: &[SegmentId]) -> FutureResult<Option<SegmentMeta>> {
+        self.merge_with_cancel_token(segment_ids, CancelToken::default())
+    }
+
+    /// Same as [`IndexWriter::merge()`], but the merge stops with a
+    /// [`TantivyError::Cancelled`] error once `cancel_token` gets cancelled.
+    ///
+    /// The segments are then left untouched.
+    pub fn merge_with_cancel_token(
+        &mut self,
+        segment_ids: &[SegmentId],
+        cancel_token: CancelToken,
+    ) -> FutureResult<Option<SegmentMeta>> {
         let merge_operation = self.segment_updater.make_merge_operation(segment_ids);
         let segment_updater = self.segment_updater.clone();
-        segment_updater.start_merge(merge_operation)
+        segment_updater.start_merge(merge_operation, cancel_token)
+    }
+
+    /// Merges each segment whose ratio of deleted docs exceeds `deleted_docs_ratio_threshold`
+    /// on its own, to reclaim the space of its deleted docs.
+    ///
+    /// Unlike merging all of the segments together, this only rewrites the segments with many
+    /// deleted docs, and leaves the other ones untouched. Only the deletes that were committed
+    /// are taken into account, and the segments being merged are left aside.
+    ///
+    /// Returns one future per started merge.
+    pub fn expunge_deletes(
+        &mut self,
+        deleted_docs_ratio_threshold: f32,
+    ) -> Vec<FutureResult<Option<SegmentMeta>>> {
+        let (committed_segments, uncommitted_segments) =
+            self.segment_updater.get_mergeable_segments();
+        committed_segments
+            .iter()
+            .chain(&uncommitted_segments)
+            .filter(|segment_meta| {
+                let num_deleted_docs = segment_meta.num_deleted_docs();
+                num_deleted_docs > 0
+                    && num_deleted_docs as f32 / segment_meta.max_doc() as f32
+                        > deleted_docs_ratio_threshold
+            })
+            .map(|segment_meta| self.merge(&[segment_meta.id()]))
+            .collect()
     }
 
     /// Closes the current document channel send.
@@ -553,18 +1095,20 @@ impl<D: Document> IndexWriter<D> {
             .take()
             .expect("The IndexWriter does not have any lock. This is a bug, please report.");
 
-        let new_index_writer = IndexWriter::new(
-            &self.index,
-            self.num_threads,
-            self.memory_budget_in_bytes_per_thread,
-            directory_lock,
-        )?;
+        let new_index_writer = IndexWriter::new(&self.index, self.options.clone(), directory_lock)?;
+        // The unique keys are kept, along with their reader and counters, without the keys of
+        // the changes rolled back.
+        let unique_keys = self.unique_keys.take();
 
         // the current `self` is dropped right away because of this call.
         //
         // This will drop the document queue, and the thread
         // should terminate.
         *self = new_index_writer;
+        if let Some(unique_keys) = unique_keys {
+            unique_keys.rollback();
+            self.unique_keys = Some(unique_keys);
+        }
 
         // Drains the document receiver pipeline :
         // Workers don't need to index the pending documents.
@@ -611,6 +1155,8 @@ impl<D: Document> IndexWriter<D> {
         // This will move uncommitted segments to the state of
         // committed segments.
         info!("Preparing commit");
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("prepare_commit").entered();
 
         // this will drop the current document channel
         // and recreate a new one.
@@ -627,6 +1173,13 @@ impl<D: Document> IndexWriter<D> {
         }
 
         let commit_opstamp = self.stamper.stamp();
+        if let Some(in_memory_segment) = &self.in_memory_segment {
+            let committed_opstamp = self.segment_updater.load_meta().opstamp;
+            in_memory_segment.prepare_commit(commit_opstamp, committed_opstamp)?;
+        }
+        if let Some(unique_keys) = &self.unique_keys {
+            unique_keys.prepare_commit(commit_opstamp);
+        }
         let prepared_commit = PreparedCommit::new(self, commit_opstamp);
         info!("Prepared commit {}", commit_opstamp);
         Ok(prepared_commit)
@@ -662,11 +1215,57 @@ impl<D: Document> IndexWriter<D> {
     /// Like adds, the deletion itself will be visible
     /// only after calling `commit()`.
     pub fn delete_term(&self, term: Term) -> Opstamp {
-        let query = TermQuery::new(term, IndexRecordOption::Basic);
         // For backward compatibility, if Term is invalid for the index, do nothing but return an
         // Opstamp
-        self.delete_query(Box::new(query))
-            .unwrap_or_else(|_| self.stamper.stamp())
+        let Ok(target) = self.term_delete_target(term) else {
+            return self.stamper.stamp();
+        };
+        let opstamp = self.stamper.stamp();
+        if let DeleteTarget::Term(term) = &target {
+            if let Some(unique_keys) = &self.unique_keys {
+                unique_keys.delete_term(term, opstamp);
+            }
+            if let Some(in_memory_segment) = &self.in_memory_segment {
+                in_memory_segment.delete_term(term);
+            }
+        }
+        self.delete_queue.push(DeleteOperation { opstamp, target });
+        opstamp
+    }
+
+    /// Returns the target of a delete of `term`, or an error if it cannot be deleted.
+    fn term_delete_target(&self, term: Term) -> crate::Result<DeleteTarget> {
+        // Creating the weight checks that the field of the term is indexed.
+        TermQuery::new(term.clone(), IndexRecordOption::Basic)
+            .specialized_weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
+        Ok(DeleteTarget::Term(term))
+    }
+
+    /// Restores the deleted documents containing a given term.
+    ///
+    /// This requires the writer to be created with
+    /// [`IndexWriterOptions::soft_deletes()`], and returns an `Err` otherwise, or if the field
+    /// of the term is not indexed.
+    ///
+    /// Only the documents deleted by a [`IndexWriter::delete_term()`] of the same term before
+    /// the undelete are restored, as long as they have not been purged by a merge. The
+    /// documents replaced by [`IndexWriter::update_document()`], or by a batch of operations
+    /// adding documents, and the documents matched by any other delete, are not. Like deletes,
+    /// the undelete is visible after calling `commit()`.
+    pub fn undelete_term(&self, term: Term) -> crate::Result<Opstamp> {
+        if !self.options.soft_deletes {
+            return Err(TantivyError::InvalidArgument(
+                "Undeletes require an index writer with soft deletes.".to_string(),
+            ));
+        }
+        TermQuery::new(term.clone(), IndexRecordOption::Basic)
+            .specialized_weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
+        let opstamp = self.stamper.stamp();
+        self.delete_queue.push(DeleteOperation {
+            opstamp,
+            target: DeleteTarget::Undelete(term),
+        });
+        Ok(opstamp)
     }
 
     /// Delete all documents matching a given query.
@@ -681,15 +1280,74 @@ impl<D: Document> IndexWriter<D> {
     #[doc(hidden)]
     pub fn delete_query(&self, query: Box<dyn Query>) -> crate::Result<Opstamp> {
         let weight = query.weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
+        if let Some(in_memory_segment) = &self.in_memory_segment {
+            in_memory_segment.delete_query(query.as_ref())?;
+        }
         let opstamp = self.stamper.stamp();
         let delete_operation = DeleteOperation {
             opstamp,
-            target: weight,
+            target: DeleteTarget::Query(weight),
         };
         self.delete_queue.push(delete_operation);
         Ok(opstamp)
     }
 
+    /// Sets the value of the fast field `field` of all documents containing a given term,
+    /// without reindexing them.
+    ///
+    /// This is meant for numerical signals that change frequently, like popularity counters.
+    /// The updated values are stored next to the segments and override the values of the fast
+    /// field when it is read. Merges fold them into the fast fields of the merged segment.
+    /// They are not visible in the stored fields nor in the inverted index.
+    ///
+    /// Only `u64`, `i64`, `f64` and `bool` fast fields can be updated, and only the documents
+    /// with exactly one value for the field are affected.
+    ///
+    /// Like deletes, the update only affects documents that were added before it, and becomes
+    /// visible after calling `commit()`.
+    pub fn update_fast_field(
+        &self,
+        term: Term,
+        field: Field,
+        value: OwnedValue,
+    ) -> crate::Result<Opstamp> {
+        let schema = self.index.schema();
+        let field_entry = schema.get_field_entry(field);
+        if !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a fast field.",
+                field_entry.name()
+            )));
+        }
+        let value = match (field_entry.field_type(), value) {
+            (FieldType::U64(_), OwnedValue::U64(val)) => val.to_u64(),
+            (FieldType::I64(_), OwnedValue::I64(val)) => val.to_u64(),
+            (FieldType::F64(_), OwnedValue::F64(val)) => val.to_u64(),
+            (FieldType::Bool(_), OwnedValue::Bool(val)) => val.to_u64(),
+            (field_type, value) => {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Cannot update field {:?} of type {:?} with value {:?}.",
+                    field_entry.name(),
+                    field_type.value_type(),
+                    value
+                )));
+            }
+        };
+        // Creating the weight checks that the field of the term is indexed.
+        TermQuery::new(term.clone(), IndexRecordOption::Basic)
+            .specialized_weight(EnableScoring::disabled_from_schema(&schema))?;
+        let opstamp = self.stamper.stamp();
+        self.delete_queue.push(DeleteOperation {
+            opstamp,
+            target: DeleteTarget::UpdateFastField {
+                term,
+                column_name: field_entry.name().to_string(),
+                value,
+            },
+        });
+        Ok(opstamp)
+    }
+
     /// Returns the opstamp of the last successful commit.
     ///
     /// This is, for instance, the opstamp the index will
@@ -709,11 +1367,153 @@ impl<D: Document> IndexWriter<D> {
     /// be used by the client to align commits with its own
     /// document queue.
     pub fn add_document(&self, document: D) -> crate::Result<Opstamp> {
+        self.validate_document(&document)?;
+        let opstamp = self.stamper.stamp();
+        if !self.check_unique_key(&document, opstamp)? {
+            return Ok(opstamp);
+        }
+        self.add_to_in_memory_segment(&document, 1.0)?;
+        self.send_add_documents_batch(smallvec![AddOperation {
+            opstamp,
+            document,
+            boost: 1.0,
+        }])?;
+        Ok(opstamp)
+    }
+
+    /// Adds a document with an index-time boost, which makes it rank higher (or lower if
+    /// `boost` is lower than 1) than the other documents for the queries scored with BM25.
+    ///
+    /// The boost is folded into the fieldnorms of the document: they are divided by `boost`, and
+    /// rounded to an integer number of tokens. It is kept as is by merges, but has a few limits:
+    /// - Its effect is bounded, and coarse on short fields: a boost that does not change the
+    ///   rounded length of a field, e.g. a boost lower than 2 on a single-token field, has no
+    ///   effect on that field.
+    /// - It only affects the fields with fieldnorms, and only through BM25 length
+    ///   normalization: it has no effect if the `b` parameter of
+    ///   [`Bm25Similarity`](crate::query::Bm25Similarity) is 0.
+    /// - [`UserOperation::Add`] does not carry a boost, so the documents added through
+    ///   [`IndexWriter::run`] or [`IndexWriter::update_document`] cannot be boosted.
+    ///
+    /// `boost` must be a positive finite number.
+    pub fn add_document_with_boost(&self, document: D, boost: Score) -> crate::Result<Opstamp> {
+        if !(boost.is_finite() && boost > 0.0) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The boost of a document must be positive and finite, got {boost}"
+            )));
+        }
+        self.validate_document(&document)?;
         let opstamp = self.stamper.stamp();
-        self.send_add_documents_batch(smallvec![AddOperation { opstamp, document }])?;
+        if !self.check_unique_key(&document, opstamp)? {
+            return Ok(opstamp);
+        }
+        self.add_to_in_memory_segment(&document, boost)?;
+        self.send_add_documents_batch(smallvec![AddOperation {
+            opstamp,
+            document,
+            boost,
+        }])?;
         Ok(opstamp)
     }
 
+    /// Checks the document against the schema, if the writer validates documents.
+    fn validate_document(&self, document: &D) -> crate::Result<()> {
+        if self.options.validate_documents {
+            self.index
+                .schema()
+                .validate(document)
+                .map_err(TantivyError::InvalidDocument)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the unique key of the document, if the writer has one.
+    ///
+    /// Returns false if the document is a duplicate to skip.
+    fn check_unique_key(&self, document: &D, opstamp: Opstamp) -> crate::Result<bool> {
+        match &self.unique_keys {
+            Some(unique_keys) => unique_keys.check_document(
+                document,
+                opstamp,
+                self.segment_updater.load_meta().opstamp,
+            ),
+            None => Ok(true),
+        }
+    }
+
+    /// Returns the number of documents skipped because their unique key already existed, see
+    /// [`IndexWriterOptions::unique_key()`].
+    pub fn num_skipped_duplicates(&self) -> u64 {
+        self.unique_keys
+            .as_ref()
+            .map(UniqueKeys::num_skipped)
+            .unwrap_or(0)
+    }
+
+    /// Copies the document to the in-memory segment, if the writer has one.
+    fn add_to_in_memory_segment(&self, document: &D, boost: Score) -> crate::Result<()> {
+        if let Some(in_memory_segment) = &self.in_memory_segment {
+            in_memory_segment.add_document(document, boost)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a searcher over the documents added since the last completed commit, without
+    /// committing them.
+    ///
+    /// This requires the writer to be created with
+    /// [`IndexWriterOptions::in_memory_segment()`]: the added documents are then also indexed
+    /// into an index in RAM, which gets the deletes too. The new documents are made searchable
+    /// when a searcher is requested, so each request only indexes the documents added since
+    /// the previous one.
+    ///
+    /// The deletes apply to the documents added before them, in the in-memory segment right
+    /// away.
+    ///
+    /// This fails if the uncommitted documents exceed the memory budget of the in-memory
+    /// segment, until the next commit.
+    ///
+    /// See [`IndexWriter::realtime_searcher()`] to search them along with the committed
+    /// documents.
+    pub fn in_memory_searcher(&self) -> crate::Result<MultiSearcher> {
+        let committed_opstamp = self.segment_updater.load_meta().opstamp;
+        MultiSearcher::new(
+            self.in_memory_segment()?
+                .searchers(None, committed_opstamp, false)?,
+        )
+    }
+
+    /// Returns a searcher over the documents of `reader`, as well as the documents added since
+    /// the commit `reader` was loaded from.
+    ///
+    /// The documents of a commit remain visible through the in-memory segment until `reader` is
+    /// reloaded with that commit, and are dropped from the in-memory segment once it is. Only the
+    /// documents of the last completed commit are kept for readers lagging further behind.
+    ///
+    /// The deletes made since that commit also hide the documents of `reader` they delete, so
+    /// that the documents replaced by [`IndexWriter::update_document()`] are not returned
+    /// along with their new version. The searcher of `reader` is then copied with the new
+    /// deletes, which requires running the delete queries on all of its segments.
+    ///
+    /// See [`IndexWriter::in_memory_searcher()`].
+    pub fn realtime_searcher(&self, reader: &IndexReader) -> crate::Result<MultiSearcher> {
+        let searcher = reader.searcher();
+        let commit_opstamp = searcher.commit_opstamp();
+        MultiSearcher::new(self.in_memory_segment()?.searchers(
+            Some(searcher),
+            commit_opstamp,
+            true,
+        )?)
+    }
+
+    fn in_memory_segment(&self) -> crate::Result<&InMemorySegment> {
+        self.in_memory_segment.as_ref().ok_or_else(|| {
+            TantivyError::InvalidArgument(
+                "The index writer was created without an in-memory segment.".to_string(),
+            )
+        })
+    }
+
     /// Gets a range of stamps from the stamper and "pops" the last stamp
     /// from the range returning a tuple of the last optstamp and the popped
     /// range.
@@ -743,34 +1543,75 @@ impl<D: Document> IndexWriter<D> {
     /// Like adds and deletes (see `IndexWriter.add_document` and
     /// `IndexWriter.delete_term`), the changes made by calling `run` will be
     /// visible to readers only after calling `commit()`.
+    ///
+    /// The added documents are validated, and their unique keys checked, before any of the
+    /// operations is applied: if one of them is invalid or rejected, an error is returned and
+    /// none of the operations is applied. If one of them is a duplicate to skip, all of the
+    /// documents of the batch are skipped, and none of the operations is applied either.
     pub fn run<I>(&self, user_operations: I) -> crate::Result<Opstamp>
     where
         I: IntoIterator<Item = UserOperation<D>>,
         I::IntoIter: ExactSizeIterator,
     {
-        let user_operations_it = user_operations.into_iter();
-        let count = user_operations_it.len() as u64;
+        let user_operations: Vec<UserOperation<D>> = user_operations.into_iter().collect();
+        let count = user_operations.len() as u64;
         if count == 0 {
             return Ok(self.stamper.stamp());
         }
+        // The operations are all checked before any of them is applied, so that a batch is
+        // either applied entirely, or not at all.
+        // The documents deleted by a batch adding documents are replaced, for good.
+        let replaces = user_operations
+            .iter()
+            .any(|user_op| matches!(user_op, UserOperation::Add(_)));
+        let mut delete_targets = Vec::new();
+        for user_op in &user_operations {
+            match user_op {
+                UserOperation::Delete(term) => {
+                    let target = match self.term_delete_target(term.clone())? {
+                        DeleteTarget::Term(term) if replaces => DeleteTarget::Replace(term),
+                        target => target,
+                    };
+                    delete_targets.push(target);
+                }
+                UserOperation::Add(document) => self.validate_document(document)?,
+            }
+        }
+        let mut delete_targets = delete_targets.into_iter();
+        if let Some(unique_keys) = &self.unique_keys {
+            let committed_opstamp = self.segment_updater.load_meta().opstamp;
+            if !unique_keys.check_batch(&user_operations, committed_opstamp)? {
+                return Ok(self.stamper.stamp());
+            }
+        }
         let (batch_opstamp, stamps) = self.get_batch_opstamps(count);
 
         let mut adds = AddBatch::default();
 
-        for (user_op, opstamp) in user_operations_it.zip(stamps) {
+        for (user_op, opstamp) in user_operations.into_iter().zip(stamps) {
             match user_op {
                 UserOperation::Delete(term) => {
-                    let query = TermQuery::new(term, IndexRecordOption::Basic);
-                    let weight =
-                        query.weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
-                    let delete_operation = DeleteOperation {
-                        opstamp,
-                        target: weight,
-                    };
-                    self.delete_queue.push(delete_operation);
+                    if let Some(unique_keys) = &self.unique_keys {
+                        unique_keys.delete_term(&term, opstamp);
+                    }
+                    if let Some(in_memory_segment) = &self.in_memory_segment {
+                        in_memory_segment.delete_term(&term);
+                    }
+                    let target = delete_targets
+                        .next()
+                        .expect("Each delete of the batch has a target.");
+                    self.delete_queue.push(DeleteOperation { opstamp, target });
                 }
                 UserOperation::Add(document) => {
-                    let add_operation = AddOperation { opstamp, document };
+                    if let Some(unique_keys) = &self.unique_keys {
+                        unique_keys.add_key(&document, opstamp);
+                    }
+                    self.add_to_in_memory_segment(&document, 1.0)?;
+                    let add_operation = AddOperation {
+                        opstamp,
+                        document,
+                        boost: 1.0,
+                    };
                     adds.push(add_operation);
                 }
             }
@@ -779,6 +1620,19 @@ impl<D: Document> IndexWriter<D> {
         Ok(batch_opstamp)
     }
 
+    /// Replaces all of the documents containing `term` by `document`.
+    ///
+    /// The delete and the add are run as a single group of operations
+    /// (see `IndexWriter.run`): they are part of the same commit, so readers
+    /// either see the previous documents or the new one, never both or none. If the document
+    /// is invalid, or its unique key is rejected or skipped, nothing is deleted.
+    ///
+    /// `term` is typically the unique id of the document, and is expected to
+    /// be one of the terms of `document`.
+    pub fn update_document(&self, term: Term, document: D) -> crate::Result<Opstamp> {
+        self.run([UserOperation::Delete(term), UserOperation::Add(document)])
+    }
+
     fn send_add_documents_batch(&self, add_ops: AddBatch<D>) -> crate::Result<()> {
         if self.index_writer_status.is_alive() && self.operation_sender.send(add_ops).is_ok() {
             Ok(())
@@ -809,21 +1663,21 @@ mod tests {
     use proptest::strategy::Strategy;
 
     use super::super::operation::UserOperation;
-    use crate::collector::TopDocs;
+    use crate::collector::{Count, TopDocs};
     use crate::directory::error::LockError;
     use crate::error::*;
     use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
-    use crate::indexer::NoMergePolicy;
+    use crate::indexer::{DuplicateKeyPolicy, IndexWriterOptions, LogMergePolicy, NoMergePolicy};
     use crate::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
     use crate::schema::document::Value;
     use crate::schema::{
-        self, Facet, FacetOptions, IndexRecordOption, IpAddrOptions, NumericOptions, Schema,
-        TextFieldIndexing, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
+        self, Facet, FacetOptions, IndexRecordOption, IpAddrOptions, NumericOptions, OwnedValue,
+        Schema, TextFieldIndexing, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
     };
     use crate::store::DOCSTORE_CACHE_CAPACITY;
     use crate::{
-        DateTime, DocAddress, Index, IndexSettings, IndexSortByField, IndexWriter, Order,
-        ReloadPolicy, TantivyDocument, Term,
+        CancelToken, DateTime, DocAddress, Index, IndexSettings, IndexSortByField, IndexWriter,
+        Order, ReloadPolicy, TantivyDocument, Term,
     };
 
     const LOREM: &str = "Doc Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
@@ -849,6 +1703,33 @@ mod tests {
         assert_eq!(batch_opstamp1, 2u64);
     }
 
+    #[test]
+    fn test_update_document() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id_field => "1", text_field => "old"))?;
+        index_writer.add_document(doc!(id_field => "2", text_field => "other"))?;
+        index_writer.commit()?;
+
+        let id_term = Term::from_field_text(id_field, "1");
+        index_writer.update_document(id_term, doc!(id_field => "1", text_field => "new"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        let count = |text: &str| {
+            let term = Term::from_field_text(text_field, text);
+            searcher.search(&TermQuery::new(term, IndexRecordOption::Basic), &Count)
+        };
+        assert_eq!(count("old")?, 0);
+        assert_eq!(count("new")?, 1);
+        assert_eq!(count("other")?, 1);
+        Ok(())
+    }
+
     #[test]
     fn test_no_need_to_rewrite_delete_file_if_no_new_deletes() {
         let mut schema_builder = schema::Schema::builder();
@@ -981,11 +1862,118 @@ mod tests {
     }
 
     #[test]
-    fn test_set_merge_policy() {
-        let schema_builder = schema::Schema::builder();
+    fn test_writer_validate_documents() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_field(
+            schema::FieldEntry::new_u64("id".to_string(), INDEXED.into()).set_required(),
+        );
         let index = Index::create_in_ram(schema_builder.build());
-        let index_writer: IndexWriter = index.writer_for_tests().unwrap();
-        assert_eq!(
+        let mut index_writer: IndexWriter =
+            index.writer_with_options(IndexWriterOptions::default().validate_documents(true))?;
+        index_writer.add_document(doc!(id_field => 1u64))?;
+        let result = index_writer.add_document(doc!());
+        assert!(
+            matches!(result, Err(TantivyError::InvalidDocument(violations)) if violations.len() == 1)
+        );
+        let result = index_writer.run([UserOperation::Add(doc!(id_field => "2"))]);
+        assert!(matches!(result, Err(TantivyError::InvalidDocument(_))));
+        index_writer.commit()?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_document_rejected() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_field(
+            schema::FieldEntry::new_u64("id".to_string(), INDEXED.into()).set_required(),
+        );
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_with_options(
+            IndexWriterOptions::default()
+                .validate_documents(true)
+                .unique_key(id_field, DuplicateKeyPolicy::Reject),
+        )?;
+        index_writer.add_document(doc!(id_field => 1u64, text_field => "old"))?;
+        index_writer.add_document(doc!(id_field => 2u64, text_field => "other"))?;
+        index_writer.commit()?;
+
+        // Neither an invalid document, nor a duplicate key, deletes the previous document.
+        let id_term = Term::from_field_u64(id_field, 1);
+        let result = index_writer.update_document(id_term.clone(), doc!(text_field => "new"));
+        assert!(matches!(result, Err(TantivyError::InvalidDocument(_))));
+        let result = index_writer
+            .update_document(id_term.clone(), doc!(id_field => 2u64, text_field => "new"));
+        assert!(matches!(result, Err(TantivyError::DuplicateKey(_))));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        let query = TermQuery::new(id_term, IndexRecordOption::Basic);
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_document_with_boost() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a b c d e f g h"))?;
+        index_writer.add_document_with_boost(doc!(text_field => "a b c d e f g h"), 2.0)?;
+        index_writer.add_document_with_boost(doc!(text_field => "a b c d e f g h"), 0.5)?;
+        assert!(index_writer
+            .add_document_with_boost(doc!(text_field => "a"), 0.0)
+            .is_err());
+        assert!(index_writer
+            .add_document_with_boost(doc!(text_field => "a"), f32::NAN)
+            .is_err());
+        // On single-token fields, the boost only has an effect once it rounds the fieldnorm
+        // down to 0.
+        index_writer.add_document(doc!(title_field => "a"))?;
+        index_writer.add_document_with_boost(doc!(title_field => "a"), 1.5)?;
+        index_writer.add_document_with_boost(doc!(title_field => "a"), 4.0)?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        let doc_ids: Vec<u32> = top_docs
+            .iter()
+            .map(|(_, doc_address)| doc_address.doc_id)
+            .collect();
+        assert_eq!(doc_ids, vec![1, 0, 2]);
+        assert!(top_docs[0].0 > top_docs[1].0);
+        assert!(top_docs[1].0 > top_docs[2].0);
+
+        let title_query = TermQuery::new(
+            Term::from_field_text(title_field, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let scores: Vec<f32> = (3..6)
+            .map(|doc_id| {
+                title_query
+                    .explain(&searcher, DocAddress::new(0, doc_id))
+                    .unwrap()
+                    .value()
+            })
+            .collect();
+        assert_eq!(scores[0], scores[1]);
+        assert!(scores[2] > scores[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_merge_policy() {
+        let schema_builder = schema::Schema::builder();
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer: IndexWriter = index.writer_for_tests().unwrap();
+        assert_eq!(
             format!("{:?}", index_writer.get_merge_policy()),
             "LogMergePolicy { min_num_segments: 8, max_docs_before_merge: 10000000, \
              min_layer_size: 10000, level_log_size: 0.75, del_docs_ratio_before_merge: 1.0 }"
@@ -998,6 +1986,271 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_writer_with_options() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        assert!(matches!(
+            index.writer_with_options::<TantivyDocument>(
+                IndexWriterOptions::default().num_merge_threads(0)
+            ),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        let mut index_writer: IndexWriter =
+            index.writer_with_options(IndexWriterOptions::default().num_merge_threads(1))?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0..4u64 {
+            index_writer.add_document(doc!(id_field => id))?;
+            index_writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        // Merges get queued when the merge threads are busy.
+        let first_merge = index_writer.merge(&segment_ids[..2]);
+        let second_merge = index_writer.merge(&segment_ids[2..]);
+        assert!(first_merge.wait()?.is_some());
+        assert!(second_merge.wait()?.is_some());
+        assert_eq!(index.searchable_segment_ids()?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_memory_searcher() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        assert!(matches!(
+            index_writer.in_memory_searcher(),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        drop(index_writer);
+
+        let mut index_writer: IndexWriter =
+            index.writer_with_options(IndexWriterOptions::default().in_memory_segment(true))?;
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "hello"),
+            IndexRecordOption::Basic,
+        );
+        index_writer.add_document(doc!(text_field => "hello world"))?;
+        let searcher = index_writer.in_memory_searcher()?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        assert_eq!(top_docs.len(), 1);
+        let doc: TantivyDocument = searcher.doc(top_docs[0].1)?;
+        assert_eq!(
+            doc.get_first(text_field).and_then(|value| value.as_str()),
+            Some("hello world")
+        );
+
+        index_writer.commit()?;
+        reader.reload()?;
+        index_writer.add_document(doc!(text_field => "hello again"))?;
+        assert_eq!(index_writer.in_memory_searcher()?.num_docs(), 1);
+        let realtime_searcher = index_writer.realtime_searcher(&reader)?;
+        assert_eq!(realtime_searcher.num_docs(), 2);
+        assert_eq!(realtime_searcher.search(&query, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_memory_searcher_deletes() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer: IndexWriter =
+            index.writer_with_options(IndexWriterOptions::default().in_memory_segment(true))?;
+        let count = |text: &str| -> crate::Result<usize> {
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            index_writer.in_memory_searcher()?.search(&query, &Count)
+        };
+        index_writer.add_document(doc!(id_field => 1u64, text_field => "first"))?;
+        index_writer.add_document(doc!(id_field => 2u64, text_field => "first"))?;
+        assert_eq!(count("first")?, 2);
+        index_writer.update_document(
+            Term::from_field_u64(id_field, 1),
+            doc!(id_field => 1u64, text_field => "second"),
+        )?;
+        assert_eq!(count("first")?, 1);
+        assert_eq!(count("second")?, 1);
+        index_writer.delete_term(Term::from_field_u64(id_field, 2));
+        assert_eq!(count("first")?, 0);
+        assert_eq!(index_writer.in_memory_searcher()?.num_docs(), 1);
+        assert!(index_writer.memory_usage().in_memory_segment > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_realtime_searcher_deletes_committed_documents() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer: IndexWriter =
+            index.writer_with_options(IndexWriterOptions::default().in_memory_segment(true))?;
+        let count = |index_writer: &IndexWriter, text: &str| -> crate::Result<usize> {
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            index_writer
+                .realtime_searcher(&reader)?
+                .search(&query, &Count)
+        };
+        index_writer.add_document(doc!(id_field => 1u64, text_field => "first"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+
+        // The committed version of the updated document is hidden.
+        index_writer.update_document(
+            Term::from_field_u64(id_field, 1),
+            doc!(id_field => 1u64, text_field => "second"),
+        )?;
+        assert_eq!(count(&index_writer, "first")?, 0);
+        assert_eq!(count(&index_writer, "second")?, 1);
+        assert_eq!(index_writer.realtime_searcher(&reader)?.num_docs(), 1);
+
+        // Same for the documents of a commit the reader was not reloaded with.
+        index_writer.add_document(doc!(id_field => 2u64, text_field => "first"))?;
+        index_writer.commit()?;
+        assert_eq!(count(&index_writer, "first")?, 1);
+        index_writer.delete_term(Term::from_field_u64(id_field, 2));
+        assert_eq!(count(&index_writer, "first")?, 0);
+        assert_eq!(index_writer.realtime_searcher(&reader)?.num_docs(), 1);
+
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert_eq!(count(&index_writer, "first")?, 0);
+        assert_eq!(count(&index_writer, "second")?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_realtime_searcher_during_commit() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer: IndexWriter =
+            index.writer_with_options(IndexWriterOptions::default().in_memory_segment(true))?;
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "hello"),
+            IndexRecordOption::Basic,
+        );
+        index_writer.add_document(doc!(text_field => "hello"))?;
+        assert_eq!(
+            index_writer
+                .realtime_searcher(&reader)?
+                .search(&query, &Count)?,
+            1
+        );
+
+        // The document stays visible, exactly once, while it is being committed and until the
+        // reader is reloaded.
+        let commit_future = index_writer.prepare_commit()?.commit_future();
+        assert_eq!(
+            index_writer
+                .realtime_searcher(&reader)?
+                .search(&query, &Count)?,
+            1
+        );
+        commit_future.wait()?;
+        assert_eq!(reader.searcher().num_docs(), 0);
+        assert_eq!(
+            index_writer
+                .realtime_searcher(&reader)?
+                .search(&query, &Count)?,
+            1
+        );
+        index_writer.add_document(doc!(text_field => "hello again"))?;
+        assert_eq!(
+            index_writer
+                .realtime_searcher(&reader)?
+                .search(&query, &Count)?,
+            2
+        );
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        let realtime_searcher = index_writer.realtime_searcher(&reader)?;
+        assert_eq!(realtime_searcher.num_docs(), 2);
+        assert_eq!(realtime_searcher.search(&query, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_with_cancel_token() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0..2u64 {
+            index_writer.add_document(doc!(id_field => id))?;
+            index_writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        let cancel_token = CancelToken::new();
+        cancel_token.cancel();
+        assert!(matches!(
+            index_writer
+                .merge_with_cancel_token(&segment_ids, cancel_token)
+                .wait(),
+            Err(TantivyError::Cancelled)
+        ));
+        assert_eq!(index.searchable_segment_ids()?.len(), 2);
+        // The segments can still be merged.
+        assert!(index_writer.merge(&segment_ids).wait()?.is_some());
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_simulate_merges() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0..3u64 {
+            index_writer.add_document(doc!(id_field => id))?;
+            index_writer.add_document(doc!(id_field => id + 10))?;
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(Term::from_field_u64(id_field, 10));
+        index_writer.commit()?;
+        assert!(index_writer.simulate_merges()?.is_empty());
+
+        let mut log_merge_policy = LogMergePolicy::default();
+        log_merge_policy.set_min_num_segments(3);
+        index_writer.set_merge_policy(Box::new(log_merge_policy));
+        let merge_simulations = index_writer.simulate_merges()?;
+        assert_eq!(merge_simulations.len(), 1);
+        let merge_simulation = &merge_simulations[0];
+        assert_eq!(merge_simulation.segment_metas.len(), 3);
+        assert!(merge_simulation.is_committed);
+        assert_eq!(merge_simulation.num_docs, 5);
+        assert_eq!(merge_simulation.num_deleted_docs, 1);
+        assert!(merge_simulation.num_bytes > 0);
+        // Nothing got merged.
+        assert_eq!(index.searchable_segment_ids()?.len(), 3);
+        Ok(())
+    }
+
     #[test]
     fn test_lockfile_released_on_drop() {
         let schema_builder = schema::Schema::builder();
@@ -1514,6 +2767,417 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_expunge_deletes() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment_ord in 0u64..3u64 {
+            for id in 0u64..10u64 {
+                index_writer.add_document(doc!(id_field => segment_ord * 10 + id))?;
+            }
+            index_writer.commit()?;
+        }
+        // Deletes half of the first segment, and a tenth of the second one.
+        for id in [0u64, 1, 2, 3, 4, 10] {
+            index_writer.delete_term(Term::from_field_u64(id_field, id));
+        }
+        index_writer.commit()?;
+
+        let merges = index_writer.expunge_deletes(0.2);
+        assert_eq!(merges.len(), 1);
+        for merge in merges {
+            let segment_meta = merge.wait()?.unwrap();
+            assert_eq!(segment_meta.max_doc(), 5);
+            assert_eq!(segment_meta.num_deleted_docs(), 0);
+        }
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), 3);
+        assert_eq!(
+            segment_metas.iter().map(|meta| meta.max_doc()).sum::<u32>(),
+            25
+        );
+        assert_eq!(
+            segment_metas
+                .iter()
+                .map(|meta| meta.num_docs())
+                .sum::<u32>(),
+            24
+        );
+        assert!(index_writer.expunge_deletes(0.2).is_empty());
+        assert_eq!(index_writer.expunge_deletes(0.0).len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_undelete_term() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let num_docs =
+            |index: &Index| -> crate::Result<u64> { Ok(index.reader()?.searcher().num_docs()) };
+        {
+            let index_writer: IndexWriter = index.writer_for_tests()?;
+            assert!(index_writer
+                .undelete_term(Term::from_field_u64(id_field, 0))
+                .is_err());
+        }
+        let mut index_writer: IndexWriter =
+            index.writer_with_options(IndexWriterOptions::default().soft_deletes(true))?;
+        for id in 0u64..3u64 {
+            index_writer.add_document(doc!(id_field => id))?;
+        }
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(id_field, 1));
+        index_writer.delete_term(Term::from_field_u64(id_field, 2));
+        index_writer.commit()?;
+        assert_eq!(num_docs(&index)?, 1);
+
+        index_writer.undelete_term(Term::from_field_u64(id_field, 1))?;
+        index_writer.commit()?;
+        assert_eq!(num_docs(&index)?, 2);
+
+        // Undeletes also restore the documents of the ongoing commit.
+        index_writer.add_document(doc!(id_field => 3u64))?;
+        index_writer.delete_term(Term::from_field_u64(id_field, 3));
+        index_writer.undelete_term(Term::from_field_u64(id_field, 3))?;
+        index_writer.delete_term(Term::from_field_u64(id_field, 0));
+        index_writer.commit()?;
+        assert_eq!(num_docs(&index)?, 2);
+
+        // Segments with deletes are merged, and the merged segment keeps the deleted
+        // documents, as the undelete window has no limit.
+        let mut merge_policy = LogMergePolicy::default();
+        merge_policy.set_min_num_segments(2);
+        index_writer.set_merge_policy(Box::new(merge_policy));
+        assert_eq!(index.searchable_segment_ids()?.len(), 2);
+        assert_eq!(index_writer.simulate_merges()?.len(), 1);
+        let segment_ids = index.searchable_segment_ids()?;
+        let merged_segment_meta = index_writer.merge(&segment_ids).wait()?.unwrap();
+        assert_eq!(merged_segment_meta.max_doc(), 4);
+        assert_eq!(merged_segment_meta.num_deleted_docs(), 2);
+        assert_eq!(num_docs(&index)?, 2);
+        index_writer.undelete_term(Term::from_field_u64(id_field, 2))?;
+        index_writer.commit()?;
+        assert_eq!(num_docs(&index)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_undelete_term_only_restores_term_deletes() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let version_field = schema_builder.add_u64_field("version", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let count_term = |field, value: u64| -> crate::Result<usize> {
+            let query =
+                TermQuery::new(Term::from_field_u64(field, value), IndexRecordOption::Basic);
+            index.reader()?.searcher().search(&query, &Count)
+        };
+        let mut index_writer: IndexWriter =
+            index.writer_with_options(IndexWriterOptions::default().soft_deletes(true))?;
+        for id in 0u64..3u64 {
+            index_writer.add_document(doc!(id_field => id, version_field => 1u64))?;
+        }
+        index_writer.commit()?;
+        index_writer.update_document(
+            Term::from_field_u64(id_field, 0),
+            doc!(id_field => 0u64, version_field => 2u64),
+        )?;
+        index_writer.delete_query(Box::new(TermQuery::new(
+            Term::from_field_u64(id_field, 1),
+            IndexRecordOption::Basic,
+        )))?;
+        index_writer.delete_term(Term::from_field_u64(id_field, 1));
+        index_writer.delete_term(Term::from_field_u64(id_field, 2));
+        index_writer.commit()?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+
+        // The replaced document, and the document deleted by a query, are not restored.
+        for id in 0u64..3u64 {
+            index_writer.undelete_term(Term::from_field_u64(id_field, id))?;
+        }
+        index_writer.commit()?;
+        assert_eq!(count_term(id_field, 0)?, 1);
+        assert_eq!(count_term(version_field, 2)?, 1);
+        assert_eq!(count_term(id_field, 1)?, 0);
+        assert_eq!(count_term(id_field, 2)?, 1);
+
+        // Same within a commit.
+        index_writer.add_document(doc!(id_field => 3u64, version_field => 1u64))?;
+        index_writer.update_document(
+            Term::from_field_u64(id_field, 3),
+            doc!(id_field => 3u64, version_field => 3u64),
+        )?;
+        index_writer.undelete_term(Term::from_field_u64(id_field, 3))?;
+        index_writer.commit()?;
+        assert_eq!(count_term(id_field, 3)?, 1);
+        assert_eq!(count_term(version_field, 3)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_soft_deletes_undelete_window() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let num_docs =
+            |index: &Index| -> crate::Result<u64> { Ok(index.reader()?.searcher().num_docs()) };
+        let options = IndexWriterOptions::default()
+            .soft_deletes(true)
+            .undelete_window(2);
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0u64..4u64 {
+            index_writer.add_document(doc!(id_field => id))?;
+        }
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id_field => 4u64))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(id_field, 0));
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(id_field, 1));
+        index_writer.commit()?;
+        let segment_metas = index.searchable_segment_metas()?;
+        let segment_meta = segment_metas
+            .iter()
+            .find(|segment_meta| segment_meta.max_doc() == 4)
+            .unwrap();
+        assert_eq!(segment_meta.previous_delete_opstamps().len(), 1);
+
+        // The merge targets the last commit, two operations after the previous one: only the
+        // delete of the last commit is within the undelete window, and the other deleted
+        // document is purged.
+        let segment_ids = index.searchable_segment_ids()?;
+        let merged_segment_meta = index_writer.merge(&segment_ids).wait()?.unwrap();
+        assert_eq!(merged_segment_meta.max_doc(), 4);
+        assert_eq!(merged_segment_meta.num_deleted_docs(), 1);
+        assert!(merged_segment_meta.previous_delete_opstamps().is_empty());
+        assert_eq!(num_docs(&index)?, 3);
+
+        // The merge policy does not rewrite a segment whose deletes cannot be purged yet.
+        let mut merge_policy = LogMergePolicy::default();
+        merge_policy.set_del_docs_ratio_before_merge(0.1);
+        index_writer.set_merge_policy(Box::new(merge_policy.clone()));
+        assert!(index_writer.simulate_merges()?.is_empty());
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.delete_term(Term::from_field_u64(id_field, 100));
+        index_writer.commit()?;
+        index_writer.set_merge_policy(Box::new(merge_policy));
+        assert_eq!(index_writer.simulate_merges()?.len(), 1);
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+
+        index_writer.undelete_term(Term::from_field_u64(id_field, 0))?;
+        index_writer.undelete_term(Term::from_field_u64(id_field, 1))?;
+        index_writer.commit()?;
+        assert_eq!(num_docs(&index)?, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_fast_field() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let popularity_field = schema_builder.add_i64_field("popularity", FAST);
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let popularities = |index: &Index| -> crate::Result<Vec<(u64, i64)>> {
+            let searcher = index.reader()?.searcher();
+            let mut popularities = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let ids = segment_reader.fast_fields().u64("id")?;
+                let popularity = segment_reader.fast_fields().i64("popularity")?;
+                for doc in segment_reader.doc_ids_alive() {
+                    popularities.push((ids.first(doc).unwrap(), popularity.first(doc).unwrap()));
+                }
+            }
+            popularities.sort();
+            Ok(popularities)
+        };
+        for id in 0u64..3u64 {
+            index_writer.add_document(doc!(id_field => id, popularity_field => 0i64))?;
+        }
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id_field => 3u64, popularity_field => 0i64))?;
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 1),
+            popularity_field,
+            OwnedValue::I64(10),
+        )?;
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 3),
+            popularity_field,
+            OwnedValue::I64(-3),
+        )?;
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 1),
+            popularity_field,
+            OwnedValue::I64(11),
+        )?;
+        assert!(index_writer
+            .update_fast_field(
+                Term::from_field_u64(id_field, 1),
+                popularity_field,
+                OwnedValue::U64(11),
+            )
+            .is_err());
+        assert!(index_writer
+            .update_fast_field(
+                Term::from_field_u64(id_field, 1),
+                title_field,
+                OwnedValue::Str("title".to_string()),
+            )
+            .is_err());
+        index_writer.commit()?;
+        assert_eq!(
+            popularities(&index)?,
+            vec![(0, 0), (1, 11), (2, 0), (3, -3)]
+        );
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), 1);
+        assert!(!segment_metas[0].has_fast_field_updates());
+        assert_eq!(
+            popularities(&index)?,
+            vec![(0, 0), (1, 11), (2, 0), (3, -3)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_updates_are_refused_by_format_version_6() -> crate::Result<()> {
+        use crate::core::{SegmentComponent, META_FILEPATH};
+        use crate::directory::error::Incompatibility;
+        use crate::directory::{Directory, Footer, RamDirectory};
+        use crate::Version;
+
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let directory = RamDirectory::create();
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id_field => 1u64))?;
+        index_writer.commit()?;
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 1),
+            id_field,
+            OwnedValue::U64(2),
+        )?;
+        index_writer.commit()?;
+
+        let meta_json = directory.atomic_read(&META_FILEPATH)?;
+        let meta_value: serde_json::Value = serde_json::from_slice(&meta_json).unwrap();
+        let meta_version: Version = serde_json::from_value(meta_value["version"].clone()).unwrap();
+        let segment_metas = index.searchable_segment_metas()?;
+        assert!(segment_metas[0].has_fast_field_updates());
+        let updates_path = segment_metas[0].relative_path(SegmentComponent::FastFieldUpdates);
+        let (updates_footer, _) = Footer::extract_footer(directory.open_read(&updates_path)?)?;
+        for version in [meta_version, updates_footer.version] {
+            assert_eq!(version.index_format_version, 7);
+            assert!(version.check_compatibility().is_ok());
+            assert!(matches!(
+                version.check_compatibility_with(4..=6),
+                Err(Incompatibility::IndexMismatch { .. })
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_widely_spaced_terms() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_reader = index.reader()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for id in 0..10_000 {
+            index_writer.add_document(doc!(id_field => format!("id{id:05}")))?;
+        }
+        index_writer.commit()?;
+
+        // The first and last terms of the dictionary, and a missing one in between.
+        for id in ["id00000", "id09999", "id05000x"] {
+            index_writer.delete_term(Term::from_field_text(id_field, id));
+        }
+        index_writer.commit()?;
+        index_reader.reload()?;
+        let searcher = index_reader.searcher();
+        assert_eq!(searcher.num_docs(), 9_998);
+        let count = |id: &str| {
+            let query = TermQuery::new(
+                Term::from_field_text(id_field, id),
+                IndexRecordOption::Basic,
+            );
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count("id00000")?, 0);
+        assert_eq!(count("id00001")?, 1);
+        assert_eq!(count("id05000")?, 1);
+        assert_eq!(count("id09999")?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_many_terms() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_reader = index.reader()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for id in 0u64..100u64 {
+            let tag = if id % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(id_field => id, tag_field => tag))?;
+        }
+        index_writer.commit()?;
+
+        // Deletes ids 10..20 and 50, with duplicates, in an unsorted order.
+        for id in (10u64..20u64).rev().chain([50, 15, 1_000]) {
+            index_writer.delete_term(Term::from_field_u64(id_field, id));
+        }
+        // Re-added after its delete, the document 15 survives.
+        index_writer.add_document(doc!(id_field => 15u64, tag_field => "odd"))?;
+        index_writer.commit()?;
+        index_reader.reload()?;
+        assert_eq!(index_reader.searcher().num_docs(), 90);
+
+        index_writer.delete_term(Term::from_field_text(tag_field, "odd"));
+        index_writer.add_document(doc!(id_field => 11u64, tag_field => "odd"))?;
+        let even_query = TermQuery::new(
+            Term::from_field_text(tag_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        index_writer.delete_term(Term::from_field_u64(id_field, 60));
+        index_writer.delete_query(Box::new(even_query))?;
+        index_writer.add_document(doc!(id_field => 60u64, tag_field => "even"))?;
+        index_writer.commit()?;
+        index_reader.reload()?;
+
+        let searcher = index_reader.searcher();
+        let mut alive_ids: Vec<u64> = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let id_reader = segment_reader.fast_fields().u64("id")?;
+            alive_ids.extend(
+                segment_reader
+                    .doc_ids_alive()
+                    .flat_map(|doc| id_reader.values_for_doc(doc)),
+            );
+        }
+        alive_ids.sort_unstable();
+        assert_eq!(alive_ids, vec![11, 60]);
+        Ok(())
+    }
+
     #[test]
     fn test_delete_query_with_sort_by_field() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -0,0 +1,111 @@
+use std::io::BufRead;
+
+use crate::schema::{DocParsingError, TantivyDocument};
+use crate::{IndexWriter, Opstamp};
+
+/// Outcome of [`IndexWriter::ingest_json_lines`].
+#[derive(Debug, Default, PartialEq)]
+pub struct IngestReport {
+    /// Number of documents added to the index writer.
+    pub num_added_docs: u64,
+    /// The lines that could not be parsed into a document, with their (1-based) line number.
+    pub errors: Vec<(usize, DocParsingError)>,
+    /// Opstamp of the last added document, if any.
+    pub last_opstamp: Option<Opstamp>,
+}
+
+impl IngestReport {
+    /// Returns true if all of the non-empty lines were added.
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl IndexWriter<TantivyDocument> {
+    /// Adds the documents read from `reader`, expected to hold one JSON document per line.
+    ///
+    /// Lines that cannot be parsed into a document of the index schema are skipped, and
+    /// reported in the returned [`IngestReport`] together with their line number. Empty lines
+    /// are ignored. This makes it possible to load a large, possibly dirty, dataset in a single
+    /// call.
+    ///
+    /// As for [`IndexWriter::add_document`], the documents only become visible after a commit.
+    ///
+    /// An error is returned, and ingestion stops, if reading from `reader` fails or if the
+    /// indexing pipeline is broken.
+    pub fn ingest_json_lines<R: BufRead>(&self, mut reader: R) -> crate::Result<IngestReport> {
+        let schema = self.index().schema();
+        let mut report = IngestReport::default();
+        let mut line = Vec::new();
+        let mut line_number = 0;
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            line_number += 1;
+            let doc_res = match std::str::from_utf8(&line) {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => TantivyDocument::parse_json(&schema, line),
+                Err(err) => Err(DocParsingError::invalid_json(
+                    &String::from_utf8_lossy(&line),
+                    err.valid_up_to(),
+                )),
+            };
+            match doc_res {
+                Ok(doc) => {
+                    report.last_opstamp = Some(self.add_document(doc)?);
+                    report.num_added_docs += 1;
+                }
+                Err(err) => report.errors.push((line_number, err)),
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{DocParsingError, Schema, STORED, TEXT};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_ingest_json_lines() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_u64_field("year", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+
+        let json_lines: &[u8] = b"{\"title\": \"Of Mice and Men\", \"year\": 1937}\n\
+            {\"title\": \"Frankenstein\", \"year\": \"1818\"}\n\
+            \n\
+            not json\n\
+            {\"title\": \"The Old Man and the Sea\"}\n\
+            {\"title\": \"\xff\"}";
+        let report = index_writer.ingest_json_lines(json_lines)?;
+        assert_eq!(report.num_added_docs, 2);
+        assert!(!report.is_success());
+        let error_lines: Vec<usize> = report.errors.iter().map(|(line, _)| *line).collect();
+        assert_eq!(error_lines, [2, 4, 6]);
+        assert!(matches!(
+            report.errors[0].1,
+            DocParsingError::ValueError { ref field, .. } if field == "year"
+        ));
+        assert!(matches!(
+            report.errors[1].1,
+            DocParsingError::InvalidJson { .. }
+        ));
+        assert!(matches!(
+            report.errors[2].1,
+            DocParsingError::InvalidJson { .. }
+        ));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+        Ok(())
+    }
+}
@@ -7,6 +7,25 @@ use crate::core::{SegmentId, SegmentMeta};
 #[derive(Debug, Clone)]
 pub struct MergeCandidate(pub Vec<SegmentId>);
 
+/// A merge the merge policy would start, as returned by
+/// [`IndexWriter::simulate_merges()`](crate::IndexWriter::simulate_merges).
+#[derive(Debug, Clone)]
+pub struct MergeSimulation {
+    /// The segments that would be merged together.
+    pub segment_metas: Vec<SegmentMeta>,
+    /// True if the segments are committed, false if they wait for the next commit.
+    pub is_committed: bool,
+    /// Number of documents of the merged segment, i.e. the number of alive documents of the
+    /// merged segments.
+    pub num_docs: u32,
+    /// Number of deleted documents the merge would get rid of.
+    pub num_deleted_docs: u32,
+    /// Total size of the files of the merged segments, in bytes.
+    ///
+    /// The merged segment is usually of a similar size, minus the deleted documents.
+    pub num_bytes: u64,
+}
+
 /// The `MergePolicy` defines which segments should be merged.
 ///
 /// Every time the list of segments changes, the segment updater
@@ -8,7 +8,7 @@ use common::ReadOnlyBitSet;
 use itertools::Itertools;
 use measure_time::debug_time;
 
-use crate::core::{Segment, SegmentReader};
+use crate::core::{CancelToken, Segment, SegmentId, SegmentReader};
 use crate::directory::WritePtr;
 use crate::docset::{DocSet, TERMINATED};
 use crate::error::DataCorruption;
@@ -20,8 +20,10 @@ use crate::postings::{InvertedIndexSerializer, Postings, SegmentPostings};
 use crate::schema::{value_type_to_column_type, Field, FieldType, Schema};
 use crate::store::StoreWriter;
 use crate::termdict::{TermMerger, TermOrdinal};
+use crate::termvector::{fields_with_term_vectors, TermVectorsSerializer};
+use crate::time::OffsetDateTime;
 use crate::{
-    DocAddress, DocId, IndexSettings, IndexSortByField, InvertedIndexReader, Order,
+    DateTime, DocAddress, DocId, IndexSettings, IndexSortByField, InvertedIndexReader, Order,
     SegmentComponent, SegmentOrdinal,
 };
 
@@ -85,6 +87,7 @@ pub struct IndexMerger {
     schema: Schema,
     pub(crate) readers: Vec<SegmentReader>,
     max_doc: u32,
+    cancel_token: CancelToken,
 }
 
 struct DeltaComputer {
@@ -178,10 +181,15 @@ impl IndexMerger {
         alive_bitset_opt: Vec<Option<AliveBitSet>>,
     ) -> crate::Result<IndexMerger> {
         let mut readers = vec![];
+        // Expired documents are dropped by the merge.
+        let expiry = index_settings
+            .expiry_field
+            .as_deref()
+            .map(|expiry_field| (expiry_field, DateTime::from_utc(OffsetDateTime::now_utc())));
         for (segment, new_alive_bitset_opt) in segments.iter().zip(alive_bitset_opt) {
             if segment.meta().num_docs() > 0 {
                 let reader =
-                    SegmentReader::open_with_custom_alive_set(segment, new_alive_bitset_opt)?;
+                    SegmentReader::open_excluding_expired(segment, new_alive_bitset_opt, expiry)?;
                 readers.push(reader);
             }
         }
@@ -203,9 +211,17 @@ impl IndexMerger {
             schema,
             readers,
             max_doc,
+            cancel_token: CancelToken::default(),
         })
     }
 
+    /// Makes the merge stop with a [`crate::TantivyError::Cancelled`] error once `cancel_token`
+    /// gets cancelled.
+    pub(crate) fn with_cancel_token(mut self, cancel_token: CancelToken) -> IndexMerger {
+        self.cancel_token = cancel_token;
+        self
+    }
+
     fn sort_readers_by_min_sort_field(
         readers: Vec<SegmentReader>,
         sort_by_field: &IndexSortByField,
@@ -255,6 +271,23 @@ impl IndexMerger {
         Ok(())
     }
 
+    fn write_term_vectors(
+        &self,
+        term_vectors_serializer: &mut TermVectorsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        if fields_with_term_vectors(&self.schema).is_empty() {
+            return Ok(());
+        }
+        for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+            let term_vectors_reader =
+                self.readers[old_doc_addr.segment_ord as usize].term_vectors();
+            term_vectors_serializer
+                .add_document_bytes(term_vectors_reader.doc_bytes(old_doc_addr.doc_id))?;
+        }
+        Ok(())
+    }
+
     fn write_fast_fields(
         &self,
         fast_field_wrt: &mut WritePtr,
@@ -268,10 +301,17 @@ impl IndexMerger {
             .map(|reader| reader.fast_fields().columnar())
             .collect();
         let merge_row_order = convert_to_merge_order(&columnars[..], doc_id_mapping);
-        columnar::merge_columnar(
+        // The fast field updates of the segments are folded into the merged fast fields.
+        columnar::merge_columnar_with_overrides(
             &columnars[..],
             &required_columns,
             merge_row_order,
+            &|segment_ord, column_name, column| {
+                self.readers[segment_ord]
+                    .fast_fields()
+                    .updates()
+                    .apply_to_dynamic_column(column_name, column)
+            },
             fast_field_wrt,
         )?;
         Ok(())
@@ -519,6 +559,7 @@ impl IndexMerger {
         let mut doc_id_and_positions = vec![];
 
         while merged_terms.advance() {
+            self.cancel_token.check()?;
             segment_postings_containing_the_term.clear();
             let term_bytes: &[u8] = merged_terms.key();
 
@@ -695,6 +736,7 @@ impl IndexMerger {
                 .collect();
 
             for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                self.cancel_token.check()?;
                 let doc_bytes_it = &mut document_iterators[old_doc_addr.segment_ord as usize];
                 if let Some(doc_bytes_res) = doc_bytes_it.next() {
                     let doc_bytes = doc_bytes_res?;
@@ -729,6 +771,7 @@ impl IndexMerger {
                     || store_reader.decompressor() != store_writer.compressor().into()
                 {
                     for doc_bytes_res in store_reader.iter_raw(reader.alive_bitset()) {
+                        self.cancel_token.check()?;
                         let doc_bytes = doc_bytes_res?;
                         store_writer.store_bytes(&doc_bytes)?;
                     }
@@ -745,19 +788,36 @@ impl IndexMerger {
     ///
     /// # Returns
     /// The number of documents in the resulting segment.
-    pub fn write(&self, mut serializer: SegmentSerializer) -> crate::Result<u32> {
-        let doc_id_mapping = if let Some(sort_by_field) = self.index_settings.sort_by_field.as_ref()
-        {
+    fn doc_id_mapping(&self) -> crate::Result<SegmentDocIdMapping> {
+        if let Some(sort_by_field) = self.index_settings.sort_by_field.as_ref() {
             // If the documents are already sorted and stackable, we ignore the mapping and execute
             // it as if there was no sorting
             if self.is_disjunct_and_sorted_on_sort_property(sort_by_field)? {
-                self.get_doc_id_from_concatenated_data()?
+                self.get_doc_id_from_concatenated_data()
             } else {
-                self.generate_doc_id_mapping_with_sort_by_field(sort_by_field)?
+                self.generate_doc_id_mapping_with_sort_by_field(sort_by_field)
             }
         } else {
-            self.get_doc_id_from_concatenated_data()?
-        };
+            self.get_doc_id_from_concatenated_data()
+        }
+    }
+
+    /// Returns the segment and the doc id in that segment of each document of the merged
+    /// segment, in the order of the merged segment.
+    pub(crate) fn merged_doc_addresses(&self) -> crate::Result<Vec<(SegmentId, DocId)>> {
+        let doc_id_mapping = self.doc_id_mapping()?;
+        Ok(doc_id_mapping
+            .iter_old_doc_addrs()
+            .map(|doc_addr| {
+                let segment_reader = &self.readers[doc_addr.segment_ord as usize];
+                (segment_reader.segment_id(), doc_addr.doc_id)
+            })
+            .collect())
+    }
+
+    pub fn write(&self, mut serializer: SegmentSerializer) -> crate::Result<u32> {
+        let doc_id_mapping = self.doc_id_mapping()?;
+        self.cancel_token.check()?;
         debug!("write-fieldnorms");
         if let Some(fieldnorms_serializer) = serializer.extract_fieldnorms_serializer() {
             self.write_fieldnorms(fieldnorms_serializer, &doc_id_mapping)?;
@@ -773,8 +833,13 @@ impl IndexMerger {
             &doc_id_mapping,
         )?;
 
+        self.cancel_token.check()?;
         debug!("write-storagefields");
         self.write_storable_fields(serializer.get_store_writer(), &doc_id_mapping)?;
+        self.cancel_token.check()?;
+        debug!("write-termvectors");
+        self.write_term_vectors(serializer.get_term_vectors_serializer(), &doc_id_mapping)?;
+        self.cancel_token.check()?;
         debug!("write-fastfields");
         self.write_fast_fields(serializer.get_fast_field_write(), doc_id_mapping)?;
 
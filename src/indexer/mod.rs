@@ -4,14 +4,17 @@
 //! `IndexWriter` is the main entry point for that, which created from
 //! [`Index::writer`](crate::Index::writer).
 
+mod delete_generations;
 pub(crate) mod delete_queue;
 pub(crate) mod path_to_unordered_id;
 
 pub(crate) mod doc_id_mapping;
 mod doc_opstamp_mapping;
 mod flat_map_with_buffer;
+mod in_memory_segment;
 pub(crate) mod index_writer;
 pub(crate) mod index_writer_status;
+mod json_lines;
 mod log_merge_policy;
 mod merge_operation;
 pub(crate) mod merge_policy;
@@ -19,6 +22,7 @@ pub(crate) mod merger;
 mod merger_sorted_index_test;
 pub(crate) mod operation;
 pub(crate) mod prepared_commit;
+mod segment_attributes;
 mod segment_entry;
 mod segment_manager;
 mod segment_register;
@@ -26,20 +30,24 @@ pub(crate) mod segment_serializer;
 pub(crate) mod segment_updater;
 pub(crate) mod segment_writer;
 mod stamper;
+mod unique_key;
 
 use crossbeam_channel as channel;
 use smallvec::SmallVec;
 
-pub use self::index_writer::IndexWriter;
+pub use self::index_writer::{IndexWriter, IndexWriterMemoryUsage, IndexWriterOptions};
+pub use self::json_lines::IngestReport;
 pub use self::log_merge_policy::LogMergePolicy;
 pub use self::merge_operation::MergeOperation;
-pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
+pub use self::merge_policy::{MergeCandidate, MergePolicy, MergeSimulation, NoMergePolicy};
 pub use self::operation::UserOperation;
 pub use self::prepared_commit::PreparedCommit;
+pub use self::segment_attributes::SegmentAttributesProvider;
 pub use self::segment_entry::SegmentEntry;
 pub(crate) use self::segment_serializer::SegmentSerializer;
 pub use self::segment_updater::{merge_filtered_segments, merge_indices};
 pub use self::segment_writer::SegmentWriter;
+pub use self::unique_key::DuplicateKeyPolicy;
 use crate::indexer::operation::AddOperation;
 
 /// Alias for the default merge policy, which is the `LogMergePolicy`.
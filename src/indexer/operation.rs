@@ -1,19 +1,55 @@
 use crate::query::Weight;
 use crate::schema::document::Document;
 use crate::schema::{TantivyDocument, Term};
-use crate::Opstamp;
+use crate::{Opstamp, Score};
 
 /// Timestamped Delete operation.
 pub struct DeleteOperation {
     pub opstamp: Opstamp,
-    pub target: Box<dyn Weight>,
+    pub target: DeleteTarget,
+}
+
+/// The documents targeted by a delete operation.
+pub enum DeleteTarget {
+    /// The documents containing a term of an indexed field.
+    ///
+    /// Term deletes are applied in batches, sorted so that each term is looked up once in the
+    /// term dictionary of the field.
+    Term(Term),
+    /// The documents containing a term, deleted by a batch of operations adding their
+    /// replacements, like `update_document`.
+    ///
+    /// They are applied like the deletes of [`DeleteTarget::Term`], but cannot be undone by an
+    /// undelete.
+    Replace(Term),
+    /// The documents matching a query.
+    Query(Box<dyn Weight>),
+    /// Not a delete: restores the deleted documents containing a term of an indexed field.
+    ///
+    /// Only the documents deleted by a [`DeleteTarget::Term`] of the same term, and by no other
+    /// delete, are restored, as long as they have not been purged by a merge yet.
+    Undelete(Term),
+    /// Not a delete: sets the value of a fast field of the documents containing a term.
+    ///
+    /// Fast field updates go through the delete queue so that they are applied in order with
+    /// deletes, and replayed on the segments produced by merges.
+    UpdateFastField {
+        /// The term identifying the updated documents.
+        term: Term,
+        /// The name of the updated fast field column.
+        column_name: String,
+        /// The new value, in the `u64` representation of the column.
+        value: u64,
+    },
 }
 
 /// Timestamped Add operation.
-#[derive(Eq, PartialEq, Debug)]
+#[derive(PartialEq, Debug)]
 pub struct AddOperation<D: Document = TantivyDocument> {
     pub opstamp: Opstamp,
     pub document: D,
+    /// The boost of the document, `1.0` if it is not boosted.
+    pub boost: Score,
 }
 
 /// UserOperation is an enum type that encapsulates other operation types.
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use crate::core::Segment;
+use crate::SegmentReader;
+
+/// Computes the custom attributes of the segments written by an
+/// [`IndexWriter`](crate::IndexWriter).
+///
+/// The attributes are an opaque key-value map persisted along with the segment in
+/// `meta.json`, and exposed by [`SegmentMeta::attributes()`](crate::SegmentMeta::attributes)
+/// and [`SegmentReader::segment_attributes()`]. They typically summarize the content of the
+/// segment, e.g. the minimum and maximum timestamp of its documents, so that whole segments
+/// can be skipped at search time.
+///
+/// The provider is called with a reader over each segment right after it gets flushed or
+/// merged. The attributes are not updated as documents get deleted, so they describe all of
+/// the documents written in the segment.
+pub trait SegmentAttributesProvider: Send + Sync + 'static {
+    /// Returns the attributes of a newly written segment.
+    fn segment_attributes(
+        &self,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<BTreeMap<String, String>>;
+}
+
+impl<F> SegmentAttributesProvider for F
+where F: Fn(&SegmentReader) -> crate::Result<BTreeMap<String, String>> + Send + Sync + 'static
+{
+    fn segment_attributes(
+        &self,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<BTreeMap<String, String>> {
+        self(segment_reader)
+    }
+}
+
+/// Returns `segment` with the attributes computed by `provider`, if any.
+pub(crate) fn with_segment_attributes(
+    segment: Segment,
+    provider: Option<&dyn SegmentAttributesProvider>,
+) -> crate::Result<Segment> {
+    let Some(provider) = provider else {
+        return Ok(segment);
+    };
+    let attributes = provider.segment_attributes(&SegmentReader::open(&segment)?)?;
+    Ok(segment.with_attributes(attributes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, FAST};
+    use crate::{Index, IndexWriter, SegmentReader};
+
+    fn timestamp_range(segment_reader: &SegmentReader) -> crate::Result<BTreeMap<String, String>> {
+        let column = segment_reader.fast_fields().u64("timestamp")?;
+        Ok(BTreeMap::from([
+            ("min".to_string(), column.min_value().to_string()),
+            ("max".to_string(), column.max_value().to_string()),
+        ]))
+    }
+
+    #[test]
+    fn test_segment_attributes() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_u64_field("timestamp", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.set_segment_attributes_provider(Box::new(timestamp_range));
+        index_writer.add_document(doc!(timestamp => 10u64))?;
+        index_writer.add_document(doc!(timestamp => 20u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(timestamp => 30u64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let mut ranges: Vec<(String, String)> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| {
+                let attributes = segment_reader.segment_attributes();
+                (attributes["min"].clone(), attributes["max"].clone())
+            })
+            .collect();
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            [
+                ("10".to_string(), "20".to_string()),
+                ("30".to_string(), "30".to_string())
+            ]
+        );
+
+        // The attributes are persisted, and computed again for merged segments.
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        let segment_metas = index.load_metas()?.segments;
+        assert_eq!(segment_metas.len(), 1);
+        assert_eq!(
+            segment_metas[0].attributes(),
+            &BTreeMap::from([
+                ("min".to_string(), "10".to_string()),
+                ("max".to_string(), "30".to_string()),
+            ])
+        );
+        Ok(())
+    }
+}
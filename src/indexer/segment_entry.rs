@@ -2,7 +2,7 @@ use std::fmt;
 
 use common::BitSet;
 
-use crate::core::{SegmentId, SegmentMeta};
+use crate::core::{RestorableDelete, SegmentId, SegmentMeta};
 use crate::indexer::delete_queue::DeleteCursor;
 
 /// A segment entry describes the state of
@@ -13,6 +13,8 @@ use crate::indexer::delete_queue::DeleteCursor;
 /// - `alive_bitset` is a bitset describing
 /// documents that were alive during the commit
 /// itself.
+/// - `restorable_deletes` are the documents of `alive_bitset`
+/// that an undelete can restore.
 /// - `delete_cursor` is the position in the delete queue.
 /// Deletes happening before the cursor are reflected either
 /// in the .del file or in the `alive_bitset`.
@@ -20,6 +22,7 @@ use crate::indexer::delete_queue::DeleteCursor;
 pub struct SegmentEntry {
     meta: SegmentMeta,
     alive_bitset: Option<BitSet>,
+    restorable_deletes: Vec<RestorableDelete>,
     delete_cursor: DeleteCursor,
 }
 
@@ -33,10 +36,22 @@ impl SegmentEntry {
         SegmentEntry {
             meta: segment_meta,
             alive_bitset,
+            restorable_deletes: Vec::new(),
             delete_cursor,
         }
     }
 
+    /// Sets the deleted documents of the in-memory alive bitset that an undelete can restore.
+    pub(crate) fn set_restorable_deletes(&mut self, restorable_deletes: Vec<RestorableDelete>) {
+        self.restorable_deletes = restorable_deletes;
+    }
+
+    /// Returns the deleted documents of the in-memory alive bitset that an undelete can
+    /// restore.
+    pub(crate) fn restorable_deletes(&self) -> &[RestorableDelete] {
+        &self.restorable_deletes
+    }
+
     /// Return a reference to the segment entry deleted bitset.
     ///
     /// `DocId` in this bitset are flagged as deleted.
@@ -44,6 +59,12 @@ impl SegmentEntry {
         self.alive_bitset.as_ref()
     }
 
+    /// Drops the in-memory alive bitset, once its deletes are written in the delete file.
+    pub fn clear_alive_bitset(&mut self) {
+        self.alive_bitset = None;
+        self.restorable_deletes.clear();
+    }
+
     /// Set the `SegmentMeta` for this segment.
     pub fn set_meta(&mut self, segment_meta: SegmentMeta) {
         self.meta = segment_meta;
@@ -7,6 +7,7 @@ use crate::core::{SegmentId, SegmentMeta};
 use crate::error::TantivyError;
 use crate::indexer::delete_queue::DeleteCursor;
 use crate::indexer::SegmentEntry;
+use crate::Opstamp;
 
 #[derive(Default)]
 struct SegmentRegisters {
@@ -113,14 +114,20 @@ impl SegmentManager {
             .expect("Failed to acquire write lock on SegmentManager.")
     }
 
-    /// Deletes all empty segments
-    fn remove_empty_segments(&self) {
+    /// Deletes the empty segments whose documents were all deleted up to `purge_opstamp`.
+    fn remove_empty_segments(&self, purge_opstamp: Option<Opstamp>) {
+        let Some(purge_opstamp) = purge_opstamp else {
+            return;
+        };
         let mut registers_lock = self.write();
         registers_lock
             .committed
             .segment_entries()
             .iter()
-            .filter(|segment| segment.meta().num_docs() == 0)
+            .filter(|segment| {
+                segment.meta().num_docs() == 0
+                    && segment.meta().delete_opstamp().unwrap_or(0) <= purge_opstamp
+            })
             .for_each(|segment| {
                 registers_lock
                     .committed
@@ -214,8 +221,12 @@ impl SegmentManager {
         Ok(segments_status)
     }
 
-    pub fn committed_segment_metas(&self) -> Vec<SegmentMeta> {
-        self.remove_empty_segments();
+    /// Returns the metas of the committed segments, once the empty ones are deleted.
+    ///
+    /// With soft deletes, the empty segments with documents deleted after `purge_opstamp` are
+    /// kept, as their documents can still be restored.
+    pub fn committed_segment_metas(&self, purge_opstamp: Option<Opstamp>) -> Vec<SegmentMeta> {
+        self.remove_empty_segments(purge_opstamp);
         let registers_lock = self.read();
         registers_lock.committed.segment_metas()
     }
@@ -5,6 +5,7 @@ use crate::directory::WritePtr;
 use crate::fieldnorm::FieldNormsSerializer;
 use crate::postings::InvertedIndexSerializer;
 use crate::store::StoreWriter;
+use crate::termvector::TermVectorsSerializer;
 
 /// Segment serializer is in charge of laying out on disk
 /// the data accumulated and sorted by the `SegmentWriter`.
@@ -14,6 +15,7 @@ pub struct SegmentSerializer {
     fast_field_write: WritePtr,
     fieldnorms_serializer: Option<FieldNormsSerializer>,
     postings_serializer: InvertedIndexSerializer,
+    term_vectors_serializer: TermVectorsSerializer,
 }
 
 impl SegmentSerializer {
@@ -54,12 +56,16 @@ impl SegmentSerializer {
         let fieldnorms_serializer = FieldNormsSerializer::from_write(fieldnorms_write)?;
 
         let postings_serializer = InvertedIndexSerializer::open(&mut segment)?;
+
+        let term_vectors_write = segment.open_write(SegmentComponent::TermVectors)?;
+        let term_vectors_serializer = TermVectorsSerializer::from_write(term_vectors_write);
         Ok(SegmentSerializer {
             segment,
             store_writer,
             fast_field_write,
             fieldnorms_serializer: Some(fieldnorms_serializer),
             postings_serializer,
+            term_vectors_serializer,
         })
     }
 
@@ -86,6 +92,11 @@ impl SegmentSerializer {
         &mut self.fast_field_write
     }
 
+    /// Accessor to the `TermVectorsSerializer`.
+    pub fn get_term_vectors_serializer(&mut self) -> &mut TermVectorsSerializer {
+        &mut self.term_vectors_serializer
+    }
+
     /// Extract the field norm serializer.
     ///
     /// Note the fieldnorms serializer can only be extracted once.
@@ -105,6 +116,7 @@ impl SegmentSerializer {
         }
         self.fast_field_write.terminate()?;
         self.postings_serializer.close()?;
+        self.term_vectors_serializer.close()?;
         self.store_writer.close()?;
         Ok(())
     }
@@ -1,5 +1,5 @@
 use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::ops::Deref;
 use std::path::PathBuf;
@@ -10,23 +10,26 @@ use rayon::{ThreadPool, ThreadPoolBuilder};
 
 use super::segment_manager::SegmentManager;
 use crate::core::{
-    Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta, META_FILEPATH,
+    metrics, CancelToken, Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta,
+    META_FILEPATH,
 };
 use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult};
 use crate::fastfield::AliveBitSet;
+use crate::indexer::delete_generations::{
+    has_purgeable_deletes, write_merged_deletes, DeleteGenerations,
+};
 use crate::indexer::delete_queue::DeleteCursor;
 use crate::indexer::index_writer::advance_deletes;
 use crate::indexer::merge_operation::MergeOperationInventory;
 use crate::indexer::merger::IndexMerger;
+use crate::indexer::segment_attributes::{with_segment_attributes, SegmentAttributesProvider};
 use crate::indexer::segment_manager::SegmentsStatus;
 use crate::indexer::stamper::Stamper;
 use crate::indexer::{
-    DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, SegmentEntry,
+    DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, MergeSimulation, SegmentEntry,
     SegmentSerializer,
 };
-use crate::{FutureResult, Opstamp};
-
-const NUM_MERGE_THREADS: usize = 4;
+use crate::{FutureResult, Opstamp, TantivyError};
 
 /// Save the index meta file.
 /// This operation is atomic:
@@ -86,26 +89,46 @@ fn garbage_collect_files(
 
 /// Merges a list of segments the list of segment givens in the `segment_entries`.
 /// This function happens in the calling thread and is computationally expensive.
+///
+/// With soft deletes, `undelete_window` is set, and the merge keeps the documents deleted in
+/// the last `undelete_window` operations, as deleted documents of the merged segment.
 fn merge(
     index: &Index,
     mut segment_entries: Vec<SegmentEntry>,
     target_opstamp: Opstamp,
+    undelete_window: Option<Opstamp>,
+    cancel_token: CancelToken,
+    segment_attributes_provider: Option<&dyn SegmentAttributesProvider>,
 ) -> crate::Result<Option<SegmentEntry>> {
     let num_docs = segment_entries
         .iter()
         .map(|segment| segment.meta().num_docs() as u64)
         .sum::<u64>();
-    if num_docs == 0 {
+    if num_docs == 0 && undelete_window.is_none() {
         return Ok(None);
     }
 
+    #[cfg(feature = "tracing")]
+    let span = tracing::info_span!(
+        "merge",
+        num_segments = segment_entries.len(),
+        num_docs,
+        num_merged_docs = tracing::field::Empty,
+    )
+    .entered();
+
     // first we need to apply deletes to our segment.
     let merged_segment = index.new_segment();
 
     // First we apply all of the delete to the merged segment, up to the target opstamp.
     for segment_entry in &mut segment_entries {
         let segment = index.segment(segment_entry.meta().clone());
-        advance_deletes(segment, segment_entry, target_opstamp)?;
+        advance_deletes(
+            segment,
+            segment_entry,
+            target_opstamp,
+            undelete_window.is_some(),
+        )?;
     }
 
     let delete_cursor = segment_entries[0].delete_cursor().clone();
@@ -116,20 +139,79 @@ fn merge(
         .collect();
 
     // An IndexMerger is like a "view" of our merged segments.
-    let merger: IndexMerger =
-        IndexMerger::open(index.schema(), index.settings().clone(), &segments[..])?;
+    let (merger, soft_deletes) = if let Some(undelete_window) = undelete_window {
+        // The merged segments are read without their deletes, and the merger only drops the
+        // documents deleted before the undelete window.
+        let purge_opstamp = target_opstamp.checked_sub(undelete_window);
+        let mut delete_generations = HashMap::new();
+        let mut kept_docs = Vec::new();
+        for segment in &segments {
+            let segment_delete_generations = DeleteGenerations::open(segment)?;
+            kept_docs.push(Some(segment_delete_generations.kept_docs(purge_opstamp)));
+            delete_generations.insert(segment.id(), segment_delete_generations);
+        }
+        let num_kept_docs: usize = kept_docs
+            .iter()
+            .flatten()
+            .map(AliveBitSet::num_alive_docs)
+            .sum();
+        if num_kept_docs == 0 {
+            return Ok(None);
+        }
+        let segments_without_deletes: Vec<Segment> = segments
+            .iter()
+            .map(|segment| index.segment(segment.meta().clone().without_deletes()))
+            .collect();
+        let merger = IndexMerger::open_with_custom_alive_set(
+            index.schema(),
+            index.settings().clone(),
+            &segments_without_deletes[..],
+            kept_docs,
+        )?;
+        (merger, Some((delete_generations, purge_opstamp)))
+    } else {
+        let merger = IndexMerger::open(index.schema(), index.settings().clone(), &segments[..])?;
+        (merger, None)
+    };
+    let merger = merger.with_cancel_token(cancel_token);
 
     // ... we just serialize this index merger in our new segment to merge the segments.
     let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone(), true)?;
 
     let num_docs = merger.write(segment_serializer)?;
+    #[cfg(feature = "tracing")]
+    span.record("num_merged_docs", num_docs);
 
     let merged_segment_id = merged_segment.id();
 
     let segment_meta = index.new_segment_meta(merged_segment_id, num_docs);
+    let mut segment = index.segment(segment_meta);
+    if let Some((delete_generations, purge_opstamp)) = soft_deletes {
+        segment = write_merged_deletes(
+            segment,
+            &merger.merged_doc_addresses()?,
+            &delete_generations,
+            purge_opstamp,
+        )?;
+    }
+    let segment_meta = with_segment_attributes(segment, segment_attributes_provider)?
+        .meta()
+        .clone();
+    metrics::incr(&index.metrics_counters().num_merges, 1);
     Ok(Some(SegmentEntry::new(segment_meta, delete_cursor, None)))
 }
 
+/// Returns the total size of the files of a segment.
+fn segment_num_bytes(directory: &dyn Directory, segment_meta: &SegmentMeta) -> crate::Result<u64> {
+    let mut num_bytes = 0;
+    for path in segment_meta.list_files() {
+        if directory.exists(&path)? {
+            num_bytes += directory.open_read(&path)?.num_bytes().get_bytes();
+        }
+    }
+    Ok(num_bytes)
+}
+
 /// Advanced: Merges a list of segments from different indices in a new index.
 ///
 /// Returns `TantivyError` if the indices list is empty or their
@@ -271,9 +353,12 @@ pub(crate) struct InnerSegmentUpdater {
     index: Index,
     segment_manager: SegmentManager,
     merge_policy: RwLock<Arc<dyn MergePolicy>>,
+    segment_attributes_provider: RwLock<Option<Arc<dyn SegmentAttributesProvider>>>,
     killed: AtomicBool,
     stamper: Stamper,
     merge_operations: MergeOperationInventory,
+    // With soft deletes, the number of operations during which deletes can be undone.
+    undelete_window: Option<Opstamp>,
 }
 
 impl SegmentUpdater {
@@ -281,6 +366,8 @@ impl SegmentUpdater {
         index: Index,
         stamper: Stamper,
         delete_cursor: &DeleteCursor,
+        num_merge_threads: usize,
+        undelete_window: Option<Opstamp>,
     ) -> crate::Result<SegmentUpdater> {
         let segments = index.searchable_segment_metas()?;
         let segment_manager = SegmentManager::from_segments(segments, delete_cursor);
@@ -295,7 +382,7 @@ impl SegmentUpdater {
             })?;
         let merge_thread_pool = ThreadPoolBuilder::new()
             .thread_name(|i| format!("merge_thread_{i}"))
-            .num_threads(NUM_MERGE_THREADS)
+            .num_threads(num_merge_threads)
             .build()
             .map_err(|_| {
                 crate::TantivyError::SystemError(
@@ -310,9 +397,11 @@ impl SegmentUpdater {
             index,
             segment_manager,
             merge_policy: RwLock::new(Arc::new(DefaultMergePolicy::default())),
+            segment_attributes_provider: RwLock::new(None),
             killed: AtomicBool::new(false),
             stamper,
             merge_operations: Default::default(),
+            undelete_window,
         })))
     }
 
@@ -325,6 +414,18 @@ impl SegmentUpdater {
         *self.merge_policy.write().unwrap() = arc_merge_policy;
     }
 
+    pub fn get_segment_attributes_provider(&self) -> Option<Arc<dyn SegmentAttributesProvider>> {
+        self.segment_attributes_provider.read().unwrap().clone()
+    }
+
+    pub fn set_segment_attributes_provider(
+        &self,
+        segment_attributes_provider: Box<dyn SegmentAttributesProvider>,
+    ) {
+        *self.segment_attributes_provider.write().unwrap() =
+            Some(Arc::from(segment_attributes_provider));
+    }
+
     fn schedule_task<T: 'static + Send, F: FnOnce() -> crate::Result<T> + 'static + Send>(
         &self,
         task: F,
@@ -372,7 +473,12 @@ impl SegmentUpdater {
         let mut segment_entries = self.segment_manager.segment_entries();
         for segment_entry in &mut segment_entries {
             let segment = self.index.segment(segment_entry.meta().clone());
-            advance_deletes(segment, segment_entry, target_opstamp)?;
+            advance_deletes(
+                segment,
+                segment_entry,
+                target_opstamp,
+                self.undelete_window.is_some(),
+            )?;
         }
         Ok(segment_entries)
     }
@@ -385,7 +491,9 @@ impl SegmentUpdater {
         if self.is_alive() {
             let index = &self.index;
             let directory = index.directory();
-            let mut commited_segment_metas = self.segment_manager.committed_segment_metas();
+            let mut commited_segment_metas = self
+                .segment_manager
+                .committed_segment_metas(self.purge_opstamp(opstamp));
 
             // We sort segment_readers by number of documents.
             // This is an heuristic to make multithreading more efficient.
@@ -442,9 +550,15 @@ impl SegmentUpdater {
     ) -> FutureResult<Opstamp> {
         let segment_updater: SegmentUpdater = self.clone();
         self.schedule_task(move || {
+            #[cfg(feature = "tracing")]
+            let span = tracing::info_span!("commit", opstamp, num_segments = tracing::field::Empty)
+                .entered();
             let segment_entries = segment_updater.purge_deletes(opstamp)?;
+            #[cfg(feature = "tracing")]
+            span.record("num_segments", segment_entries.len());
             segment_updater.segment_manager.commit(segment_entries);
             segment_updater.save_metas(opstamp, payload)?;
+            metrics::incr(&segment_updater.index.metrics_counters().num_commits, 1);
             let _ = garbage_collect_files(segment_updater.clone());
             segment_updater.consider_merge_options();
             Ok(opstamp)
@@ -455,7 +569,7 @@ impl SegmentUpdater {
         *self.active_index_meta.write().unwrap() = Arc::new(index_meta.clone());
     }
 
-    fn load_meta(&self) -> Arc<IndexMeta> {
+    pub(crate) fn load_meta(&self) -> Arc<IndexMeta> {
         self.active_index_meta.read().unwrap().clone()
     }
 
@@ -481,9 +595,12 @@ impl SegmentUpdater {
     // suggested and the moment when it ended up being executed.)
     //
     // `segment_ids` is required to be non-empty.
+    //
+    // The merge stops with a `Cancelled` error once `cancel_token` gets cancelled.
     pub fn start_merge(
         &self,
         merge_operation: MergeOperation,
+        cancel_token: CancelToken,
     ) -> FutureResult<Option<SegmentMeta>> {
         assert!(
             !merge_operation.segment_ids().is_empty(),
@@ -510,6 +627,7 @@ impl SegmentUpdater {
         let (scheduled_result, merging_future_send) =
             FutureResult::create("Merge operation failed.");
 
+        let segment_attributes_provider = self.get_segment_attributes_provider();
         self.merge_thread_pool.spawn(move || {
             // The fact that `merge_operation` is moved here is important.
             // Its lifetime is used to track how many merging thread are currently running,
@@ -519,6 +637,9 @@ impl SegmentUpdater {
                 &segment_updater.index,
                 segment_entries,
                 merge_operation.target_opstamp(),
+                segment_updater.undelete_window,
+                cancel_token,
+                segment_attributes_provider.as_deref(),
             ) {
                 Ok(after_merge_segment_entry) => {
                     let res = segment_updater.end_merge(merge_operation, after_merge_segment_entry);
@@ -530,7 +651,7 @@ impl SegmentUpdater {
                         merge_operation.segment_ids().to_vec(),
                         merge_error
                     );
-                    if cfg!(test) {
+                    if cfg!(test) && !matches!(merge_error, TantivyError::Cancelled) {
                         panic!("{merge_error:?}");
                     }
                     let _send_result = merging_future_send.send(Err(merge_error));
@@ -547,16 +668,89 @@ impl SegmentUpdater {
             .get_mergeable_segments(&merge_segment_ids)
     }
 
+    /// Returns the opstamp up to which the deletes are purged by a merge or a commit at
+    /// `opstamp`, or `None` if none of them are.
+    ///
+    /// With soft deletes, the deletes of the undelete window are kept.
+    fn purge_opstamp(&self, opstamp: Opstamp) -> Option<Opstamp> {
+        match self.undelete_window {
+            Some(undelete_window) => opstamp.checked_sub(undelete_window),
+            None => Some(Opstamp::MAX),
+        }
+    }
+
+    /// Returns the merges suggested by the merge policy for `segment_metas`, targeting
+    /// `opstamp`.
+    ///
+    /// With soft deletes, the merges of a single segment are left out when none of its deleted
+    /// documents would be purged, as the merge would just rewrite the same segment.
+    fn merge_candidates(
+        &self,
+        segment_metas: &[SegmentMeta],
+        opstamp: Opstamp,
+    ) -> Vec<MergeCandidate> {
+        let mut merge_candidates = self
+            .get_merge_policy()
+            .compute_merge_candidates(segment_metas);
+        if self.undelete_window.is_some() {
+            let purge_opstamp = self.purge_opstamp(opstamp);
+            merge_candidates.retain(|merge_candidate| {
+                let [segment_id] = &merge_candidate.0[..] else {
+                    return true;
+                };
+                segment_metas
+                    .iter()
+                    .filter(|segment_meta| segment_meta.id() == *segment_id)
+                    .any(|segment_meta| has_purgeable_deletes(segment_meta, purge_opstamp))
+            });
+        }
+        merge_candidates
+    }
+
+    /// Returns the merges `consider_merge_options` would start, without starting them.
+    pub(crate) fn simulate_merges(&self) -> crate::Result<Vec<MergeSimulation>> {
+        let (committed_segments, uncommitted_segments) = self.get_mergeable_segments();
+        // The next opstamp, without stamping it.
+        let current_opstamp = self.stamper.stamps(0).start;
+        let commit_opstamp = self.load_meta().opstamp;
+        let mut merge_simulations = Vec::new();
+        for (segment_metas, is_committed, opstamp) in [
+            (uncommitted_segments, false, current_opstamp),
+            (committed_segments, true, commit_opstamp),
+        ] {
+            for merge_candidate in self.merge_candidates(&segment_metas, opstamp) {
+                let merged_segment_metas: Vec<SegmentMeta> = segment_metas
+                    .iter()
+                    .filter(|segment_meta| merge_candidate.0.contains(&segment_meta.id()))
+                    .cloned()
+                    .collect();
+                let mut num_bytes = 0;
+                for segment_meta in &merged_segment_metas {
+                    num_bytes += segment_num_bytes(self.index.directory(), segment_meta)?;
+                }
+                merge_simulations.push(MergeSimulation {
+                    num_docs: merged_segment_metas.iter().map(SegmentMeta::num_docs).sum(),
+                    num_deleted_docs: merged_segment_metas
+                        .iter()
+                        .map(SegmentMeta::num_deleted_docs)
+                        .sum(),
+                    num_bytes,
+                    segment_metas: merged_segment_metas,
+                    is_committed,
+                });
+            }
+        }
+        Ok(merge_simulations)
+    }
+
     fn consider_merge_options(&self) {
         let (committed_segments, uncommitted_segments) = self.get_mergeable_segments();
 
         // Committed segments cannot be merged with uncommitted_segments.
         // We therefore consider merges using these two sets of segments independently.
-        let merge_policy = self.get_merge_policy();
-
         let current_opstamp = self.stamper.stamp();
-        let mut merge_candidates: Vec<MergeOperation> = merge_policy
-            .compute_merge_candidates(&uncommitted_segments)
+        let mut merge_candidates: Vec<MergeOperation> = self
+            .merge_candidates(&uncommitted_segments, current_opstamp)
             .into_iter()
             .map(|merge_candidate| {
                 MergeOperation::new(&self.merge_operations, current_opstamp, merge_candidate.0)
@@ -564,8 +758,8 @@ impl SegmentUpdater {
             .collect();
 
         let commit_opstamp = self.load_meta().opstamp;
-        let committed_merge_candidates = merge_policy
-            .compute_merge_candidates(&committed_segments)
+        let committed_merge_candidates = self
+            .merge_candidates(&committed_segments, commit_opstamp)
             .into_iter()
             .map(|merge_candidate: MergeCandidate| {
                 MergeOperation::new(&self.merge_operations, commit_opstamp, merge_candidate.0)
@@ -575,7 +769,7 @@ impl SegmentUpdater {
         for merge_operation in merge_candidates {
             // If a merge cannot be started this is not a fatal error.
             // We do log a warning in `start_merge`.
-            drop(self.start_merge(merge_operation));
+            drop(self.start_merge(merge_operation, CancelToken::default()));
         }
     }
 
@@ -611,6 +805,7 @@ impl SegmentUpdater {
                                 segment,
                                 after_merge_segment_entry,
                                 committed_opstamp,
+                                segment_updater.undelete_window.is_some(),
                             ) {
                                 error!(
                                     "Merge of {:?} was cancelled (advancing deletes failed): {:?}",
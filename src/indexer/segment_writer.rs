@@ -15,8 +15,9 @@ use crate::postings::{
     PerFieldPostingsWriter, PostingsWriter,
 };
 use crate::schema::document::{Document, ReferenceValue, Value};
-use crate::schema::{FieldEntry, FieldType, Schema, Term, DATE_TIME_PRECISION_INDEXED};
-use crate::store::{StoreReader, StoreWriter};
+use crate::schema::{Field, FieldEntry, FieldType, Schema, Term};
+use crate::store::{StoreReader, StoreWriter, StoredValueTransforms};
+use crate::termvector::TermVectorsWriter;
 use crate::tokenizer::{FacetTokenizer, PreTokenizedStream, TextAnalyzer, Tokenizer};
 use crate::{DocId, Opstamp, SegmentComponent, TantivyError};
 
@@ -67,11 +68,16 @@ pub struct SegmentWriter {
     pub(crate) segment_serializer: SegmentSerializer,
     pub(crate) fast_field_writers: FastFieldsWriter,
     pub(crate) fieldnorms_writer: FieldNormsWriter,
+    pub(crate) term_vectors_writer: TermVectorsWriter,
     pub(crate) json_path_writer: JsonPathWriter,
     pub(crate) doc_opstamps: Vec<Opstamp>,
-    per_field_text_analyzers: Vec<TextAnalyzer>,
+    // `None` for the fields that are not indexed.
+    per_field_text_analyzers: Vec<Option<TextAnalyzer>>,
+    // Whether the values of each field are indexed.
+    indexed_fields: Vec<bool>,
     term_buffer: Term,
     schema: Schema,
+    stored_value_transforms: StoredValueTransforms,
 }
 
 impl SegmentWriter {
@@ -88,12 +94,18 @@ impl SegmentWriter {
         let schema = segment.schema();
         let tokenizer_manager = segment.index().tokenizers().clone();
         let tokenizer_manager_fast_field = segment.index().fast_field_tokenizer().clone();
+        let stored_value_transforms = segment.index().stored_value_transforms().clone();
         let table_size = compute_initial_table_size(memory_budget_in_bytes)?;
         let segment_serializer = SegmentSerializer::for_segment(segment, false)?;
         let per_field_postings_writers = PerFieldPostingsWriter::for_schema(&schema);
+        // Only the indexed fields get an analyzer: the values of the other ones, e.g. stored-only
+        // payloads, are never tokenized.
         let per_field_text_analyzers = schema
             .fields()
             .map(|(_, field_entry): (_, &FieldEntry)| {
+                if !field_entry.is_indexed() {
+                    return Ok(None);
+                }
                 let text_options = match field_entry.field_type() {
                     FieldType::Str(ref text_options) => text_options.get_indexing_options(),
                     FieldType::JsonObject(ref json_object_options) => {
@@ -105,19 +117,25 @@ impl SegmentWriter {
                     .map(|text_index_option| text_index_option.tokenizer())
                     .unwrap_or("default");
 
-                tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
+                let text_analyzer = tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
                     TantivyError::SchemaError(format!(
                         "Error getting tokenizer for field: {}",
                         field_entry.name()
                     ))
-                })
+                })?;
+                Ok(Some(text_analyzer))
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<crate::Result<Vec<_>>>()?;
+        let indexed_fields = schema
+            .fields()
+            .map(|(_, field_entry)| field_entry.is_indexed() && !field_entry.is_ignored())
+            .collect();
         Ok(Self {
             max_doc: 0,
             ctx: IndexingContext::new(table_size),
             per_field_postings_writers,
             fieldnorms_writer: FieldNormsWriter::for_schema(&schema),
+            term_vectors_writer: TermVectorsWriter::for_schema(&schema),
             json_path_writer: JsonPathWriter::default(),
             segment_serializer,
             fast_field_writers: FastFieldsWriter::from_schema_and_tokenizer_manager(
@@ -126,8 +144,10 @@ impl SegmentWriter {
             )?,
             doc_opstamps: Vec::with_capacity(1_000),
             per_field_text_analyzers,
+            indexed_fields,
             term_buffer: Term::with_capacity(16),
             schema,
+            stored_value_transforms,
         })
     }
 
@@ -152,6 +172,7 @@ impl SegmentWriter {
             self.ctx,
             self.fast_field_writers,
             &self.fieldnorms_writer,
+            &self.term_vectors_writer,
             self.segment_serializer,
             mapping.as_ref(),
         )?;
@@ -164,16 +185,34 @@ impl SegmentWriter {
     pub fn mem_usage(&self) -> usize {
         self.ctx.mem_usage()
             + self.fieldnorms_writer.mem_usage()
+            + self.term_vectors_writer.mem_usage()
             + self.fast_field_writers.mem_usage()
             + self.segment_serializer.mem_usage()
     }
 
+    /// Returns the part of the memory usage held by the term hashmaps and their arenas.
+    pub(crate) fn term_hashmap_mem_usage(&self) -> usize {
+        self.ctx.mem_usage()
+    }
+
     fn index_document<D: Document>(&mut self, doc: &D) -> crate::Result<()> {
         let doc_id = self.max_doc;
 
-        // TODO: Can this be optimised a bit?
+        // The values of the fields that are not indexed, e.g. stored-only payloads, require
+        // neither tokenization nor postings: they are left aside before being grouped by field,
+        // and the documents without any indexed value are not indexed at all. So are the values
+        // of ignored fields.
+        let indexed_fields = &self.indexed_fields;
+        let is_indexed = |field: Field| indexed_fields[field.field_id() as usize];
+        if !doc
+            .iter_fields_and_values()
+            .any(|(field, _)| is_indexed(field))
+        {
+            return Ok(());
+        }
         let vals_grouped_by_field = doc
             .iter_fields_and_values()
+            .filter(|(field, _)| is_indexed(*field))
             .sorted_by_key(|(field, _)| *field)
             .group_by(|(field, _)| *field);
 
@@ -188,9 +227,6 @@ impl SegmentWriter {
                     field_entry.name()
                 ))
             };
-            if !field_entry.is_indexed() {
-                continue;
-            }
 
             let (term_buffer, ctx) = (&mut self.term_buffer, &mut self.ctx);
             let postings_writer: &mut dyn PostingsWriter =
@@ -217,15 +253,20 @@ impl SegmentWriter {
                         );
                     }
                 }
-                FieldType::Str(_) => {
+                FieldType::Str(text_options) => {
                     let mut indexing_position = IndexingPosition::default();
+                    if let Some(text_indexing) = text_options.get_indexing_options() {
+                        indexing_position.position_gap = text_indexing.position_gap();
+                    }
                     for value_access in values {
                         // Used to help with linting and type checking.
                         let value = value_access as D::Value<'_>;
 
                         let mut token_stream = if let Some(text) = value.as_str() {
-                            let text_analyzer =
-                                &mut self.per_field_text_analyzers[field.field_id() as usize];
+                            let text_analyzer = self.per_field_text_analyzers
+                                [field.field_id() as usize]
+                                .as_mut()
+                                .expect("Indexed fields have a text analyzer.");
                             text_analyzer.token_stream(text)
                         } else if let Some(tok_str) = value.as_pre_tokenized_text() {
                             BoxTokenStream::new(PreTokenizedStream::from(tok_str.clone()))
@@ -262,7 +303,8 @@ impl SegmentWriter {
                         self.fieldnorms_writer.record(doc_id, field, num_vals);
                     }
                 }
-                FieldType::Date(_) => {
+                FieldType::Date(date_options) => {
+                    let indexed_precision = date_options.get_indexed_precision();
                     let mut num_vals = 0;
                     for value_access in values {
                         // Used to help with linting and type checking.
@@ -271,8 +313,7 @@ impl SegmentWriter {
 
                         num_vals += 1;
                         let date_val = value.as_datetime().ok_or_else(make_schema_error)?;
-                        term_buffer
-                            .set_u64(date_val.truncate(DATE_TIME_PRECISION_INDEXED).to_u64());
+                        term_buffer.set_u64(date_val.truncate(indexed_precision).to_u64());
                         postings_writer.subscribe(doc_id, 0u32, term_buffer, ctx);
                     }
                     if field_entry.has_fieldnorms() {
@@ -309,6 +350,21 @@ impl SegmentWriter {
                         self.fieldnorms_writer.record(doc_id, field, num_vals);
                     }
                 }
+                FieldType::ScaledF64(scaled_options) => {
+                    let mut num_vals = 0;
+                    for value_access in values {
+                        // Used to help with linting and type checking.
+                        let value = value_access as D::Value<'_>;
+
+                        num_vals += 1;
+                        let f64_val = value.as_f64().ok_or_else(make_schema_error)?;
+                        term_buffer.set_i64(scaled_options.scale(f64_val));
+                        postings_writer.subscribe(doc_id, 0u32, term_buffer, ctx);
+                    }
+                    if field_entry.has_fieldnorms() {
+                        self.fieldnorms_writer.record(doc_id, field, num_vals);
+                    }
+                }
                 FieldType::Bool(_) => {
                     let mut num_vals = 0;
                     for value_access in values {
@@ -340,8 +396,9 @@ impl SegmentWriter {
                     }
                 }
                 FieldType::JsonObject(json_options) => {
-                    let text_analyzer =
-                        &mut self.per_field_text_analyzers[field.field_id() as usize];
+                    let text_analyzer = self.per_field_text_analyzers[field.field_id() as usize]
+                        .as_mut()
+                        .expect("Indexed fields have a text analyzer.");
                     let json_values_it = values.map(|value_access| {
                         // Used to help with linting and type checking.
                         let value_access = value_access as D::Value<'_>;
@@ -390,12 +447,26 @@ impl SegmentWriter {
         &mut self,
         add_operation: AddOperation<D>,
     ) -> crate::Result<()> {
-        let AddOperation { document, opstamp } = add_operation;
+        let AddOperation {
+            document,
+            opstamp,
+            boost,
+        } = add_operation;
         self.doc_opstamps.push(opstamp);
         self.fast_field_writers.add_document(&document)?;
         self.index_document(&document)?;
+        self.term_vectors_writer
+            .add_document(&document, &mut self.per_field_text_analyzers);
+        if boost != 1.0 {
+            self.fieldnorms_writer.boost_doc(self.max_doc, boost);
+        }
         let doc_writer = self.segment_serializer.get_store_writer();
-        doc_writer.store(&document, &self.schema)?;
+        if self.stored_value_transforms.is_empty() {
+            doc_writer.store(&document, &self.schema)?;
+        } else {
+            let transformed_document = self.stored_value_transforms.before_store(&document);
+            doc_writer.store(&transformed_document, &self.schema)?;
+        }
         self.max_doc += 1;
         Ok(())
     }
@@ -426,12 +497,14 @@ impl SegmentWriter {
 /// to the `SegmentSerializer`.
 ///
 /// `doc_id_map` is used to map to the new doc_id order.
+#[allow(clippy::too_many_arguments)]
 fn remap_and_write(
     schema: Schema,
     per_field_postings_writers: &PerFieldPostingsWriter,
     ctx: IndexingContext,
     fast_field_writers: FastFieldsWriter,
     fieldnorms_writer: &FieldNormsWriter,
+    term_vectors_writer: &TermVectorsWriter,
     mut serializer: SegmentSerializer,
     doc_id_map: Option<&DocIdMapping>,
 ) -> crate::Result<()> {
@@ -453,6 +526,7 @@ fn remap_and_write(
     )?;
     debug!("fastfield-serialize");
     fast_field_writers.serialize(serializer.get_fast_field_write(), doc_id_map)?;
+    term_vectors_writer.serialize(serializer.get_term_vectors_serializer(), doc_id_map)?;
 
     // finalize temp docstore and create version, which reflects the doc_id_map
     if let Some(doc_id_map) = doc_id_map {
@@ -502,8 +576,8 @@ mod tests {
     use crate::query::{PhraseQuery, QueryParser};
     use crate::schema::document::Value;
     use crate::schema::{
-        Document, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Type, STORED, STRING,
-        TEXT,
+        Document, FieldEntry, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Type,
+        FAST, STORED, STRING, TEXT,
     };
     use crate::store::{Compressor, StoreReader, StoreWriter};
     use crate::time::format_description::well_known::Rfc3339;
@@ -1023,6 +1097,83 @@ mod tests {
         assert_eq!(positions, &[4]); //< as opposed to 3 if we had a position length of 1.
     }
 
+    #[test]
+    fn test_stored_only_fields_are_not_indexed() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let payload = schema_builder.add_text_field("payload", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            payload => "first payload",
+            title => "hello",
+            payload => "second payload",
+        ))?;
+        // Documents without any indexed value skip the indexing entirely.
+        index_writer.add_document(doc!(payload => "third payload"))?;
+        index_writer.add_document(doc!(title => "world"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(
+            segment_reader.inverted_index(payload)?.terms().num_terms(),
+            0
+        );
+        assert_eq!(segment_reader.inverted_index(title)?.terms().num_terms(), 2);
+        let fieldnorm_reader = segment_reader.get_fieldnorms_reader(title)?;
+        assert_eq!(fieldnorm_reader.fieldnorm(1), 0);
+        assert_eq!(fieldnorm_reader.fieldnorm(2), 1);
+        let doc: TantivyDocument = searcher.doc(DocAddress::new(0, 1))?;
+        assert_eq!(
+            doc.get_first(payload).and_then(|value| value.as_str()),
+            Some("third payload")
+        );
+        let doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        let payloads: Vec<&str> = doc
+            .get_all(payload)
+            .filter_map(|value| value.as_str())
+            .collect();
+        assert_eq!(payloads, ["first payload", "second payload"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignored_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let legacy = schema_builder.add_field(
+            FieldEntry::new_text("legacy".to_string(), TEXT | STORED | FAST).set_ignored(),
+        );
+        let schema = schema_builder.build();
+        assert!(schema.get_field_entry(legacy).is_ignored());
+        let schema_json = serde_json::to_string(&schema)?;
+        assert!(schema_json.contains(r#""ignored":true"#));
+        assert_eq!(serde_json::from_str::<Schema>(&schema_json)?, schema);
+
+        // Ignored values are skipped by the parsers, and by the writer.
+        let doc = TantivyDocument::parse_json(&schema, r#"{"title": "a", "legacy": ["b", 3]}"#)?;
+        assert_eq!(doc.len(), 1);
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc)?;
+        index_writer.add_document(doc!(title => "c", legacy => "d"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(
+            segment_reader.inverted_index(legacy)?.terms().num_terms(),
+            0
+        );
+        assert!(segment_reader.fast_fields().str("legacy")?.is_none());
+        let doc: TantivyDocument = searcher.doc(DocAddress::new(0, 1))?;
+        assert_eq!(
+            doc.get_first(title).and_then(|value| value.as_str()),
+            Some("c")
+        );
+        assert_eq!(doc.get_first(legacy), None);
+        Ok(())
+    }
+
     #[test]
     fn test_show_error_when_tokenizer_not_registered() {
         let text_field_indexing = TextFieldIndexing::default()
@@ -0,0 +1,503 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use super::operation::UserOperation;
+use crate::schema::document::{Document, Value};
+use crate::schema::{Field, FieldType, IndexRecordOption};
+use crate::{DocSet, Index, IndexReader, Opstamp, ReloadPolicy, TantivyError, Term, TERMINATED};
+
+/// What an [`IndexWriter`](super::IndexWriter) does with the documents whose unique key already
+/// exists in the index, see [`IndexWriterOptions::unique_key()`](super::IndexWriterOptions).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// The document is rejected with a [`TantivyError::DuplicateKey`] error.
+    Reject,
+    /// The document is skipped. The skipped documents are counted by
+    /// [`IndexWriter::num_skipped_duplicates()`](super::IndexWriter::num_skipped_duplicates).
+    Skip,
+}
+
+/// Detects the documents whose unique key already exists in the committed segments, or in the
+/// documents added since the last commit.
+///
+/// The committed segments are checked through a reader, which relies on the bloom filters of
+/// the key field to skip the segments that do not contain the key. The keys added or deleted
+/// since the last commit are tracked with their opstamp, and dropped once the reader sees the
+/// commit that includes them. The lock on these keys is not held while the committed segments
+/// are checked, so that concurrent adds only contend on the in-memory maps.
+pub(crate) struct UniqueKeys {
+    field: Field,
+    policy: DuplicateKeyPolicy,
+    reader: IndexReader,
+    state: Mutex<UniqueKeysState>,
+    num_skipped: AtomicU64,
+}
+
+#[derive(Default)]
+struct UniqueKeysState {
+    added_keys: HashMap<Vec<u8>, Opstamp>,
+    deleted_keys: HashMap<Vec<u8>, Opstamp>,
+    // Set by `delete_all_documents`: the committed segments are ignored until the next commit.
+    ignore_committed: bool,
+    // Opstamp of the commit the reader has to be reloaded for.
+    pending_commit_opstamp: Option<Opstamp>,
+}
+
+impl UniqueKeys {
+    pub(crate) fn open(
+        index: &Index,
+        field: Field,
+        policy: DuplicateKeyPolicy,
+    ) -> crate::Result<UniqueKeys> {
+        let schema = index.schema();
+        let field_entry = schema.get_field_entry(field);
+        let is_valid_key = match field_entry.field_type() {
+            FieldType::Str(text_options) => text_options
+                .get_indexing_options()
+                .map(|indexing_options| indexing_options.tokenizer() == "raw")
+                .unwrap_or(false),
+            FieldType::U64(_) | FieldType::I64(_) => field_entry.is_indexed(),
+            _ => false,
+        };
+        if !is_valid_key {
+            return Err(TantivyError::SchemaError(format!(
+                "The unique key field {:?} has to be an indexed u64, i64 or `raw` tokenized text \
+                 field.",
+                field_entry.name()
+            )));
+        }
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(UniqueKeys {
+            field,
+            policy,
+            reader,
+            state: Mutex::default(),
+            num_skipped: AtomicU64::new(0),
+        })
+    }
+
+    /// Returns the key of `document`, if it has one.
+    fn key<D: Document>(&self, document: &D) -> Option<Term> {
+        let value = document
+            .iter_fields_and_values()
+            .find(|(field, _)| *field == self.field)
+            .map(|(_, value)| value)?;
+        if let Some(text) = value.as_str() {
+            Some(Term::from_field_text(self.field, text))
+        } else if let Some(val) = value.as_u64() {
+            Some(Term::from_field_u64(self.field, val))
+        } else {
+            value
+                .as_i64()
+                .map(|val| Term::from_field_i64(self.field, val))
+        }
+    }
+
+    /// Checks the key of a document added with `opstamp`, `committed_opstamp` being the opstamp
+    /// of the last commit of the writer.
+    ///
+    /// Returns false if the document has to be skipped, and an error if it is rejected.
+    pub(crate) fn check_document<D: Document>(
+        &self,
+        document: &D,
+        opstamp: Opstamp,
+        committed_opstamp: Opstamp,
+    ) -> crate::Result<bool> {
+        let Some(key) = self.key(document) else {
+            return Ok(true);
+        };
+        let key_bytes = key.serialized_value_bytes();
+        // The key is reserved before the committed segments are checked, outside of the lock,
+        // so that the concurrent adds of the same key see it.
+        let check_committed = {
+            let mut state = self.state.lock().unwrap();
+            self.refresh(&mut state, committed_opstamp)?;
+            if state.added_keys.contains_key(key_bytes) {
+                drop(state);
+                return self.duplicate(key, 1);
+            }
+            state.added_keys.insert(key_bytes.to_vec(), opstamp);
+            !state.ignore_committed && !state.deleted_keys.contains_key(key_bytes)
+        };
+        let is_committed = if check_committed {
+            self.is_committed(&key)
+        } else {
+            Ok(false)
+        };
+        if !matches!(is_committed, Ok(false)) {
+            let mut state = self.state.lock().unwrap();
+            if state.added_keys.get(key_bytes) == Some(&opstamp) {
+                state.added_keys.remove(key_bytes);
+            }
+        }
+        if is_committed? {
+            return self.duplicate(key, 1);
+        }
+        Ok(true)
+    }
+
+    /// Checks the keys of the documents added by a batch of operations, before any of them is
+    /// applied, taking the deletes of the batch into account.
+    ///
+    /// Returns false if the batch has to be skipped, because one of its documents is a
+    /// duplicate, and an error if it is rejected. Otherwise, the keys the batch adds are
+    /// reserved until [`UniqueKeys::add_key()`] records them with their opstamp.
+    pub(crate) fn check_batch<D: Document>(
+        &self,
+        operations: &[UserOperation<D>],
+        committed_opstamp: Opstamp,
+    ) -> crate::Result<bool> {
+        let mut batch_added_keys: HashMap<Vec<u8>, Term> = HashMap::new();
+        let mut batch_deleted_keys: HashSet<Vec<u8>> = HashSet::new();
+        // The keys that were not deleted earlier in the batch, which have to be checked against
+        // the documents added before the batch.
+        let mut keys_to_check: Vec<Term> = Vec::new();
+        let num_adds = operations
+            .iter()
+            .filter(|operation| matches!(operation, UserOperation::Add(_)))
+            .count() as u64;
+        for operation in operations {
+            match operation {
+                UserOperation::Delete(term) if term.field() == self.field => {
+                    let key_bytes = term.serialized_value_bytes();
+                    batch_added_keys.remove(key_bytes);
+                    batch_deleted_keys.insert(key_bytes.to_vec());
+                }
+                UserOperation::Delete(_) => {}
+                UserOperation::Add(document) => {
+                    let Some(key) = self.key(document) else {
+                        continue;
+                    };
+                    let key_bytes = key.serialized_value_bytes().to_vec();
+                    if batch_added_keys.contains_key(&key_bytes) {
+                        return self.duplicate(key, num_adds);
+                    }
+                    if !batch_deleted_keys.contains(&key_bytes) {
+                        keys_to_check.push(key.clone());
+                    }
+                    batch_added_keys.insert(key_bytes, key);
+                }
+            }
+        }
+        // Same as `check_document`: the keys are reserved before the committed segments are
+        // checked outside of the lock.
+        let mut keys_to_check_committed = Vec::new();
+        {
+            let mut state = self.state.lock().unwrap();
+            self.refresh(&mut state, committed_opstamp)?;
+            for key in keys_to_check {
+                let key_bytes = key.serialized_value_bytes();
+                if state.added_keys.contains_key(key_bytes) {
+                    drop(state);
+                    return self.duplicate(key, num_adds);
+                }
+                if !state.ignore_committed && !state.deleted_keys.contains_key(key_bytes) {
+                    keys_to_check_committed.push(key);
+                }
+            }
+            for key_bytes in batch_added_keys.keys() {
+                state.added_keys.insert(key_bytes.clone(), Opstamp::MAX);
+            }
+        }
+        for key in keys_to_check_committed {
+            let is_committed = self.is_committed(&key);
+            if !matches!(is_committed, Ok(false)) {
+                let mut state = self.state.lock().unwrap();
+                for key_bytes in batch_added_keys.keys() {
+                    if state.added_keys.get(key_bytes) == Some(&Opstamp::MAX) {
+                        state.added_keys.remove(key_bytes);
+                    }
+                }
+            }
+            if is_committed? {
+                return self.duplicate(key, num_adds);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Records the key of a document added with `opstamp` by a batch accepted by
+    /// [`UniqueKeys::check_batch()`].
+    pub(crate) fn add_key<D: Document>(&self, document: &D, opstamp: Opstamp) {
+        if let Some(key) = self.key(document) {
+            let mut state = self.state.lock().unwrap();
+            state
+                .added_keys
+                .insert(key.serialized_value_bytes().to_vec(), opstamp);
+        }
+    }
+
+    /// Handles a duplicate `key`, found among the keys of `num_docs` documents added together,
+    /// which are all skipped.
+    fn duplicate(&self, key: Term, num_docs: u64) -> crate::Result<bool> {
+        match self.policy {
+            DuplicateKeyPolicy::Reject => Err(TantivyError::DuplicateKey(key)),
+            DuplicateKeyPolicy::Skip => {
+                self.num_skipped.fetch_add(num_docs, Ordering::Relaxed);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Records the delete of a term. Only the deletes of keys are taken into account.
+    pub(crate) fn delete_term(&self, term: &Term, opstamp: Opstamp) {
+        if term.field() != self.field {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        let key_bytes = term.serialized_value_bytes();
+        // The keys added after the delete, or reserved by a batch that is not stamped yet, stay.
+        if state
+            .added_keys
+            .get(key_bytes)
+            .map_or(false, |&added_opstamp| added_opstamp < opstamp)
+        {
+            state.added_keys.remove(key_bytes);
+        }
+        state.deleted_keys.insert(key_bytes.to_vec(), opstamp);
+    }
+
+    /// Records the delete of all of the documents.
+    pub(crate) fn delete_all_documents(&self, committed_opstamp: Opstamp) -> crate::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        // The previous commit must not reset `ignore_committed` later on.
+        self.refresh(&mut state, committed_opstamp)?;
+        state.added_keys.clear();
+        state.deleted_keys.clear();
+        state.ignore_committed = true;
+        Ok(())
+    }
+
+    /// Records a commit, so that the reader gets reloaded once it is done.
+    pub(crate) fn prepare_commit(&self, commit_opstamp: Opstamp) {
+        self.state.lock().unwrap().pending_commit_opstamp = Some(commit_opstamp);
+    }
+
+    /// Drops the keys added or deleted since the last commit, and the pending commit, if any.
+    pub(crate) fn rollback(&self) {
+        *self.state.lock().unwrap() = UniqueKeysState::default();
+    }
+
+    pub(crate) fn num_skipped(&self) -> u64 {
+        self.num_skipped.load(Ordering::Relaxed)
+    }
+
+    /// Reloads the reader if the pending commit is done, and drops the keys it includes.
+    fn refresh(
+        &self,
+        state: &mut UniqueKeysState,
+        committed_opstamp: Opstamp,
+    ) -> crate::Result<()> {
+        let Some(commit_opstamp) = state.pending_commit_opstamp else {
+            return Ok(());
+        };
+        if committed_opstamp < commit_opstamp {
+            return Ok(());
+        }
+        self.reader.reload()?;
+        state
+            .added_keys
+            .retain(|_, opstamp| *opstamp > commit_opstamp);
+        state
+            .deleted_keys
+            .retain(|_, opstamp| *opstamp > commit_opstamp);
+        state.ignore_committed = false;
+        state.pending_commit_opstamp = None;
+        Ok(())
+    }
+
+    /// Returns true if an alive document of the committed segments has the key.
+    fn is_committed(&self, key: &Term) -> crate::Result<bool> {
+        let searcher = self.reader.searcher();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(self.field)?;
+            if !inverted_index.may_contain_term(key) {
+                continue;
+            }
+            let Some(mut postings) = inverted_index.read_postings(key, IndexRecordOption::Basic)?
+            else {
+                continue;
+            };
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if !segment_reader.is_deleted(doc) {
+                    return Ok(true);
+                }
+                doc = postings.advance();
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::DuplicateKeyPolicy;
+    use crate::collector::Count;
+    use crate::indexer::operation::UserOperation;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter, IndexWriterOptions, TantivyError, Term};
+
+    #[test]
+    fn test_unique_key() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let num_docs =
+            |index: &Index| -> crate::Result<u64> { Ok(index.reader()?.searcher().num_docs()) };
+        assert!(index
+            .writer_with_options::<crate::TantivyDocument>(
+                IndexWriterOptions::default().unique_key(text, DuplicateKeyPolicy::Reject)
+            )
+            .is_err());
+
+        let mut index_writer: IndexWriter = index.writer_with_options(
+            IndexWriterOptions::default().unique_key(id, DuplicateKeyPolicy::Reject),
+        )?;
+        index_writer.add_document(doc!(id => "a"))?;
+        assert!(matches!(
+            index_writer.add_document(doc!(id => "a")),
+            Err(TantivyError::DuplicateKey(_))
+        ));
+        index_writer.add_document(doc!(text => "no key"))?;
+        index_writer.add_document(doc!(text => "no key"))?;
+        index_writer.commit()?;
+        assert!(index_writer.add_document(doc!(id => "a")).is_err());
+
+        // Updates delete the key before adding it again.
+        index_writer.run([
+            UserOperation::Delete(Term::from_field_text(id, "a")),
+            UserOperation::Add(doc!(id => "a", text => "updated")),
+        ])?;
+        index_writer.commit()?;
+        assert_eq!(num_docs(&index)?, 3);
+        drop(index_writer);
+
+        let mut index_writer: IndexWriter = index.writer_with_options(
+            IndexWriterOptions::default().unique_key(id, DuplicateKeyPolicy::Skip),
+        )?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        assert_eq!(index_writer.num_skipped_duplicates(), 2);
+        index_writer.commit()?;
+        assert_eq!(num_docs(&index)?, 4);
+
+        index_writer.delete_all_documents()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.commit()?;
+        assert_eq!(num_docs(&index)?, 1);
+        index_writer.add_document(doc!(id => "a"))?;
+        assert_eq!(index_writer.num_skipped_duplicates(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_key_concurrent_adds() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_with_options(
+            IndexWriterOptions::default().unique_key(id, DuplicateKeyPolicy::Skip),
+        )?;
+        for key in 0..50 {
+            index_writer.add_document(doc!(id => key.to_string()))?;
+        }
+        index_writer.commit()?;
+        let index_writer = Arc::new(index_writer);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let index_writer = index_writer.clone();
+                thread::spawn(move || -> crate::Result<()> {
+                    for key in 0..100 {
+                        index_writer.add_document(doc!(id => key.to_string()))?;
+                    }
+                    Ok(())
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap()?;
+        }
+        let Ok(mut index_writer) = Arc::try_unwrap(index_writer) else {
+            panic!("The index writer is still shared.");
+        };
+        assert_eq!(index_writer.num_skipped_duplicates(), 4 * 100 - 50);
+        index_writer.commit()?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 100);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_key_abort() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_with_options(
+            IndexWriterOptions::default().unique_key(id, DuplicateKeyPolicy::Reject),
+        )?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.prepare_commit()?.abort()?;
+        // The key of the aborted commit can be added again, the committed one cannot.
+        index_writer.add_document(doc!(id => "b"))?;
+        assert!(index_writer.add_document(doc!(id => "a")).is_err());
+        index_writer.commit()?;
+        assert!(index_writer.add_document(doc!(id => "b")).is_err());
+        assert_eq!(index.reader()?.searcher().num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_key_batch() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_with_options(
+            IndexWriterOptions::default().unique_key(id, DuplicateKeyPolicy::Skip),
+        )?;
+        index_writer.add_document(doc!(id => "a", text => "first"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+        // A duplicate skips the whole batch, including its deletes.
+        index_writer.run([
+            UserOperation::Delete(Term::from_field_text(id, "a")),
+            UserOperation::Add(doc!(id => "a", text => "second")),
+            UserOperation::Add(doc!(id => "b")),
+        ])?;
+        assert_eq!(index_writer.num_skipped_duplicates(), 2);
+        // Keys can be added twice within a batch if they are deleted in between.
+        index_writer.run([
+            UserOperation::Add(doc!(id => "c", text => "first")),
+            UserOperation::Delete(Term::from_field_text(id, "c")),
+            UserOperation::Add(doc!(id => "c", text => "second")),
+        ])?;
+        assert_eq!(index_writer.num_skipped_duplicates(), 2);
+        index_writer.add_document(doc!(id => "c"))?;
+        assert_eq!(index_writer.num_skipped_duplicates(), 3);
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let count = |text_value: &str| {
+            let query = TermQuery::new(
+                Term::from_field_text(text, text_value),
+                IndexRecordOption::Basic,
+            );
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count("first")?, 1);
+        assert_eq!(count("second")?, 1);
+        Ok(())
+    }
+}
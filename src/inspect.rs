@@ -0,0 +1,148 @@
+//! Low level access to the term dictionaries and posting lists of a segment, for debugging.
+//!
+//! This is the equivalent of Lucene's `CheckIndex` or Luke: it makes it possible to check what
+//! actually got indexed for a field, without going through the query machinery. It is also
+//! available from the command line, through the `inspect` command of the `tantivy` tool.
+//!
+//! The functions work segment by segment, and report the data as it is stored: doc ids are
+//! segment local, and document frequencies include the deleted documents.
+
+use crate::postings::Postings;
+use crate::schema::{Field, IndexRecordOption, Term};
+use crate::{DocId, DocSet, SegmentReader, TERMINATED};
+
+/// A term of a term dictionary, along with the number of documents containing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermEntry {
+    /// The term.
+    pub term: Term,
+    /// Number of documents of the segment containing the term, deleted documents included.
+    pub doc_freq: u32,
+}
+
+/// A document of a posting list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PostingEntry {
+    /// The segment local id of the document.
+    pub doc: DocId,
+    /// True if the document has been deleted.
+    pub is_deleted: bool,
+    /// Number of occurrences of the term in the document, or `1` if the field does not record
+    /// term frequencies.
+    pub term_freq: u32,
+    /// Positions of the term in the document, empty if the field does not record positions.
+    pub positions: Vec<u32>,
+}
+
+/// Returns all of the terms of the term dictionary of `field`, in dictionary order.
+pub fn field_terms(segment_reader: &SegmentReader, field: Field) -> crate::Result<Vec<TermEntry>> {
+    let typ = segment_reader
+        .schema()
+        .get_field_entry(field)
+        .field_type()
+        .value_type();
+    let inverted_index = segment_reader.inverted_index(field)?;
+    let mut term_stream = inverted_index.terms().stream()?;
+    let mut term_entries = Vec::new();
+    while term_stream.advance() {
+        let mut term = Term::with_type_and_field(typ, field);
+        term.append_bytes(term_stream.key());
+        term_entries.push(TermEntry {
+            term,
+            doc_freq: term_stream.value().doc_freq,
+        });
+    }
+    Ok(term_entries)
+}
+
+/// Returns the posting list of `term`, with the term frequencies and positions recorded for its
+/// field. The list is empty if the term is not in the segment.
+pub fn term_postings(
+    segment_reader: &SegmentReader,
+    term: &Term,
+) -> crate::Result<Vec<PostingEntry>> {
+    let inverted_index = segment_reader.inverted_index(term.field())?;
+    let Some(mut postings) =
+        inverted_index.read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
+    else {
+        return Ok(Vec::new());
+    };
+    let mut posting_entries = Vec::new();
+    let mut doc = postings.doc();
+    while doc != TERMINATED {
+        let mut positions = Vec::new();
+        postings.positions(&mut positions);
+        posting_entries.push(PostingEntry {
+            doc,
+            is_deleted: segment_reader.is_deleted(doc),
+            term_freq: postings.term_freq(),
+            positions,
+        });
+        doc = postings.advance();
+    }
+    Ok(posting_entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{Schema, INDEXED, TEXT};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_inspect_terms_and_postings() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let year = schema_builder.add_u64_field("year", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "the old man and the sea", year => 1952u64))?;
+        index_writer.add_document(doc!(title => "the sun also rises", year => 1926u64))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(year, 1926));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let title_terms = field_terms(segment_reader, title)?;
+        let the_entry = TermEntry {
+            term: Term::from_field_text(title, "the"),
+            doc_freq: 2,
+        };
+        assert_eq!(title_terms.len(), 8);
+        assert_eq!(title_terms[7], the_entry);
+        assert_eq!(
+            field_terms(segment_reader, year)?,
+            [
+                TermEntry {
+                    term: Term::from_field_u64(year, 1926),
+                    doc_freq: 1,
+                },
+                TermEntry {
+                    term: Term::from_field_u64(year, 1952),
+                    doc_freq: 1,
+                },
+            ]
+        );
+
+        assert_eq!(
+            term_postings(segment_reader, &the_entry.term)?,
+            [
+                PostingEntry {
+                    doc: 0,
+                    is_deleted: false,
+                    term_freq: 2,
+                    positions: vec![0, 4],
+                },
+                PostingEntry {
+                    doc: 1,
+                    is_deleted: true,
+                    term_freq: 1,
+                    positions: vec![0],
+                },
+            ]
+        );
+        assert!(term_postings(segment_reader, &Term::from_field_text(title, "moon"))?.is_empty());
+        Ok(())
+    }
+}
@@ -145,6 +145,18 @@
 //! 3. **Merging**: To optimize space and search speed, segments might be merged. This operation is
 //!    performed in the background. Customize the merge behaviour via
 //!    [IndexWriter::set_merge_policy].
+//!
+//! # WebAssembly
+//!
+//! Without the default `mmap` feature, tantivy does not touch the filesystem, and compiles to
+//! `wasm32-unknown-unknown`, e.g. with
+//! `cargo build --target wasm32-unknown-unknown --no-default-features --features lz4-compression`.
+//! Indexes then live in a [RamDirectory](directory::RamDirectory), which can be filled with the
+//! files of an existing index (see [Directory::atomic_write]) before opening it with
+//! [Index::open].
+//!
+//! Searching such an index works as usual, as long as the reader is not given warmers. The
+//! [IndexWriter] on the other hand relies on threads, and is not available on this target.
 #[cfg_attr(test, macro_use)]
 extern crate serde_json;
 #[macro_use]
@@ -188,16 +200,23 @@ pub mod aggregation;
 pub mod collector;
 pub mod directory;
 pub mod fastfield;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod fieldnorm;
+pub mod inspect;
 pub mod positions;
 pub mod postings;
 
 /// Module containing the different query implementations.
 pub mod query;
+pub mod replication;
 pub mod schema;
 pub mod space_usage;
 pub mod store;
 pub mod termdict;
+pub mod termvector;
+#[cfg(any(test, feature = "testutil"))]
+pub mod testutil;
 
 mod reader;
 
@@ -221,24 +240,38 @@ pub use self::snippet::{Snippet, SnippetGenerator};
 #[doc(hidden)]
 pub use crate::core::json_utils;
 pub use crate::core::{
-    merge_field_meta_data, Executor, FieldMetadata, Index, IndexBuilder, IndexMeta, IndexSettings,
-    IndexSortByField, InvertedIndexReader, Order, Searcher, SearcherGeneration, Segment,
-    SegmentComponent, SegmentId, SegmentMeta, SegmentReader, SingleSegmentIndexWriter,
+    merge_field_meta_data, CancelToken, Completion, Executor, FieldMetadata, FieldStatistics,
+    Index, IndexBuilder, IndexMeta, IndexMetrics, IndexSettings, IndexSortByField,
+    IndexValidationReport, InvertedIndexReader, MultiSearcher, Order, QueryResultCache, Searcher,
+    SearcherGeneration, SearcherMemoryUsage, Segment, SegmentComponent, SegmentFilter, SegmentId,
+    SegmentMeta, SegmentReader, SegmentValidationReport, ShardedIndex, ShardedIndexReader,
+    ShardedIndexWriter, SingleSegmentIndexWriter, Suggestion, ValidationIssue,
+    VocabularyStatistics,
 };
+#[cfg(feature = "mmap")]
+pub use crate::core::{IndexAlias, IndexAliasReader};
 pub use crate::directory::Directory;
-pub use crate::indexer::IndexWriter;
 #[deprecated(
     since = "0.22.0",
     note = "Will be removed in tantivy 0.23. Use export from indexer module instead"
 )]
-pub use crate::indexer::{merge_filtered_segments, merge_indices, PreparedCommit};
+pub use crate::indexer::{
+    merge_filtered_segments, merge_indices, PreparedCommit, SegmentAttributesProvider,
+};
+pub use crate::indexer::{
+    DuplicateKeyPolicy, IndexWriter, IndexWriterMemoryUsage, IndexWriterOptions, IngestReport,
+};
 pub use crate::postings::Postings;
 #[allow(deprecated)]
 pub use crate::schema::DatePrecision;
 pub use crate::schema::{DateOptions, DateTimePrecision, Document, TantivyDocument, Term};
 
 /// Index format version.
-const INDEX_FORMAT_VERSION: u32 = 6;
+///
+/// Version 7 adds the bloom filters of the `raw` tokenized text fields (sub-index 1 of their
+/// field in the `.term` file), and the fast field updates segment component (`.<opstamp>.upd`
+/// files, referenced by the `fast_field_updates` opstamp of the segments in `meta.json`).
+const INDEX_FORMAT_VERSION: u32 = 7;
 /// Oldest index format version this tantivy version can read.
 const INDEX_FORMAT_OLDEST_SUPPORTED_VERSION: u32 = 4;
 
@@ -264,6 +297,31 @@ static VERSION: Lazy<Version> = Lazy::new(|| Version {
     index_format_version: INDEX_FORMAT_VERSION,
 });
 
+impl Version {
+    /// Checks that an index written with this version can be read by the current version of
+    /// tantivy.
+    pub(crate) fn check_compatibility(
+        &self,
+    ) -> std::result::Result<(), directory::error::Incompatibility> {
+        self.check_compatibility_with(INDEX_FORMAT_OLDEST_SUPPORTED_VERSION..=INDEX_FORMAT_VERSION)
+    }
+
+    /// Checks that an index written with this version can be read by a version of tantivy
+    /// supporting the `supported_index_format_versions`.
+    fn check_compatibility_with(
+        &self,
+        supported_index_format_versions: std::ops::RangeInclusive<u32>,
+    ) -> std::result::Result<(), directory::error::Incompatibility> {
+        if !supported_index_format_versions.contains(&self.index_format_version) {
+            return Err(directory::error::Incompatibility::IndexMismatch {
+                library_version: version().clone(),
+                index_version: self.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
 impl ToString for Version {
     fn to_string(&self) -> String {
         format!(
@@ -347,6 +405,22 @@ pub struct DocAddress {
     pub doc_id: DocId,
 }
 
+/// `StableDocAddress` identifies a document independently of any `Searcher`.
+///
+/// Contrary to [`DocAddress`], which relies on the ordinal of the segment within a
+/// given `Searcher`, it identifies the segment by its [`SegmentId`]. It can therefore be kept
+/// across reloads, and resolved against a newer `Searcher` with
+/// [`Searcher::resolve_doc_address`], which tells whether the document is still visible.
+///
+/// It is obtained via [`Searcher::stable_doc_address`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StableDocAddress {
+    /// The id of the segment hosting the document.
+    pub segment_id: SegmentId,
+    /// The segment-local `DocId`.
+    pub doc_id: DocId,
+}
+
 #[macro_export]
 /// Enable fail_point if feature is enabled.
 macro_rules! fail_point {
@@ -506,6 +580,167 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_total_term_freq() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a b a", id_field=>"x"))?;
+        index_writer.commit()?;
+        for _ in 0..200 {
+            index_writer.add_document(doc!(text_field=>"a a c", id_field=>"x"))?;
+        }
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let term_a = Term::from_field_text(text_field, "a");
+        assert_eq!(searcher.total_term_freq(&term_a)?, 402);
+        let term_b = Term::from_field_text(text_field, "b");
+        assert_eq!(searcher.total_term_freq(&term_b)?, 1);
+        let term_d = Term::from_field_text(text_field, "d");
+        assert_eq!(searcher.total_term_freq(&term_d)?, 0);
+        // `STRING` fields do not record term frequencies.
+        let term_x = Term::from_field_text(id_field, "x");
+        assert_eq!(searcher.total_term_freq(&term_x)?, 201);
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_statistics() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let no_fieldnorms_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_fieldnorms(false));
+        let tag_field = schema_builder.add_text_field("tag", no_fieldnorms_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a b c", title_field=>"t"))?;
+        index_writer.add_document(doc!(text_field=>"a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id_field=>"x"))?;
+        index_writer.add_document(doc!(text_field=>"a b"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let text_statistics = searcher.field_statistics(text_field)?;
+        assert_eq!(text_statistics.num_docs_with_field, 3);
+        assert_eq!(text_statistics.total_num_tokens, 6);
+        assert_eq!(text_statistics.average_field_length(), 2.0);
+        let title_statistics = searcher.field_statistics(title_field)?;
+        assert_eq!(title_statistics.num_docs_with_field, 1);
+        assert_eq!(title_statistics.total_num_tokens, 1);
+        let id_statistics = searcher.field_statistics(id_field)?;
+        assert_eq!(id_statistics.num_docs_with_field, 1);
+        assert_eq!(id_statistics.average_field_length(), 1.0);
+        assert!(searcher.field_statistics(tag_field).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_vocabulary_statistics() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let stored_field = schema_builder.add_text_field("stored", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a b c d"))?;
+        index_writer.add_document(doc!(text_field=>"a b"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"a c"))?;
+        index_writer.add_document(doc!(text_field=>"a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let vocabulary_statistics = searcher.vocabulary_statistics(text_field, 2)?;
+        assert_eq!(vocabulary_statistics.num_unique_terms, 4);
+        // d: 1, b and c: 2, a: 4
+        assert_eq!(vocabulary_statistics.doc_freq_histogram, vec![1, 2, 1]);
+        assert_eq!(
+            vocabulary_statistics.top_terms,
+            vec![
+                (Term::from_field_text(text_field, "a"), 4),
+                (Term::from_field_text(text_field, "b"), 2),
+            ]
+        );
+        assert!(searcher
+            .vocabulary_statistics(text_field, 0)?
+            .top_terms
+            .is_empty());
+        assert!(searcher.vocabulary_statistics(stored_field, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggest() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"apple apply"))?;
+        index_writer.add_document(doc!(text_field=>"apply"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"ample maple"))?;
+        index_writer.add_document(doc!(text_field=>"apply"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let suggestions: Vec<(String, u8, u64)> = searcher
+            .suggest("appel", text_field, 2)?
+            .into_iter()
+            .map(|suggestion| {
+                let text = suggestion.term.value().as_str().unwrap().to_string();
+                (text, suggestion.distance, suggestion.doc_freq)
+            })
+            .collect();
+        assert_eq!(
+            suggestions,
+            vec![
+                ("apple".to_string(), 1, 1),
+                ("apply".to_string(), 2, 3),
+                ("ample".to_string(), 2, 1),
+            ]
+        );
+        assert_eq!(searcher.suggest("apply", text_field, 0)?.len(), 1);
+        assert!(searcher.suggest("appel", text_field, 3).is_err());
+        assert!(searcher.suggest("appel", id_field, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_complete() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"apple apply"))?;
+        index_writer.add_document(doc!(text_field=>"apply banana"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"apricot apply"))?;
+        index_writer.add_document(doc!(text_field=>"apple"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let complete = |prefix: &str, k: usize| -> crate::Result<Vec<(String, u64)>> {
+            Ok(searcher
+                .complete(prefix, text_field, k)?
+                .into_iter()
+                .map(|completion| {
+                    let text = completion.term.value().as_str().unwrap().to_string();
+                    (text, completion.doc_freq)
+                })
+                .collect())
+        };
+        assert_eq!(
+            complete("ap", 2)?,
+            vec![("apply".to_string(), 3), ("apple".to_string(), 2)]
+        );
+        assert_eq!(complete("apr", 10)?, vec![("apricot".to_string(), 1)]);
+        assert_eq!(complete("ap", 0)?, vec![]);
+        assert_eq!(complete("cherry", 10)?, vec![]);
+        Ok(())
+    }
+
     #[test]
     fn test_fieldnorm_no_docs_with_field() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
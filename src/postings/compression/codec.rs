@@ -0,0 +1,162 @@
+use bitpacking::{BitPacker, BitPacker4x};
+
+use super::COMPRESSION_BLOCK_SIZE;
+
+/// A `PostingsCodec` compresses full blocks of `COMPRESSION_BLOCK_SIZE` integers:
+/// doc ids (sorted) and term frequencies or positions (unsorted).
+///
+/// Each compressed block is described by a single `u8` parameter, returned when
+/// writing the block. It is stored in the skip list, and passed back when reading
+/// or skipping the block. For the default codec, it is the number of bits used to
+/// encode each value.
+///
+/// Incomplete blocks are not handled by the codec: they are always encoded using
+/// variable int encoding.
+///
+/// The codec is picked at compile time, with [`DefaultPostingsCodec`](super::DefaultPostingsCodec).
+/// It is not recorded in the index, so changing it is a format change: the indexes written
+/// with a codec can only be read with the same codec.
+pub trait PostingsCodec: Clone + Default + Send + Sync {
+    /// Compresses a strictly sorted block of integers into `output`, delta-encoding them
+    /// with regard to `offset`.
+    ///
+    /// Returns the block parameter and the number of bytes written.
+    fn compress_sorted(&self, offset: u32, block: &[u32], output: &mut [u8]) -> (u8, usize);
+
+    /// Compresses a block of unsorted integers into `output`.
+    ///
+    /// Returns the block parameter and the number of bytes written.
+    fn compress_unsorted(&self, block: &[u32], output: &mut [u8]) -> (u8, usize);
+
+    /// Decompresses a block written with `compress_sorted`.
+    ///
+    /// `strict_delta` is false for blocks written by older versions of tantivy, which did
+    /// not rely on the doc ids being strictly increasing.
+    ///
+    /// Returns the number of bytes consumed.
+    fn decompress_sorted(
+        &self,
+        offset: u32,
+        compressed_data: &[u8],
+        output: &mut [u32; COMPRESSION_BLOCK_SIZE],
+        block_param: u8,
+        strict_delta: bool,
+    ) -> usize;
+
+    /// Decompresses a block written with `compress_unsorted`.
+    ///
+    /// Returns the number of bytes consumed.
+    fn decompress_unsorted(
+        &self,
+        compressed_data: &[u8],
+        output: &mut [u32; COMPRESSION_BLOCK_SIZE],
+        block_param: u8,
+    ) -> usize;
+
+    /// Returns the size in bytes of a compressed block, given its block parameter.
+    ///
+    /// This is used to skip over blocks without decompressing them.
+    fn compressed_block_size(block_param: u8) -> usize;
+}
+
+/// The default codec, relying on SIMD bitpacking.
+///
+/// The underlying `BitPacker4x` picks its implementation at runtime: the SSE3 version is used
+/// when the CPU supports it, and the scalar version otherwise. Both produce the same
+/// format, so an index written on one machine can be read on any other.
+#[derive(Clone, Copy)]
+pub struct BitpackedCodec {
+    bitpacker: BitPacker4x,
+}
+
+impl Default for BitpackedCodec {
+    fn default() -> Self {
+        BitpackedCodec {
+            bitpacker: BitPacker4x::new(),
+        }
+    }
+}
+
+impl PostingsCodec for BitpackedCodec {
+    fn compress_sorted(&self, offset: u32, block: &[u32], output: &mut [u8]) -> (u8, usize) {
+        // if offset is zero, convert it to None. This is correct as long as we do the same when
+        // decompressing. It's required in case the block starts with an actual zero.
+        let offset = if offset == 0u32 { None } else { Some(offset) };
+        let num_bits = self.bitpacker.num_bits_strictly_sorted(offset, block);
+        let written_size = self
+            .bitpacker
+            .compress_strictly_sorted(offset, block, output, num_bits);
+        (num_bits, written_size)
+    }
+
+    fn compress_unsorted(&self, block: &[u32], output: &mut [u8]) -> (u8, usize) {
+        let num_bits = self.bitpacker.num_bits(block);
+        let written_size = self.bitpacker.compress(block, output, num_bits);
+        (num_bits, written_size)
+    }
+
+    fn decompress_sorted(
+        &self,
+        offset: u32,
+        compressed_data: &[u8],
+        output: &mut [u32; COMPRESSION_BLOCK_SIZE],
+        num_bits: u8,
+        strict_delta: bool,
+    ) -> usize {
+        if strict_delta {
+            let offset = std::num::NonZeroU32::new(offset).map(std::num::NonZeroU32::get);
+            self.bitpacker
+                .decompress_strictly_sorted(offset, compressed_data, output, num_bits)
+        } else {
+            self.bitpacker
+                .decompress_sorted(offset, compressed_data, output, num_bits)
+        }
+    }
+
+    fn decompress_unsorted(
+        &self,
+        compressed_data: &[u8],
+        output: &mut [u32; COMPRESSION_BLOCK_SIZE],
+        num_bits: u8,
+    ) -> usize {
+        self.bitpacker.decompress(compressed_data, output, num_bits)
+    }
+
+    fn compressed_block_size(num_bits: u8) -> usize {
+        (num_bits as usize) * COMPRESSION_BLOCK_SIZE / 8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitpackedCodec, PostingsCodec};
+    use crate::postings::compression::{COMPRESSED_BLOCK_MAX_SIZE, COMPRESSION_BLOCK_SIZE};
+
+    #[test]
+    fn test_bitpacked_codec_compressed_block_size() {
+        let codec = BitpackedCodec::default();
+        let mut output = [0u8; COMPRESSED_BLOCK_MAX_SIZE];
+        let sorted: Vec<u32> = (0..COMPRESSION_BLOCK_SIZE as u32)
+            .map(|i| 3 + i * 5)
+            .collect();
+        let (block_param, written_size) = codec.compress_sorted(1, &sorted, &mut output);
+        assert_eq!(
+            BitpackedCodec::compressed_block_size(block_param),
+            written_size
+        );
+        let mut decompressed = [0u32; COMPRESSION_BLOCK_SIZE];
+        let consumed = codec.decompress_sorted(1, &output, &mut decompressed, block_param, true);
+        assert_eq!(consumed, written_size);
+        assert_eq!(&decompressed[..], &sorted[..]);
+
+        let unsorted: Vec<u32> = (0..COMPRESSION_BLOCK_SIZE as u32).map(|i| i % 7).collect();
+        let (block_param, written_size) = codec.compress_unsorted(&unsorted, &mut output);
+        assert_eq!(
+            BitpackedCodec::compressed_block_size(block_param),
+            written_size
+        );
+        let consumed = codec.decompress_unsorted(&output, &mut decompressed, block_param);
+        assert_eq!(consumed, written_size);
+        assert_eq!(&decompressed[..], &unsorted[..]);
+    }
+}
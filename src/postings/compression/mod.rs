@@ -4,15 +4,26 @@ use common::FixedSize;
 pub const COMPRESSION_BLOCK_SIZE: usize = BitPacker4x::BLOCK_LEN;
 const COMPRESSED_BLOCK_MAX_SIZE: usize = COMPRESSION_BLOCK_SIZE * u32::SIZE_IN_BYTES;
 
+mod codec;
 mod vint;
 
-/// Returns the size in bytes of a compressed block, given `num_bits`.
+pub use self::codec::{BitpackedCodec, PostingsCodec};
+
+/// The codec used to compress full blocks of postings and positions.
+///
+/// Experimenting with another compression scheme only requires implementing
+/// [`PostingsCodec`] and pointing this alias to it.
+pub type DefaultPostingsCodec = BitpackedCodec;
+
+/// Returns the size in bytes of a compressed block, given its block parameter (`num_bits`).
 pub fn compressed_block_size(num_bits: u8) -> usize {
-    (num_bits as usize) * COMPRESSION_BLOCK_SIZE / 8
+    DefaultPostingsCodec::compressed_block_size(num_bits)
 }
 
+/// Compresses blocks of `COMPRESSION_BLOCK_SIZE` doc ids or term frequencies,
+/// using the [`DefaultPostingsCodec`].
 pub struct BlockEncoder {
-    bitpacker: BitPacker4x,
+    codec: DefaultPostingsCodec,
     pub output: [u8; COMPRESSED_BLOCK_MAX_SIZE],
     pub output_len: usize,
 }
@@ -26,21 +37,16 @@ impl Default for BlockEncoder {
 impl BlockEncoder {
     pub fn new() -> BlockEncoder {
         BlockEncoder {
-            bitpacker: BitPacker4x::new(),
+            codec: DefaultPostingsCodec::default(),
             output: [0u8; COMPRESSED_BLOCK_MAX_SIZE],
             output_len: 0,
         }
     }
 
     pub fn compress_block_sorted(&mut self, block: &[u32], offset: u32) -> (u8, &[u8]) {
-        // if offset is zero, convert it to None. This is correct as long as we do the same when
-        // decompressing. It's required in case the block starts with an actual zero.
-        let offset = if offset == 0u32 { None } else { Some(offset) };
-
-        let num_bits = self.bitpacker.num_bits_strictly_sorted(offset, block);
-        let written_size =
-            self.bitpacker
-                .compress_strictly_sorted(offset, block, &mut self.output[..], num_bits);
+        let (num_bits, written_size) =
+            self.codec
+                .compress_sorted(offset, block, &mut self.output[..]);
         (num_bits, &self.output[..written_size])
     }
 
@@ -67,17 +73,15 @@ impl BlockEncoder {
             block
         };
 
-        let num_bits = self.bitpacker.num_bits(block);
-        let written_size = self
-            .bitpacker
-            .compress(block, &mut self.output[..], num_bits);
+        let (num_bits, written_size) = self.codec.compress_unsorted(block, &mut self.output[..]);
         (num_bits, &self.output[..written_size])
     }
 }
 
+/// Decodes blocks written by [`BlockEncoder`].
 #[derive(Clone)]
 pub struct BlockDecoder {
-    bitpacker: BitPacker4x,
+    codec: DefaultPostingsCodec,
     output: [u32; COMPRESSION_BLOCK_SIZE],
     pub output_len: usize,
 }
@@ -91,7 +95,7 @@ impl Default for BlockDecoder {
 impl BlockDecoder {
     pub fn with_val(val: u32) -> BlockDecoder {
         BlockDecoder {
-            bitpacker: BitPacker4x::new(),
+            codec: DefaultPostingsCodec::default(),
             output: [val; COMPRESSION_BLOCK_SIZE],
             output_len: 0,
         }
@@ -108,21 +112,14 @@ impl BlockDecoder {
         num_bits: u8,
         strict_delta: bool,
     ) -> usize {
-        if strict_delta {
-            let offset = std::num::NonZeroU32::new(offset).map(std::num::NonZeroU32::get);
-
-            self.output_len = COMPRESSION_BLOCK_SIZE;
-            self.bitpacker.decompress_strictly_sorted(
-                offset,
-                compressed_data,
-                &mut self.output,
-                num_bits,
-            )
-        } else {
-            self.output_len = COMPRESSION_BLOCK_SIZE;
-            self.bitpacker
-                .decompress_sorted(offset, compressed_data, &mut self.output, num_bits)
-        }
+        self.output_len = COMPRESSION_BLOCK_SIZE;
+        self.codec.decompress_sorted(
+            offset,
+            compressed_data,
+            &mut self.output,
+            num_bits,
+            strict_delta,
+        )
     }
 
     /// Decompress block of unsorted integers.
@@ -138,8 +135,8 @@ impl BlockDecoder {
     ) -> usize {
         self.output_len = COMPRESSION_BLOCK_SIZE;
         let res = self
-            .bitpacker
-            .decompress(compressed_data, &mut self.output, num_bits);
+            .codec
+            .decompress_unsorted(compressed_data, &mut self.output, num_bits);
         if minus_one_encoded {
             for val in &mut self.output {
                 *val += 1;
@@ -23,7 +23,9 @@ pub use self::block_segment_postings::BlockSegmentPostings;
 pub(crate) use self::indexing_context::IndexingContext;
 pub(crate) use self::per_field_postings_writer::PerFieldPostingsWriter;
 pub use self::postings::Postings;
-pub(crate) use self::postings_writer::{serialize_postings, IndexingPosition, PostingsWriter};
+pub(crate) use self::postings_writer::{
+    serialize_postings, IndexingPosition, PostingsWriter, POSITION_GAP,
+};
 pub use self::segment_postings::SegmentPostings;
 pub use self::serializer::{FieldSerializer, InvertedIndexSerializer};
 pub(crate) use self::skip::{BlockInfo, SkipReader};
@@ -230,6 +232,7 @@ pub mod tests {
                 // checking that position works if the field has two values
                 let op = AddOperation {
                     opstamp: 0u64,
+                    boost: 1.0,
                     document: doc!(
                        text_field => "a b a c a d a a.",
                        text_field => "d d d d a"
@@ -240,6 +243,7 @@ pub mod tests {
             {
                 let op = AddOperation {
                     opstamp: 1u64,
+                    boost: 1.0,
                     document: doc!(text_field => "b a"),
                 };
                 segment_writer.add_document(op).unwrap();
@@ -249,6 +253,7 @@ pub mod tests {
                 text.push_str(" a");
                 let op = AddOperation {
                     opstamp: 2u64,
+                    boost: 1.0,
                     document: doc!(text_field => text),
                 };
                 segment_writer.add_document(op).unwrap();
@@ -47,6 +47,7 @@ fn posting_writer_from_field_entry(field_entry: &FieldEntry) -> Box<dyn Postings
         FieldType::U64(_)
         | FieldType::I64(_)
         | FieldType::F64(_)
+        | FieldType::ScaledF64(_)
         | FieldType::Bool(_)
         | FieldType::Date(_)
         | FieldType::Bytes(_)
@@ -15,7 +15,7 @@ use crate::schema::{Field, Schema, Term, Type};
 use crate::tokenizer::{Token, TokenStream, MAX_TOKEN_LEN};
 use crate::DocId;
 
-const POSITION_GAP: u32 = 1;
+pub(crate) const POSITION_GAP: u32 = 1;
 
 fn make_field_partition(
     term_offsets: &[(Field, OrderedPathId, &[u8], Addr)],
@@ -96,10 +96,22 @@ pub(crate) fn serialize_postings(
     Ok(())
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub(crate) struct IndexingPosition {
     pub num_tokens: u32,
     pub end_position: u32,
+    /// The number of positions left empty after each value.
+    pub position_gap: u32,
+}
+
+impl Default for IndexingPosition {
+    fn default() -> IndexingPosition {
+        IndexingPosition {
+            num_tokens: 0,
+            end_position: 0,
+            position_gap: POSITION_GAP,
+        }
+    }
 }
 
 /// The `PostingsWriter` is in charge of receiving documenting
@@ -158,7 +170,7 @@ pub(crate) trait PostingsWriter: Send + Sync {
             num_tokens += 1;
         });
 
-        indexing_position.end_position = end_position + POSITION_GAP;
+        indexing_position.end_position = end_position + indexing_position.position_gap;
         indexing_position.num_tokens += num_tokens;
         term_buffer.truncate_value_bytes(end_of_path_idx);
     }
@@ -12,14 +12,14 @@ use crate::postings::compression::{BlockEncoder, VIntEncoder, COMPRESSION_BLOCK_
 use crate::postings::skip::SkipSerializer;
 use crate::query::Bm25Weight;
 use crate::schema::{Field, FieldEntry, FieldType, IndexRecordOption, Schema};
-use crate::termdict::TermDictionaryBuilder;
+use crate::termdict::{hash_term, serialize_bloom_filter, TermDictionaryBuilder, BLOOM_FILTER_IDX};
 use crate::{DocId, Score};
 
 /// `InvertedIndexSerializer` is in charge of serializing
 /// postings on disk, in the
 /// * `.idx` (inverted index)
 /// * `.pos` (positions file)
-/// * `.term` (term dictionary)
+/// * `.term` (term dictionary, and bloom filters for `raw` tokenized text fields)
 ///
 /// `PostingsWriter` are in charge of pushing the data to the
 /// serializer.
@@ -51,6 +51,8 @@ pub struct InvertedIndexSerializer {
     postings_write: CompositeWrite<WritePtr>,
     positions_write: CompositeWrite<WritePtr>,
     schema: Schema,
+    // Hashes of the terms of the field being serialized, if it gets a bloom filter.
+    pending_bloom_filter: Option<(Field, Vec<u64>)>,
 }
 
 impl InvertedIndexSerializer {
@@ -62,6 +64,7 @@ impl InvertedIndexSerializer {
             postings_write: CompositeWrite::wrap(segment.open_write(Postings)?),
             positions_write: CompositeWrite::wrap(segment.open_write(Positions)?),
             schema: segment.schema(),
+            pending_bloom_filter: None,
         };
         Ok(inv_index_serializer)
     }
@@ -76,11 +79,19 @@ impl InvertedIndexSerializer {
         total_num_tokens: u64,
         fieldnorm_reader: Option<FieldNormReader>,
     ) -> io::Result<FieldSerializer> {
+        self.write_pending_bloom_filter()?;
         let field_entry: &FieldEntry = self.schema.get_field_entry(field);
+        if has_bloom_filter(field_entry.field_type()) {
+            self.pending_bloom_filter = Some((field, Vec::new()));
+        }
         let term_dictionary_write = self.terms_write.for_field(field);
         let postings_write = self.postings_write.for_field(field);
         let positions_write = self.positions_write.for_field(field);
         let field_type: FieldType = (*field_entry.field_type()).clone();
+        let term_hashes_opt = self
+            .pending_bloom_filter
+            .as_mut()
+            .map(|(_, term_hashes)| term_hashes);
         FieldSerializer::create(
             &field_type,
             total_num_tokens,
@@ -88,11 +99,25 @@ impl InvertedIndexSerializer {
             postings_write,
             positions_write,
             fieldnorm_reader,
+            term_hashes_opt,
         )
     }
 
+    /// Writes the bloom filter of the last serialized field, if any.
+    ///
+    /// The bloom filter is written in the terms composite file, right after the term
+    /// dictionary of the field.
+    fn write_pending_bloom_filter(&mut self) -> io::Result<()> {
+        if let Some((field, term_hashes)) = self.pending_bloom_filter.take() {
+            let bloom_filter_write = self.terms_write.for_field_with_idx(field, BLOOM_FILTER_IDX);
+            serialize_bloom_filter(&term_hashes, bloom_filter_write)?;
+        }
+        Ok(())
+    }
+
     /// Closes the serializer.
-    pub fn close(self) -> io::Result<()> {
+    pub fn close(mut self) -> io::Result<()> {
+        self.write_pending_bloom_filter()?;
         self.terms_write.close()?;
         self.postings_write.close()?;
         self.positions_write.close()?;
@@ -100,6 +125,21 @@ impl InvertedIndexSerializer {
     }
 }
 
+/// Returns true if the terms of the field should be summarized in a bloom filter.
+///
+/// This is the case of text fields indexed with the `raw` tokenizer, like `STRING` fields,
+/// which are typically used as primary keys.
+pub(crate) fn has_bloom_filter(field_type: &FieldType) -> bool {
+    if let FieldType::Str(text_options) = field_type {
+        text_options
+            .get_indexing_options()
+            .map(|indexing_options| indexing_options.tokenizer() == "raw")
+            .unwrap_or(false)
+    } else {
+        false
+    }
+}
+
 /// The field serializer is in charge of
 /// the serialization of a specific field.
 pub struct FieldSerializer<'a> {
@@ -108,6 +148,7 @@ pub struct FieldSerializer<'a> {
     positions_serializer_opt: Option<PositionSerializer<&'a mut CountingWriter<WritePtr>>>,
     current_term_info: TermInfo,
     term_open: bool,
+    term_hashes_opt: Option<&'a mut Vec<u64>>,
 }
 
 impl<'a> FieldSerializer<'a> {
@@ -118,6 +159,7 @@ impl<'a> FieldSerializer<'a> {
         postings_write: &'a mut CountingWriter<WritePtr>,
         positions_write: &'a mut CountingWriter<WritePtr>,
         fieldnorm_reader: Option<FieldNormReader>,
+        term_hashes_opt: Option<&'a mut Vec<u64>>,
     ) -> io::Result<FieldSerializer<'a>> {
         total_num_tokens.serialize(postings_write)?;
         let index_record_option = field_type
@@ -146,6 +188,7 @@ impl<'a> FieldSerializer<'a> {
             positions_serializer_opt,
             current_term_info: TermInfo::default(),
             term_open: false,
+            term_hashes_opt,
         })
     }
 
@@ -182,6 +225,9 @@ impl<'a> FieldSerializer<'a> {
         self.postings_serializer.clear();
         self.current_term_info = self.current_term_info();
         self.term_dictionary_builder.insert_key(term)?;
+        if let Some(term_hashes) = self.term_hashes_opt.as_mut() {
+            term_hashes.push(hash_term(term));
+        }
         self.postings_serializer
             .new_term(term_doc_freq, record_term_freq);
         Ok(())
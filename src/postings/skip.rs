@@ -161,7 +161,13 @@ impl SkipReader {
     //
     // The block max score is available for all full bitpacked block,
     // but no available for the last VInt encoded incomplete block.
+    //
+    // The block max score is computed with the default BM25 parameters when the postings are
+    // serialized, so it is not available for the other parameters either.
     pub fn block_max_score(&self, bm25_weight: &Bm25Weight) -> Option<Score> {
+        if !bm25_weight.has_default_similarity() {
+            return None;
+        }
         match self.block_info {
             BlockInfo::BitPacked {
                 block_wand_fieldnorm_id,
@@ -264,7 +270,8 @@ impl SkipReader {
                 ..
             } => {
                 self.remaining_docs -= COMPRESSION_BLOCK_SIZE as u32;
-                self.byte_offset += compressed_block_size(doc_num_bits + tf_num_bits);
+                self.byte_offset +=
+                    compressed_block_size(doc_num_bits) + compressed_block_size(tf_num_bits);
                 self.position_offset += tf_sum as u64;
             }
             BlockInfo::VInt { num_docs } => {
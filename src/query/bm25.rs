@@ -3,11 +3,45 @@ use serde::{Deserialize, Serialize};
 use crate::fieldnorm::FieldNormReader;
 use crate::query::Explanation;
 use crate::schema::Field;
-use crate::{Score, Searcher, Term};
+use crate::{Score, Searcher, TantivyError, Term};
 
 const K1: Score = 1.2;
 const B: Score = 0.75;
 
+/// The parameters of the BM25 scoring formula.
+///
+/// They are set on the index with
+/// [`Index::set_bm25_similarity()`](crate::Index::set_bm25_similarity).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bm25Similarity {
+    /// The term frequency saturation parameter. Defaults to 1.2.
+    pub k1: Score,
+    /// The length normalization parameter, between 0 and 1. Defaults to 0.75.
+    pub b: Score,
+}
+
+impl Default for Bm25Similarity {
+    fn default() -> Bm25Similarity {
+        Bm25Similarity { k1: K1, b: B }
+    }
+}
+
+impl Bm25Similarity {
+    /// Returns an error if `k1` is negative, or if `b` is not between 0 and 1.
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        let valid_k1 = self.k1.is_finite() && self.k1 >= 0.0;
+        let valid_b = (0.0..=1.0).contains(&self.b);
+        if !(valid_k1 && valid_b) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Invalid BM25 parameters: k1 = {}, b = {}. k1 has to be positive, and b between \
+                 0 and 1.",
+                self.k1, self.b
+            )));
+        }
+        Ok(())
+    }
+}
+
 /// An interface to compute the statistics needed in BM25 scoring.
 ///
 /// The standard implementation is a [Searcher] but you can also
@@ -22,6 +56,11 @@ pub trait Bm25StatisticsProvider {
 
     /// The number of documents containing the given term.
     fn doc_freq(&self, term: &Term) -> crate::Result<u64>;
+
+    /// The parameters of the BM25 formula.
+    fn bm25_similarity(&self) -> Bm25Similarity {
+        Bm25Similarity::default()
+    }
 }
 
 impl Bm25StatisticsProvider for Searcher {
@@ -47,6 +86,10 @@ impl Bm25StatisticsProvider for Searcher {
     fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
         self.doc_freq(term)
     }
+
+    fn bm25_similarity(&self) -> Bm25Similarity {
+        self.index().bm25_similarity()
+    }
 }
 
 pub(crate) fn idf(doc_freq: u64, doc_count: u64) -> Score {
@@ -55,15 +98,32 @@ pub(crate) fn idf(doc_freq: u64, doc_count: u64) -> Score {
     (1.0 + x).ln()
 }
 
-fn cached_tf_component(fieldnorm: u32, average_fieldnorm: Score) -> Score {
-    K1 * (1.0 - B + B * fieldnorm as Score / average_fieldnorm)
+fn idf_explain(term_doc_freq: u64, total_num_docs: u64) -> Explanation {
+    let idf = idf(term_doc_freq, total_num_docs);
+    let mut idf_explain =
+        Explanation::new("idf, computed as log(1 + (N - n + 0.5) / (n + 0.5))", idf);
+    idf_explain.add_const(
+        "n, number of docs containing this term",
+        term_doc_freq as Score,
+    );
+    idf_explain.add_const("N, total number of docs", total_num_docs as Score);
+    idf_explain
+}
+
+fn cached_tf_component(
+    fieldnorm: u32,
+    average_fieldnorm: Score,
+    similarity: Bm25Similarity,
+) -> Score {
+    let Bm25Similarity { k1, b } = similarity;
+    k1 * (1.0 - b + b * fieldnorm as Score / average_fieldnorm)
 }
 
-fn compute_tf_cache(average_fieldnorm: Score) -> [Score; 256] {
+fn compute_tf_cache(average_fieldnorm: Score, similarity: Bm25Similarity) -> [Score; 256] {
     let mut cache: [Score; 256] = [0.0; 256];
     for (fieldnorm_id, cache_mut) in cache.iter_mut().enumerate() {
         let fieldnorm = FieldNormReader::id_to_fieldnorm(fieldnorm_id as u8);
-        *cache_mut = cached_tf_component(fieldnorm, average_fieldnorm);
+        *cache_mut = cached_tf_component(fieldnorm, average_fieldnorm, similarity);
     }
     cache
 }
@@ -81,6 +141,7 @@ pub struct Bm25Weight {
     weight: Score,
     cache: [Score; 256],
     average_fieldnorm: Score,
+    similarity: Bm25Similarity,
 }
 
 impl Bm25Weight {
@@ -91,6 +152,7 @@ impl Bm25Weight {
             weight: self.weight * boost,
             cache: self.cache,
             average_fieldnorm: self.average_fieldnorm,
+            similarity: self.similarity,
         }
     }
 
@@ -113,41 +175,33 @@ impl Bm25Weight {
         let total_num_docs = statistics.total_num_docs()?;
         let average_fieldnorm = total_num_tokens as Score / total_num_docs as Score;
 
-        if terms.len() == 1 {
+        let idf_explain = if terms.len() == 1 {
             let term_doc_freq = statistics.doc_freq(&terms[0])?;
-            Ok(Bm25Weight::for_one_term(
-                term_doc_freq,
-                total_num_docs,
-                average_fieldnorm,
-            ))
+            idf_explain(term_doc_freq, total_num_docs)
         } else {
             let mut idf_sum: Score = 0.0;
             for term in terms {
                 let term_doc_freq = statistics.doc_freq(term)?;
                 idf_sum += idf(term_doc_freq, total_num_docs);
             }
-            let idf_explain = Explanation::new("idf", idf_sum);
-            Ok(Bm25Weight::new(idf_explain, average_fieldnorm))
-        }
+            Explanation::new("idf", idf_sum)
+        };
+        Ok(Bm25Weight::with_similarity(
+            idf_explain,
+            average_fieldnorm,
+            statistics.bm25_similarity(),
+        ))
     }
 
-    /// Construct a [Bm25Weight] for a single term.
+    /// Construct a [Bm25Weight] for a single term, with the default BM25 parameters.
     pub fn for_one_term(
         term_doc_freq: u64,
         total_num_docs: u64,
         avg_fieldnorm: Score,
     ) -> Bm25Weight {
-        let idf = idf(term_doc_freq, total_num_docs);
-        let mut idf_explain =
-            Explanation::new("idf, computed as log(1 + (N - n + 0.5) / (n + 0.5))", idf);
-        idf_explain.add_const(
-            "n, number of docs containing this term",
-            term_doc_freq as Score,
-        );
-        idf_explain.add_const("N, total number of docs", total_num_docs as Score);
-        Bm25Weight::new(idf_explain, avg_fieldnorm)
+        Bm25Weight::new(idf_explain(term_doc_freq, total_num_docs), avg_fieldnorm)
     }
-    /// Construct a [Bm25Weight] for a single term.
+    /// Construct a [Bm25Weight] for a single term, with the default BM25 parameters.
     /// This method does not carry the [Explanation] for the idf.
     pub fn for_one_term_without_explain(
         term_doc_freq: u64,
@@ -158,25 +212,48 @@ impl Bm25Weight {
         Bm25Weight::new_without_explain(idf, avg_fieldnorm)
     }
 
-    pub(crate) fn new(idf_explain: Explanation, average_fieldnorm: Score) -> Bm25Weight {
-        let weight = idf_explain.value() * (1.0 + K1);
+    /// Construct the [Bm25Weight] of a query whose scoring is disabled.
+    pub(crate) fn no_score() -> Bm25Weight {
+        Bm25Weight::new(Explanation::new("<no score>", 1.0), 1.0)
+    }
+
+    fn new(idf_explain: Explanation, average_fieldnorm: Score) -> Bm25Weight {
+        Bm25Weight::with_similarity(idf_explain, average_fieldnorm, Bm25Similarity::default())
+    }
+
+    fn with_similarity(
+        idf_explain: Explanation,
+        average_fieldnorm: Score,
+        similarity: Bm25Similarity,
+    ) -> Bm25Weight {
+        let weight = idf_explain.value() * (1.0 + similarity.k1);
         Bm25Weight {
             idf_explain: Some(idf_explain),
             weight,
-            cache: compute_tf_cache(average_fieldnorm),
+            cache: compute_tf_cache(average_fieldnorm, similarity),
             average_fieldnorm,
+            similarity,
         }
     }
-    pub(crate) fn new_without_explain(idf: f32, average_fieldnorm: Score) -> Bm25Weight {
-        let weight = idf * (1.0 + K1);
+
+    fn new_without_explain(idf: f32, average_fieldnorm: Score) -> Bm25Weight {
+        let similarity = Bm25Similarity::default();
+        let weight = idf * (1.0 + similarity.k1);
         Bm25Weight {
             idf_explain: None,
             weight,
-            cache: compute_tf_cache(average_fieldnorm),
+            cache: compute_tf_cache(average_fieldnorm, similarity),
             average_fieldnorm,
+            similarity,
         }
     }
 
+    /// Returns true if the weight uses the default BM25 parameters, which are the ones the
+    /// block max scores of the postings are computed with.
+    pub(crate) fn has_default_similarity(&self) -> bool {
+        self.similarity == Bm25Similarity::default()
+    }
+
     /// Compute the BM25 score of a single document.
     #[inline]
     pub fn score(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
@@ -211,8 +288,8 @@ impl Bm25Weight {
         );
 
         tf_explanation.add_const("freq, occurrences of term within document", term_freq);
-        tf_explanation.add_const("k1, term saturation parameter", K1);
-        tf_explanation.add_const("b, length normalization parameter", B);
+        tf_explanation.add_const("k1, term saturation parameter", self.similarity.k1);
+        tf_explanation.add_const("b, length normalization parameter", self.similarity.b);
         tf_explanation.add_const(
             "dl, length of field",
             FieldNormReader::id_to_fieldnorm(fieldnorm_id) as Score,
@@ -220,7 +297,7 @@ impl Bm25Weight {
         tf_explanation.add_const("avgdl, average length of field", self.average_fieldnorm);
 
         let mut explanation = Explanation::new("TermQuery, product of...", score);
-        explanation.add_detail(Explanation::new("(K1+1)", K1 + 1.0));
+        explanation.add_detail(Explanation::new("(K1+1)", self.similarity.k1 + 1.0));
         if let Some(idf_explain) = &self.idf_explain {
             explanation.add_detail(idf_explain.clone());
         }
@@ -232,12 +309,55 @@ impl Bm25Weight {
 #[cfg(test)]
 mod tests {
 
-    use super::idf;
-    use crate::{assert_nearly_equals, Score};
+    use super::{idf, Bm25Similarity};
+    use crate::collector::TopDocs;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TEXT};
+    use crate::{assert_nearly_equals, Index, IndexWriter, Score};
 
     #[test]
     fn test_idf() {
         let score: Score = 2.0;
         assert_nearly_equals!(idf(1, 2), score.ln());
     }
+
+    #[test]
+    fn test_bm25_similarity() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..1_000 {
+            let doc_text = format!("{} {}", "a ".repeat(i % 7 + 1), "b ".repeat(i % 13));
+            index_writer.add_document(doc!(text => doc_text))?;
+        }
+        index_writer.commit()?;
+        assert!(index
+            .set_bm25_similarity(Bm25Similarity { k1: 1.2, b: 1.5 })
+            .is_err());
+        assert!(index
+            .set_bm25_similarity(Bm25Similarity { k1: -1.0, b: 0.5 })
+            .is_err());
+
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("a")?;
+        let top_scores = |index: &Index, limit: usize| -> crate::Result<Vec<Score>> {
+            let searcher = index.reader()?.searcher();
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+            Ok(top_docs.into_iter().map(|(score, _)| score).collect())
+        };
+        let default_scores = top_scores(&index, 10)?;
+        index.set_bm25_similarity(Bm25Similarity { k1: 0.5, b: 0.0 })?;
+        let scores = top_scores(&index, 10)?;
+        assert!(scores
+            .iter()
+            .zip(&default_scores)
+            .any(|(score, default_score)| score != default_score));
+        // Without length normalization, the documents with the most occurrences of `a` have
+        // the same score, whatever their length.
+        assert_nearly_equals!(scores[0], scores[9]);
+        // The pruning of the top-k search stays correct.
+        let all_scores = top_scores(&index, 1_000)?;
+        assert_eq!(&all_scores[..10], &scores[..]);
+        Ok(())
+    }
 }
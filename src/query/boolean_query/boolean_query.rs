@@ -1,4 +1,5 @@
 use super::boolean_weight::BooleanWeight;
+use crate::query::profile::sub_query_weight;
 use crate::query::{EnableScoring, Occur, Query, SumWithCoordsCombiner, TermQuery, Weight};
 use crate::schema::{IndexRecordOption, Term};
 
@@ -147,7 +148,9 @@ impl Query for BooleanQuery {
         let sub_weights = self
             .subqueries
             .iter()
-            .map(|(occur, subquery)| Ok((*occur, subquery.weight(enable_scoring)?)))
+            .map(|(occur, subquery)| {
+                Ok((*occur, sub_query_weight(subquery.as_ref(), enable_scoring)?))
+            })
             .collect::<crate::Result<_>>()?;
         Ok(Box::new(BooleanWeight::new(
             sub_weights,
@@ -161,6 +164,12 @@ impl Query for BooleanQuery {
             subquery.query_terms(visitor);
         }
     }
+
+    fn query_phrases<'a>(&'a self, visitor: &mut dyn FnMut(&'a [(usize, Term)])) {
+        for (_occur, subquery) in &self.subqueries {
+            subquery.query_phrases(visitor);
+        }
+    }
 }
 
 impl BooleanQuery {
@@ -11,7 +11,7 @@ use crate::query::{
     intersect_scorers, EmptyScorer, Exclude, Explanation, Occur, RequiredOptionalScorer, Scorer,
     Union, Weight,
 };
-use crate::{DocId, Score};
+use crate::{DocId, DocSet, Score, TERMINATED};
 
 enum SpecializedScorer {
     TermUnion(Vec<TermScorer>),
@@ -222,6 +222,37 @@ impl<TScoreCombiner: ScoreCombiner + Sync> Weight for BooleanWeight<TScoreCombin
         Ok(())
     }
 
+    fn for_each_with_matched_clauses(
+        &self,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score, u64),
+    ) -> crate::Result<()> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        // The clauses are followed with scorers of their own, which are moved forward to each
+        // matching doc.
+        let mut clause_scorers: Vec<Box<dyn Scorer>> = self
+            .weights
+            .iter()
+            .take(u64::BITS as usize)
+            .map(|(_, weight)| weight.scorer(reader, 1.0))
+            .collect::<crate::Result<_>>()?;
+        let mut doc = scorer.doc();
+        while doc != TERMINATED {
+            let mut matched_clauses = 0u64;
+            for (clause_ord, clause_scorer) in clause_scorers.iter_mut().enumerate() {
+                if clause_scorer.doc() < doc {
+                    clause_scorer.seek(doc);
+                }
+                if clause_scorer.doc() == doc {
+                    matched_clauses |= 1u64 << clause_ord;
+                }
+            }
+            callback(doc, scorer.score(), matched_clauses);
+            doc = scorer.advance();
+        }
+        Ok(())
+    }
+
     fn for_each_no_score(
         &self,
         reader: &SegmentReader,
@@ -11,7 +11,7 @@ mod tests {
 
     use super::*;
     use crate::collector::tests::TEST_COLLECTOR_WITH_SCORE;
-    use crate::collector::TopDocs;
+    use crate::collector::{Collector, SegmentCollector, TopDocs};
     use crate::query::score_combiner::SumWithCoordsCombiner;
     use crate::query::term_query::TermScorer;
     use crate::query::{
@@ -19,7 +19,10 @@ mod tests {
         TermQuery,
     };
     use crate::schema::*;
-    use crate::{assert_nearly_equals, DocAddress, DocId, Index, IndexWriter, Score};
+    use crate::{
+        assert_nearly_equals, DocAddress, DocId, Index, IndexWriter, Score, SegmentOrdinal,
+        SegmentReader,
+    };
 
     fn aux_test_helper() -> crate::Result<(Index, Field)> {
         let mut schema_builder = Schema::builder();
@@ -291,6 +294,95 @@ mod tests {
         Ok(())
     }
 
+    struct MatchedClausesCollector;
+
+    struct MatchedClausesSegmentCollector(Vec<(DocId, u64)>);
+
+    impl Collector for MatchedClausesCollector {
+        type Fruit = Vec<(DocId, u64)>;
+        type Child = MatchedClausesSegmentCollector;
+
+        fn for_segment(
+            &self,
+            _segment_local_id: SegmentOrdinal,
+            _segment: &SegmentReader,
+        ) -> crate::Result<MatchedClausesSegmentCollector> {
+            Ok(MatchedClausesSegmentCollector(Vec::new()))
+        }
+
+        fn requires_scoring(&self) -> bool {
+            false
+        }
+
+        fn requires_matched_clauses(&self) -> bool {
+            true
+        }
+
+        fn merge_fruits(
+            &self,
+            segment_fruits: Vec<Vec<(DocId, u64)>>,
+        ) -> crate::Result<Vec<(DocId, u64)>> {
+            Ok(segment_fruits.into_iter().flatten().collect())
+        }
+    }
+
+    impl SegmentCollector for MatchedClausesSegmentCollector {
+        type Fruit = Vec<(DocId, u64)>;
+
+        fn collect(&mut self, _doc: DocId, _score: Score) {
+            panic!("The matched clauses are required");
+        }
+
+        fn collect_with_matched_clauses(
+            &mut self,
+            doc: DocId,
+            _score: Score,
+            matched_clauses: u64,
+        ) {
+            self.0.push((doc, matched_clauses));
+        }
+
+        fn harvest(self) -> Vec<(DocId, u64)> {
+            self.0
+        }
+    }
+
+    #[test]
+    pub fn test_boolean_query_matched_clauses() -> crate::Result<()> {
+        let (index, text_field) = aux_test_helper()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = |text: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let query = BooleanQuery::new(vec![
+            (Occur::Should, term_query("a")),
+            (Occur::Should, term_query("b")),
+            (Occur::Should, term_query("d")),
+        ]);
+        assert_eq!(
+            searcher.search(&query, &MatchedClausesCollector)?,
+            vec![(0, 0b011), (1, 0b001), (2, 0b010), (3, 0b111), (4, 0b100)]
+        );
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, term_query("c")),
+            (Occur::MustNot, term_query("a")),
+            (Occur::Should, term_query("b")),
+        ]);
+        assert_eq!(
+            searcher.search(&query, &MatchedClausesCollector)?,
+            vec![(2, 0b101)]
+        );
+        // Other queries report no clause.
+        assert_eq!(
+            searcher.search(&term_query("d"), &MatchedClausesCollector)?,
+            vec![(3, 0), (4, 0)]
+        );
+        Ok(())
+    }
+
     #[test]
     pub fn test_explain() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
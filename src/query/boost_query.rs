@@ -2,6 +2,7 @@ use std::fmt;
 
 use crate::docset::BUFFER_LEN;
 use crate::fastfield::AliveBitSet;
+use crate::query::profile::sub_query_weight;
 use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
 use crate::{DocId, DocSet, Score, SegmentReader, Term};
 
@@ -39,7 +40,7 @@ impl fmt::Debug for BoostQuery {
 
 impl Query for BoostQuery {
     fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
-        let weight_without_boost = self.query.weight(enable_scoring)?;
+        let weight_without_boost = sub_query_weight(self.query.as_ref(), enable_scoring)?;
         let boosted_weight = if enable_scoring.is_scoring_enabled() {
             Box::new(BoostWeight::new(weight_without_boost, self.boost))
         } else {
@@ -51,6 +52,10 @@ impl Query for BoostQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor)
     }
+
+    fn query_phrases<'a>(&'a self, visitor: &mut dyn FnMut(&'a [(usize, Term)])) {
+        self.query.query_phrases(visitor)
+    }
 }
 
 /// Weight associated to the BoostQuery.
@@ -1,6 +1,7 @@
 use std::fmt;
 
 use crate::docset::BUFFER_LEN;
+use crate::query::profile::sub_query_weight;
 use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
 use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
 
@@ -38,7 +39,7 @@ impl fmt::Debug for ConstScoreQuery {
 
 impl Query for ConstScoreQuery {
     fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
-        let inner_weight = self.query.weight(enable_scoring)?;
+        let inner_weight = sub_query_weight(self.query.as_ref(), enable_scoring)?;
         Ok(if enable_scoring.is_scoring_enabled() {
             Box::new(ConstWeight::new(inner_weight, self.score))
         } else {
@@ -49,6 +50,10 @@ impl Query for ConstScoreQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor);
     }
+
+    fn query_phrases<'a>(&'a self, visitor: &mut dyn FnMut(&'a [(usize, Term)])) {
+        self.query.query_phrases(visitor);
+    }
 }
 
 struct ConstWeight {
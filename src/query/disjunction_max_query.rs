@@ -1,3 +1,4 @@
+use crate::query::profile::sub_query_weight;
 use crate::query::{BooleanWeight, DisjunctionMaxCombiner, EnableScoring, Occur, Query, Weight};
 use crate::{Score, Term};
 
@@ -94,7 +95,12 @@ impl Query for DisjunctionMaxQuery {
         let disjuncts = self
             .disjuncts
             .iter()
-            .map(|disjunct| Ok((Occur::Should, disjunct.weight(enable_scoring)?)))
+            .map(|disjunct| {
+                Ok((
+                    Occur::Should,
+                    sub_query_weight(disjunct.as_ref(), enable_scoring)?,
+                ))
+            })
             .collect::<crate::Result<_>>()?;
         let tie_breaker = self.tie_breaker;
         Ok(Box::new(BooleanWeight::new(
@@ -109,6 +115,12 @@ impl Query for DisjunctionMaxQuery {
             disjunct.query_terms(visitor);
         }
     }
+
+    fn query_phrases<'a>(&'a self, visitor: &mut dyn FnMut(&'a [(usize, Term)])) {
+        for disjunct in &self.disjuncts {
+            disjunct.query_phrases(visitor);
+        }
+    }
 }
 
 impl DisjunctionMaxQuery {
@@ -0,0 +1,345 @@
+use std::fmt::Debug;
+
+use common::BitSet;
+
+use crate::core::SegmentReader;
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::geo_point::{encoded_lat_range, EARTH_RADIUS_METERS};
+use crate::schema::{FieldType, GeoPoint};
+use crate::{DocId, Score, TantivyError};
+
+/// A shape matched against the geo points of a field.
+trait GeoShape: Clone + Debug + Send + Sync + 'static {
+    /// Name of the query, used in explanations.
+    const QUERY_NAME: &'static str;
+
+    /// Latitudes of the southmost and northmost points of the shape.
+    fn lat_bounds(&self) -> (f64, f64);
+
+    /// Returns true if the point is within the shape.
+    fn contains(&self, point: GeoPoint) -> bool;
+}
+
+/// The area between two latitudes and two longitudes.
+#[derive(Clone, Debug)]
+struct BoundingBox {
+    top_left: GeoPoint,
+    bottom_right: GeoPoint,
+}
+
+impl GeoShape for BoundingBox {
+    const QUERY_NAME: &'static str = "BoundingBoxQuery";
+
+    fn lat_bounds(&self) -> (f64, f64) {
+        (self.bottom_right.lat, self.top_left.lat)
+    }
+
+    fn contains(&self, point: GeoPoint) -> bool {
+        if point.lat < self.bottom_right.lat || point.lat > self.top_left.lat {
+            return false;
+        }
+        if self.top_left.lon <= self.bottom_right.lon {
+            self.top_left.lon <= point.lon && point.lon <= self.bottom_right.lon
+        } else {
+            // The box crosses the antimeridian.
+            self.top_left.lon <= point.lon || point.lon <= self.bottom_right.lon
+        }
+    }
+}
+
+/// The area within a given distance of a point.
+#[derive(Clone, Debug)]
+struct Circle {
+    center: GeoPoint,
+    radius_meters: f64,
+}
+
+impl GeoShape for Circle {
+    const QUERY_NAME: &'static str = "GeoDistanceQuery";
+
+    fn lat_bounds(&self) -> (f64, f64) {
+        let radius_degrees = (self.radius_meters / EARTH_RADIUS_METERS).to_degrees();
+        (
+            (self.center.lat - radius_degrees).max(-90.0),
+            (self.center.lat + radius_degrees).min(90.0),
+        )
+    }
+
+    fn contains(&self, point: GeoPoint) -> bool {
+        self.center.distance(&point) <= self.radius_meters
+    }
+}
+
+/// Matches the documents with a geo point within a bounding box.
+///
+/// The field needs to be a `u64` fast field holding points encoded with
+/// [`GeoPoint::to_u64`]. A document with several points matches if any of them
+/// is within the box.
+///
+/// If the longitude of the top left corner is greater than the longitude of the bottom
+/// right corner, the box is considered to cross the antimeridian.
+///
+/// All of the matched documents get the score 1.0.
+#[derive(Clone, Debug)]
+pub struct BoundingBoxQuery {
+    field_name: String,
+    bounding_box: BoundingBox,
+}
+
+impl BoundingBoxQuery {
+    /// Creates a new `BoundingBoxQuery` from its top left (north-west) and
+    /// bottom right (south-east) corners.
+    ///
+    /// Executing the search with this query will return an error if the field is
+    /// not a `u64` fast field, or if the top left corner is south of the bottom right one.
+    pub fn new(field_name: String, top_left: GeoPoint, bottom_right: GeoPoint) -> Self {
+        BoundingBoxQuery {
+            field_name,
+            bounding_box: BoundingBox {
+                top_left,
+                bottom_right,
+            },
+        }
+    }
+}
+
+impl Query for BoundingBoxQuery {
+    fn weight(&self, enable_scoring: EnableScoring) -> crate::Result<Box<dyn Weight>> {
+        if self.bounding_box.top_left.lat < self.bounding_box.bottom_right.lat {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The top left corner {} of the bounding box is south of its bottom right corner {}",
+                self.bounding_box.top_left, self.bounding_box.bottom_right
+            )));
+        }
+        let weight =
+            GeoShapeWeight::new(&self.field_name, self.bounding_box.clone(), enable_scoring)?;
+        Ok(Box::new(weight))
+    }
+}
+
+/// Matches the documents with a geo point within a given distance of a center point.
+///
+/// The field needs to be a `u64` fast field holding points encoded with
+/// [`GeoPoint::to_u64`]. A document with several points matches if any of them
+/// is within the distance. Distances are computed with [`GeoPoint::distance`].
+///
+/// All of the matched documents get the score 1.0. In order to rank the documents
+/// by their distance to a point, see
+/// [`TopDocs::order_by_geo_distance`](crate::collector::TopDocs::order_by_geo_distance).
+#[derive(Clone, Debug)]
+pub struct GeoDistanceQuery {
+    field_name: String,
+    circle: Circle,
+}
+
+impl GeoDistanceQuery {
+    /// Creates a new `GeoDistanceQuery` matching the points within `radius_meters`
+    /// of `center`.
+    ///
+    /// Executing the search with this query will return an error if the field is
+    /// not a `u64` fast field, or if the radius is negative or not a number.
+    pub fn new(field_name: String, center: GeoPoint, radius_meters: f64) -> Self {
+        GeoDistanceQuery {
+            field_name,
+            circle: Circle {
+                center,
+                radius_meters,
+            },
+        }
+    }
+}
+
+impl Query for GeoDistanceQuery {
+    fn weight(&self, enable_scoring: EnableScoring) -> crate::Result<Box<dyn Weight>> {
+        if self.circle.radius_meters.is_nan() || self.circle.radius_meters < 0.0 {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The radius {} of the geo distance query is not a positive distance",
+                self.circle.radius_meters
+            )));
+        }
+        let weight = GeoShapeWeight::new(&self.field_name, self.circle.clone(), enable_scoring)?;
+        Ok(Box::new(weight))
+    }
+}
+
+/// Weight matching the documents with a geo point within a shape.
+struct GeoShapeWeight<S> {
+    field_name: String,
+    shape: S,
+}
+
+impl<S: GeoShape> GeoShapeWeight<S> {
+    fn new(
+        field_name: &str,
+        shape: S,
+        enable_scoring: EnableScoring,
+    ) -> crate::Result<GeoShapeWeight<S>> {
+        let schema = enable_scoring.schema();
+        let field = schema.get_field(field_name)?;
+        match schema.get_field_entry(field).field_type() {
+            FieldType::U64(options) if options.is_fast() => {}
+            _ => {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {field_name} is not a u64 fast field, and cannot hold geo points."
+                )));
+            }
+        }
+        Ok(GeoShapeWeight {
+            field_name: field_name.to_string(),
+            shape,
+        })
+    }
+}
+
+impl<S: GeoShape> Weight for GeoShapeWeight<S> {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        if let Some(column) = reader.fast_fields().column_opt::<u64>(&self.field_name)? {
+            // The latitude being stored in the high bits, the fast field can
+            // prefilter the candidates on their latitude.
+            let (min_lat, max_lat) = self.shape.lat_bounds();
+            let mut candidates = Vec::new();
+            column.get_docids_for_value_range(
+                encoded_lat_range(min_lat, max_lat),
+                0..max_doc,
+                &mut candidates,
+            );
+            for doc in candidates {
+                if doc_bitset.contains(doc) {
+                    continue;
+                }
+                if column
+                    .values_for_doc(doc)
+                    .any(|val| self.shape.contains(GeoPoint::from_u64(val)))
+                {
+                    doc_bitset.insert(doc);
+                }
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new(S::QUERY_NAME, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoundingBoxQuery, GeoDistanceQuery};
+    use crate::collector::{Count, DocSetCollector};
+    use crate::schema::{GeoPoint, Schema, FAST, STRING};
+    use crate::{Index, IndexWriter};
+
+    fn create_index(points: &[(f64, f64)]) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_u64_field("location", FAST);
+        let name = schema_builder.add_text_field("name", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for &(lat, lon) in points {
+            index_writer.add_document(doc!(location => GeoPoint::new(lat, lon)))?;
+        }
+        // A document without location.
+        index_writer.add_document(doc!(name => "nowhere"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_bounding_box_query() -> crate::Result<()> {
+        let index = create_index(&[
+            (48.8566, 2.3522),    // Paris
+            (51.5074, -0.1278),   // London
+            (52.52, 13.405),      // Berlin
+            (40.7128, -74.006),   // New York
+            (-33.8688, 151.2093), // Sydney
+        ])?;
+        let searcher = index.reader()?.searcher();
+        let europe = BoundingBoxQuery::new(
+            "location".to_string(),
+            GeoPoint::new(60.0, -10.0),
+            GeoPoint::new(35.0, 30.0),
+        );
+        assert_eq!(searcher.search(&europe, &Count)?, 3);
+        let docs = searcher.search(&europe, &DocSetCollector)?;
+        let mut doc_ids: Vec<u32> = docs.into_iter().map(|doc| doc.doc_id).collect();
+        doc_ids.sort();
+        assert_eq!(doc_ids, vec![0, 1, 2]);
+
+        let west_of_greenwich = BoundingBoxQuery::new(
+            "location".to_string(),
+            GeoPoint::new(90.0, -180.0),
+            GeoPoint::new(-90.0, 0.0),
+        );
+        assert_eq!(searcher.search(&west_of_greenwich, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounding_box_query_antimeridian() -> crate::Result<()> {
+        let index = create_index(&[
+            (-33.8688, 151.2093), // Sydney
+            (21.3069, -157.8583), // Honolulu
+            (48.8566, 2.3522),    // Paris
+        ])?;
+        let searcher = index.reader()?.searcher();
+        let pacific = BoundingBoxQuery::new(
+            "location".to_string(),
+            GeoPoint::new(50.0, 140.0),
+            GeoPoint::new(-50.0, -150.0),
+        );
+        assert_eq!(searcher.search(&pacific, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounding_box_query_errors() -> crate::Result<()> {
+        let index = create_index(&[])?;
+        let searcher = index.reader()?.searcher();
+        let not_fast = BoundingBoxQuery::new(
+            "name".to_string(),
+            GeoPoint::new(1.0, 0.0),
+            GeoPoint::new(0.0, 1.0),
+        );
+        assert!(searcher.search(&not_fast, &Count).is_err());
+        let inverted = BoundingBoxQuery::new(
+            "location".to_string(),
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(1.0, 1.0),
+        );
+        assert!(searcher.search(&inverted, &Count).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_geo_distance_query() -> crate::Result<()> {
+        let index = create_index(&[
+            (48.8566, 2.3522),  // Paris
+            (51.5074, -0.1278), // London
+            (52.52, 13.405),    // Berlin
+            (40.7128, -74.006), // New York
+        ])?;
+        let searcher = index.reader()?.searcher();
+        let paris = GeoPoint::new(48.8566, 2.3522);
+        let near_paris = GeoDistanceQuery::new("location".to_string(), paris, 10_000.0);
+        assert_eq!(searcher.search(&near_paris, &Count)?, 1);
+        let within_500km = GeoDistanceQuery::new("location".to_string(), paris, 500_000.0);
+        let docs = searcher.search(&within_500km, &DocSetCollector)?;
+        let mut doc_ids: Vec<u32> = docs.into_iter().map(|doc| doc.doc_id).collect();
+        doc_ids.sort();
+        assert_eq!(doc_ids, vec![0, 1]);
+        let within_1000km = GeoDistanceQuery::new("location".to_string(), paris, 1_000_000.0);
+        assert_eq!(searcher.search(&within_1000km, &Count)?, 3);
+        let negative = GeoDistanceQuery::new("location".to_string(), paris, -1.0);
+        assert!(searcher.search(&negative, &Count).is_err());
+        Ok(())
+    }
+}
@@ -11,10 +11,12 @@ mod exclude;
 mod exist_query;
 mod explanation;
 mod fuzzy_query;
+mod geo_query;
 mod intersection;
 mod more_like_this;
 mod phrase_prefix_query;
 mod phrase_query;
+pub(crate) mod profile;
 mod query;
 mod query_parser;
 mod range_query;
@@ -22,6 +24,7 @@ mod regex_query;
 mod reqopt_scorer;
 mod scorer;
 mod set_query;
+mod static_rank;
 mod term_query;
 mod union;
 mod weight;
@@ -35,7 +38,7 @@ pub use query_grammar::Occur;
 pub use self::all_query::{AllQuery, AllScorer, AllWeight};
 pub use self::automaton_weight::AutomatonWeight;
 pub use self::bitset::BitSetDocSet;
-pub use self::bm25::{Bm25StatisticsProvider, Bm25Weight};
+pub use self::bm25::{Bm25Similarity, Bm25StatisticsProvider, Bm25Weight};
 pub use self::boolean_query::{BooleanQuery, BooleanWeight};
 pub use self::boost_query::{BoostQuery, BoostWeight};
 pub use self::const_score_query::{ConstScoreQuery, ConstScorer};
@@ -44,15 +47,16 @@ pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::Exclude;
 pub use self::exist_query::ExistsQuery;
 pub use self::explanation::Explanation;
-#[cfg(test)]
-pub(crate) use self::fuzzy_query::DfaWrapper;
 pub use self::fuzzy_query::FuzzyTermQuery;
+pub(crate) use self::fuzzy_query::{levenshtein_automaton_builder, DfaWrapper};
+pub use self::geo_query::{BoundingBoxQuery, GeoDistanceQuery};
 pub use self::intersection::{intersect_scorers, Intersection};
 pub use self::more_like_this::{MoreLikeThisQuery, MoreLikeThisQueryBuilder};
 pub use self::phrase_prefix_query::PhrasePrefixQuery;
 pub use self::phrase_query::PhraseQuery;
+pub use self::profile::{QueryProfile, SearchProfile};
 pub use self::query::{EnableScoring, Query, QueryClone};
-pub use self::query_parser::{QueryParser, QueryParserError};
+pub use self::query_parser::{ParseOptions, QueryParser, QueryParserError};
 pub use self::range_query::{FastFieldRangeWeight, IPFastFieldRangeWeight, RangeQuery};
 pub use self::regex_query::RegexQuery;
 pub use self::reqopt_scorer::RequiredOptionalScorer;
@@ -61,6 +65,8 @@ pub use self::score_combiner::{
 };
 pub use self::scorer::Scorer;
 pub use self::set_query::TermSetQuery;
+pub(crate) use self::static_rank::with_static_rank;
+pub use self::static_rank::StaticRank;
 pub use self::term_query::TermQuery;
 pub use self::union::Union;
 #[cfg(test)]
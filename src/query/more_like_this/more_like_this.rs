@@ -267,6 +267,17 @@ impl MoreLikeThis {
                     }
                 }
             }
+            FieldType::ScaledF64(ref scaled_options) => {
+                for value in values {
+                    let val = value.as_f64().ok_or_else(|| {
+                        TantivyError::InvalidArgument("invalid value".to_string())
+                    })?;
+                    if !self.is_noise_word(val.to_string()) {
+                        let term = Term::from_field_i64(field, scaled_options.scale(val));
+                        *term_frequencies.entry(term).or_insert(0) += 1;
+                    }
+                }
+            }
             _ => {}
         }
         Ok(())
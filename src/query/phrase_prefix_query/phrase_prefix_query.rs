@@ -1,9 +1,10 @@
 use std::ops::Bound;
 
-use super::{prefix_end, PhrasePrefixWeight};
+use super::PhrasePrefixWeight;
 use crate::query::bm25::Bm25Weight;
 use crate::query::{EnableScoring, Query, RangeQuery, Weight};
 use crate::schema::{Field, IndexRecordOption, Term};
+use crate::termdict::prefix_end;
 
 const DEFAULT_MAX_EXPANSIONS: u32 = 50;
 
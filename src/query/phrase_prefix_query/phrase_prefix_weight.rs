@@ -1,4 +1,4 @@
-use super::{prefix_end, PhrasePrefixScorer};
+use super::PhrasePrefixScorer;
 use crate::core::SegmentReader;
 use crate::fieldnorm::FieldNormReader;
 use crate::postings::SegmentPostings;
@@ -6,6 +6,7 @@ use crate::query::bm25::Bm25Weight;
 use crate::query::explanation::does_not_match;
 use crate::query::{EmptyScorer, Explanation, Scorer, Weight};
 use crate::schema::{IndexRecordOption, Term};
+use crate::termdict::prefix_end;
 use crate::{DocId, DocSet, Score};
 
 pub struct PhrasePrefixWeight {
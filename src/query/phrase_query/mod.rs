@@ -14,9 +14,12 @@ pub mod tests {
 
     use super::*;
     use crate::collector::tests::{TEST_COLLECTOR_WITHOUT_SCORE, TEST_COLLECTOR_WITH_SCORE};
+    use crate::collector::Count;
     use crate::core::Index;
     use crate::query::{EnableScoring, QueryParser, Weight};
-    use crate::schema::{Schema, Term, TEXT};
+    use crate::schema::{
+        Field, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions, TEXT,
+    };
     use crate::{assert_nearly_equals, DocAddress, DocId, IndexWriter, TERMINATED};
 
     pub fn create_index(texts: &[&'static str]) -> crate::Result<Index> {
@@ -88,6 +91,79 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_phrase_query_repeated() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a a a b c a"))?;
+        index_writer.add_document(doc!(text_field => "b a c"))?;
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text_field, "a"),
+            Term::from_field_text(text_field, "b"),
+            Term::from_field_text(text_field, "c"),
+        ]);
+        // The term infos of the first run are reused by the next ones, even by the clones of the
+        // query.
+        for _ in 0..2 {
+            assert_eq!(reader.searcher().search(&phrase_query, &Count)?, 1);
+        }
+        index_writer.add_document(doc!(text_field => "a b"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field => "c a b c"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        for _ in 0..2 {
+            assert_eq!(searcher.search(&phrase_query.clone(), &Count)?, 2);
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_phrase_query_position_gap() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let default_gap = schema_builder.add_text_field("default_gap", TEXT);
+        let large_gap = schema_builder.add_text_field(
+            "large_gap",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                    .set_position_gap(10),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            default_gap => "a b",
+            default_gap => "c d",
+            large_gap => "a b",
+            large_gap => "c d",
+        ))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let count_phrase = |field: Field, slop: u32| {
+            let phrase_query = PhraseQuery::new_with_offset_and_slop(
+                vec![
+                    (0, Term::from_field_text(field, "b")),
+                    (1, Term::from_field_text(field, "c")),
+                ],
+                slop,
+            );
+            searcher.search(&phrase_query, &Count)
+        };
+        assert_eq!(count_phrase(default_gap, 0)?, 0);
+        assert_eq!(count_phrase(default_gap, 1)?, 1);
+        assert_eq!(count_phrase(large_gap, 1)?, 0);
+        assert_eq!(count_phrase(large_gap, 9)?, 0);
+        assert_eq!(count_phrase(large_gap, 10)?, 1);
+        Ok(())
+    }
+
     #[test]
     pub fn test_phrase_query_no_score() -> crate::Result<()> {
         let index = create_index(&[
@@ -1,3 +1,7 @@
+use std::fmt;
+use std::sync::Arc;
+
+use super::phrase_weight::TermInfosCache;
 use super::PhraseWeight;
 use crate::query::bm25::Bm25Weight;
 use crate::query::{EnableScoring, Query, Weight};
@@ -19,11 +23,22 @@ use crate::schema::{Field, IndexRecordOption, Term};
 ///
 /// Using a `PhraseQuery` on a field requires positions
 /// to be indexed for this field.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct PhraseQuery {
     field: Field,
     phrase_terms: Vec<(usize, Term)>,
     slop: u32,
+    term_infos_cache: Arc<TermInfosCache>,
+}
+
+impl fmt::Debug for PhraseQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PhraseQuery")
+            .field("field", &self.field)
+            .field("phrase_terms", &self.phrase_terms)
+            .field("slop", &self.slop)
+            .finish()
+    }
 }
 
 impl PhraseQuery {
@@ -60,6 +75,7 @@ impl PhraseQuery {
             field,
             phrase_terms: terms,
             slop,
+            term_infos_cache: Arc::default(),
         }
     }
 
@@ -125,6 +141,7 @@ impl PhraseQuery {
             EnableScoring::Disabled { .. } => None,
         };
         let mut weight = PhraseWeight::new(self.phrase_terms.clone(), bm25_weight_opt);
+        weight.set_term_infos_cache(self.term_infos_cache.clone());
         if self.slop > 0 {
             weight.slop(self.slop);
         }
@@ -146,4 +163,8 @@ impl Query for PhraseQuery {
             visitor(term, true);
         }
     }
+
+    fn query_phrases<'a>(&'a self, visitor: &mut dyn FnMut(&'a [(usize, Term)])) {
+        visitor(&self.phrase_terms);
+    }
 }
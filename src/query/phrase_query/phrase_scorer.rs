@@ -23,6 +23,10 @@ impl<TPostings: Postings> PostingsWithOffset<TPostings> {
     pub fn positions(&mut self, output: &mut Vec<u32>) {
         self.postings.positions_with_offset(self.offset, output)
     }
+
+    pub fn term_freq(&self) -> u32 {
+        self.postings.term_freq()
+    }
 }
 
 impl<TPostings: Postings> DocSet for PostingsWithOffset<TPostings> {
@@ -55,6 +59,8 @@ pub struct PhraseScorer<TPostings: Postings> {
     left_slops: Vec<u8>,
     positions_buffer: Vec<u32>,
     slops_buffer: Vec<u8>,
+    // The order in which the positions of the terms are intersected.
+    term_ords: Vec<usize>,
 }
 
 /// Returns true if and only if the two sorted arrays contain a common element
@@ -393,6 +399,7 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
             left_slops: Vec::with_capacity(100),
             slops_buffer: Vec::with_capacity(100),
             positions_buffer: Vec::with_capacity(100),
+            term_ords: (0..num_docsets).collect(),
         };
         if scorer.doc() != TERMINATED && !scorer.phrase_match() {
             scorer.advance();
@@ -459,9 +466,18 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
     }
 
     fn compute_phrase_match(&mut self) {
+        if !self.has_slop() {
+            // Without slop, the positions can be intersected in any order: starting with the
+            // terms having the fewest occurrences in the doc keeps the intersection small, and
+            // makes it possible to stop early.
+            let intersection_docset = &mut self.intersection_docset;
+            self.term_ords.sort_unstable_by_key(|&ord| {
+                intersection_docset.docset_mut_specialized(ord).term_freq()
+            });
+        }
         {
             self.intersection_docset
-                .docset_mut_specialized(0)
+                .docset_mut_specialized(self.term_ords[0])
                 .positions(&mut self.left_positions);
             if self.has_slop() {
                 self.left_slops.clear();
@@ -470,7 +486,7 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
         for i in 1..self.num_terms - 1 {
             {
                 self.intersection_docset
-                    .docset_mut_specialized(i)
+                    .docset_mut_specialized(self.term_ords[i])
                     .positions(&mut self.right_positions);
             }
             if self.has_slop() {
@@ -500,7 +516,7 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
             }
         }
         self.intersection_docset
-            .docset_mut_specialized(self.num_terms - 1)
+            .docset_mut_specialized(self.term_ords[self.num_terms - 1])
             .positions(&mut self.right_positions);
     }
 
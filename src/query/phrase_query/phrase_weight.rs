@@ -1,17 +1,54 @@
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+
 use super::PhraseScorer;
 use crate::core::SegmentReader;
 use crate::fieldnorm::FieldNormReader;
-use crate::postings::SegmentPostings;
+use crate::postings::{SegmentPostings, TermInfo};
 use crate::query::bm25::Bm25Weight;
 use crate::query::explanation::does_not_match;
 use crate::query::{EmptyScorer, Explanation, Scorer, Weight};
 use crate::schema::{IndexRecordOption, Term};
-use crate::{DocId, DocSet, Score};
+use crate::{DocId, DocSet, Score, SegmentId};
+
+/// Number of segments whose term infos are kept by a [`TermInfosCache`].
+const TERM_INFOS_CACHE_NUM_SEGMENTS: usize = 128;
+
+/// The term infos of the terms of a phrase, per segment, or `None` if one of the terms is absent
+/// from the segment.
+///
+/// The cache is shared by the clones of a phrase query, so that running the same phrase again
+/// does not look its terms up in the term dictionaries of the segments again.
+pub(crate) struct TermInfosCache {
+    segments: Mutex<LruCache<SegmentId, Option<Arc<[TermInfo]>>>>,
+}
+
+impl Default for TermInfosCache {
+    fn default() -> TermInfosCache {
+        let capacity = NonZeroUsize::new(TERM_INFOS_CACHE_NUM_SEGMENTS).unwrap();
+        TermInfosCache {
+            segments: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+}
+
+impl TermInfosCache {
+    fn get(&self, segment_id: SegmentId) -> Option<Option<Arc<[TermInfo]>>> {
+        self.segments.lock().unwrap().get(&segment_id).cloned()
+    }
+
+    fn put(&self, segment_id: SegmentId, term_infos: Option<Arc<[TermInfo]>>) {
+        self.segments.lock().unwrap().put(segment_id, term_infos);
+    }
+}
 
 pub struct PhraseWeight {
     phrase_terms: Vec<(usize, Term)>,
     similarity_weight_opt: Option<Bm25Weight>,
     slop: u32,
+    term_infos_cache: Arc<TermInfosCache>,
 }
 
 impl PhraseWeight {
@@ -26,9 +63,35 @@ impl PhraseWeight {
             phrase_terms,
             similarity_weight_opt,
             slop,
+            term_infos_cache: Arc::default(),
         }
     }
 
+    pub(crate) fn set_term_infos_cache(&mut self, term_infos_cache: Arc<TermInfosCache>) {
+        self.term_infos_cache = term_infos_cache;
+    }
+
+    /// Returns the term infos of the phrase terms in the segment, or `None` if one of them is
+    /// absent from the segment.
+    fn term_infos(&self, reader: &SegmentReader) -> crate::Result<Option<Arc<[TermInfo]>>> {
+        let segment_id = reader.segment_id();
+        if let Some(term_infos) = self.term_infos_cache.get(segment_id) {
+            return Ok(term_infos);
+        }
+        let mut term_infos = Vec::with_capacity(self.phrase_terms.len());
+        for (_, term) in &self.phrase_terms {
+            let Some(term_info) = reader.inverted_index(term.field())?.get_term_info(term)? else {
+                self.term_infos_cache.put(segment_id, None);
+                return Ok(None);
+            };
+            term_infos.push(term_info);
+        }
+        let term_infos: Arc<[TermInfo]> = term_infos.into();
+        self.term_infos_cache
+            .put(segment_id, Some(term_infos.clone()));
+        Ok(Some(term_infos))
+    }
+
     fn fieldnorm_reader(&self, reader: &SegmentReader) -> crate::Result<FieldNormReader> {
         let field = self.phrase_terms[0].1.field();
         if self.similarity_weight_opt.is_some() {
@@ -49,29 +112,15 @@ impl PhraseWeight {
             .as_ref()
             .map(|similarity_weight| similarity_weight.boost_by(boost));
         let fieldnorm_reader = self.fieldnorm_reader(reader)?;
-        let mut term_postings_list = Vec::new();
-        if reader.has_deletes() {
-            for &(offset, ref term) in &self.phrase_terms {
-                if let Some(postings) = reader
-                    .inverted_index(term.field())?
-                    .read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
-                {
-                    term_postings_list.push((offset, postings));
-                } else {
-                    return Ok(None);
-                }
-            }
-        } else {
-            for &(offset, ref term) in &self.phrase_terms {
-                if let Some(postings) = reader
-                    .inverted_index(term.field())?
-                    .read_postings_no_deletes(term, IndexRecordOption::WithFreqsAndPositions)?
-                {
-                    term_postings_list.push((offset, postings));
-                } else {
-                    return Ok(None);
-                }
-            }
+        let Some(term_infos) = self.term_infos(reader)? else {
+            return Ok(None);
+        };
+        let inverted_index = reader.inverted_index(self.phrase_terms[0].1.field())?;
+        let mut term_postings_list = Vec::with_capacity(self.phrase_terms.len());
+        for (&(offset, _), term_info) in self.phrase_terms.iter().zip(term_infos.iter()) {
+            let postings = inverted_index
+                .read_postings_from_terminfo(term_info, IndexRecordOption::WithFreqsAndPositions)?;
+            term_postings_list.push((offset, postings));
         }
         Ok(Some(PhraseScorer::new(
             term_postings_list,
@@ -0,0 +1,303 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::docset::BUFFER_LEN;
+use crate::fastfield::AliveBitSet;
+use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::{DocId, DocSet, Score, SegmentReader, TERMINATED};
+
+/// Where the time of a search went, as returned by
+/// [`Searcher::search_with_profile()`](crate::Searcher::search_with_profile).
+#[derive(Clone, Debug)]
+pub struct SearchProfile {
+    /// Time spent building the weight of the query, e.g. computing the BM25 statistics of its
+    /// terms.
+    pub weight_creation: Duration,
+    /// Time spent in the collector itself, i.e. outside of the scorers, summed over the
+    /// segments.
+    pub collect: Duration,
+    /// The profile of the query.
+    pub query: QueryProfile,
+}
+
+/// Where the time spent on one node of a query went.
+///
+/// The timings of a node include the time spent in its sub-queries, and are summed over the
+/// segments.
+#[derive(Clone, Debug)]
+pub struct QueryProfile {
+    /// The `Debug` representation of the query.
+    pub query: String,
+    /// Time spent creating the scorers of the query, e.g. looking terms up in the term
+    /// dictionary and opening their posting lists.
+    pub scorer_creation: Duration,
+    /// Time spent advancing the scorers, or seeking a document.
+    pub seek: Duration,
+    /// Time spent computing scores.
+    pub score: Duration,
+    /// Number of documents the scorers went through.
+    pub num_docs: u64,
+    /// The profiles of the sub-queries, for compound queries like the
+    /// [`BooleanQuery`](crate::query::BooleanQuery).
+    pub children: Vec<QueryProfile>,
+}
+
+#[derive(Default)]
+struct NodeStats {
+    scorer_creation_nanos: AtomicU64,
+    seek_nanos: AtomicU64,
+    score_nanos: AtomicU64,
+    num_docs: AtomicU64,
+}
+
+impl NodeStats {
+    fn time<T>(counter: &AtomicU64, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let res = f();
+        counter.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        res
+    }
+
+    fn add_docs(&self, num_docs: u64) {
+        self.num_docs.fetch_add(num_docs, Ordering::Relaxed);
+    }
+
+    fn total_nanos(&self) -> u64 {
+        self.scorer_creation_nanos.load(Ordering::Relaxed)
+            + self.seek_nanos.load(Ordering::Relaxed)
+            + self.score_nanos.load(Ordering::Relaxed)
+    }
+}
+
+/// A query node being profiled.
+pub(crate) struct ProfileNode {
+    query: String,
+    stats: Arc<NodeStats>,
+    children: Vec<ProfileNode>,
+}
+
+impl ProfileNode {
+    fn new(query: &dyn Query) -> ProfileNode {
+        ProfileNode {
+            query: format!("{query:?}"),
+            stats: Arc::default(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Total time spent in the scorers of the node.
+    pub(crate) fn total_time(&self) -> Duration {
+        Duration::from_nanos(self.stats.total_nanos())
+    }
+
+    pub(crate) fn to_profile(&self) -> QueryProfile {
+        let load = |counter: &AtomicU64| Duration::from_nanos(counter.load(Ordering::Relaxed));
+        QueryProfile {
+            query: self.query.clone(),
+            scorer_creation: load(&self.stats.scorer_creation_nanos),
+            seek: load(&self.stats.seek_nanos),
+            score: load(&self.stats.score_nanos),
+            num_docs: self.stats.num_docs.load(Ordering::Relaxed),
+            children: self.children.iter().map(ProfileNode::to_profile).collect(),
+        }
+    }
+}
+
+thread_local! {
+    /// The nodes whose weight is being built, from the root query to the innermost sub-query,
+    /// if a profiled search is running on this thread.
+    static PROFILE_STACK: RefCell<Option<Vec<ProfileNode>>> = const { RefCell::new(None) };
+}
+
+/// Clears the profile stack, even if building the weight panicked.
+struct ProfileStackGuard;
+
+impl Drop for ProfileStackGuard {
+    fn drop(&mut self) {
+        PROFILE_STACK.with(|stack| stack.borrow_mut().take());
+    }
+}
+
+/// Builds the weight of `query`, with all of the sub-queries built through
+/// [`sub_query_weight()`] being profiled.
+pub(crate) fn profiled_weight(
+    query: &dyn Query,
+    enable_scoring: EnableScoring<'_>,
+) -> crate::Result<(Box<dyn Weight>, ProfileNode)> {
+    let root = ProfileNode::new(query);
+    let stats = root.stats.clone();
+    let _guard = ProfileStackGuard;
+    PROFILE_STACK.with(|stack| *stack.borrow_mut() = Some(vec![root]));
+    let weight = query.weight(enable_scoring)?;
+    let root = PROFILE_STACK
+        .with(|stack| stack.borrow_mut().take())
+        .and_then(|mut stack| stack.pop())
+        .expect("the root node should remain on the profile stack");
+    Ok((Box::new(ProfiledWeight { weight, stats }), root))
+}
+
+/// Builds the weight of a sub-query.
+///
+/// Compound queries should use this function, rather than calling [`Query::weight()`]
+/// directly, so that their sub-queries show up in the profile of profiled searches.
+pub(crate) fn sub_query_weight(
+    query: &dyn Query,
+    enable_scoring: EnableScoring<'_>,
+) -> crate::Result<Box<dyn Weight>> {
+    let stats_opt = PROFILE_STACK.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        let stack = stack.as_mut()?;
+        let node = ProfileNode::new(query);
+        let stats = node.stats.clone();
+        stack.push(node);
+        Some(stats)
+    });
+    let Some(stats) = stats_opt else {
+        return query.weight(enable_scoring);
+    };
+    let weight_res = query.weight(enable_scoring);
+    PROFILE_STACK.with(|stack| {
+        if let Some(stack) = stack.borrow_mut().as_mut() {
+            if let Some(node) = stack.pop() {
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            }
+        }
+    });
+    let weight = weight_res?;
+    Ok(Box::new(ProfiledWeight { weight, stats }))
+}
+
+/// Records the time spent in the scorers of the wrapped weight.
+///
+/// The scorers it returns hide the actual scorers, which disables some optimizations of
+/// compound queries (e.g. block WAND), so that the profile of a query is only indicative of
+/// the cost of a regular search.
+struct ProfiledWeight {
+    weight: Box<dyn Weight>,
+    stats: Arc<NodeStats>,
+}
+
+impl Weight for ProfiledWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let scorer = NodeStats::time(&self.stats.scorer_creation_nanos, || {
+            self.weight.scorer(reader, boost)
+        })?;
+        if scorer.doc() != TERMINATED {
+            self.stats.add_docs(1);
+        }
+        Ok(Box::new(ProfiledScorer {
+            scorer,
+            stats: self.stats.clone(),
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        self.weight.explain(reader, doc)
+    }
+}
+
+struct ProfiledScorer {
+    scorer: Box<dyn Scorer>,
+    stats: Arc<NodeStats>,
+}
+
+impl ProfiledScorer {
+    fn seek_with<T>(&mut self, f: impl FnOnce(&mut dyn Scorer) -> T) -> T {
+        let scorer = self.scorer.as_mut();
+        NodeStats::time(&self.stats.seek_nanos, || f(scorer))
+    }
+}
+
+impl DocSet for ProfiledScorer {
+    fn advance(&mut self) -> DocId {
+        let doc = self.seek_with(|scorer| scorer.advance());
+        if doc != TERMINATED {
+            self.stats.add_docs(1);
+        }
+        doc
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        let doc = self.seek_with(|scorer| scorer.seek(target));
+        if doc != TERMINATED {
+            self.stats.add_docs(1);
+        }
+        doc
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId; BUFFER_LEN]) -> usize {
+        let num_docs = self.seek_with(|scorer| scorer.fill_buffer(buffer));
+        self.stats.add_docs(num_docs as u64);
+        num_docs
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        let count = self.seek_with(|scorer| scorer.count(alive_bitset));
+        self.stats.add_docs(count as u64);
+        count
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        let count = self.seek_with(|scorer| scorer.count_including_deleted());
+        self.stats.add_docs(count as u64);
+        count
+    }
+}
+
+impl Scorer for ProfiledScorer {
+    fn score(&mut self) -> Score {
+        let scorer = self.scorer.as_mut();
+        NodeStats::time(&self.stats.score_nanos, || scorer.score())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Count, TopDocs};
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_search_with_profile() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "the old man and the sea"))?;
+        index_writer.add_document(doc!(title => "the sun also rises"))?;
+        index_writer.add_document(doc!(title => "a farewell to arms"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("+the -sun")?;
+
+        let ((top_docs, count), profile) =
+            searcher.search_with_profile(&query, &(TopDocs::with_limit(10), Count))?;
+        assert_eq!(count, 1);
+        assert_eq!(top_docs.len(), 1);
+        let query_profile = &profile.query;
+        assert!(query_profile.query.starts_with("BooleanQuery"));
+        assert_eq!(query_profile.num_docs, 1);
+        assert_eq!(query_profile.children.len(), 2);
+        assert!(query_profile.children[0].query.contains("the"));
+        assert_eq!(query_profile.children[0].num_docs, 2);
+        assert!(query_profile.children[1].query.contains("sun"));
+        assert!(query_profile.children[1].children.is_empty());
+
+        // Regular searches are not affected.
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+}
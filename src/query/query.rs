@@ -5,7 +5,7 @@ use downcast_rs::impl_downcast;
 use super::bm25::Bm25StatisticsProvider;
 use super::Weight;
 use crate::core::searcher::Searcher;
-use crate::query::Explanation;
+use crate::query::{with_static_rank, Explanation};
 use crate::schema::Schema;
 use crate::{DocAddress, Term};
 
@@ -136,7 +136,12 @@ pub trait Query: QueryClone + Send + Sync + downcast_rs::Downcast + fmt::Debug {
 
     /// Returns an `Explanation` for the score of the document.
     fn explain(&self, searcher: &Searcher, doc_address: DocAddress) -> crate::Result<Explanation> {
-        let weight = self.weight(EnableScoring::enabled_from_searcher(searcher))?;
+        let enable_scoring = EnableScoring::enabled_from_searcher(searcher);
+        let weight = with_static_rank(
+            searcher.index(),
+            enable_scoring,
+            self.weight(enable_scoring)?,
+        );
         let reader = searcher.segment_reader(doc_address.segment_ord);
         weight.explain(reader, doc_address.doc_id)
     }
@@ -160,6 +165,12 @@ pub trait Query: QueryClone + Send + Sync + downcast_rs::Downcast + fmt::Debug {
     /// Note that there can be multiple instances of any given term
     /// in a query and deduplication must be handled by the visitor.
     fn query_terms<'a>(&'a self, _visitor: &mut dyn FnMut(&'a Term, bool)) {}
+
+    /// Extract all of the phrases of the query and pass them to the given closure.
+    ///
+    /// Each phrase is given as its terms, along with their offset in the phrase. The terms of
+    /// the phrases are also visited by [`Query::query_terms()`].
+    fn query_phrases<'a>(&'a self, _visitor: &mut dyn FnMut(&'a [(usize, Term)])) {}
 }
 
 /// Implements `box_clone`.
@@ -188,6 +199,10 @@ impl Query for Box<dyn Query> {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.as_ref().query_terms(visitor);
     }
+
+    fn query_phrases<'a>(&'a self, visitor: &mut dyn FnMut(&'a [(usize, Term)])) {
+        self.as_ref().query_phrases(visitor);
+    }
 }
 
 impl QueryClone for Box<dyn Query> {
@@ -1,4 +1,4 @@
 mod query_parser;
 
 pub mod logical_ast;
-pub use self::query_parser::{QueryParser, QueryParserError};
+pub use self::query_parser::{ParseOptions, QueryParser, QueryParserError};
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::net::{AddrParseError, IpAddr};
 use std::num::{ParseFloatError, ParseIntError};
 use std::ops::Bound;
@@ -209,6 +210,35 @@ pub struct QueryParser {
     tokenizer_manager: TokenizerManager,
     boost: FxHashMap<Field, Score>,
     fuzzy: FxHashMap<Field, Fuzzy>,
+    analyzers: FxHashMap<Field, String>,
+}
+
+/// Options overriding the defaults of a [`QueryParser`] for a single query, see
+/// [`QueryParser::parse_query_with_options()`].
+///
+/// This makes it possible to share a single parser between endpoints searching different
+/// fields, or combining the terms of a query differently.
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    default_fields: Option<Vec<Field>>,
+    conjunction_by_default: Option<bool>,
+}
+
+impl ParseOptions {
+    /// Sets the fields used to search if no field is specifically defined in the query.
+    #[must_use]
+    pub fn default_fields(mut self, default_fields: Vec<Field>) -> Self {
+        self.default_fields = Some(default_fields);
+        self
+    }
+
+    /// Sets whether the terms of the query are combined as a conjunction (`AND`) rather than a
+    /// disjunction (`OR`) by default, see [`QueryParser::set_conjunction_by_default()`].
+    #[must_use]
+    pub fn conjunction_by_default(mut self, conjunction_by_default: bool) -> Self {
+        self.conjunction_by_default = Some(conjunction_by_default);
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -263,6 +293,7 @@ impl QueryParser {
             conjunction_by_default: false,
             boost: Default::default(),
             fuzzy: Default::default(),
+            analyzers: Default::default(),
         }
     }
 
@@ -324,6 +355,24 @@ impl QueryParser {
         );
     }
 
+    /// Sets the tokenizer used to analyze the text searched in the given field, in place of the
+    /// one of its indexing options.
+    ///
+    /// For instance, setting the `raw` tokenizer for an identifier field makes the parser search
+    /// it literally. The tokenizer is looked up in the tokenizer manager of the parser, and has
+    /// to produce the terms of the index to find anything.
+    pub fn set_field_analyzer(&mut self, field: Field, tokenizer_name: &str) {
+        self.analyzers.insert(field, tokenizer_name.to_string());
+    }
+
+    /// Returns the name of the tokenizer used to analyze the text searched in `field`.
+    fn tokenizer_name<'a>(&'a self, field: Field, indexing_tokenizer: &'a str) -> &'a str {
+        self.analyzers
+            .get(&field)
+            .map(String::as_str)
+            .unwrap_or(indexing_tokenizer)
+    }
+
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
@@ -333,6 +382,43 @@ impl QueryParser {
         Ok(convert_to_query(&self.fuzzy, logical_ast))
     }
 
+    /// Parse a query, overriding the defaults of the parser with `options`.
+    ///
+    /// See [`QueryParser::parse_query()`].
+    pub fn parse_query_with_options(
+        &self,
+        query: &str,
+        options: &ParseOptions,
+    ) -> Result<Box<dyn Query>, QueryParserError> {
+        self.with_options(options).parse_query(query)
+    }
+
+    /// Parse a query leniently, overriding the defaults of the parser with `options`.
+    ///
+    /// See [`QueryParser::parse_query_lenient()`].
+    pub fn parse_query_lenient_with_options(
+        &self,
+        query: &str,
+        options: &ParseOptions,
+    ) -> (Box<dyn Query>, Vec<QueryParserError>) {
+        self.with_options(options).parse_query_lenient(query)
+    }
+
+    /// Returns the parser with the defaults overridden by `options`.
+    fn with_options(&self, options: &ParseOptions) -> Cow<'_, QueryParser> {
+        if options.default_fields.is_none() && options.conjunction_by_default.is_none() {
+            return Cow::Borrowed(self);
+        }
+        let mut query_parser = self.clone();
+        if let Some(default_fields) = &options.default_fields {
+            query_parser.default_fields = default_fields.clone();
+        }
+        if let Some(conjunction_by_default) = options.conjunction_by_default {
+            query_parser.conjunction_by_default = conjunction_by_default;
+        }
+        Cow::Owned(query_parser)
+    }
+
     /// Parse a query leniently
     ///
     /// This variant parses invalid query on a best effort basis. If some part of the query can't
@@ -456,6 +542,10 @@ impl QueryParser {
                 let val: f64 = f64::from_str(phrase)?;
                 Ok(Term::from_field_f64(field, val))
             }
+            FieldType::ScaledF64(ref scaled_options) => {
+                let val: f64 = f64::from_str(phrase)?;
+                Ok(Term::from_field_i64(field, scaled_options.scale(val)))
+            }
             FieldType::Bool(_) => {
                 let val: bool = bool::from_str(phrase)?;
                 Ok(Term::from_field_bool(field, val))
@@ -469,13 +559,14 @@ impl QueryParser {
                     // This should have been seen earlier really.
                     QueryParserError::FieldNotIndexed(field_entry.name().to_string())
                 })?;
+                let tokenizer_name = self.tokenizer_name(field, option.tokenizer());
                 let mut text_analyzer =
-                    self.tokenizer_manager
-                        .get(option.tokenizer())
-                        .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                    self.tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
+                        QueryParserError::UnknownTokenizer {
                             field: field_entry.name().to_string(),
-                            tokenizer: option.tokenizer().to_string(),
-                        })?;
+                            tokenizer: tokenizer_name.to_string(),
+                        }
+                    })?;
                 let mut terms: Vec<Term> = Vec::new();
                 let mut token_stream = text_analyzer.token_stream(phrase);
                 token_stream.process(&mut |token| {
@@ -548,14 +639,20 @@ impl QueryParser {
                 let f64_term = Term::from_field_f64(field, val);
                 Ok(vec![LogicalLiteral::Term(f64_term)])
             }
+            FieldType::ScaledF64(ref scaled_options) => {
+                let val: f64 = f64::from_str(phrase)?;
+                let i64_term = Term::from_field_i64(field, scaled_options.scale(val));
+                Ok(vec![LogicalLiteral::Term(i64_term)])
+            }
             FieldType::Bool(_) => {
                 let val: bool = bool::from_str(phrase)?;
                 let bool_term = Term::from_field_bool(field, val);
                 Ok(vec![LogicalLiteral::Term(bool_term)])
             }
-            FieldType::Date(_) => {
+            FieldType::Date(ref date_options) => {
                 let dt = OffsetDateTime::parse(phrase, &Rfc3339)?;
-                let dt_term = Term::from_field_date(field, DateTime::from_utc(dt));
+                let dt = DateTime::from_utc(dt).truncate(date_options.get_indexed_precision());
+                let dt_term = Term::from_field_date(field, dt);
                 Ok(vec![LogicalLiteral::Term(dt_term)])
             }
             FieldType::Str(ref str_options) => {
@@ -563,12 +660,13 @@ impl QueryParser {
                     // This should have been seen earlier really.
                     QueryParserError::FieldNotIndexed(field_name.to_string())
                 })?;
-                let mut text_analyzer = self
-                    .tokenizer_manager
-                    .get(indexing_options.tokenizer())
-                    .ok_or_else(|| QueryParserError::UnknownTokenizer {
-                        field: field_name.to_string(),
-                        tokenizer: indexing_options.tokenizer().to_string(),
+                let tokenizer_name = self.tokenizer_name(field, indexing_options.tokenizer());
+                let mut text_analyzer =
+                    self.tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
+                        QueryParserError::UnknownTokenizer {
+                            field: field_name.to_string(),
+                            tokenizer: tokenizer_name.to_string(),
+                        }
                     })?;
                 Ok(generate_literals_for_str(
                     field_name,
@@ -588,6 +686,7 @@ impl QueryParser {
                 json_path,
                 phrase,
                 &self.tokenizer_manager,
+                self.analyzers.get(&field).map(String::as_str),
                 json_options,
             ),
             FieldType::Facet(_) => match Facet::from_text(phrase) {
@@ -629,7 +728,12 @@ impl QueryParser {
         if bound.term_str() == "*" {
             return Ok(Bound::Unbounded);
         }
-        let term = self.compute_boundary_term(field, json_path, bound.term_str())?;
+        let mut term = self.compute_boundary_term(field, json_path, bound.term_str())?;
+        if let FieldType::ScaledF64(_) = self.schema.get_field_entry(field).field_type() {
+            // The bounds of scaled fields are kept unscaled, and the `RangeQuery` maps them
+            // to the scaled values without rounding.
+            term = Term::from_field_f64(field, f64::from_str(bound.term_str())?);
+        }
         match *bound {
             UserInputBound::Inclusive(_) => Ok(Bound::Included(term)),
             UserInputBound::Exclusive(_) => Ok(Bound::Excluded(term)),
@@ -793,7 +897,10 @@ impl QueryParser {
                     .split_full_path(&full_path)
                     .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.clone())));
                 let field_entry = self.schema.get_field_entry(field);
-                let value_type = field_entry.field_type().value_type();
+                let value_type = match field_entry.field_type() {
+                    FieldType::ScaledF64(_) => Type::F64,
+                    field_type => field_type.value_type(),
+                };
                 let mut errors = Vec::new();
                 let lower = match self.resolve_bound(field, json_path, &lower) {
                     Ok(bound) => bound,
@@ -951,18 +1058,20 @@ fn generate_literals_for_json_object(
     json_path: &str,
     phrase: &str,
     tokenizer_manager: &TokenizerManager,
+    tokenizer_override: Option<&str>,
     json_options: &JsonObjectOptions,
 ) -> Result<Vec<LogicalLiteral>, QueryParserError> {
     let text_options = json_options.get_text_indexing_options().ok_or_else(|| {
         // This should have been seen earlier really.
         QueryParserError::FieldNotIndexed(field_name.to_string())
     })?;
-    let mut text_analyzer = tokenizer_manager
-        .get(text_options.tokenizer())
-        .ok_or_else(|| QueryParserError::UnknownTokenizer {
+    let tokenizer_name = tokenizer_override.unwrap_or(text_options.tokenizer());
+    let mut text_analyzer = tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
+        QueryParserError::UnknownTokenizer {
             field: field_name.to_string(),
-            tokenizer: text_options.tokenizer().to_string(),
-        })?;
+            tokenizer: tokenizer_name.to_string(),
+        }
+    })?;
     let index_record_option = text_options.index_option();
     let mut logical_literals = Vec::new();
     let mut term = Term::with_capacity(100);
@@ -1026,7 +1135,7 @@ mod test {
     use matches::assert_matches;
 
     use super::super::logical_ast::*;
-    use super::{QueryParser, QueryParserError};
+    use super::{ParseOptions, QueryParser, QueryParserError};
     use crate::query::Query;
     use crate::schema::{
         FacetOptions, Field, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions, FAST,
@@ -1119,6 +1228,38 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_query_with_options() {
+        let query_parser = make_query_parser();
+        let title_field = make_schema().get_field("title").unwrap();
+        let options = ParseOptions::default()
+            .default_fields(vec![title_field])
+            .conjunction_by_default(true);
+        let query = query_parser
+            .parse_query_with_options("a b", &options)
+            .unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "BooleanQuery { subqueries: [(Must, TermQuery(Term(field=0, type=Str, \"a\"))), \
+             (Must, TermQuery(Term(field=0, type=Str, \"b\")))] }"
+        );
+        let (query, errors) = query_parser.parse_query_lenient_with_options("a", &options);
+        assert!(errors.is_empty());
+        assert_eq!(
+            format!("{query:?}"),
+            "TermQuery(Term(field=0, type=Str, \"a\"))"
+        );
+        // The parser itself is left unchanged.
+        let query = query_parser
+            .parse_query_with_options("a", &ParseOptions::default())
+            .unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "BooleanQuery { subqueries: [(Should, TermQuery(Term(field=0, type=Str, \"a\"))), \
+             (Should, TermQuery(Term(field=1, type=Str, \"a\")))] }"
+        );
+    }
+
     #[test]
     pub fn test_parse_query_with_boost() {
         let mut query_parser = make_query_parser();
@@ -1132,6 +1273,29 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_query_with_field_analyzer() {
+        let mut query_parser = make_query_parser();
+        let schema = make_schema();
+        let text_field = schema.get_field("text").unwrap();
+        query_parser.set_field_analyzer(text_field, "raw");
+        let query = query_parser.parse_query("text:\"Hello World\"").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            r#"TermQuery(Term(field=1, type=Str, "Hello World"))"#
+        );
+        let query = query_parser.parse_query("title:\"Hello World\"").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            r#"PhraseQuery { field: Field(0), phrase_terms: [(0, Term(field=0, type=Str, "hello")), (1, Term(field=0, type=Str, "world"))], slop: 0 }"#
+        );
+        query_parser.set_field_analyzer(text_field, "unknown");
+        assert!(matches!(
+            query_parser.parse_query("text:hello"),
+            Err(QueryParserError::UnknownTokenizer { tokenizer, .. }) if tokenizer == "unknown"
+        ));
+    }
+
     #[test]
     pub fn test_parse_query_range_with_boost() {
         let query = make_query_parser().parse_query("title:[A TO B]").unwrap();
@@ -2,7 +2,7 @@ use std::io;
 use std::net::Ipv6Addr;
 use std::ops::{Bound, Range};
 
-use columnar::MonotonicallyMappableToU128;
+use columnar::{MonotonicallyMappableToU128, MonotonicallyMappableToU64};
 use common::{BinarySerializable, BitSet};
 
 use super::map_bound;
@@ -13,7 +13,9 @@ use crate::query::explanation::does_not_match;
 use crate::query::range_query::range_query_ip_fastfield::IPFastFieldRangeWeight;
 use crate::query::range_query::{is_type_valid_for_fastfield_range_query, map_bound_res};
 use crate::query::{BitSetDocSet, ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight};
-use crate::schema::{Field, IndexRecordOption, Term, Type};
+use crate::schema::{
+    DateTimePrecision, Field, FieldType, IndexRecordOption, Term, Type, DATE_TIME_PRECISION_INDEXED,
+};
 use crate::termdict::{TermDictionary, TermStreamer};
 use crate::{DateTime, DocId, Score};
 
@@ -151,6 +153,9 @@ impl RangeQuery {
     /// The two `Bound` arguments make it possible to create more complex
     /// ranges than semi-inclusive range.
     ///
+    /// The field can also be a `scaled_f64` field, in which case the bounds are compared to the
+    /// values of the field as they were indexed, i.e. rounded to the precision of the field.
+    ///
     /// If the field is not of the type `f64`, tantivy
     /// will panic when the `Weight` object is created.
     pub fn new_f64_bounds(
@@ -259,6 +264,41 @@ impl RangeQuery {
         }
     }
 
+    /// Create a new `RangeQuery` over a `date` field, comparing dates at the given precision.
+    ///
+    /// Both the bounds and the values of the field are truncated to `precision` before being
+    /// compared. For instance, with [`DateTimePrecision::Days`], an included upper bound
+    /// matches all of the values of its day. Precisions finer than
+    /// [`DATE_TIME_PRECISION_INDEXED`] are not supported, and fall back to it.
+    ///
+    /// If the field is not of the type `date`, tantivy
+    /// will panic when the `Weight` object is created.
+    pub fn new_date_bounds_with_precision(
+        field: String,
+        lower_bound: Bound<DateTime>,
+        upper_bound: Bound<DateTime>,
+        precision: DateTimePrecision,
+    ) -> RangeQuery {
+        let precision = precision.min(DATE_TIME_PRECISION_INDEXED);
+        let next_unit = |val: &DateTime| {
+            let truncated = val.truncate(precision).into_timestamp_nanos();
+            DateTime::from_timestamp_nanos(truncated + precision_nanos(precision))
+        };
+        // `trunc(val) >= trunc(bound)` is equivalent to `val >= trunc(bound)`, and
+        // `trunc(val) > trunc(bound)` to `val >= trunc(bound) + unit`.
+        let lower_bound = match lower_bound {
+            Bound::Included(val) => Bound::Included(val.truncate(precision)),
+            Bound::Excluded(val) => Bound::Included(next_unit(&val)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let upper_bound = match upper_bound {
+            Bound::Included(val) => Bound::Excluded(next_unit(&val)),
+            Bound::Excluded(val) => Bound::Excluded(val.truncate(precision)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        RangeQuery::new_date_bounds(field, lower_bound, upper_bound)
+    }
+
     /// Create a new `RangeQuery` over a `date` field.
     ///
     /// If the field is not of the type `date`, tantivy
@@ -334,6 +374,24 @@ impl Query for RangeQuery {
         let field_type = schema
             .get_field_entry(schema.get_field(&self.field)?)
             .field_type();
+        if let FieldType::ScaledF64(scaled_options) = field_type {
+            if self.value_type == Type::F64 {
+                // Ranges over the values of a scaled field are run over the scaled values.
+                let to_f64 = |data: &Vec<u8>| {
+                    f64::from_u64(u64::from_be(
+                        BinarySerializable::deserialize(&mut &data[..]).unwrap(),
+                    ))
+                };
+                let (lower_bound, upper_bound) = scaled_options.scale_bounds(
+                    map_bound(&self.lower_bound, to_f64),
+                    map_bound(&self.upper_bound, to_f64),
+                );
+                let mut scaled_query =
+                    RangeQuery::new_i64_bounds(self.field.clone(), lower_bound, upper_bound);
+                scaled_query.limit = self.limit;
+                return scaled_query.weight(enable_scoring);
+            }
+        }
         let value_type = field_type.value_type();
         if value_type != self.value_type {
             let err_msg = format!(
@@ -379,16 +437,81 @@ impl Query for RangeQuery {
                 )))
             }
         } else {
+            let (lower_bound, upper_bound) = match field_type {
+                FieldType::Date(date_options)
+                    if date_options.get_indexed_precision() < DATE_TIME_PRECISION_INDEXED =>
+                {
+                    truncate_date_bounds(
+                        &self.lower_bound,
+                        &self.upper_bound,
+                        date_options.get_indexed_precision(),
+                    )
+                }
+                _ => (self.lower_bound.clone(), self.upper_bound.clone()),
+            };
             Ok(Box::new(RangeWeight {
                 field: self.field.to_string(),
-                lower_bound: self.lower_bound.clone(),
-                upper_bound: self.upper_bound.clone(),
+                lower_bound,
+                upper_bound,
                 limit: self.limit,
             }))
         }
     }
 }
 
+/// Returns the duration of one unit of a precision, in nanoseconds.
+fn precision_nanos(precision: DateTimePrecision) -> i64 {
+    match precision {
+        DateTimePrecision::Days => 24 * 3_600 * 1_000_000_000,
+        DateTimePrecision::Hours => 3_600 * 1_000_000_000,
+        DateTimePrecision::Minutes => 60 * 1_000_000_000,
+        DateTimePrecision::Seconds => 1_000_000_000,
+        DateTimePrecision::Milliseconds => 1_000_000,
+        DateTimePrecision::Microseconds => 1_000,
+        DateTimePrecision::Nanoseconds => 1,
+    }
+}
+
+/// Maps the bounds of a date range to the terms of a field indexed with a coarse precision.
+///
+/// The resulting range matches all of the terms whose unit intersects the original range.
+fn truncate_date_bounds(
+    lower_bound: &Bound<Vec<u8>>,
+    upper_bound: &Bound<Vec<u8>>,
+    precision: DateTimePrecision,
+) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    let to_date = |data: &Vec<u8>| {
+        DateTime::from_u64(u64::from_be(
+            BinarySerializable::deserialize(&mut &data[..]).unwrap(),
+        ))
+    };
+    let to_term_val = |val: DateTime| {
+        Term::from_field_date(Field::from_field_id(0), val)
+            .serialized_value_bytes()
+            .to_owned()
+    };
+    let lower_bound = match lower_bound {
+        Bound::Included(data) | Bound::Excluded(data) => {
+            Bound::Included(to_term_val(to_date(data).truncate(precision)))
+        }
+        Bound::Unbounded => Bound::Unbounded,
+    };
+    let upper_bound = match upper_bound {
+        Bound::Included(data) => Bound::Included(to_term_val(to_date(data).truncate(precision))),
+        Bound::Excluded(data) => {
+            let val = to_date(data);
+            let truncated = val.truncate(precision);
+            if truncated == val {
+                Bound::Excluded(to_term_val(truncated))
+            } else {
+                Bound::Included(to_term_val(truncated))
+            }
+        }
+        Bound::Unbounded => Bound::Unbounded,
+    };
+    (lower_bound, upper_bound)
+}
+
 pub struct RangeWeight {
     field: String,
     lower_bound: Bound<Vec<u8>>,
@@ -475,9 +598,10 @@ mod tests {
     use crate::indexer::NoMergePolicy;
     use crate::query::QueryParser;
     use crate::schema::{
-        Field, IntoIpv6Addr, Schema, TantivyDocument, FAST, INDEXED, STORED, TEXT,
+        DateOptions, DateTimePrecision, Field, IntoIpv6Addr, Schema, TantivyDocument, Value, FAST,
+        INDEXED, STORED, TEXT,
     };
-    use crate::{doc, Index, IndexWriter};
+    use crate::{DateTime, Index, IndexWriter};
 
     #[test]
     fn test_range_query_simple() -> crate::Result<()> {
@@ -676,6 +800,55 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_range_scaled_f64() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_scaled_f64_field("price", 100.0, INDEXED | STORED);
+        let price_fast = schema_builder.add_scaled_f64_field("price_fast", 100.0, FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // `0.1 + 0.2` is `0.30000000000000004` as a f64, and `0.3` once scaled.
+        for val in [0.1 + 0.2, 0.3, 10.0, 10.01, 19.99, 20.0] {
+            index_writer.add_document(doc!(price => val, price_fast => val))?;
+        }
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let fast_field_reader = searcher.segment_reader(0).fast_fields();
+        let column = fast_field_reader.i64("price_fast")?;
+        assert_eq!(column.first(0), Some(30));
+        assert_eq!(column.first(3), Some(1001));
+        let stored_doc: TantivyDocument = searcher.doc(crate::DocAddress::new(0, 0))?;
+        assert_eq!(
+            stored_doc.get_first(price).and_then(|val| val.as_f64()),
+            Some(0.1 + 0.2)
+        );
+
+        for field in ["price", "price_fast"] {
+            let count = |lower: Bound<f64>, upper: Bound<f64>| {
+                let query = RangeQuery::new_f64_bounds(field.to_string(), lower, upper);
+                searcher.search(&query, &Count).unwrap()
+            };
+            assert_eq!(count(Bound::Included(0.3), Bound::Included(0.3)), 2);
+            assert_eq!(count(Bound::Included(10.0), Bound::Excluded(20.0)), 3);
+            assert_eq!(count(Bound::Excluded(10.0), Bound::Included(20.0)), 3);
+            assert_eq!(count(Bound::Included(10.005), Bound::Included(19.995)), 2);
+            assert_eq!(count(Bound::Unbounded, Bound::Excluded(10.001)), 3);
+        }
+
+        let query_parser = QueryParser::for_index(&index, vec![price]);
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("price:0.3"), 2);
+        assert_eq!(count("price:10.01"), 1);
+        assert_eq!(count("price:[10 TO 20}"), 3);
+        assert_eq!(count("price:{10.005 TO *]"), 3);
+        Ok(())
+    }
+
     #[test]
     fn test_bug_reproduce_range_query() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -700,6 +873,92 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_date_range_query_with_precision() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let day_field = schema_builder.add_date_field(
+            "day",
+            DateOptions::from(INDEXED).set_indexed_precision(DateTimePrecision::Days),
+        );
+        let date_field = schema_builder.add_date_field("date", INDEXED);
+        let fast_date_field = schema_builder.add_date_field("fast_date", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let hour = 3_600;
+        let day = 24 * hour;
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            // Two documents per day, at 06:00 and 18:00, over 10 days.
+            for timestamp_secs in (0..10).flat_map(|d| [d * day + 6 * hour, d * day + 18 * hour]) {
+                let date = DateTime::from_timestamp_secs(timestamp_secs);
+                index_writer.add_document(doc!(
+                    day_field => date,
+                    date_field => date,
+                    fast_date_field => date,
+                ))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(
+            segment_reader
+                .inverted_index(day_field)?
+                .terms()
+                .num_terms(),
+            10
+        );
+        assert_eq!(
+            segment_reader
+                .inverted_index(date_field)?
+                .terms()
+                .num_terms(),
+            20
+        );
+
+        // From day 2 at 12:00 to day 4 at 12:00.
+        let lower = DateTime::from_timestamp_secs(2 * day + 12 * hour);
+        let upper = DateTime::from_timestamp_secs(4 * day + 12 * hour);
+        let count = |query: RangeQuery| searcher.search(&query, &Count);
+
+        let query = |field: &str| {
+            RangeQuery::new_date_bounds(
+                field.to_string(),
+                Bound::Included(lower),
+                Bound::Excluded(upper),
+            )
+        };
+        assert_eq!(count(query("date"))?, 4);
+        assert_eq!(count(query("fast_date"))?, 4);
+        // The terms of the days intersecting the range match.
+        assert_eq!(count(query("day"))?, 6);
+
+        let query_days = |field: &str| {
+            RangeQuery::new_date_bounds_with_precision(
+                field.to_string(),
+                Bound::Included(lower),
+                Bound::Included(upper),
+                DateTimePrecision::Days,
+            )
+        };
+        assert_eq!(count(query_days("date"))?, 6);
+        assert_eq!(count(query_days("fast_date"))?, 6);
+        assert_eq!(count(query_days("day"))?, 6);
+
+        let query_days_excluded = RangeQuery::new_date_bounds_with_precision(
+            "fast_date".to_string(),
+            Bound::Excluded(lower),
+            Bound::Excluded(upper),
+            DateTimePrecision::Days,
+        );
+        assert_eq!(count(query_days_excluded)?, 2);
+
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let query = query_parser.parse_query("day:\"1970-01-03T23:59:59Z\"")?;
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        Ok(())
+    }
+
     #[test]
     fn search_ip_range_test_posting_list() {
         search_ip_range_test_opt(false);
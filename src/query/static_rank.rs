@@ -0,0 +1,245 @@
+use std::fmt;
+use std::sync::Arc;
+
+use columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+
+use crate::docset::BUFFER_LEN;
+use crate::fastfield::AliveBitSet;
+use crate::query::{EnableScoring, Explanation, Scorer, Weight};
+use crate::schema::{Schema, Type};
+use crate::{DocId, DocSet, Index, Score, SegmentReader, TantivyError};
+
+type BlendFn = dyn Fn(Score, f64) -> Score + Send + Sync;
+
+/// A query-independent score, read from a fast field, blended into the score of every
+/// document matched by a search.
+///
+/// This is typically used for priors like PageRank or popularity. Once the static rank is set
+/// on the index with [`Index::set_static_rank`], the searches of the [`Searcher`](crate::Searcher)
+/// apply it to all queries, without wrapping them.
+///
+/// The static rank field has to be a `u64`, `i64` or `f64` fast field. Documents without a value
+/// have a static rank of `0`.
+#[derive(Clone)]
+pub struct StaticRank {
+    field_name: String,
+    blend: Arc<BlendFn>,
+}
+
+impl StaticRank {
+    /// Creates a static rank read from the fast field `field_name`.
+    ///
+    /// `blend` computes the final score of a document given the score of the query and the
+    /// static rank of the document.
+    pub fn new<F>(field_name: &str, blend: F) -> StaticRank
+    where F: Fn(Score, f64) -> Score + Send + Sync + 'static {
+        StaticRank {
+            field_name: field_name.to_string(),
+            blend: Arc::new(blend),
+        }
+    }
+
+    /// Creates a static rank adding `weight * static_rank` to the score of the query.
+    pub fn linear(field_name: &str, weight: Score) -> StaticRank {
+        StaticRank::new(field_name, move |score, static_rank| {
+            score + weight * static_rank as Score
+        })
+    }
+
+    /// Returns the name of the fast field holding the static rank.
+    pub fn field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    /// Checks that the static rank field is a numerical fast field of the schema.
+    pub(crate) fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        let field = schema.get_field(&self.field_name)?;
+        let field_entry = schema.get_field_entry(field);
+        let is_numerical = matches!(
+            field_entry.field_type().value_type(),
+            Type::U64 | Type::I64 | Type::F64
+        );
+        if !is_numerical || !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "The static rank field `{}` is not a u64, i64 or f64 fast field.",
+                self.field_name
+            )));
+        }
+        Ok(())
+    }
+
+    fn static_rank_column(&self, reader: &SegmentReader) -> crate::Result<StaticRankColumn> {
+        let column_opt = reader.fast_fields().u64_lenient_for_type(
+            Some(&[ColumnType::U64, ColumnType::I64, ColumnType::F64]),
+            &self.field_name,
+        )?;
+        Ok(StaticRankColumn { column_opt })
+    }
+}
+
+impl fmt::Debug for StaticRank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StaticRank")
+            .field("field_name", &self.field_name)
+            .finish()
+    }
+}
+
+/// Wraps `weight` so that the static rank of the index, if any, is blended into its scores.
+pub(crate) fn with_static_rank(
+    index: &Index,
+    enable_scoring: EnableScoring<'_>,
+    weight: Box<dyn Weight>,
+) -> Box<dyn Weight> {
+    match index.static_rank() {
+        Some(static_rank) if enable_scoring.is_scoring_enabled() => Box::new(StaticRankWeight {
+            weight,
+            static_rank: static_rank.clone(),
+        }),
+        _ => weight,
+    }
+}
+
+struct StaticRankColumn {
+    column_opt: Option<(Column<u64>, ColumnType)>,
+}
+
+impl StaticRankColumn {
+    fn get(&self, doc: DocId) -> f64 {
+        let Some((column, column_type)) = &self.column_opt else {
+            return 0.0;
+        };
+        let Some(val) = column.first(doc) else {
+            return 0.0;
+        };
+        match column_type {
+            ColumnType::I64 => i64::from_u64(val) as f64,
+            ColumnType::F64 => f64::from_u64(val),
+            _ => val as f64,
+        }
+    }
+}
+
+struct StaticRankWeight {
+    weight: Box<dyn Weight>,
+    static_rank: StaticRank,
+}
+
+impl Weight for StaticRankWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let underlying = self.weight.scorer(reader, boost)?;
+        let column = self.static_rank.static_rank_column(reader)?;
+        Ok(Box::new(StaticRankScorer {
+            underlying,
+            column,
+            blend: self.static_rank.blend.clone(),
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let underlying_explanation = self.weight.explain(reader, doc)?;
+        let static_rank = self.static_rank.static_rank_column(reader)?.get(doc);
+        let score = (self.static_rank.blend)(underlying_explanation.value(), static_rank);
+        let mut explanation = Explanation::new_with_string(
+            format!(
+                "Blend with static rank {static_rank} of field {}",
+                self.static_rank.field_name
+            ),
+            score,
+        );
+        explanation.add_detail(underlying_explanation);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+struct StaticRankScorer {
+    underlying: Box<dyn Scorer>,
+    column: StaticRankColumn,
+    blend: Arc<BlendFn>,
+}
+
+impl DocSet for StaticRankScorer {
+    fn advance(&mut self) -> DocId {
+        self.underlying.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.underlying.seek(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId; BUFFER_LEN]) -> usize {
+        self.underlying.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        self.underlying.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.underlying.size_hint()
+    }
+
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        self.underlying.count(alive_bitset)
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        self.underlying.count_including_deleted()
+    }
+}
+
+impl Scorer for StaticRankScorer {
+    fn score(&mut self) -> Score {
+        let static_rank = self.column.get(self.doc());
+        (self.blend)(self.underlying.score(), static_rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StaticRank;
+    use crate::collector::TopDocs;
+    use crate::query::{Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_static_rank() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let popularity = schema_builder.add_f64_field("popularity", FAST);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello hello", popularity => 0.0))?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.add_document(doc!(text => "hello", popularity => 10.0))?;
+        index_writer.commit()?;
+        assert!(index
+            .set_static_rank(StaticRank::linear("text", 1.0))
+            .is_err());
+        index.set_static_rank(StaticRank::linear("popularity", 0.5))?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::WithFreqs,
+        );
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        let doc_ids: Vec<u32> = top_docs
+            .iter()
+            .map(|(_, doc_address)| doc_address.doc_id)
+            .collect();
+        assert_eq!(doc_ids, vec![2, 0, 1]);
+        let explanation = query.explain(&searcher, DocAddress::new(0, 2))?;
+        assert!((explanation.value() - top_docs[0].0).abs() < 0.0001);
+
+        index.clear_static_rank();
+        let searcher = index.reader()?.searcher();
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+        Ok(())
+    }
+}
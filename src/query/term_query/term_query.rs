@@ -2,7 +2,7 @@ use std::fmt;
 
 use super::term_weight::TermWeight;
 use crate::query::bm25::Bm25Weight;
-use crate::query::{EnableScoring, Explanation, Query, Weight};
+use crate::query::{EnableScoring, Query, Weight};
 use crate::schema::IndexRecordOption;
 use crate::Term;
 
@@ -100,9 +100,7 @@ impl TermQuery {
                 statistics_provider,
                 ..
             } => Bm25Weight::for_terms(statistics_provider, &[self.term.clone()])?,
-            EnableScoring::Disabled { .. } => {
-                Bm25Weight::new(Explanation::new("<no score>", 1.0f32), 1.0f32)
-            }
+            EnableScoring::Disabled { .. } => Bm25Weight::no_score(),
         };
         let scoring_enabled = enable_scoring.is_scoring_enabled();
         let index_record_option = if scoring_enabled {
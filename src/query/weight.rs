@@ -96,6 +96,22 @@ pub trait Weight: Send + Sync + 'static {
         Ok(())
     }
 
+    /// Iterates through all of the documents matched by the weight, along with the clauses of
+    /// the query they match.
+    ///
+    /// The clauses are reported as a bitmask, the `i`-th bit being set if the document matches
+    /// the `i`-th clause, which makes it possible to rank documents on how much of the query
+    /// they cover (e.g. with [`u64::count_ones()`]). Only the weights of
+    /// [`BooleanQuery`](crate::query::BooleanQuery) report their first 64 clauses, the other
+    /// ones report no clause.
+    fn for_each_with_matched_clauses(
+        &self,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score, u64),
+    ) -> crate::Result<()> {
+        self.for_each(reader, &mut |doc, score| callback(doc, score, 0u64))
+    }
+
     /// Iterates through all of the document matched by the DocSet
     /// `DocSet` and push the scored documents to the collector.
     fn for_each_no_score(
@@ -11,7 +11,11 @@ use self::warming::WarmingState;
 use crate::core::searcher::{SearcherGeneration, SearcherInner};
 use crate::directory::{Directory, WatchCallback, WatchHandle, META_LOCK};
 use crate::store::DOCSTORE_CACHE_CAPACITY;
-use crate::{Index, Inventory, Searcher, SegmentReader, TrackedObject};
+use crate::time::OffsetDateTime;
+use crate::{
+    DateTime, Index, Inventory, Opstamp, Searcher, Segment, SegmentMeta, SegmentReader,
+    TrackedObject,
+};
 
 /// Defines when a new version of the index should be reloaded.
 ///
@@ -193,15 +197,33 @@ impl InnerIndexReader {
     ///
     /// This function acquires a lock to prevent GC from removing files
     /// as we are opening our index.
-    fn open_segment_readers(index: &Index) -> crate::Result<Vec<SegmentReader>> {
+    fn open_segment_readers(
+        index: &Index,
+    ) -> crate::Result<(Opstamp, Vec<SegmentMeta>, Vec<SegmentReader>)> {
         // Prevents segment files from getting deleted while we are in the process of opening them
         let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
-        let searchable_segments = index.searchable_segments()?;
+        let index_meta = index.load_metas()?;
+        let searchable_segments: Vec<Segment> = index_meta
+            .segments
+            .into_iter()
+            .map(|segment_meta| index.segment(segment_meta))
+            .collect();
+        // The clock is only read if documents can expire, as it is not available on every
+        // platform, e.g. `wasm32-unknown-unknown`.
+        let expiry = index
+            .settings()
+            .expiry_field
+            .as_deref()
+            .map(|expiry_field| (expiry_field, DateTime::from_utc(OffsetDateTime::now_utc())));
         let segment_readers = searchable_segments
             .iter()
-            .map(SegmentReader::open)
+            .map(|segment| SegmentReader::open_excluding_expired(segment, None, expiry))
             .collect::<crate::Result<_>>()?;
-        Ok(segment_readers)
+        let segment_metas = searchable_segments
+            .into_iter()
+            .map(|segment| segment.meta().clone())
+            .collect();
+        Ok((index_meta.opstamp, segment_metas, segment_readers))
     }
 
     fn track_segment_readers_in_inventory(
@@ -222,7 +244,7 @@ impl InnerIndexReader {
         searcher_generation_counter: &Arc<AtomicU64>,
         searcher_generation_inventory: &Inventory<SearcherGeneration>,
     ) -> crate::Result<Arc<SearcherInner>> {
-        let segment_readers = Self::open_segment_readers(index)?;
+        let (opstamp, segment_metas, segment_readers) = Self::open_segment_readers(index)?;
         let searcher_generation = Self::track_segment_readers_in_inventory(
             &segment_readers,
             searcher_generation_counter,
@@ -233,6 +255,8 @@ impl InnerIndexReader {
         let searcher = Arc::new(SearcherInner::new(
             schema,
             index.clone(),
+            opstamp,
+            segment_metas,
             segment_readers,
             searcher_generation,
             doc_store_cache_num_blocks,
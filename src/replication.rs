@@ -0,0 +1,770 @@
+//! Helpers to replicate an index, from a primary to replicas, by shipping its files.
+//!
+//! Segment files are immutable, so bringing a replica up to date with the primary only requires
+//! to copy the files of the segments that appeared since its last update, and then the
+//! `meta.json` file that makes them part of the index.
+//!
+//! On the primary, a [`GenerationSnapshot`] captures the last commit of the index, called a
+//! generation, and lists the files a replica needs to download to move from its own generation
+//! to it. The files of the snapshot are protected from garbage collection for as long as the
+//! snapshot is alive, so it should be kept around until the replicas are done downloading them.
+//!
+//! On a replica, [`Replica::apply()`] downloads the added files, verifies their checksums,
+//! publishes the new generation by atomically replacing `meta.json`, and deletes the files that
+//! are not used anymore. Readers opened on the replica directory then pick the new generation up
+//! like any other commit.
+//!
+//! The transport is left to the application: [`Generation`] and [`GenerationChanges`] are
+//! serializable, and the replica downloads the files through a callback.
+//!
+//! A [`BackupStore`] relies on the same snapshots to back up an index incrementally: each
+//! backup only copies the files that appeared since the previously backed-up generation.
+//!
+//! [`diff_directories()`] compares the last commits of two index directories, e.g. a primary and
+//! one of its replicas, and reports how they diverge.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use common::HasLen;
+use crc32fast::Hasher;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{IndexMeta, SegmentMetaInventory, META_FILEPATH};
+use crate::directory::error::OpenReadError;
+use crate::directory::{Directory, FileSlice, Footer, OwnedBytes, TerminatingWrite};
+use crate::error::DataCorruption;
+use crate::{Index, Opstamp, SegmentId, SegmentMeta, TantivyError};
+
+/// The file listing the backups of a [`BackupStore`].
+static BACKUP_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new("backup.json"));
+
+/// A commit of an index, along with the checksums of its files.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Generation {
+    /// The opstamp of the commit, which identifies the generation.
+    pub opstamp: Opstamp,
+    /// The content of the `meta.json` file of the commit.
+    pub meta_json: String,
+    /// The files of the segments of the commit, along with the checksum recorded in their
+    /// footer.
+    pub files: BTreeMap<PathBuf, u32>,
+}
+
+impl Generation {
+    fn new(
+        metas: &IndexMeta,
+        open_read: impl Fn(&Path) -> Result<FileSlice, OpenReadError>,
+        exists: impl Fn(&Path) -> Result<bool, OpenReadError>,
+    ) -> crate::Result<Generation> {
+        let mut meta_json = serde_json::to_string_pretty(metas)?;
+        meta_json.push('\n');
+        let mut files = BTreeMap::new();
+        for segment_meta in &metas.segments {
+            for path in segment_meta.list_files() {
+                // The list includes components that may not have been written, like the
+                // delete file of a segment without deletes.
+                if !exists(&path)? {
+                    continue;
+                }
+                let (footer, _) = Footer::extract_footer(open_read(&path)?)
+                    .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.clone()))?;
+                files.insert(path, footer.crc());
+            }
+        }
+        Ok(Generation {
+            opstamp: metas.opstamp,
+            meta_json,
+            files,
+        })
+    }
+
+    /// Returns the files to add and to remove to go from the `previous` generation, or from an
+    /// empty directory if `None`, to this one.
+    pub fn changes_since(&self, previous: Option<&Generation>) -> GenerationChanges {
+        let previous_files: BTreeSet<&PathBuf> = previous
+            .map(|previous| previous.files.keys().collect())
+            .unwrap_or_default();
+        let added_files = self
+            .files
+            .keys()
+            .filter(|path| !previous_files.contains(path))
+            .cloned()
+            .collect();
+        let removed_files = previous_files
+            .into_iter()
+            .filter(|path| !self.files.contains_key(*path))
+            .cloned()
+            .collect();
+        GenerationChanges {
+            previous_opstamp: previous.map(|previous| previous.opstamp),
+            generation: self.clone(),
+            added_files,
+            removed_files,
+        }
+    }
+}
+
+/// The files to ship to a replica to bring it from one generation to another.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerationChanges {
+    /// The opstamp of the generation the changes apply to, or `None` for an empty replica.
+    pub previous_opstamp: Option<Opstamp>,
+    /// The new generation.
+    pub generation: Generation,
+    /// The files of the new generation that are not in the previous one.
+    pub added_files: Vec<PathBuf>,
+    /// The files of the previous generation that are not in the new one.
+    pub removed_files: Vec<PathBuf>,
+}
+
+/// The last commit of a primary index, with its files protected from garbage collection.
+pub struct GenerationSnapshot {
+    index: Index,
+    generation: Generation,
+    // Living segment metas prevent the garbage collector from deleting their files.
+    _segment_metas: Vec<SegmentMeta>,
+}
+
+impl GenerationSnapshot {
+    /// Captures the last commit of `index`.
+    pub fn capture(index: &Index) -> crate::Result<GenerationSnapshot> {
+        let metas = index.load_metas()?;
+        let directory = index.directory();
+        let generation = Generation::new(
+            &metas,
+            |path| directory.open_read_with_footer(path),
+            |path| directory.exists(path),
+        )?;
+        Ok(GenerationSnapshot {
+            index: index.clone(),
+            generation,
+            _segment_metas: metas.segments,
+        })
+    }
+
+    /// Returns the captured generation.
+    pub fn generation(&self) -> &Generation {
+        &self.generation
+    }
+
+    /// Returns the files to ship to a replica at the `previous` generation, or to an empty
+    /// replica if `None`.
+    pub fn changes_since(&self, previous: Option<&Generation>) -> GenerationChanges {
+        self.generation.changes_since(previous)
+    }
+
+    /// Reads one of the files of the generation, as it is stored, footer included.
+    pub fn read_file(&self, path: &Path) -> crate::Result<OwnedBytes> {
+        if !self.generation.files.contains_key(path) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "{path:?} is not part of generation {}",
+                self.generation.opstamp
+            )));
+        }
+        let file_slice = self.index.directory().open_read_with_footer(path)?;
+        Ok(file_slice.read_bytes()?)
+    }
+}
+
+/// The replica side of the replication, writing the files shipped from the primary into a
+/// directory.
+///
+/// The replica directory should not be written to by anything else, in particular not by an
+/// [`IndexWriter`](crate::IndexWriter).
+pub struct Replica {
+    directory: Box<dyn Directory>,
+    generation: Option<Generation>,
+}
+
+impl Replica {
+    /// Opens a replica in `directory`, resuming from the generation found in it, if any.
+    pub fn open<D: Into<Box<dyn Directory>>>(directory: D) -> crate::Result<Replica> {
+        let directory = directory.into();
+        let generation = read_generation(directory.as_ref())?.map(|(generation, _)| generation);
+        Ok(Replica {
+            directory,
+            generation,
+        })
+    }
+
+    /// Returns the generation of the replica, or `None` if nothing was replicated yet.
+    ///
+    /// This is the generation to pass to the primary, to get the next changes.
+    pub fn generation(&self) -> Option<&Generation> {
+        self.generation.as_ref()
+    }
+
+    /// Applies the `changes` shipped from the primary, downloading the added files with
+    /// `fetch_file`.
+    ///
+    /// The new generation only becomes visible once all of the added files have been written
+    /// and verified, so that a failure leaves the replica at its previous generation. Applying
+    /// the same changes again after a failure is fine.
+    pub fn apply<F>(
+        &mut self,
+        changes: &GenerationChanges,
+        mut fetch_file: F,
+    ) -> crate::Result<()>
+    where
+        F: FnMut(&Path) -> crate::Result<Vec<u8>>,
+    {
+        let current_opstamp = self
+            .generation
+            .as_ref()
+            .map(|generation| generation.opstamp);
+        if changes.previous_opstamp != current_opstamp {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The changes apply to generation {:?}, but the replica is at generation {:?}",
+                changes.previous_opstamp, current_opstamp
+            )));
+        }
+        let generation = &changes.generation;
+        for path in &changes.added_files {
+            let expected_crc = *generation.files.get(path).ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "{path:?} is not part of generation {}",
+                    generation.opstamp
+                ))
+            })?;
+            let data = fetch_file(path)?;
+            verify_checksum(path, &data, expected_crc)?;
+            write_file(self.directory.as_ref(), path, &data)?;
+        }
+        self.directory.sync_directory()?;
+        self.directory
+            .atomic_write(&META_FILEPATH, generation.meta_json.as_bytes())?;
+        self.generation = Some(generation.clone());
+        for path in &changes.removed_files {
+            // Readers may still hold the files, e.g. on Windows. They are only left behind.
+            if let Err(err) = self.directory.delete(path) {
+                warn!("Failed to delete replicated file {path:?}: {err}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A generation saved in a [`BackupStore`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// The opstamp of the generation backed up before this one, or `None` for the first backup.
+    pub previous_opstamp: Option<Opstamp>,
+    /// The generation.
+    pub generation: Generation,
+    /// The files of the generation copied by this backup, the other ones having been copied by
+    /// the previous backups.
+    pub added_files: Vec<PathBuf>,
+}
+
+/// Incremental backups of an index, stored in a directory.
+///
+/// Each backup only copies the files of the segments that appeared since the previously
+/// backed-up generation, and appends a [`BackupManifest`] to the `backup.json` file of the
+/// directory. All of the backed-up generations can be restored, as segment files are never
+/// modified and are kept in the backup directory.
+pub struct BackupStore {
+    directory: Box<dyn Directory>,
+    manifests: Vec<BackupManifest>,
+}
+
+impl BackupStore {
+    /// Opens the backups stored in `directory`, which may be empty.
+    pub fn open<D: Into<Box<dyn Directory>>>(directory: D) -> crate::Result<BackupStore> {
+        let directory = directory.into();
+        let manifests = if directory.exists(&BACKUP_FILEPATH)? {
+            let data = directory.atomic_read(&BACKUP_FILEPATH)?;
+            serde_json::from_slice(&data).map_err(|err| {
+                DataCorruption::new(
+                    BACKUP_FILEPATH.to_path_buf(),
+                    format!("Backup file cannot be deserialized. {err:?}"),
+                )
+            })?
+        } else {
+            Vec::new()
+        };
+        Ok(BackupStore {
+            directory,
+            manifests,
+        })
+    }
+
+    /// Returns the backed-up generations, from the oldest to the most recent one.
+    pub fn manifests(&self) -> &[BackupManifest] {
+        &self.manifests
+    }
+
+    /// Returns the most recent backed-up generation, or `None` if nothing was backed up yet.
+    pub fn last_generation(&self) -> Option<&Generation> {
+        self.manifests.last().map(|manifest| &manifest.generation)
+    }
+
+    /// Backs up the generation captured by `snapshot`, copying the files that are not part of
+    /// the previous backup.
+    ///
+    /// The backup is only recorded once all of its files have been copied, so a failure leaves
+    /// the store at its previous generation. Backing up the last backed-up generation again
+    /// copies nothing.
+    pub fn backup(&mut self, snapshot: &GenerationSnapshot) -> crate::Result<&BackupManifest> {
+        let previous = self.last_generation();
+        if previous.map(|generation| generation.opstamp) != Some(snapshot.generation().opstamp) {
+            let changes = snapshot.changes_since(previous);
+            for path in &changes.added_files {
+                let data = snapshot.read_file(path)?;
+                write_file(self.directory.as_ref(), path, &data)?;
+            }
+            self.directory.sync_directory()?;
+            let mut manifests = self.manifests.clone();
+            manifests.push(BackupManifest {
+                previous_opstamp: changes.previous_opstamp,
+                generation: changes.generation,
+                added_files: changes.added_files,
+            });
+            let mut manifests_json = serde_json::to_string_pretty(&manifests)?;
+            manifests_json.push('\n');
+            self.directory
+                .atomic_write(&BACKUP_FILEPATH, manifests_json.as_bytes())?;
+            self.manifests = manifests;
+        }
+        Ok(self.manifests.last().unwrap())
+    }
+
+    /// Restores the backed-up generation `opstamp` into `directory`, which should be empty.
+    ///
+    /// The checksums of the files are verified as they are copied. The generation becomes
+    /// visible once all of its files have been copied.
+    pub fn restore(&self, opstamp: Opstamp, directory: &dyn Directory) -> crate::Result<()> {
+        let manifest = self
+            .manifests
+            .iter()
+            .find(|manifest| manifest.generation.opstamp == opstamp)
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!("Generation {opstamp} was not backed up"))
+            })?;
+        let generation = &manifest.generation;
+        for (path, &expected_crc) in &generation.files {
+            let data = self.directory.open_read(path)?.read_bytes()?;
+            verify_checksum(path, &data, expected_crc)?;
+            write_file(directory, path, &data)?;
+        }
+        directory.sync_directory()?;
+        directory.atomic_write(&META_FILEPATH, generation.meta_json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Writes a file, replacing it if it already exists, e.g. because a previous attempt failed
+/// after writing it.
+fn write_file(directory: &dyn Directory, path: &Path, data: &[u8]) -> crate::Result<()> {
+    if directory.exists(path)? {
+        directory
+            .delete(path)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    }
+    let mut writer = directory.open_write(path)?;
+    writer.write_all(data)?;
+    writer.terminate()?;
+    Ok(())
+}
+
+/// Reads the last commit of the index in `directory`, if there is one.
+fn read_generation(directory: &dyn Directory) -> crate::Result<Option<(Generation, IndexMeta)>> {
+    if !directory.exists(&META_FILEPATH)? {
+        return Ok(None);
+    }
+    let meta_data = directory.atomic_read(&META_FILEPATH)?;
+    let meta_string = String::from_utf8(meta_data).map_err(|_| {
+        DataCorruption::new(
+            META_FILEPATH.to_path_buf(),
+            "Meta file does not contain valid utf8 file.".to_string(),
+        )
+    })?;
+    let metas =
+        IndexMeta::deserialize(&meta_string, &SegmentMetaInventory::default()).map_err(|err| {
+            DataCorruption::new(
+                META_FILEPATH.to_path_buf(),
+                format!("Meta file cannot be deserialized. {err:?}"),
+            )
+        })?;
+    let generation = Generation::new(
+        &metas,
+        |path| directory.open_read(path),
+        |path| directory.exists(path),
+    )?;
+    Ok(Some((generation, metas)))
+}
+
+/// A difference between two index directories, reported by [`diff_directories()`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Divergence {
+    /// The last commits of the indexes have different opstamps. `None` stands for a directory
+    /// without any commit.
+    OpstampMismatch {
+        /// Opstamp of the left index.
+        left: Option<Opstamp>,
+        /// Opstamp of the right index.
+        right: Option<Opstamp>,
+    },
+    /// A segment is only part of the left index.
+    SegmentOnlyInLeft(SegmentId),
+    /// A segment is only part of the right index.
+    SegmentOnlyInRight(SegmentId),
+    /// A segment of both indexes does not have the same number of alive documents, i.e. the
+    /// deletes applied to it differ.
+    DocCountMismatch {
+        /// Id of the segment.
+        segment_id: SegmentId,
+        /// Number of alive documents in the left index.
+        left: u32,
+        /// Number of alive documents in the right index.
+        right: u32,
+    },
+    /// A file of a segment of both indexes is only part of the left index.
+    FileOnlyInLeft(PathBuf),
+    /// A file of a segment of both indexes is only part of the right index.
+    FileOnlyInRight(PathBuf),
+    /// A file is part of both indexes, but the checksums recorded in their footers differ.
+    ChecksumMismatch {
+        /// Path of the file.
+        path: PathBuf,
+        /// Checksum of the file in the left index.
+        left: u32,
+        /// Checksum of the file in the right index.
+        right: u32,
+    },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::OpstampMismatch { left, right } => {
+                write!(f, "Opstamp mismatch: {left:?} != {right:?}")
+            }
+            Divergence::SegmentOnlyInLeft(segment_id) => {
+                write!(
+                    f,
+                    "Segment {} only in left index",
+                    segment_id.short_uuid_string()
+                )
+            }
+            Divergence::SegmentOnlyInRight(segment_id) => {
+                write!(
+                    f,
+                    "Segment {} only in right index",
+                    segment_id.short_uuid_string()
+                )
+            }
+            Divergence::DocCountMismatch {
+                segment_id,
+                left,
+                right,
+            } => write!(
+                f,
+                "Segment {} holds {left} docs in left index, {right} in right index",
+                segment_id.short_uuid_string()
+            ),
+            Divergence::FileOnlyInLeft(path) => write!(f, "File {path:?} only in left index"),
+            Divergence::FileOnlyInRight(path) => write!(f, "File {path:?} only in right index"),
+            Divergence::ChecksumMismatch { path, left, right } => {
+                write!(f, "Checksum mismatch in {path:?}: {left} != {right}")
+            }
+        }
+    }
+}
+
+/// Result of [`diff_directories()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexDiff {
+    /// The differences found, empty if the indexes are in sync.
+    pub divergences: Vec<Divergence>,
+}
+
+impl IndexDiff {
+    /// Returns true if no difference was found.
+    pub fn is_in_sync(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Compares the last commits of the indexes in two directories: their opstamps, their
+/// segments, the number of documents of the segments, and the checksums recorded in the
+/// footers of their files.
+///
+/// The files are not read beyond their footers. Their content can be checked against their
+/// checksums with [`Index::validate()`]. A replica found to diverge from its primary can be
+/// repaired by shipping it the files of the primary with a [`Replica`].
+pub fn diff_directories(left: &dyn Directory, right: &dyn Directory) -> crate::Result<IndexDiff> {
+    let left = read_generation(left)?;
+    let right = read_generation(right)?;
+    let mut divergences = Vec::new();
+    let left_opstamp = left.as_ref().map(|(generation, _)| generation.opstamp);
+    let right_opstamp = right.as_ref().map(|(generation, _)| generation.opstamp);
+    if left_opstamp != right_opstamp {
+        divergences.push(Divergence::OpstampMismatch {
+            left: left_opstamp,
+            right: right_opstamp,
+        });
+    }
+    let segment_metas = |generation: &Option<(Generation, IndexMeta)>| {
+        generation
+            .iter()
+            .flat_map(|(_, metas)| metas.segments.iter())
+            .map(|segment_meta| (segment_meta.id(), segment_meta.clone()))
+            .collect::<BTreeMap<SegmentId, SegmentMeta>>()
+    };
+    let left_segments = segment_metas(&left);
+    let right_segments = segment_metas(&right);
+    let empty_files = BTreeMap::new();
+    let left_files = left
+        .as_ref()
+        .map_or(&empty_files, |(generation, _)| &generation.files);
+    let right_files = right
+        .as_ref()
+        .map_or(&empty_files, |(generation, _)| &generation.files);
+    for (segment_id, left_segment) in &left_segments {
+        let Some(right_segment) = right_segments.get(segment_id) else {
+            divergences.push(Divergence::SegmentOnlyInLeft(*segment_id));
+            continue;
+        };
+        if left_segment.num_docs() != right_segment.num_docs() {
+            divergences.push(Divergence::DocCountMismatch {
+                segment_id: *segment_id,
+                left: left_segment.num_docs(),
+                right: right_segment.num_docs(),
+            });
+        }
+        let segment_files = left_segment
+            .list_files()
+            .into_iter()
+            .chain(right_segment.list_files())
+            .collect::<BTreeSet<PathBuf>>();
+        for path in segment_files {
+            match (left_files.get(&path), right_files.get(&path)) {
+                (Some(_), None) => divergences.push(Divergence::FileOnlyInLeft(path)),
+                (None, Some(_)) => divergences.push(Divergence::FileOnlyInRight(path)),
+                (Some(&left_crc), Some(&right_crc)) if left_crc != right_crc => {
+                    divergences.push(Divergence::ChecksumMismatch {
+                        path,
+                        left: left_crc,
+                        right: right_crc,
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+    for segment_id in right_segments.keys() {
+        if !left_segments.contains_key(segment_id) {
+            divergences.push(Divergence::SegmentOnlyInRight(*segment_id));
+        }
+    }
+    Ok(IndexDiff { divergences })
+}
+
+fn verify_checksum(path: &Path, data: &[u8], expected_crc: u32) -> crate::Result<()> {
+    let corrupted = |comment: String| DataCorruption::new(path.to_path_buf(), comment);
+    let (footer, body) = Footer::extract_footer(FileSlice::from(data.to_vec()))
+        .map_err(|io_error| corrupted(io_error.to_string()))?;
+    let mut hasher = Hasher::new();
+    hasher.update(&data[..body.len()]);
+    let crc = hasher.finalize();
+    if crc != footer.crc() || crc != expected_crc {
+        return Err(corrupted(format!(
+            "Checksum mismatch: expected {expected_crc}, got {crc}."
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::SegmentComponent;
+    use crate::directory::RamDirectory;
+    use crate::schema::{Schema, STRING};
+    use crate::{IndexReader, IndexSettings, IndexWriter, ReloadPolicy, Term};
+
+    #[test]
+    fn test_replication() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let primary = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = primary.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+
+        let replica_directory = RamDirectory::create();
+        let mut replica = Replica::open(replica_directory.clone())?;
+        assert!(replica.generation().is_none());
+        let snapshot = GenerationSnapshot::capture(&primary)?;
+        let changes = snapshot.changes_since(replica.generation());
+        assert!(changes.removed_files.is_empty());
+        replica.apply(&changes, |path| Ok(snapshot.read_file(path)?.to_vec()))?;
+        let replica_reader: IndexReader = Index::open(replica_directory.clone())?
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        assert_eq!(replica_reader.searcher().num_docs(), 2);
+
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.add_document(doc!(id => "c"))?;
+        index_writer.commit()?;
+        let snapshot = GenerationSnapshot::capture(&primary)?;
+        let changes = snapshot.changes_since(replica.generation());
+        // The first segment got a delete file.
+        assert!(changes.removed_files.is_empty());
+        assert!(changes
+            .added_files
+            .iter()
+            .any(|path| path.to_string_lossy().ends_with(".del")));
+
+        // A corrupted download is rejected, and leaves the replica untouched.
+        let err = replica
+            .apply(&changes, |path| {
+                let mut data = snapshot.read_file(path)?.to_vec();
+                data[0] ^= 1;
+                Ok(data)
+            })
+            .unwrap_err();
+        assert!(matches!(err, TantivyError::DataCorruption(_)));
+        assert_eq!(
+            Replica::open(replica_directory.clone())?.generation(),
+            replica.generation()
+        );
+
+        replica.apply(&changes, |path| Ok(snapshot.read_file(path)?.to_vec()))?;
+        assert_eq!(replica.generation(), Some(snapshot.generation()));
+        assert_eq!(
+            Replica::open(replica_directory.clone())?.generation(),
+            Some(snapshot.generation())
+        );
+        replica_reader.reload()?;
+        let searcher = replica_reader.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        // Changes computed for another generation are refused.
+        assert!(replica.apply(&changes, |_| unreachable!()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_incremental_backup() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+
+        let backup_directory = RamDirectory::create();
+        let mut backup_store = BackupStore::open(backup_directory.clone())?;
+        assert!(backup_store.last_generation().is_none());
+        let first_snapshot = GenerationSnapshot::capture(&index)?;
+        let first_manifest = backup_store.backup(&first_snapshot)?.clone();
+        assert_eq!(first_manifest.previous_opstamp, None);
+        assert_eq!(
+            first_manifest.added_files.len(),
+            first_snapshot.generation().files.len()
+        );
+        // Backing up the same generation again copies nothing.
+        backup_store.backup(&first_snapshot)?;
+        assert_eq!(backup_store.manifests().len(), 1);
+
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.add_document(doc!(id => "c"))?;
+        index_writer.commit()?;
+        let second_snapshot = GenerationSnapshot::capture(&index)?;
+        let second_manifest = backup_store.backup(&second_snapshot)?.clone();
+        assert_eq!(
+            second_manifest.previous_opstamp,
+            Some(first_snapshot.generation().opstamp)
+        );
+        assert!(second_manifest
+            .added_files
+            .iter()
+            .all(|path| !first_snapshot.generation().files.contains_key(path)));
+        assert!(!second_manifest.added_files.is_empty());
+
+        let backup_store = BackupStore::open(backup_directory)?;
+        assert_eq!(backup_store.manifests(), &[first_manifest, second_manifest]);
+        for (opstamp, num_docs) in [
+            (first_snapshot.generation().opstamp, 2),
+            (second_snapshot.generation().opstamp, 2),
+        ] {
+            let restore_directory = RamDirectory::create();
+            backup_store.restore(opstamp, &restore_directory)?;
+            let restored_index = Index::open(restore_directory)?;
+            assert_eq!(restored_index.load_metas()?.opstamp, opstamp);
+            assert_eq!(restored_index.reader()?.searcher().num_docs(), num_docs);
+        }
+        assert!(backup_store
+            .restore(u64::MAX, &RamDirectory::create())
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_directories() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let primary_directory = RamDirectory::create();
+        let primary = Index::create(
+            primary_directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let mut index_writer: IndexWriter = primary.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+
+        let replica_directory = RamDirectory::create();
+        let diff = diff_directories(&primary_directory, &replica_directory)?;
+        let segment_id = primary.searchable_segment_ids()?[0];
+        assert_eq!(
+            diff.divergences,
+            vec![
+                Divergence::OpstampMismatch {
+                    left: Some(primary.load_metas()?.opstamp),
+                    right: None
+                },
+                Divergence::SegmentOnlyInLeft(segment_id),
+            ]
+        );
+
+        let mut replica = Replica::open(replica_directory.clone())?;
+        let snapshot = GenerationSnapshot::capture(&primary)?;
+        let changes = snapshot.changes_since(replica.generation());
+        replica.apply(&changes, |path| Ok(snapshot.read_file(path)?.to_vec()))?;
+        assert!(diff_directories(&primary_directory, &replica_directory)?.is_in_sync());
+
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.commit()?;
+        let diff = diff_directories(&primary_directory, &replica_directory)?;
+        let delete_file =
+            primary.searchable_segment_metas()?[0].relative_path(SegmentComponent::Delete);
+        assert_eq!(
+            diff.divergences,
+            vec![
+                Divergence::OpstampMismatch {
+                    left: Some(primary.load_metas()?.opstamp),
+                    right: Some(changes.generation.opstamp)
+                },
+                Divergence::DocCountMismatch {
+                    segment_id,
+                    left: 1,
+                    right: 2
+                },
+                Divergence::FileOnlyInLeft(delete_file),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -7,11 +7,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::schema::flags::{FastFlag, IndexedFlag, SchemaFlagList, StoredFlag};
 
-/// The precision of the indexed date/time values in the inverted index.
+/// The default precision of the indexed date/time values in the inverted index.
+///
+/// See [`DateOptions::set_indexed_precision`] to index values with a coarser precision.
 pub const DATE_TIME_PRECISION_INDEXED: DateTimePrecision = DateTimePrecision::Seconds;
 
 /// Defines how DateTime field should be handled by tantivy.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DateOptions {
     indexed: bool,
     // This boolean has no effect if the field is not marked as indexed true.
@@ -23,6 +25,33 @@ pub struct DateOptions {
     // compression on fast fields.
     #[serde(default)]
     precision: DateTimePrecision,
+    // Precision of the terms in the inverted index.
+    #[serde(
+        default = "default_indexed_precision",
+        skip_serializing_if = "is_default_indexed_precision"
+    )]
+    indexed_precision: DateTimePrecision,
+}
+
+impl Default for DateOptions {
+    fn default() -> DateOptions {
+        DateOptions {
+            indexed: false,
+            fieldnorms: false,
+            fast: false,
+            stored: false,
+            precision: DateTimePrecision::default(),
+            indexed_precision: DATE_TIME_PRECISION_INDEXED,
+        }
+    }
+}
+
+fn default_indexed_precision() -> DateTimePrecision {
+    DATE_TIME_PRECISION_INDEXED
+}
+
+fn is_default_indexed_precision(precision: &DateTimePrecision) -> bool {
+    *precision == DATE_TIME_PRECISION_INDEXED
 }
 
 impl DateOptions {
@@ -92,7 +121,7 @@ impl DateOptions {
     }
 
     /// Sets the precision for this DateTime field on the fast field.
-    /// Indexed precision is always [`DATE_TIME_PRECISION_INDEXED`].
+    /// See [`DateOptions::set_indexed_precision`] for the precision of the inverted index.
     ///
     /// Internal storage precision, used to optimize storage
     /// compression on fast fields.
@@ -108,6 +137,26 @@ impl DateOptions {
     pub fn get_precision(&self) -> DateTimePrecision {
         self.precision
     }
+
+    /// Sets the precision of the values in the inverted index.
+    ///
+    /// Values are truncated to this precision at indexing time. A coarse precision, e.g.
+    /// [`DateTimePrecision::Days`], keeps the number of distinct terms small, which makes range
+    /// queries over large time spans cheap, at the cost of not being able to filter within a
+    /// day. Precisions finer than [`DATE_TIME_PRECISION_INDEXED`] are not supported, and fall
+    /// back to it.
+    ///
+    /// Defaults to [`DATE_TIME_PRECISION_INDEXED`].
+    #[must_use]
+    pub fn set_indexed_precision(mut self, precision: DateTimePrecision) -> DateOptions {
+        self.indexed_precision = precision;
+        self
+    }
+
+    /// Returns the precision of the values in the inverted index.
+    pub fn get_indexed_precision(&self) -> DateTimePrecision {
+        self.indexed_precision.min(DATE_TIME_PRECISION_INDEXED)
+    }
 }
 
 impl From<()> for DateOptions {
@@ -155,6 +204,7 @@ impl<T: Into<DateOptions>> BitOr<T> for DateOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             precision: self.precision,
+            indexed_precision: self.indexed_precision,
         }
     }
 }
@@ -173,6 +223,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::INDEXED;
 
     #[test]
     fn test_date_options_consistent_with_default() {
@@ -231,11 +282,33 @@ mod tests {
             "indexed": true,
             "fieldnorms": false,
             "stored": false,
-            "precision": "hours"
+            "precision": "weeks"
         }"#
         )
         .unwrap_err()
         .to_string()
-        .contains("unknown variant `hours`"));
+        .contains("unknown variant `weeks`"));
+    }
+
+    #[test]
+    fn test_date_options_indexed_precision() {
+        let date_options =
+            DateOptions::from(INDEXED).set_indexed_precision(DateTimePrecision::Days);
+        assert_eq!(
+            date_options.get_indexed_precision(),
+            DateTimePrecision::Days
+        );
+        let date_options_json = serde_json::to_value(&date_options).unwrap();
+        assert_eq!(date_options_json["indexed_precision"], "days");
+        let deser: DateOptions = serde_json::from_value(date_options_json).unwrap();
+        assert_eq!(deser, date_options);
+
+        // Precisions finer than seconds are not supported in the inverted index.
+        let date_options =
+            DateOptions::from(INDEXED).set_indexed_precision(DateTimePrecision::Milliseconds);
+        assert_eq!(
+            date_options.get_indexed_precision(),
+            DATE_TIME_PRECISION_INDEXED
+        );
     }
 }
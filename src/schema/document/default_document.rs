@@ -186,7 +186,7 @@ impl TantivyDocument {
     ) -> Result<TantivyDocument, DocParsingError> {
         let mut document = TantivyDocument::new();
         for (field_name, values) in named_doc.0 {
-            if let Ok(field) = schema.get_field(&field_name) {
+            if let Some(field) = schema.get_field_for_new_values(&field_name) {
                 for value in values {
                     document.add_field_value(field, value);
                 }
@@ -198,7 +198,46 @@ impl TantivyDocument {
     /// Build a document object from a json-object.
     pub fn parse_json(schema: &Schema, doc_json: &str) -> Result<TantivyDocument, DocParsingError> {
         let json_obj: Map<String, serde_json::Value> =
-            serde_json::from_str(doc_json).map_err(|_| DocParsingError::invalid_json(doc_json))?;
+            serde_json::from_str(doc_json).map_err(|err| {
+                DocParsingError::invalid_json(doc_json, json_error_offset(doc_json, &err))
+            })?;
+        Self::from_json_object(schema, json_obj).map_err(|error| {
+            // The offsets of the values are only known while streaming the payload, which is
+            // only done again to locate the error.
+            match TantivyDocument::new().parse_json_into(schema, doc_json) {
+                Err(located_error @ DocParsingError::ValueError { .. }) => located_error,
+                _ => error,
+            }
+        })
+    }
+
+    /// Build a document object from a MessagePack-encoded map.
+    ///
+    /// The map is interpreted as the equivalent JSON object would be by
+    /// [`TantivyDocument::parse_json`]. In particular, bytes values are expected to be
+    /// base64 encoded strings.
+    #[cfg(feature = "msgpack")]
+    pub fn parse_msgpack(
+        schema: &Schema,
+        doc_msgpack: &[u8],
+    ) -> Result<TantivyDocument, DocParsingError> {
+        let json_obj: Map<String, serde_json::Value> = rmp_serde::from_slice(doc_msgpack)
+            .map_err(|err| DocParsingError::InvalidPayload(err.to_string()))?;
+        Self::from_json_object(schema, json_obj)
+    }
+
+    /// Build a document object from a CBOR-encoded map.
+    ///
+    /// The map is interpreted as the equivalent JSON object would be by
+    /// [`TantivyDocument::parse_json`]. In particular, bytes values are expected to be
+    /// base64 encoded strings.
+    #[cfg(feature = "cbor")]
+    pub fn parse_cbor(
+        schema: &Schema,
+        doc_cbor: &[u8],
+    ) -> Result<TantivyDocument, DocParsingError> {
+        let json_obj: Map<String, serde_json::Value> = ciborium::de::from_reader(doc_cbor)
+            .map_err(|err| DocParsingError::InvalidPayload(err.to_string()))?;
         Self::from_json_object(schema, json_obj)
     }
 
@@ -209,29 +248,217 @@ impl TantivyDocument {
     ) -> Result<TantivyDocument, DocParsingError> {
         let mut doc = TantivyDocument::default();
         for (field_name, json_value) in json_obj {
-            if let Ok(field) = schema.get_field(&field_name) {
-                let field_entry = schema.get_field_entry(field);
-                let field_type = field_entry.field_type();
-                match json_value {
-                    serde_json::Value::Array(json_items) => {
-                        for json_item in json_items {
-                            let value = field_type
-                                .value_from_json(json_item)
-                                .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
-                            doc.add_field_value(field, value);
-                        }
-                    }
-                    _ => {
-                        let value = field_type
-                            .value_from_json(json_value)
-                            .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
-                        doc.add_field_value(field, value);
-                    }
-                }
+            if let Some(field) = schema.get_field_for_new_values(&field_name) {
+                doc.add_json_value(schema, field, json_value)?;
             }
         }
         Ok(doc)
     }
+
+    /// Parses a json-object into this document, replacing its content.
+    ///
+    /// This is equivalent to [`TantivyDocument::parse_json`], but reuses the memory of the
+    /// document, and parses the values straight into it. Values of fields that are not in the
+    /// schema are skipped without being materialized. Ingestion loops can keep a single
+    /// document around, and parse every input line into it.
+    pub fn parse_json_into(
+        &mut self,
+        schema: &Schema,
+        doc_json: &str,
+    ) -> Result<(), DocParsingError> {
+        self.field_values.clear();
+        let mut value_error = None;
+        let mut deserializer = serde_json::Deserializer::from_str(doc_json);
+        let parse_res = serde::de::DeserializeSeed::deserialize(
+            JsonObjectSeed {
+                schema,
+                doc: self,
+                value_error: &mut value_error,
+            },
+            &mut deserializer,
+        )
+        .and_then(|()| deserializer.end());
+        if let Some(mut value_error) = value_error {
+            self.field_values.clear();
+            if let (DocParsingError::ValueError { offset, .. }, Err(err)) =
+                (&mut value_error, &parse_res)
+            {
+                *offset = Some(json_error_offset(doc_json, err));
+            }
+            return Err(value_error);
+        }
+        if let Err(err) = parse_res {
+            self.field_values.clear();
+            return Err(DocParsingError::invalid_json(
+                doc_json,
+                json_error_offset(doc_json, &err),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Encodes the document in JSON into `json`, replacing its content.
+    ///
+    /// The output is the same as the one of [`Document::to_json`], but the values are
+    /// serialized straight from the document, rather than being copied into a
+    /// [`NamedFieldDocument`] first, and the memory of `json` is reused.
+    pub fn to_json_into(&self, schema: &Schema, json: &mut String) {
+        // Values are grouped by field name, in the order of the `NamedFieldDocument`.
+        let mut order: Vec<usize> = (0..self.field_values.len()).collect();
+        order.sort_unstable_by_key(|&ord| {
+            (schema.get_field_name(self.field_values[ord].field()), ord)
+        });
+        let mut buffer = std::mem::take(json).into_bytes();
+        buffer.clear();
+        serde_json::to_writer(
+            &mut buffer,
+            &GroupedFieldValues {
+                schema,
+                field_values: &self.field_values,
+                order: &order,
+            },
+        )
+        .expect("doc encoding failed. This is a bug");
+        *json = String::from_utf8(buffer).expect("serde_json only writes valid UTF-8");
+    }
+
+    fn add_json_value(
+        &mut self,
+        schema: &Schema,
+        field: Field,
+        json_value: serde_json::Value,
+    ) -> Result<(), DocParsingError> {
+        let field_entry = schema.get_field_entry(field);
+        let field_type = field_entry.field_type();
+        let parse_value = |json_value, array_index| {
+            field_type.value_from_json(json_value).map_err(|error| {
+                DocParsingError::value_error(field_entry.name(), array_index, error)
+            })
+        };
+        match json_value {
+            serde_json::Value::Array(json_items) => {
+                for (array_index, json_item) in json_items.into_iter().enumerate() {
+                    let value = parse_value(json_item, Some(array_index))?;
+                    self.add_field_value(field, value);
+                }
+            }
+            _ => {
+                let value = parse_value(json_value, None)?;
+                self.add_field_value(field, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a json-object into a document, for [`TantivyDocument::parse_json_into`].
+struct JsonObjectSeed<'a> {
+    schema: &'a Schema,
+    doc: &'a mut TantivyDocument,
+    // Value errors abort the deserialization, and are reported as is.
+    value_error: &'a mut Option<DocParsingError>,
+}
+
+impl<'de, 'a> serde::de::DeserializeSeed<'de> for JsonObjectSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where D: serde::Deserializer<'de> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, 'a> serde::de::Visitor<'de> for JsonObjectSeed<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a json object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where A: serde::de::MapAccess<'de> {
+        while let Some(field_opt) = map.next_key_seed(FieldSeed(self.schema))? {
+            let Some(field) = field_opt else {
+                map.next_value::<serde::de::IgnoredAny>()?;
+                continue;
+            };
+            let json_value: serde_json::Value = map.next_value()?;
+            if let Err(value_error) = self.doc.add_json_value(self.schema, field, json_value) {
+                *self.value_error = Some(value_error);
+                return Err(serde::de::Error::custom("invalid value"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resolves a field name into a field of the schema, without allocating.
+struct FieldSeed<'a>(&'a Schema);
+
+impl<'de, 'a> serde::de::DeserializeSeed<'de> for FieldSeed<'a> {
+    type Value = Option<Field>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Option<Field>, D::Error>
+    where D: serde::Deserializer<'de> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de, 'a> serde::de::Visitor<'de> for FieldSeed<'a> {
+    type Value = Option<Field>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a field name")
+    }
+
+    fn visit_str<E>(self, field_name: &str) -> Result<Option<Field>, E> {
+        Ok(self.0.get_field_for_new_values(field_name))
+    }
+}
+
+/// Serializes field values as a `NamedFieldDocument` would be.
+struct GroupedFieldValues<'a> {
+    schema: &'a Schema,
+    field_values: &'a [FieldValue],
+    // Ordinals of the field values, grouped by field.
+    order: &'a [usize],
+}
+
+impl<'a> serde::Serialize for GroupedFieldValues<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(None)?;
+        let mut remaining = self.order;
+        while let Some(&first) = remaining.first() {
+            let field = self.field_values[first].field();
+            let group_len = remaining
+                .iter()
+                .take_while(|&&ord| self.field_values[ord].field() == field)
+                .count();
+            let (group, rest) = remaining.split_at(group_len);
+            let values = FieldGroup {
+                field_values: self.field_values,
+                group,
+            };
+            map.serialize_entry(self.schema.get_field_name(field), &values)?;
+            remaining = rest;
+        }
+        map.end()
+    }
+}
+
+/// Serializes the values of a group of field values, as a sequence.
+struct FieldGroup<'a> {
+    field_values: &'a [FieldValue],
+    group: &'a [usize],
+}
+
+impl<'a> serde::Serialize for FieldGroup<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        serializer.collect_seq(self.group.iter().map(|&ord| self.field_values[ord].value()))
+    }
 }
 
 /// Error that may happen when deserializing
@@ -239,19 +466,67 @@ impl TantivyDocument {
 #[derive(Debug, Error, PartialEq)]
 pub enum DocParsingError {
     /// The payload given is not valid JSON.
-    #[error("The provided string is not valid JSON")]
-    InvalidJson(String),
+    #[error("The provided string is not valid JSON, at byte {offset}: {sample:?}")]
+    InvalidJson {
+        /// The first characters of the payload.
+        sample: String,
+        /// The approximate byte offset of the error in the payload.
+        offset: usize,
+    },
+    /// The binary payload given could not be decoded into a map.
+    #[error("The provided payload could not be decoded: {0}")]
+    InvalidPayload(String),
     /// One of the value node could not be parsed.
-    #[error("The field '{0:?}' could not be parsed: {1:?}")]
-    ValueError(String, ValueParsingError),
+    #[error("The value at '{path}'{} could not be parsed: {error}", offset_suffix(.offset))]
+    ValueError {
+        /// The name of the field of the value.
+        field: String,
+        /// The JSON pointer of the value in the payload, e.g. `/tags/2`.
+        path: String,
+        /// The approximate byte offset of the end of the value in the payload, when the
+        /// payload is JSON text.
+        offset: Option<usize>,
+        /// The reason why the value could not be parsed.
+        error: ValueParsingError,
+    },
 }
 
 impl DocParsingError {
     /// Builds a NotJson DocParsingError
-    fn invalid_json(invalid_json: &str) -> Self {
+    pub(crate) fn invalid_json(invalid_json: &str, offset: usize) -> Self {
         let sample = invalid_json.chars().take(20).collect();
-        DocParsingError::InvalidJson(sample)
+        DocParsingError::InvalidJson { sample, offset }
     }
+
+    /// Builds the error of a value of `field_name`, which may be an item of an array.
+    fn value_error(field_name: &str, array_index: Option<usize>, error: ValueParsingError) -> Self {
+        let mut path = format!("/{}", field_name.replace('~', "~0").replace('/', "~1"));
+        if let Some(array_index) = array_index {
+            path.push_str(&format!("/{array_index}"));
+        }
+        DocParsingError::ValueError {
+            field: field_name.to_string(),
+            path,
+            offset: None,
+            error,
+        }
+    }
+}
+
+fn offset_suffix(offset: &Option<usize>) -> String {
+    offset
+        .map(|offset| format!(" (byte {offset})"))
+        .unwrap_or_default()
+}
+
+/// Converts the line and column of a `serde_json` error into a byte offset in `json`.
+fn json_error_offset(json: &str, err: &serde_json::Error) -> usize {
+    let line_start: usize = json
+        .split_inclusive('\n')
+        .take(err.line().saturating_sub(1))
+        .map(str::len)
+        .sum();
+    (line_start + err.column().saturating_sub(1)).min(json.len())
 }
 
 #[cfg(test)]
@@ -268,6 +543,134 @@ mod tests {
         assert_eq!(doc.field_values().len(), 1);
     }
 
+    #[test]
+    fn test_parse_json_into_and_to_json_into() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let year = schema_builder.add_u64_field("year", INDEXED | STORED);
+        schema_builder.add_text_field("author", STRING | STORED);
+        let schema = schema_builder.build();
+        let mut doc = TantivyDocument::default();
+        let mut json = String::new();
+        for doc_json in [
+            r#"{"year": 1937, "title": ["a", "b"], "unknown": {"x": [1, 2]}}"#,
+            r#"{"title": "c", "auth\u006fr": "d"}"#,
+        ] {
+            doc.parse_json_into(&schema, doc_json).unwrap();
+            let expected_doc = TantivyDocument::parse_json(&schema, doc_json).unwrap();
+            assert_eq!(doc, expected_doc);
+            doc.to_json_into(&schema, &mut json);
+            assert_eq!(json, expected_doc.to_json(&schema));
+        }
+        assert_eq!(doc.get_all(title).count(), 1);
+        assert_eq!(doc.get_first(year), None);
+
+        assert!(matches!(
+            doc.parse_json_into(&schema, r#"{"year": "1937"}"#),
+            Err(DocParsingError::ValueError { field, .. }) if field == "year"
+        ));
+        assert_eq!(doc.len(), 0);
+        assert!(matches!(
+            doc.parse_json_into(&schema, r#"{"year": 1937} trailing"#),
+            Err(DocParsingError::InvalidJson { .. })
+        ));
+        assert!(matches!(
+            doc.parse_json_into(&schema, "[1937]"),
+            Err(DocParsingError::InvalidJson { .. })
+        ));
+    }
+
+    #[test]
+    fn test_doc_parsing_error_location() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u64_field("years", INDEXED);
+        let schema = schema_builder.build();
+
+        let doc_json = r#"{"title": "a", "years": [1937, "1938"]}"#;
+        let err = TantivyDocument::parse_json(&schema, doc_json).unwrap_err();
+        let DocParsingError::ValueError {
+            field,
+            path,
+            offset,
+            ..
+        } = &err
+        else {
+            panic!("unexpected error {err:?}");
+        };
+        assert_eq!(field, "years");
+        assert_eq!(path, "/years/1");
+        assert_eq!(*offset, Some(doc_json.find(']').unwrap() + 1));
+        assert_eq!(
+            err.to_string(),
+            "The value at '/years/1' (byte 38) could not be parsed: Type error. Expected a u64, \
+             got \"1938\""
+        );
+        let mut doc = TantivyDocument::default();
+        assert_eq!(doc.parse_json_into(&schema, doc_json), Err(err));
+
+        let doc_json = "{\"title\": \"a\",\n\"years\" 1937}";
+        let err = TantivyDocument::parse_json(&schema, doc_json).unwrap_err();
+        assert_eq!(
+            err,
+            DocParsingError::InvalidJson {
+                sample: "{\"title\": \"a\",\n\"year".to_string(),
+                offset: doc_json.find("1937").unwrap(),
+            }
+        );
+        assert!(err
+            .to_string()
+            .starts_with("The provided string is not valid JSON, at byte 23: "));
+    }
+
+    #[cfg(any(feature = "msgpack", feature = "cbor"))]
+    fn binary_payload_test_schema() -> (Schema, Field, Field) {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let year = schema_builder.add_u64_field("year", INDEXED);
+        (schema_builder.build(), title, year)
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_parse_msgpack() {
+        let (schema, title, year) = binary_payload_test_schema();
+        let payload =
+            rmp_serde::to_vec(&serde_json::json!({"title": ["a", "b"], "year": 1937})).unwrap();
+        let doc = TantivyDocument::parse_msgpack(&schema, &payload).unwrap();
+        assert_eq!(doc.get_all(title).count(), 2);
+        assert_eq!(doc.get_first(year), Some(&OwnedValue::U64(1937)));
+
+        let payload = rmp_serde::to_vec(&serde_json::json!({"year": "1937"})).unwrap();
+        assert!(matches!(
+            TantivyDocument::parse_msgpack(&schema, &payload),
+            Err(DocParsingError::ValueError { .. })
+        ));
+        assert!(matches!(
+            TantivyDocument::parse_msgpack(&schema, &[0xc1]),
+            Err(DocParsingError::InvalidPayload(_))
+        ));
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_parse_cbor() {
+        let (schema, title, year) = binary_payload_test_schema();
+        let mut payload = Vec::new();
+        ciborium::ser::into_writer(
+            &serde_json::json!({"title": ["a", "b"], "year": 1937}),
+            &mut payload,
+        )
+        .unwrap();
+        let doc = TantivyDocument::parse_cbor(&schema, &payload).unwrap();
+        assert_eq!(doc.get_all(title).count(), 2);
+        assert_eq!(doc.get_first(year), Some(&OwnedValue::U64(1937)));
+        assert!(matches!(
+            TantivyDocument::parse_cbor(&schema, &[0xff]),
+            Err(DocParsingError::InvalidPayload(_))
+        ));
+    }
+
     // TODO: Should this be re-added with the serialize method
     //       technically this is no longer useful since the doc types
     //       do not implement BinarySerializable due to orphan rules.
@@ -13,7 +13,7 @@ use crate::schema::document::{
     ArrayAccess, DeserializeError, ObjectAccess, ReferenceValue, Value, ValueDeserialize,
     ValueDeserializer, ValueVisitor,
 };
-use crate::schema::Facet;
+use crate::schema::{Facet, GeoPoint};
 use crate::tokenizer::PreTokenizedString;
 use crate::DateTime;
 
@@ -349,6 +349,12 @@ impl From<Facet> for OwnedValue {
     }
 }
 
+impl From<GeoPoint> for OwnedValue {
+    fn from(point: GeoPoint) -> OwnedValue {
+        OwnedValue::U64(point.to_u64())
+    }
+}
+
 impl From<Vec<u8>> for OwnedValue {
     fn from(bytes: Vec<u8>) -> OwnedValue {
         OwnedValue::Bytes(bytes)
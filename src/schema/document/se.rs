@@ -28,8 +28,10 @@ where W: Write
     pub(crate) fn serialize_doc<D>(&mut self, doc: &D) -> io::Result<()>
     where D: Document {
         let stored_field_values = || {
-            doc.iter_fields_and_values()
-                .filter(|(field, _)| self.schema.get_field_entry(*field).is_stored())
+            doc.iter_fields_and_values().filter(|(field, _)| {
+                let field_entry = self.schema.get_field_entry(*field);
+                field_entry.is_stored() && !field_entry.is_ignored()
+            })
         };
         let num_field_values = stored_field_values().count();
         let mut actual_length = 0;
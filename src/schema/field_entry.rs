@@ -4,7 +4,7 @@ use super::ip_options::IpAddrOptions;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::{
     is_valid_field_name, DateOptions, FacetOptions, FieldType, JsonObjectOptions, NumericOptions,
-    TextOptions,
+    ScaledF64Options, TextOptions,
 };
 
 /// A `FieldEntry` represents a field and its configuration.
@@ -19,6 +19,14 @@ pub struct FieldEntry {
     name: String,
     #[serde(flatten)]
     field_type: FieldType,
+    #[serde(default, skip_serializing_if = "is_false")]
+    required: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    ignored: bool,
+}
+
+fn is_false(val: &bool) -> bool {
+    !val
 }
 
 impl FieldEntry {
@@ -28,9 +36,40 @@ impl FieldEntry {
         FieldEntry {
             name: field_name,
             field_type,
+            required: false,
+            ignored: false,
         }
     }
 
+    /// Marks the field as required: documents without a value for it are rejected by
+    /// [`Schema::validate()`](crate::schema::Schema::validate).
+    #[must_use]
+    pub fn set_required(mut self) -> FieldEntry {
+        self.required = true;
+        self
+    }
+
+    /// Returns true if the field is required.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Marks the field as ignored, which makes it possible to retire a field gradually.
+    ///
+    /// The field stays in the schema, and the data of the segments written before remains
+    /// accessible. The values of the field are however skipped when parsing documents, and are
+    /// neither indexed, stored, nor written to fast fields for new documents.
+    #[must_use]
+    pub fn set_ignored(mut self) -> FieldEntry {
+        self.ignored = true;
+        self
+    }
+
+    /// Returns true if the values of the field are ignored for new documents.
+    pub fn is_ignored(&self) -> bool {
+        self.ignored
+    }
+
     /// Creates a new text field entry.
     pub fn new_text(field_name: String, text_options: TextOptions) -> FieldEntry {
         Self::new(field_name, FieldType::Str(text_options))
@@ -51,6 +90,11 @@ impl FieldEntry {
         Self::new(field_name, FieldType::F64(f64_options))
     }
 
+    /// Creates a new scaled f64 field entry.
+    pub fn new_scaled_f64(field_name: String, scaled_f64_options: ScaledF64Options) -> FieldEntry {
+        Self::new(field_name, FieldType::ScaledF64(scaled_f64_options))
+    }
+
     /// Creates a new bool field entry.
     pub fn new_bool(field_name: String, bool_options: NumericOptions) -> FieldEntry {
         Self::new(field_name, FieldType::Bool(bool_options))
@@ -124,6 +168,7 @@ impl FieldEntry {
             | FieldType::I64(ref options)
             | FieldType::F64(ref options)
             | FieldType::Bool(ref options) => options.is_stored(),
+            FieldType::ScaledF64(ref options) => options.numeric_options().is_stored(),
             FieldType::Date(ref options) => options.is_stored(),
             FieldType::Str(ref options) => options.is_stored(),
             FieldType::Facet(ref options) => options.is_stored(),
@@ -14,7 +14,7 @@ use crate::schema::bytes_options::BytesOptions;
 use crate::schema::facet_options::FacetOptions;
 use crate::schema::{
     DateOptions, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions, OwnedValue,
-    TextFieldIndexing, TextOptions,
+    ScaledF64Options, TextFieldIndexing, TextOptions, Value,
 };
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
@@ -165,6 +165,8 @@ pub enum FieldType {
     I64(NumericOptions),
     /// 64-bits float 64 field type configuration
     F64(NumericOptions),
+    /// 64-bits float indexed as a scaled 64-bits integer field type configuration
+    ScaledF64(ScaledF64Options),
     /// Bool field type configuration
     Bool(NumericOptions),
     /// Signed 64-bits Date 64 field type configuration,
@@ -187,6 +189,7 @@ impl FieldType {
             FieldType::U64(_) => Type::U64,
             FieldType::I64(_) => Type::I64,
             FieldType::F64(_) => Type::F64,
+            FieldType::ScaledF64(_) => Type::I64,
             FieldType::Bool(_) => Type::Bool,
             FieldType::Date(_) => Type::Date,
             FieldType::Facet(_) => Type::Facet,
@@ -196,6 +199,29 @@ impl FieldType {
         }
     }
 
+    /// Returns true if `value` can be indexed in a field of this type.
+    ///
+    /// Null values are accepted by every type.
+    pub fn accepts_value<'a, V: Value<'a>>(&self, value: &V) -> bool {
+        if value.is_null() {
+            return true;
+        }
+        match self {
+            FieldType::Str(_) => {
+                value.as_str().is_some() || value.as_pre_tokenized_text().is_some()
+            }
+            FieldType::U64(_) => value.as_u64().is_some(),
+            FieldType::I64(_) => value.as_i64().is_some(),
+            FieldType::F64(_) | FieldType::ScaledF64(_) => value.as_f64().is_some(),
+            FieldType::Bool(_) => value.as_bool().is_some(),
+            FieldType::Date(_) => value.as_datetime().is_some(),
+            FieldType::Facet(_) => value.as_facet().is_some(),
+            FieldType::Bytes(_) => value.as_bytes().is_some(),
+            FieldType::JsonObject(_) => value.is_object(),
+            FieldType::IpAddr(_) => value.as_ip_addr().is_some(),
+        }
+    }
+
     /// returns true if this is an ip address field
     pub fn is_ip_addr(&self) -> bool {
         matches!(self, FieldType::IpAddr(_))
@@ -214,6 +240,9 @@ impl FieldType {
             | FieldType::I64(ref int_options)
             | FieldType::F64(ref int_options)
             | FieldType::Bool(ref int_options) => int_options.is_indexed(),
+            FieldType::ScaledF64(ref scaled_options) => {
+                scaled_options.numeric_options().is_indexed()
+            }
             FieldType::Date(ref date_options) => date_options.is_indexed(),
             FieldType::Facet(ref _facet_options) => true,
             FieldType::Bytes(ref bytes_options) => bytes_options.is_indexed(),
@@ -252,6 +281,7 @@ impl FieldType {
             | FieldType::I64(ref int_options)
             | FieldType::F64(ref int_options)
             | FieldType::Bool(ref int_options) => int_options.is_fast(),
+            FieldType::ScaledF64(ref scaled_options) => scaled_options.numeric_options().is_fast(),
             FieldType::Date(ref date_options) => date_options.is_fast(),
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_fast(),
             FieldType::Facet(_) => true,
@@ -270,6 +300,9 @@ impl FieldType {
             | FieldType::I64(ref int_options)
             | FieldType::F64(ref int_options)
             | FieldType::Bool(ref int_options) => int_options.fieldnorms(),
+            FieldType::ScaledF64(ref scaled_options) => {
+                scaled_options.numeric_options().fieldnorms()
+            }
             FieldType::Date(ref date_options) => date_options.fieldnorms(),
             FieldType::Facet(_) => false,
             FieldType::Bytes(ref bytes_options) => bytes_options.fieldnorms(),
@@ -301,6 +334,13 @@ impl FieldType {
                     None
                 }
             }
+            FieldType::ScaledF64(ref scaled_options) => {
+                if scaled_options.numeric_options().is_indexed() {
+                    Some(IndexRecordOption::Basic)
+                } else {
+                    None
+                }
+            }
             FieldType::Date(ref date_options) => {
                 if date_options.is_indexed() {
                     Some(IndexRecordOption::Basic)
@@ -393,6 +433,9 @@ impl FieldType {
                             })
                         }
                     }
+                    // Scaled values are parsed as plain `f64` values.
+                    FieldType::ScaledF64(opt) => FieldType::F64(opt.numeric_options().clone())
+                        .value_from_json(JsonValue::String(field_text)),
                     FieldType::Bool(opt) => {
                         if opt.should_coerce() {
                             Ok(OwnedValue::Bool(field_text.parse().map_err(|_| {
@@ -450,7 +493,7 @@ impl FieldType {
                         })
                     }
                 }
-                FieldType::F64(_) => {
+                FieldType::F64(_) | FieldType::ScaledF64(_) => {
                     if let Some(field_val_f64) = field_val_num.as_f64() {
                         Ok(OwnedValue::F64(field_val_f64))
                     } else {
@@ -0,0 +1,219 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+const LAT_SCALE: f64 = u32::MAX as f64 / 180.0;
+const LON_SCALE: f64 = u32::MAX as f64 / 360.0;
+
+/// The alphabet used to encode geohashes.
+const GEOHASH_ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Maximum precision of a geohash, in characters.
+pub(crate) const MAX_GEOHASH_PRECISION: u8 = 12;
+
+/// Mean radius of the earth, in meters.
+pub(crate) const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// A point on earth, given by its latitude and longitude in degrees.
+///
+/// Geo points are indexed in a `u64` fast field. The latitude and the longitude are
+/// each quantized on 32 bits, which gives a precision better than a centimeter.
+/// The latitude is stored in the high bits, so that a range of latitudes maps to a
+/// range of `u64` values.
+///
+/// ```rust
+/// use tantivy::schema::{GeoPoint, Schema, FAST, STORED};
+/// use tantivy::doc;
+///
+/// let mut schema_builder = Schema::builder();
+/// let location = schema_builder.add_u64_field("location", FAST | STORED);
+/// let doc = doc!(location => GeoPoint::new(48.8566, 2.3522));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct GeoPoint {
+    /// Latitude in degrees, within `[-90, 90]`.
+    pub lat: f64,
+    /// Longitude in degrees, within `[-180, 180]`.
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    /// Creates a new `GeoPoint`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the latitude is not within `[-90, 90]` or the longitude is not
+    /// within `[-180, 180]`.
+    pub fn new(lat: f64, lon: f64) -> GeoPoint {
+        assert!(
+            (-90.0..=90.0).contains(&lat),
+            "Latitude {lat} is out of the [-90, 90] range"
+        );
+        assert!(
+            (-180.0..=180.0).contains(&lon),
+            "Longitude {lon} is out of the [-180, 180] range"
+        );
+        GeoPoint { lat, lon }
+    }
+
+    /// Encodes the point into the `u64` stored in the fast field.
+    pub fn to_u64(self) -> u64 {
+        (u64::from(encode_lat(self.lat)) << 32) | u64::from(encode_lon(self.lon))
+    }
+
+    /// Decodes a point encoded with [`GeoPoint::to_u64`].
+    pub fn from_u64(val: u64) -> GeoPoint {
+        GeoPoint {
+            lat: (val >> 32) as f64 / LAT_SCALE - 90.0,
+            lon: (val & u64::from(u32::MAX)) as f64 / LON_SCALE - 180.0,
+        }
+    }
+
+    /// Returns the geohash of the cell holding the point, with `precision` characters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the precision is not within `[1, 12]`.
+    pub fn geohash(&self, precision: u8) -> String {
+        geohash_cell_to_string(self.geohash_cell(precision), precision)
+    }
+
+    /// Returns the geohash cell holding the point, as the `5 * precision` interleaved bits
+    /// of the geohash.
+    pub(crate) fn geohash_cell(&self, precision: u8) -> u64 {
+        assert!(
+            (1..=MAX_GEOHASH_PRECISION).contains(&precision),
+            "Geohash precision {precision} is out of the [1, {MAX_GEOHASH_PRECISION}] range"
+        );
+        let (mut min_lat, mut max_lat) = (-90.0, 90.0);
+        let (mut min_lon, mut max_lon) = (-180.0, 180.0);
+        let mut cell = 0u64;
+        // Bits alternate between longitude and latitude, starting with the longitude.
+        for bit_ord in 0..u32::from(precision) * 5 {
+            cell <<= 1;
+            if bit_ord % 2 == 0 {
+                let mid = (min_lon + max_lon) / 2.0;
+                if self.lon >= mid {
+                    cell |= 1;
+                    min_lon = mid;
+                } else {
+                    max_lon = mid;
+                }
+            } else {
+                let mid = (min_lat + max_lat) / 2.0;
+                if self.lat >= mid {
+                    cell |= 1;
+                    min_lat = mid;
+                } else {
+                    max_lat = mid;
+                }
+            }
+        }
+        cell
+    }
+
+    /// Returns the great-circle distance to another point, in meters.
+    ///
+    /// The distance is computed with the haversine formula, on a spherical earth.
+    pub fn distance(&self, other: &GeoPoint) -> f64 {
+        let d_lat = (other.lat - self.lat).to_radians();
+        let d_lon = (other.lon - self.lon).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2)
+            + self.lat.to_radians().cos()
+                * other.lat.to_radians().cos()
+                * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()
+    }
+}
+
+impl fmt::Display for GeoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.lat, self.lon)
+    }
+}
+
+/// Converts a cell returned by [`GeoPoint::geohash_cell`] into its geohash.
+pub(crate) fn geohash_cell_to_string(cell: u64, precision: u8) -> String {
+    (0..precision)
+        .rev()
+        .map(|char_ord| {
+            let char_bits = (cell >> (u32::from(char_ord) * 5)) & 31;
+            char::from(GEOHASH_ALPHABET[char_bits as usize])
+        })
+        .collect()
+}
+
+/// Returns the range of encoded values holding the points within the given latitudes.
+pub(crate) fn encoded_lat_range(min_lat: f64, max_lat: f64) -> std::ops::RangeInclusive<u64> {
+    let min_lat = min_lat.clamp(-90.0, 90.0);
+    let max_lat = max_lat.clamp(-90.0, 90.0);
+    // Quantization may shift a point by one unit: we widen the range accordingly,
+    // and rely on the exact check of the decoded points.
+    let min_bits = encode_lat(min_lat).saturating_sub(1);
+    let max_bits = encode_lat(max_lat).saturating_add(1);
+    (u64::from(min_bits) << 32)..=((u64::from(max_bits) << 32) | u64::from(u32::MAX))
+}
+
+fn encode_lat(lat: f64) -> u32 {
+    ((lat + 90.0) * LAT_SCALE).round() as u32
+}
+
+fn encode_lon(lon: f64) -> u32 {
+    ((lon + 180.0) * LON_SCALE).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encoded_lat_range, GeoPoint};
+
+    #[test]
+    fn test_geo_point_encoding() {
+        for &(lat, lon) in &[
+            (0.0, 0.0),
+            (-90.0, -180.0),
+            (90.0, 180.0),
+            (48.8566, 2.3522),
+            (-33.8688, 151.2093),
+        ] {
+            let point = GeoPoint::new(lat, lon);
+            let decoded = GeoPoint::from_u64(point.to_u64());
+            assert!((decoded.lat - lat).abs() < 1e-7, "{point} {decoded}");
+            assert!((decoded.lon - lon).abs() < 1e-7, "{point} {decoded}");
+        }
+    }
+
+    #[test]
+    fn test_geo_point_lat_range() {
+        let lat_range = encoded_lat_range(10.0, 20.0);
+        assert!(lat_range.contains(&GeoPoint::new(10.0, -180.0).to_u64()));
+        assert!(lat_range.contains(&GeoPoint::new(15.0, 0.0).to_u64()));
+        assert!(lat_range.contains(&GeoPoint::new(20.0, 180.0).to_u64()));
+        assert!(!lat_range.contains(&GeoPoint::new(9.0, 0.0).to_u64()));
+        assert!(!lat_range.contains(&GeoPoint::new(21.0, 0.0).to_u64()));
+    }
+
+    #[test]
+    fn test_geo_point_distance() {
+        let paris = GeoPoint::new(48.8566, 2.3522);
+        let london = GeoPoint::new(51.5074, -0.1278);
+        assert_eq!(paris.distance(&paris), 0.0);
+        let distance = paris.distance(&london);
+        assert!((distance - 343_550.0).abs() < 1_000.0, "{distance}");
+        assert_eq!(distance, london.distance(&paris));
+    }
+
+    #[test]
+    fn test_geo_point_geohash() {
+        let point = GeoPoint::new(57.64911, 10.40744);
+        assert_eq!(point.geohash(11), "u4pruydqqvj");
+        assert_eq!(point.geohash(1), "u");
+        assert_eq!(GeoPoint::new(48.8566, 2.3522).geohash(5), "u09tv");
+        assert_eq!(GeoPoint::new(-33.8688, 151.2093).geohash(4), "r3gx");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_geo_point_invalid_lat() {
+        GeoPoint::new(91.0, 0.0);
+    }
+}
@@ -120,11 +120,13 @@ mod bytes_options;
 mod date_time_options;
 mod field;
 mod flags;
+pub(crate) mod geo_point;
 mod index_record_option;
 mod ip_options;
 mod json_object_options;
 mod named_field_document;
 mod numeric_options;
+mod scaled_f64_options;
 mod text_options;
 
 use columnar::ColumnType;
@@ -142,6 +144,7 @@ pub use self::field_entry::FieldEntry;
 pub use self::field_type::{FieldType, Type};
 pub use self::field_value::FieldValue;
 pub use self::flags::{COERCE, FAST, INDEXED, STORED};
+pub use self::geo_point::GeoPoint;
 pub use self::index_record_option::IndexRecordOption;
 pub use self::ip_options::{IntoIpv6Addr, IpAddrOptions};
 pub use self::json_object_options::JsonObjectOptions;
@@ -149,7 +152,8 @@ pub use self::named_field_document::NamedFieldDocument;
 #[allow(deprecated)]
 pub use self::numeric_options::IntOptions;
 pub use self::numeric_options::NumericOptions;
-pub use self::schema::{Schema, SchemaBuilder};
+pub use self::scaled_f64_options::ScaledF64Options;
+pub use self::schema::{DocumentViolation, Schema, SchemaBuilder};
 pub use self::term::{Term, ValueBytes, JSON_END_OF_PATH};
 pub use self::text_options::{TextFieldIndexing, TextOptions, STRING, TEXT};
 
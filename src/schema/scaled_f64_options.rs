@@ -0,0 +1,148 @@
+use std::ops::Bound;
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::NumericOptions;
+
+/// Define how a `scaled_f64` field should be handled by tantivy.
+///
+/// A `scaled_f64` field receives `f64` values, but indexes them as the `i64` obtained by
+/// multiplying them by a scaling factor and rounding the result. With a scaling factor of `100`,
+/// `12.34` is indexed as `1234`.
+///
+/// This gives exact equality, range queries and aggregations over values with a fixed number of
+/// decimals, such as money amounts, without the rounding surprises of floats.
+/// The terms and the fast field column of the field hold the scaled `i64` values, so
+/// aggregations report scaled values too. The doc store keeps the original `f64` values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScaledF64Options {
+    scaling_factor: f64,
+    #[serde(flatten)]
+    numeric_options: NumericOptions,
+}
+
+impl ScaledF64Options {
+    /// Creates new options for a `scaled_f64` field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scaling factor is not a finite, strictly positive number.
+    pub fn new<T: Into<NumericOptions>>(scaling_factor: f64, numeric_options: T) -> Self {
+        assert!(
+            scaling_factor.is_finite() && scaling_factor > 0.0,
+            "The scaling factor must be a finite positive number, got {scaling_factor}"
+        );
+        ScaledF64Options {
+            scaling_factor,
+            numeric_options: numeric_options.into(),
+        }
+    }
+
+    /// Returns the factor values are multiplied by before being indexed.
+    #[inline]
+    pub fn scaling_factor(&self) -> f64 {
+        self.scaling_factor
+    }
+
+    /// Returns the indexing options of the field.
+    #[inline]
+    pub fn numeric_options(&self) -> &NumericOptions {
+        &self.numeric_options
+    }
+
+    /// Returns the scaled `i64` a value is indexed as.
+    ///
+    /// Values out of the `i64` range saturate to `i64::MIN` or `i64::MAX`.
+    #[inline]
+    pub fn scale(&self, val: f64) -> i64 {
+        (val * self.scaling_factor).round() as i64
+    }
+
+    /// Returns the `f64` value a scaled value stands for.
+    #[inline]
+    pub fn unscale(&self, scaled_val: i64) -> f64 {
+        scaled_val as f64 / self.scaling_factor
+    }
+
+    /// Maps the bounds of a range over the `f64` values to a range over the scaled values.
+    ///
+    /// Values get rounded when indexed, but the bounds are not: the resulting range matches
+    /// exactly the scaled values whose unscaled value is within the original range.
+    pub(crate) fn scale_bounds(
+        &self,
+        lower_bound: Bound<f64>,
+        upper_bound: Bound<f64>,
+    ) -> (Bound<i64>, Bound<i64>) {
+        let scale = |val: f64| val * self.scaling_factor;
+        let lower_bound = match lower_bound {
+            Bound::Included(val) => Bound::Included(scale(val).ceil() as i64),
+            Bound::Excluded(val) => Bound::Included((scale(val).floor() as i64).saturating_add(1)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let upper_bound = match upper_bound {
+            Bound::Included(val) => Bound::Included(scale(val).floor() as i64),
+            Bound::Excluded(val) => Bound::Excluded(scale(val).ceil() as i64),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        (lower_bound, upper_bound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::*;
+    use crate::schema::{FAST, INDEXED};
+
+    #[test]
+    fn test_scaled_f64_options_scale() {
+        let options = ScaledF64Options::new(100.0, INDEXED);
+        assert_eq!(options.scale(12.34), 1234);
+        assert_eq!(options.scale(0.1 + 0.2), 30);
+        assert_eq!(options.scale(-1.005), -100);
+        assert_eq!(options.unscale(1234), 12.34);
+        assert_eq!(options.scale(f64::MAX), i64::MAX);
+    }
+
+    #[test]
+    fn test_scaled_f64_options_scale_bounds() {
+        let options = ScaledF64Options::new(100.0, INDEXED);
+        assert_eq!(
+            options.scale_bounds(Bound::Included(10.005), Bound::Included(20.005)),
+            (Bound::Included(1001), Bound::Included(2000))
+        );
+        assert_eq!(
+            options.scale_bounds(Bound::Excluded(10.0), Bound::Excluded(20.0)),
+            (Bound::Included(1001), Bound::Excluded(2000))
+        );
+        assert_eq!(
+            options.scale_bounds(Bound::Unbounded, Bound::Excluded(20.001)),
+            (Bound::Unbounded, Bound::Excluded(2001))
+        );
+    }
+
+    #[test]
+    fn test_scaled_f64_options_serialization() {
+        let options = ScaledF64Options::new(100.0, INDEXED | FAST);
+        let json = serde_json::to_value(&options).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "scaling_factor": 100.0,
+                "indexed": true,
+                "fieldnorms": true,
+                "fast": true,
+                "stored": false
+            })
+        );
+        let deser: ScaledF64Options = serde_json::from_value(json).unwrap();
+        assert_eq!(deser, options);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_scaled_f64_options_invalid_scaling_factor() {
+        ScaledF64Options::new(0.0, INDEXED);
+    }
+}
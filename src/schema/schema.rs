@@ -5,11 +5,13 @@ use std::sync::Arc;
 use serde::de::{SeqAccess, Visitor};
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
 
 use super::ip_options::IpAddrOptions;
 use super::*;
 use crate::json_utils::split_json_path;
 use crate::schema::bytes_options::BytesOptions;
+use crate::tokenizer::TokenizerManager;
 use crate::TantivyError;
 
 /// Tantivy has a very strict schema.
@@ -91,6 +93,30 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a new scaled f64 field.
+    /// Returns the associated field handle
+    ///
+    /// The values of the field are indexed as `i64`, after being multiplied by
+    /// `scaling_factor`. See [`ScaledF64Options`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when field already exists, or if the scaling factor is not a finite positive
+    /// number.
+    pub fn add_scaled_f64_field<T: Into<NumericOptions>>(
+        &mut self,
+        field_name_str: &str,
+        scaling_factor: f64,
+        field_options: T,
+    ) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_scaled_f64(
+            field_name,
+            ScaledF64Options::new(scaling_factor, field_options),
+        );
+        self.add_field(field_entry)
+    }
+
     /// Adds a new bool field.
     /// Returns the associated field handle
     ///
@@ -308,6 +334,54 @@ impl Schema {
         SchemaBuilder::default()
     }
 
+    /// Parses a schema from its JSON representation, as serialized in the `meta.json` file of
+    /// an index.
+    ///
+    /// Unlike plain deserialization, the schema is validated:
+    /// - field names need to be valid and unique,
+    /// - the tokenizers need to be registered in the default [`TokenizerManager`],
+    /// - the options need to be consistent, e.g. a field has to be indexed, stored or fast.
+    ///
+    /// Use [`Schema::from_json_with_tokenizers()`] if the schema refers to custom tokenizers.
+    ///
+    /// ```
+    /// use tantivy::schema::Schema;
+    ///
+    /// let schema = Schema::from_json(
+    ///     r#"[{"name": "title", "type": "text", "options": {"stored": true,
+    ///     "indexing": {"record": "position", "fieldnorms": true, "tokenizer": "en_stem"}}}]"#,
+    /// )
+    /// .unwrap();
+    /// assert!(schema.get_field("title").is_ok());
+    /// ```
+    pub fn from_json(schema_json: &str) -> crate::Result<Schema> {
+        Schema::from_json_with_tokenizers(schema_json, &TokenizerManager::default())
+    }
+
+    /// Parses and validates a schema, like [`Schema::from_json()`], with the tokenizers
+    /// registered in `tokenizers`.
+    pub fn from_json_with_tokenizers(
+        schema_json: &str,
+        tokenizers: &TokenizerManager,
+    ) -> crate::Result<Schema> {
+        let field_entries: Vec<FieldEntry> = serde_json::from_str(schema_json)
+            .map_err(|err| TantivyError::SchemaError(format!("Invalid schema: {err}")))?;
+        let mut schema_builder = Schema::builder();
+        for field_entry in field_entries {
+            validate_field_entry(&field_entry, tokenizers).map_err(|err| {
+                TantivyError::SchemaError(format!("Invalid field {:?}: {err}", field_entry.name()))
+            })?;
+            if schema_builder.fields_map.contains_key(field_entry.name()) {
+                return Err(TantivyError::SchemaError(format!(
+                    "Duplicate field {:?}",
+                    field_entry.name()
+                )));
+            }
+            schema_builder.add_field(field_entry);
+        }
+        Ok(schema_builder.build())
+    }
+
     /// Returns the field option associated with a given name.
     pub fn get_field(&self, field_name: &str) -> crate::Result<Field> {
         self.0
@@ -346,6 +420,61 @@ impl Schema {
         None
     }
 
+    /// Returns the field named `field_name`, unless it does not exist or is
+    /// [ignored](FieldEntry::set_ignored).
+    pub(crate) fn get_field_for_new_values(&self, field_name: &str) -> Option<Field> {
+        let field = self.get_field(field_name).ok()?;
+        if self.get_field_entry(field).is_ignored() {
+            return None;
+        }
+        Some(field)
+    }
+
+    /// Checks that `doc` has a value for each of the required fields, and that its values have
+    /// the types of their fields.
+    ///
+    /// Returns all of the violations found, each field being reported at most once for a wrong
+    /// value type. Null values are not considered as values of their field.
+    pub fn validate<D: Document>(&self, doc: &D) -> Result<(), Vec<DocumentViolation>> {
+        let mut has_value = vec![false; self.num_fields()];
+        let mut has_wrong_value_type = vec![false; self.num_fields()];
+        for (field, value) in doc.iter_fields_and_values() {
+            let field_ord = field.field_id() as usize;
+            let Some(field_entry) = self.0.fields.get(field_ord) else {
+                continue;
+            };
+            if field_entry.is_ignored() {
+                continue;
+            }
+            if !field_entry.field_type().accepts_value(&value) {
+                has_wrong_value_type[field_ord] = true;
+            } else if !value.is_null() {
+                has_value[field_ord] = true;
+            }
+        }
+        let mut violations = Vec::new();
+        for (field_ord, field_entry) in self.0.fields.iter().enumerate() {
+            if has_wrong_value_type[field_ord] {
+                violations.push(DocumentViolation::WrongValueType {
+                    field_name: field_entry.name().to_string(),
+                    expected: field_entry.field_type().value_type(),
+                });
+            } else if field_entry.is_required()
+                && !field_entry.is_ignored()
+                && !has_value[field_ord]
+            {
+                violations.push(DocumentViolation::MissingRequiredField(
+                    field_entry.name().to_string(),
+                ));
+            }
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
     /// Transforms a user-supplied fast field name into a column name.
     ///
     /// This is similar to `.find_field` except it includes some fallback logic to
@@ -372,6 +501,65 @@ impl Schema {
     }
 }
 
+/// A reason why a document does not comply with its schema, as returned by
+/// [`Schema::validate()`].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum DocumentViolation {
+    /// The document has no value for a required field.
+    #[error("The required field {0:?} is missing")]
+    MissingRequiredField(String),
+    /// The document has a value of the wrong type for a field.
+    #[error("Expected a {expected:?} for field {field_name:?}")]
+    WrongValueType {
+        /// The name of the field.
+        field_name: String,
+        /// The type of the values of the field.
+        expected: Type,
+    },
+}
+
+fn validate_field_entry(
+    field_entry: &FieldEntry,
+    tokenizers: &TokenizerManager,
+) -> Result<(), String> {
+    if !is_valid_field_name(field_entry.name()) {
+        return Err("invalid field name".to_string());
+    }
+    if !field_entry.is_indexed() && !field_entry.is_stored() && !field_entry.is_fast() {
+        return Err("the field is neither indexed, stored nor fast".to_string());
+    }
+    let (indexing_options, fast_tokenizer_name) = match field_entry.field_type() {
+        FieldType::Str(options) => (
+            options.get_indexing_options(),
+            options.get_fast_field_tokenizer_name(),
+        ),
+        FieldType::JsonObject(options) => (
+            options.get_text_indexing_options(),
+            options.get_fast_field_tokenizer_name(),
+        ),
+        FieldType::ScaledF64(options) => {
+            let scaling_factor = options.scaling_factor();
+            if !(scaling_factor.is_finite() && scaling_factor > 0.0) {
+                return Err(format!(
+                    "the scaling factor must be a finite positive number, got {scaling_factor}"
+                ));
+            }
+            (None, None)
+        }
+        _ => (None, None),
+    };
+    let tokenizer_names = indexing_options
+        .map(TextFieldIndexing::tokenizer)
+        .into_iter()
+        .chain(fast_tokenizer_name);
+    for tokenizer_name in tokenizer_names {
+        if tokenizers.get(tokenizer_name).is_none() {
+            return Err(format!("unknown tokenizer {tokenizer_name:?}"));
+        }
+    }
+    Ok(())
+}
+
 impl Serialize for Schema {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where S: Serializer {
@@ -661,6 +849,49 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn test_validate_document() {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder
+            .add_field(FieldEntry::new_u64("id".to_string(), INDEXED.into()).set_required());
+        let title = schema_builder
+            .add_field(FieldEntry::new_text("title".to_string(), TEXT).set_required());
+        let count = schema_builder.add_u64_field("count", INDEXED);
+        let schema = schema_builder.build();
+        assert!(schema.get_field_entry(id).is_required());
+        assert!(!schema.get_field_entry(count).is_required());
+        let schema_json = serde_json::to_string(&schema).unwrap();
+        assert!(schema_json.contains(r#""required":true"#));
+        let deserialized_schema: Schema = serde_json::from_str(&schema_json).unwrap();
+        assert_eq!(deserialized_schema, schema);
+
+        assert_eq!(schema.validate(&doc!(id => 1u64, title => "a")), Ok(()));
+        assert_eq!(
+            schema.validate(&doc!(id => 1u64, title => "a", title => "b", count => 2u64)),
+            Ok(())
+        );
+        assert_eq!(
+            schema.validate(&doc!(id => 1u64, count => 2u64)),
+            Err(vec![DocumentViolation::MissingRequiredField(
+                "title".to_string()
+            )])
+        );
+        assert_eq!(
+            schema.validate(&doc!(id => "1", title => OwnedValue::Null, count => -1i64)),
+            Err(vec![
+                DocumentViolation::WrongValueType {
+                    field_name: "id".to_string(),
+                    expected: Type::U64,
+                },
+                DocumentViolation::MissingRequiredField("title".to_string()),
+                DocumentViolation::WrongValueType {
+                    field_name: "count".to_string(),
+                    expected: Type::U64,
+                },
+            ])
+        );
+    }
+
     #[test]
     pub fn test_document_missing_field_no_error() {
         let schema = Schema::builder().build();
@@ -741,10 +972,10 @@ mod tests {
             );
             assert_matches!(
                 json_err,
-                Err(DocParsingError::ValueError(
-                    _,
-                    ValueParsingError::TypeError { .. }
-                ))
+                Err(DocParsingError::ValueError {
+                    error: ValueParsingError::TypeError { .. },
+                    ..
+                })
             );
         }
         {
@@ -760,10 +991,10 @@ mod tests {
             );
             assert_matches!(
                 json_err,
-                Err(DocParsingError::ValueError(
-                    _,
-                    ValueParsingError::OverflowError { .. }
-                ))
+                Err(DocParsingError::ValueError {
+                    error: ValueParsingError::OverflowError { .. },
+                    ..
+                })
             );
         }
         {
@@ -779,10 +1010,10 @@ mod tests {
             );
             assert!(!matches!(
                 json_err,
-                Err(DocParsingError::ValueError(
-                    _,
-                    ValueParsingError::OverflowError { .. }
-                ))
+                Err(DocParsingError::ValueError {
+                    error: ValueParsingError::OverflowError { .. },
+                    ..
+                })
             ));
         }
         {
@@ -798,16 +1029,16 @@ mod tests {
             );
             assert_matches!(
                 json_err,
-                Err(DocParsingError::ValueError(
-                    _,
-                    ValueParsingError::OverflowError { .. }
-                ))
+                Err(DocParsingError::ValueError {
+                    error: ValueParsingError::OverflowError { .. },
+                    ..
+                })
             );
         }
         {
             // Short JSON, under the 20 char take.
             let json_err = TantivyDocument::parse_json(&schema, r#"{"count": 50,}"#);
-            assert_matches!(json_err, Err(InvalidJson(_)));
+            assert_matches!(json_err, Err(InvalidJson { .. }));
         }
         {
             let json_err = TantivyDocument::parse_json(
@@ -818,7 +1049,7 @@ mod tests {
                 "count": 50,
             }"#,
             );
-            assert_matches!(json_err, Err(InvalidJson(_)));
+            assert_matches!(json_err, Err(InvalidJson { .. }));
         }
     }
 
@@ -964,4 +1195,53 @@ mod tests {
         assert_eq!(schema.find_field("thiswouldbeareallylongfieldname"), None);
         assert_eq!(schema.find_field("baz.bar.foo"), None);
     }
+
+    #[test]
+    fn test_schema_from_json() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_u64_field("year", INDEXED | FAST);
+        schema_builder.add_json_field("attributes", TEXT);
+        let schema = schema_builder.build();
+        let schema_json = serde_json::to_string(&schema).unwrap();
+        assert_eq!(Schema::from_json(&schema_json).unwrap(), schema);
+
+        let is_schema_error = |schema_json: &str| {
+            matches!(
+                Schema::from_json(schema_json),
+                Err(crate::TantivyError::SchemaError(_))
+            )
+        };
+        assert!(is_schema_error("{}"));
+        assert!(is_schema_error(
+            r#"[{"name": "year", "type": "u64", "options": {"indexed": true}},
+            {"name": "year", "type": "i64", "options": {"indexed": true}}]"#
+        ));
+        assert!(is_schema_error(
+            r#"[{"name": "title", "type": "text", "options": {"indexing": {"record": "basic",
+            "fieldnorms": true, "tokenizer": "klingon"}}}]"#
+        ));
+        assert!(is_schema_error(
+            r#"[{"name": "title", "type": "text", "options": {"fast": {"with_tokenizer": "klingon"}}}]"#
+        ));
+        assert!(is_schema_error(
+            r#"[{"name": "year", "type": "u64", "options": {"indexed": false}}]"#
+        ));
+        assert!(is_schema_error(
+            r#"[{"name": "price", "type": "scaled_f64", "options": {"scaling_factor": 0.0,
+            "indexed": true}}]"#
+        ));
+        assert!(is_schema_error(
+            r#"[{"name": "-year", "type": "u64", "options": {"indexed": true}}]"#
+        ));
+
+        let tokenizers = crate::tokenizer::TokenizerManager::default();
+        tokenizers.register("klingon", crate::tokenizer::RawTokenizer::default());
+        assert!(Schema::from_json_with_tokenizers(
+            r#"[{"name": "title", "type": "text", "options": {"indexing": {"record": "basic",
+            "fieldnorms": true, "tokenizer": "klingon"}}}]"#,
+            &tokenizers,
+        )
+        .is_ok());
+    }
 }
@@ -4,6 +4,7 @@ use std::ops::BitOr;
 use serde::{Deserialize, Serialize};
 
 use super::flags::{CoerceFlag, FastFlag};
+use crate::postings::POSITION_GAP;
 use crate::schema::flags::{SchemaFlagList, StoredFlag};
 use crate::schema::IndexRecordOption;
 
@@ -194,6 +195,10 @@ impl TokenizerName {
 /// - The name of the `Tokenizer` that should be used to process the field.
 /// - Flag indicating, if fieldnorms should be stored (See [fieldnorm](crate::fieldnorm)). Defaults
 ///   to `true`.
+/// - The number of positions left empty between the values of a multi-valued field. Defaults to
+///   `1`.
+/// - Flag indicating, if term vectors should be stored (See [termvector](crate::termvector)).
+///   Defaults to `false`.
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     #[serde(default)]
@@ -202,18 +207,34 @@ pub struct TextFieldIndexing {
     fieldnorms: bool,
     #[serde(default)]
     tokenizer: TokenizerName,
+    #[serde(default = "default_position_gap")]
+    #[serde(skip_serializing_if = "is_default_position_gap")]
+    position_gap: u32,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    term_vectors: bool,
 }
 
 pub(crate) fn default_fieldnorms() -> bool {
     true
 }
 
+fn default_position_gap() -> u32 {
+    POSITION_GAP
+}
+
+fn is_default_position_gap(position_gap: &u32) -> bool {
+    *position_gap == POSITION_GAP
+}
+
 impl Default for TextFieldIndexing {
     fn default() -> TextFieldIndexing {
         TextFieldIndexing {
             tokenizer: TokenizerName::default(),
             record: IndexRecordOption::default(),
             fieldnorms: default_fieldnorms(),
+            position_gap: POSITION_GAP,
+            term_vectors: false,
         }
     }
 }
@@ -258,6 +279,37 @@ impl TextFieldIndexing {
     pub fn index_option(&self) -> IndexRecordOption {
         self.record
     }
+
+    /// Sets the number of positions left empty between the values of a multi-valued field.
+    ///
+    /// A large enough gap prevents phrase queries, even with some slop, from matching the end of
+    /// a value followed by the beginning of the next one. Defaults to `1`, which only prevents
+    /// exact phrase queries from doing so.
+    #[must_use]
+    pub fn set_position_gap(mut self, position_gap: u32) -> TextFieldIndexing {
+        self.position_gap = position_gap;
+        self
+    }
+
+    /// Returns the number of positions left empty between the values of a multi-valued field.
+    pub fn position_gap(&self) -> u32 {
+        self.position_gap
+    }
+
+    /// Sets whether the term vectors of the field should be stored.
+    ///
+    /// The term vector of a document field lists its terms with their positions and offsets,
+    /// as they were when the document was indexed. See [termvector](crate::termvector).
+    #[must_use]
+    pub fn set_term_vectors(mut self, term_vectors: bool) -> TextFieldIndexing {
+        self.term_vectors = term_vectors;
+        self
+    }
+
+    /// Returns true if and only if the term vectors of the field are stored.
+    pub fn term_vectors(&self) -> bool {
+        self.term_vectors
+    }
 }
 
 /// The field will be untokenized and indexed.
@@ -266,6 +318,8 @@ pub const STRING: TextOptions = TextOptions {
         tokenizer: TokenizerName::from_static(NO_TOKENIZER_NAME),
         fieldnorms: true,
         record: IndexRecordOption::Basic,
+        position_gap: POSITION_GAP,
+        term_vectors: false,
     }),
     stored: false,
     fast: FastFieldTextOptions::IsEnabled(false),
@@ -278,6 +332,8 @@ pub const TEXT: TextOptions = TextOptions {
         tokenizer: TokenizerName::from_static(DEFAULT_TOKENIZER_NAME),
         fieldnorms: true,
         record: IndexRecordOption::WithFreqsAndPositions,
+        position_gap: POSITION_GAP,
+        term_vectors: false,
     }),
     stored: false,
     coerce: false,
@@ -226,6 +226,66 @@ fn search_fragments(
     fragments
 }
 
+/// Adds the score of the terms of a phrase a second time to the fragments containing the
+/// full phrase, so that they get preferred over the fragments containing its terms
+/// scattered around.
+///
+/// `phrases` are the terms of each phrase, along with their offset in the phrase.
+fn score_phrases(
+    tokenizer: &mut TextAnalyzer,
+    text: &str,
+    terms: &BTreeMap<String, Score>,
+    phrases: &[Vec<(usize, String)>],
+    fragments: &mut [FragmentCandidate],
+) {
+    let mut tokens: Vec<(usize, String, Range<usize>)> = Vec::new();
+    tokenizer.token_stream(text).process(&mut |token| {
+        tokens.push((
+            token.position,
+            token.text.to_lowercase(),
+            token.offset_from..token.offset_to,
+        ));
+    });
+    for phrase in phrases {
+        let (first_offset, first_text) = &phrase[0];
+        let phrase_score: Score = phrase
+            .iter()
+            .map(|(_, term_text)| terms.get(term_text).copied().unwrap_or(0.0))
+            .sum();
+        for (position, _, range) in tokens.iter().filter(|(_, text, _)| text == first_text) {
+            let Some(phrase_position) = position.checked_sub(*first_offset) else {
+                continue;
+            };
+            let mut phrase_range = range.clone();
+            let is_match = phrase[1..].iter().all(|(offset, term_text)| {
+                let target_position = phrase_position + offset;
+                let start = tokens.partition_point(|(position, _, _)| *position < target_position);
+                let matching_token = tokens[start..]
+                    .iter()
+                    .take_while(|(position, _, _)| *position == target_position)
+                    .find(|(_, text, _)| text == term_text);
+                if let Some((_, _, range)) = matching_token {
+                    phrase_range.start = phrase_range.start.min(range.start);
+                    phrase_range.end = phrase_range.end.max(range.end);
+                    true
+                } else {
+                    false
+                }
+            });
+            if !is_match {
+                continue;
+            }
+            for fragment in fragments.iter_mut() {
+                if fragment.start_offset <= phrase_range.start
+                    && phrase_range.end <= fragment.stop_offset
+                {
+                    fragment.score += phrase_score;
+                }
+            }
+        }
+    }
+}
+
 /// Returns a Snippet
 ///
 /// Takes a vector of `FragmentCandidate`s and the text.
@@ -347,6 +407,7 @@ fn is_sorted(mut it: impl Iterator<Item = usize>) -> bool {
 /// ```
 pub struct SnippetGenerator {
     terms_text: BTreeMap<String, Score>,
+    phrases: Vec<Vec<(usize, String)>>,
     tokenizer: TextAnalyzer,
     field: Field,
     max_num_chars: usize,
@@ -362,6 +423,7 @@ impl SnippetGenerator {
     ) -> Self {
         SnippetGenerator {
             terms_text,
+            phrases: Vec::new(),
             tokenizer,
             field,
             max_num_chars,
@@ -393,9 +455,27 @@ impl SnippetGenerator {
                 terms_text.insert(term_str.to_string(), score);
             }
         }
+        let mut phrases: Vec<Vec<(usize, String)>> = Vec::new();
+        query.query_phrases(&mut |phrase_terms| {
+            let phrase: Option<Vec<(usize, String)>> = phrase_terms
+                .iter()
+                .map(|(offset, term)| {
+                    let term_value = term.value();
+                    let term_str = term_value.as_str()?;
+                    (term.field() == field && terms_text.contains_key(term_str))
+                        .then(|| (*offset, term_str.to_string()))
+                })
+                .collect();
+            if let Some(phrase) = phrase {
+                if !phrase.is_empty() && !phrases.contains(&phrase) {
+                    phrases.push(phrase);
+                }
+            }
+        });
         let tokenizer = searcher.index().tokenizer_for_field(field)?;
         Ok(SnippetGenerator {
             terms_text,
+            phrases,
             tokenizer,
             field,
             max_num_chars: DEFAULT_MAX_NUM_CHARS,
@@ -434,13 +514,22 @@ impl SnippetGenerator {
     }
 
     /// Generates a snippet for the given text.
+    ///
+    /// The fragments containing the full phrases of the query are preferred over the ones
+    /// containing their terms scattered around.
     pub fn snippet(&self, text: &str) -> Snippet {
-        let fragment_candidates = search_fragments(
-            &mut self.tokenizer.clone(),
-            text,
-            &self.terms_text,
-            self.max_num_chars,
-        );
+        let mut tokenizer = self.tokenizer.clone();
+        let mut fragment_candidates =
+            search_fragments(&mut tokenizer, text, &self.terms_text, self.max_num_chars);
+        if !self.phrases.is_empty() {
+            score_phrases(
+                &mut tokenizer,
+                text,
+                &self.terms_text,
+                &self.phrases,
+                &mut fragment_candidates,
+            );
+        }
         select_best_fragment_combination(&fragment_candidates[..], text)
     }
 }
@@ -741,6 +830,37 @@ Survey in 2016, 2017, and 2018."#;
         Ok(())
     }
 
+    #[test]
+    fn test_snippet_generator_phrase() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let text = "Rust is a systems language. It has been around for a while now. The Rust \
+                    language is fast.";
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => text))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+
+        let query = query_parser.parse_query("rust language")?;
+        let mut snippet_generator = SnippetGenerator::create(&searcher, &*query, text_field)?;
+        snippet_generator.set_max_num_chars(30);
+        assert_eq!(
+            snippet_generator.snippet(text).to_html(),
+            "<b>Rust</b> is a systems <b>language</b>. It"
+        );
+
+        let query = query_parser.parse_query("\"rust language\"")?;
+        let mut snippet_generator = SnippetGenerator::create(&searcher, &*query, text_field)?;
+        snippet_generator.set_max_num_chars(30);
+        assert_eq!(
+            snippet_generator.snippet(text).to_html(),
+            "now. The <b>Rust</b> <b>language</b> is fast"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_collapse_overlapped_ranges() {
         assert_eq!(&collapse_overlapped_ranges(&[0..1, 2..3,]), &[0..1, 2..3]);
@@ -72,6 +72,9 @@ pub struct SegmentSpaceUsage {
 
     store: StoreSpaceUsage,
 
+    #[serde(default)]
+    term_vectors: ByteCount,
+
     deletes: ByteCount,
 
     total: ByteCount,
@@ -87,6 +90,7 @@ impl SegmentSpaceUsage {
         fast_fields: PerFieldSpaceUsage,
         fieldnorms: PerFieldSpaceUsage,
         store: StoreSpaceUsage,
+        term_vectors: ByteCount,
         deletes: ByteCount,
     ) -> SegmentSpaceUsage {
         let total = termdict.total()
@@ -95,6 +99,7 @@ impl SegmentSpaceUsage {
             + fast_fields.total()
             + fieldnorms.total()
             + store.total()
+            + term_vectors
             + deletes;
         SegmentSpaceUsage {
             num_docs,
@@ -104,6 +109,7 @@ impl SegmentSpaceUsage {
             fast_fields,
             fieldnorms,
             store,
+            term_vectors,
             deletes,
             total,
         }
@@ -119,11 +125,12 @@ impl SegmentSpaceUsage {
         match component {
             Postings => PerField(self.postings().clone()),
             Positions => PerField(self.positions().clone()),
-            FastFields => PerField(self.fast_fields().clone()),
+            FastFields | FastFieldUpdates => PerField(self.fast_fields().clone()),
             FieldNorms => PerField(self.fieldnorms().clone()),
             Terms => PerField(self.termdict().clone()),
             SegmentComponent::Store => ComponentSpaceUsage::Store(self.store().clone()),
             SegmentComponent::TempStore => ComponentSpaceUsage::Store(self.store().clone()),
+            TermVectors => Basic(self.term_vectors()),
             Delete => Basic(self.deletes()),
         }
     }
@@ -163,6 +170,11 @@ impl SegmentSpaceUsage {
         &self.store
     }
 
+    /// Space usage for term vectors
+    pub fn term_vectors(&self) -> ByteCount {
+        self.term_vectors
+    }
+
     /// Space usage for document deletions
     pub fn deletes(&self) -> ByteCount {
         self.deletes
@@ -357,6 +369,10 @@ mod test {
         // TODO: understand why the following fails
         //        assert_eq!(0, segment.store().total());
         assert_eq!(segment.deletes(), 0);
+
+        let index_space_usage = index.space_usage()?;
+        assert_eq!(index_space_usage.total(), searcher_space_usage.total());
+        assert_eq!(index_space_usage.segments().len(), 1);
         Ok(())
     }
 
@@ -34,11 +34,16 @@ mod decompressors;
 mod footer;
 mod index;
 mod reader;
+mod stored_document;
+mod transform;
 mod writer;
 pub use self::compressors::{Compressor, ZstdCompressor};
 pub use self::decompressors::Decompressor;
 pub(crate) use self::reader::DOCSTORE_CACHE_CAPACITY;
 pub use self::reader::{CacheStats, StoreReader};
+pub use self::stored_document::{StoredArray, StoredDocument, StoredObject, StoredValue};
+pub(crate) use self::transform::StoredValueTransforms;
+pub use self::transform::StoredValueTransform;
 pub use self::writer::StoreWriter;
 mod store_compressor;
 
@@ -149,6 +154,44 @@ pub mod tests {
             }
         }
 
+        let mut num_docs = 0;
+        for doc_res in store.iter_with_doc_ids::<TantivyDocument>(Some(&alive_bitset)) {
+            let (doc_id, doc) = doc_res?;
+            assert!(alive_bitset.is_alive(doc_id));
+            assert_eq!(
+                doc.get_first(field_title).unwrap().as_str(),
+                Some(format!("Doc {doc_id}").as_str())
+            );
+            num_docs += 1;
+        }
+        assert_eq!(num_docs, NUM_DOCS - deleted_doc_ids.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_get_many() -> crate::Result<()> {
+        let path = Path::new("store");
+        let directory = RamDirectory::create();
+        let store_wrt = directory.open_write(path)?;
+        let schema =
+            write_lorem_ipsum_store(store_wrt, NUM_DOCS, Compressor::Lz4, BLOCK_SIZE, true);
+        let field_title = schema.get_field("title").unwrap();
+        let store_file = directory.open_read(path)?;
+        let store = StoreReader::open(store_file, 0)?;
+        let doc_ids = [999u32, 3, 500, 3, 0, 998];
+        let docs = store.get_many::<TantivyDocument>(&doc_ids)?;
+        assert_eq!(docs.len(), doc_ids.len());
+        for (doc_id, doc) in doc_ids.iter().zip(&docs) {
+            assert_eq!(
+                doc.get_first(field_title).unwrap().as_str(),
+                Some(format!("Doc {doc_id}").as_str())
+            );
+        }
+        assert!(store.get_many::<TantivyDocument>(&[]).unwrap().is_empty());
+        assert!(store
+            .get_many::<TantivyDocument>(&[NUM_DOCS as u32])
+            .is_err());
         Ok(())
     }
 
@@ -10,7 +10,9 @@ use lru::LruCache;
 
 use super::footer::DocStoreFooter;
 use super::index::SkipIndex;
-use super::Decompressor;
+use super::transform::StoredValueTransforms;
+use super::{Decompressor, StoredDocument};
+use crate::core::metrics::{self, MetricsCounters};
 use crate::directory::FileSlice;
 use crate::error::DataCorruption;
 use crate::fastfield::AliveBitSet;
@@ -30,6 +32,7 @@ pub struct StoreReader {
     skip_index: Arc<SkipIndex>,
     space_usage: StoreSpaceUsage,
     cache: BlockCache,
+    stored_value_transforms: Option<StoredValueTransforms>,
 }
 
 /// The cache for decompressed blocks.
@@ -37,6 +40,8 @@ struct BlockCache {
     cache: Option<Mutex<LruCache<usize, Block>>>,
     cache_hits: AtomicUsize,
     cache_misses: AtomicUsize,
+    // The index wide counters, also updated on cache hits and misses.
+    metrics: Option<Arc<MetricsCounters>>,
 }
 
 impl BlockCache {
@@ -47,9 +52,15 @@ impl BlockCache {
             .and_then(|cache| cache.lock().unwrap().get(&pos).cloned())
         {
             self.cache_hits.fetch_add(1, Ordering::SeqCst);
+            if let Some(metrics) = &self.metrics {
+                metrics::incr(&metrics.doc_store_cache_hits, 1);
+            }
             return Some(block);
         }
         self.cache_misses.fetch_add(1, Ordering::SeqCst);
+        if let Some(metrics) = &self.metrics {
+            metrics::incr(&metrics.doc_store_cache_misses, 1);
+        }
         None
     }
 
@@ -64,9 +75,21 @@ impl BlockCache {
             cache_hits: self.cache_hits.load(Ordering::Relaxed),
             cache_misses: self.cache_misses.load(Ordering::Relaxed),
             num_entries: self.len(),
+            num_bytes: self.num_bytes(),
         }
     }
 
+    fn num_bytes(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| {
+            cache
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(_, block)| block.len())
+                .sum()
+        })
+    }
+
     fn len(&self) -> usize {
         self.cache
             .as_ref()
@@ -90,6 +113,8 @@ pub struct CacheStats {
     pub cache_hits: usize,
     /// The number of cache misses.
     pub cache_misses: usize,
+    /// The number of bytes of the decompressed blocks in the cache.
+    pub num_bytes: usize,
 }
 
 impl AddAssign for CacheStats {
@@ -98,6 +123,7 @@ impl AddAssign for CacheStats {
             num_entries: self.num_entries + other.num_entries,
             cache_hits: self.cache_hits + other.cache_hits,
             cache_misses: self.cache_misses + other.cache_misses,
+            num_bytes: self.num_bytes + other.num_bytes,
         };
     }
 }
@@ -133,12 +159,41 @@ impl StoreReader {
                     .map(|cache_num_blocks| Mutex::new(LruCache::new(cache_num_blocks))),
                 cache_hits: Default::default(),
                 cache_misses: Default::default(),
+                metrics: None,
             },
             skip_index: Arc::new(skip_index),
             space_usage,
+            stored_value_transforms: None,
         })
     }
 
+    /// Reports the cache hits and misses to the given index metrics as well.
+    pub(crate) fn with_metrics(mut self, metrics: Arc<MetricsCounters>) -> StoreReader {
+        self.cache.metrics = Some(metrics);
+        self
+    }
+
+    /// Applies the stored value transforms of the index to the documents it returns.
+    pub(crate) fn with_stored_value_transforms(
+        mut self,
+        stored_value_transforms: StoredValueTransforms,
+    ) -> StoreReader {
+        self.stored_value_transforms = Some(stored_value_transforms);
+        self
+    }
+
+    fn deserialize_doc<D: DocumentDeserialize>(
+        &self,
+        mut doc_bytes: OwnedBytes,
+    ) -> crate::Result<D> {
+        if let Some(stored_value_transforms) = &self.stored_value_transforms {
+            return stored_value_transforms.deserialize(doc_bytes);
+        }
+        let deserializer = BinaryDocumentDeserializer::from_reader(&mut doc_bytes)
+            .map_err(crate::TantivyError::from)?;
+        D::deserialize(deserializer).map_err(crate::TantivyError::from)
+    }
+
     pub(crate) fn block_checkpoints(&self) -> impl Iterator<Item = Checkpoint> + '_ {
         self.skip_index.checkpoints()
     }
@@ -199,11 +254,46 @@ impl StoreReader {
     /// It should not be called to score documents
     /// for instance.
     pub fn get<D: DocumentDeserialize>(&self, doc_id: DocId) -> crate::Result<D> {
-        let mut doc_bytes = self.get_document_bytes(doc_id)?;
+        let doc_bytes = self.get_document_bytes(doc_id)?;
+        self.deserialize_doc(doc_bytes)
+    }
 
-        let deserializer = BinaryDocumentDeserializer::from_reader(&mut doc_bytes)
-            .map_err(crate::TantivyError::from)?;
-        D::deserialize(deserializer).map_err(crate::TantivyError::from)
+    /// Reads several documents, returned in the order of `doc_ids`.
+    ///
+    /// The documents are read in the order of their `DocId`, so that each block is
+    /// decompressed once, no matter how many of the requested documents it holds.
+    pub fn get_many<D: DocumentDeserialize>(&self, doc_ids: &[DocId]) -> crate::Result<Vec<D>> {
+        let mut doc_ords: Vec<usize> = (0..doc_ids.len()).collect();
+        doc_ords.sort_by_key(|&doc_ord| doc_ids[doc_ord]);
+        let mut docs: Vec<Option<D>> = (0..doc_ids.len()).map(|_| None).collect();
+        let mut current_block: Option<(Checkpoint, Block)> = None;
+        for doc_ord in doc_ords {
+            let doc_id = doc_ids[doc_ord];
+            let (checkpoint, block) = match current_block {
+                Some((ref checkpoint, ref block)) if checkpoint.doc_range.contains(&doc_id) => {
+                    (checkpoint, block)
+                }
+                _ => {
+                    let checkpoint = self.block_checkpoint(doc_id)?;
+                    let block = self.read_block(&checkpoint)?;
+                    let (checkpoint, block) = current_block.insert((checkpoint, block));
+                    (&*checkpoint, &*block)
+                }
+            };
+            let doc_bytes = Self::get_document_bytes_from_block(block.clone(), doc_id, checkpoint)?;
+            docs[doc_ord] = Some(self.deserialize_doc(doc_bytes)?);
+        }
+        // Every document has been read at this point.
+        Ok(docs.into_iter().flatten().collect())
+    }
+
+    /// Reads a given document, without copying its values.
+    ///
+    /// See [`StoredDocument`] for more information, and [`StoreReader::get()`] about the cost
+    /// of reading a document.
+    pub fn get_stored_document(&self, doc_id: DocId) -> crate::Result<StoredDocument> {
+        let doc_bytes = self.get_document_bytes(doc_id)?;
+        Ok(StoredDocument::open(doc_bytes)?)
     }
 
     /// Returns raw bytes of a given document.
@@ -239,13 +329,25 @@ impl StoreReader {
         &'b self,
         alive_bitset: Option<&'a AliveBitSet>,
     ) -> impl Iterator<Item = crate::Result<D>> + 'b {
-        self.iter_raw(alive_bitset).map(|doc_bytes_res| {
-            let mut doc_bytes = doc_bytes_res?;
+        self.iter_raw(alive_bitset)
+            .map(|doc_bytes_res| self.deserialize_doc(doc_bytes_res?))
+    }
 
-            let deserializer = BinaryDocumentDeserializer::from_reader(&mut doc_bytes)
-                .map_err(crate::TantivyError::from)?;
-            D::deserialize(deserializer).map_err(crate::TantivyError::from)
-        })
+    /// Iterator over all Documents along with their `DocId`, in their order as they are stored
+    /// in the doc store.
+    ///
+    /// Like [`StoreReader::iter()`], every block is decompressed once, rather than once per
+    /// document, which makes it the way to go to export the documents of a segment.
+    /// The `alive_bitset` has to be forwarded from the `SegmentReader` or the results may be wrong.
+    pub fn iter_with_doc_ids<'a: 'b, 'b, D: Document + DocumentDeserialize>(
+        &'b self,
+        alive_bitset: Option<&'a AliveBitSet>,
+    ) -> impl Iterator<Item = crate::Result<(DocId, D)>> + 'b {
+        self.iter_raw_with_doc_ids(alive_bitset)
+            .map(|doc_bytes_res| {
+                let (doc_id, doc_bytes) = doc_bytes_res?;
+                Ok((doc_id, self.deserialize_doc(doc_bytes)?))
+            })
     }
 
     /// Iterator over all raw Documents in their order as they are stored in the doc store.
@@ -255,6 +357,16 @@ impl StoreReader {
         &'b self,
         alive_bitset: Option<&'a AliveBitSet>,
     ) -> impl Iterator<Item = crate::Result<OwnedBytes>> + 'b {
+        self.iter_raw_with_doc_ids(alive_bitset)
+            .map(|doc_bytes_res| doc_bytes_res.map(|(_, doc_bytes)| doc_bytes))
+    }
+
+    /// Iterator over all raw Documents along with their `DocId`, in their order as they are
+    /// stored in the doc store.
+    fn iter_raw_with_doc_ids<'a: 'b, 'b>(
+        &'b self,
+        alive_bitset: Option<&'a AliveBitSet>,
+    ) -> impl Iterator<Item = crate::Result<(DocId, OwnedBytes)>> + 'b {
         let last_doc_id = self
             .block_checkpoints()
             .last()
@@ -282,14 +394,14 @@ impl StoreReader {
 
                 let alive = alive_bitset.map_or(true, |bitset| bitset.is_alive(doc_id));
                 let res = if alive {
-                    Some((curr_block.clone(), doc_pos))
+                    Some((doc_id, curr_block.clone(), doc_pos))
                 } else {
                     None
                 };
                 doc_pos += 1;
                 res
             })
-            .map(move |(block, doc_pos)| {
+            .map(move |(doc_id, block, doc_pos)| {
                 let block = block
                     .ok_or_else(|| {
                         DataCorruption::comment_only(
@@ -302,7 +414,7 @@ impl StoreReader {
                     })?;
 
                 let range = block_read_index(&block, doc_pos)?;
-                Ok(block.slice(range))
+                Ok((doc_id, block.slice(range)))
             })
     }
 
@@ -371,11 +483,8 @@ impl StoreReader {
 
     /// Fetches a document asynchronously. Async version of [`get`](Self::get).
     pub async fn get_async<D: DocumentDeserialize>(&self, doc_id: DocId) -> crate::Result<D> {
-        let mut doc_bytes = self.get_document_bytes_async(doc_id).await?;
-
-        let deserializer = BinaryDocumentDeserializer::from_reader(&mut doc_bytes)
-            .map_err(crate::TantivyError::from)?;
-        D::deserialize(deserializer).map_err(crate::TantivyError::from)
+        let doc_bytes = self.get_document_bytes_async(doc_id).await?;
+        self.deserialize_doc(doc_bytes)
     }
 }
 
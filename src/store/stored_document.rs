@@ -0,0 +1,432 @@
+use std::io;
+use std::net::Ipv6Addr;
+
+use columnar::MonotonicallyMappableToU128;
+use common::{u64_to_f64, BinarySerializable, DateTime, OwnedBytes, VInt};
+
+use crate::schema::document::type_codes;
+use crate::schema::{Facet, Field, OwnedValue};
+
+/// A document of the doc store, giving access to its stored values without copying them.
+///
+/// The text and bytes values borrow directly from the decompressed block of the doc store the
+/// document belongs to, which the `StoredDocument` keeps alive. This avoids allocating a
+/// `String` or a `Vec` for each value, which matters when serving large pages of results,
+/// or documents with large values.
+///
+/// The document is validated when it is read, so that accessing its values cannot fail.
+#[derive(Clone)]
+pub struct StoredDocument {
+    // The serialized field values, without their count.
+    field_values: OwnedBytes,
+    num_field_values: usize,
+}
+
+impl StoredDocument {
+    /// Reads a document serialized in the doc store.
+    pub(crate) fn open(mut doc_bytes: OwnedBytes) -> io::Result<StoredDocument> {
+        let num_field_values = VInt::deserialize_u64(&mut doc_bytes)? as usize;
+        let mut data = doc_bytes.as_slice();
+        for _ in 0..num_field_values {
+            Field::deserialize(&mut data)?;
+            read_value(&mut data)?;
+        }
+        Ok(StoredDocument {
+            field_values: doc_bytes,
+            num_field_values,
+        })
+    }
+
+    /// Returns the number of stored field values of the document.
+    pub fn len(&self) -> usize {
+        self.num_field_values
+    }
+
+    /// Returns true if the document has no stored field value.
+    pub fn is_empty(&self) -> bool {
+        self.num_field_values == 0
+    }
+
+    /// Iterates over the stored field values of the document, in the order they were added.
+    pub fn field_values(&self) -> impl Iterator<Item = (Field, StoredValue<'_>)> + '_ {
+        let mut data = self.field_values.as_slice();
+        (0..self.num_field_values).map(move |_| {
+            let field = Field::deserialize(&mut data).expect("the document was validated");
+            let value = read_value(&mut data).expect("the document was validated");
+            (field, value)
+        })
+    }
+
+    /// Returns all of the values of a given field.
+    pub fn get_all(&self, field: Field) -> impl Iterator<Item = StoredValue<'_>> + '_ {
+        self.field_values()
+            .filter(move |(value_field, _)| *value_field == field)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the first value of a given field, if any.
+    pub fn get_first(&self, field: Field) -> Option<StoredValue<'_>> {
+        self.get_all(field).next()
+    }
+}
+
+/// A value of a [`StoredDocument`], borrowing its text and bytes from the document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StoredValue<'a> {
+    /// A null value.
+    Null,
+    /// A text value.
+    Str(&'a str),
+    /// Unsigned 64-bits Integer `u64`
+    U64(u64),
+    /// Signed 64-bits Integer `i64`
+    I64(i64),
+    /// 64-bits Float `f64`
+    F64(f64),
+    /// Date/time with microseconds precision
+    Date(DateTime),
+    /// The encoded path of a facet, as returned by [`Facet::encoded_str()`].
+    Facet(&'a str),
+    /// Arbitrarily sized byte array
+    Bytes(&'a [u8]),
+    /// IpV6 Address
+    IpAddr(Ipv6Addr),
+    /// Bool value
+    Bool(bool),
+    /// An array of values.
+    Array(StoredArray<'a>),
+    /// An object of key-value pairs.
+    Object(StoredObject<'a>),
+}
+
+impl<'a> StoredValue<'a> {
+    /// Returns the text of a `Str` value.
+    pub fn as_str(&self) -> Option<&'a str> {
+        if let StoredValue::Str(text) = self {
+            Some(text)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the bytes of a `Bytes` value.
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        if let StoredValue::Bytes(bytes) = self {
+            Some(bytes)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of a `U64` value.
+    pub fn as_u64(&self) -> Option<u64> {
+        if let StoredValue::U64(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of an `I64` value.
+    pub fn as_i64(&self) -> Option<i64> {
+        if let StoredValue::I64(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of an `F64` value.
+    pub fn as_f64(&self) -> Option<f64> {
+        if let StoredValue::F64(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of a `Bool` value.
+    pub fn as_bool(&self) -> Option<bool> {
+        if let StoredValue::Bool(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
+    /// Copies the value into an [`OwnedValue`].
+    pub fn to_owned_value(&self) -> OwnedValue {
+        match *self {
+            StoredValue::Null => OwnedValue::Null,
+            StoredValue::Str(text) => OwnedValue::Str(text.to_string()),
+            StoredValue::U64(val) => OwnedValue::U64(val),
+            StoredValue::I64(val) => OwnedValue::I64(val),
+            StoredValue::F64(val) => OwnedValue::F64(val),
+            StoredValue::Date(val) => OwnedValue::Date(val),
+            StoredValue::Facet(encoded_str) => {
+                OwnedValue::Facet(Facet::from_encoded_string(encoded_str.to_string()))
+            }
+            StoredValue::Bytes(bytes) => OwnedValue::Bytes(bytes.to_vec()),
+            StoredValue::IpAddr(val) => OwnedValue::IpAddr(val),
+            StoredValue::Bool(val) => OwnedValue::Bool(val),
+            StoredValue::Array(array) => {
+                OwnedValue::Array(array.iter().map(|value| value.to_owned_value()).collect())
+            }
+            StoredValue::Object(object) => OwnedValue::Object(
+                object
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_owned_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// An array of values of a [`StoredDocument`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StoredArray<'a> {
+    len: usize,
+    data: &'a [u8],
+}
+
+impl<'a> StoredArray<'a> {
+    /// Returns the number of elements of the array.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the array has no element.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates over the elements of the array.
+    pub fn iter(&self) -> impl Iterator<Item = StoredValue<'a>> + 'a {
+        let mut data = self.data;
+        (0..self.len).map(move |_| read_value(&mut data).expect("the document was validated"))
+    }
+}
+
+/// An object of a [`StoredDocument`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StoredObject<'a> {
+    len: usize,
+    data: &'a [u8],
+}
+
+impl<'a> StoredObject<'a> {
+    /// Returns the number of key-value pairs of the object.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the object has no key-value pair.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates over the key-value pairs of the object.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, StoredValue<'a>)> + 'a {
+        let mut data = self.data;
+        (0..self.len).map(move |_| {
+            let key = read_value(&mut data).expect("the document was validated");
+            let value = read_value(&mut data).expect("the document was validated");
+            (key.as_str().expect("the document was validated"), value)
+        })
+    }
+
+    /// Returns the value associated with `key`, if any.
+    pub fn get(&self, key: &str) -> Option<StoredValue<'a>> {
+        self.iter()
+            .find(|(entry_key, _)| *entry_key == key)
+            .map(|(_, value)| value)
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_bytes<'a>(data: &mut &'a [u8]) -> io::Result<&'a [u8]> {
+    let len = VInt::deserialize_u64(data)? as usize;
+    if len > data.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the stored value is truncated",
+        ));
+    }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+fn read_str<'a>(data: &mut &'a [u8]) -> io::Result<&'a str> {
+    let bytes = read_bytes(data)?;
+    std::str::from_utf8(bytes).map_err(|err| invalid_data(err.to_string()))
+}
+
+/// Reads `len` values, and returns the bytes they span.
+fn read_values<'a>(data: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    let start = *data;
+    for _ in 0..len {
+        read_value(data)?;
+    }
+    Ok(&start[..start.len() - data.len()])
+}
+
+fn read_value<'a>(data: &mut &'a [u8]) -> io::Result<StoredValue<'a>> {
+    let type_code = u8::deserialize(data)?;
+    let value = match type_code {
+        type_codes::NULL_CODE => StoredValue::Null,
+        type_codes::TEXT_CODE => StoredValue::Str(read_str(data)?),
+        type_codes::U64_CODE => StoredValue::U64(u64::deserialize(data)?),
+        type_codes::I64_CODE => StoredValue::I64(i64::deserialize(data)?),
+        type_codes::F64_CODE => StoredValue::F64(u64_to_f64(u64::deserialize(data)?)),
+        type_codes::DATE_CODE => StoredValue::Date(DateTime::deserialize(data)?),
+        type_codes::HIERARCHICAL_FACET_CODE => StoredValue::Facet(read_str(data)?),
+        type_codes::BYTES_CODE => StoredValue::Bytes(read_bytes(data)?),
+        type_codes::IP_CODE => StoredValue::IpAddr(Ipv6Addr::from_u128(u128::deserialize(data)?)),
+        type_codes::BOOL_CODE => StoredValue::Bool(bool::deserialize(data)?),
+        type_codes::ARRAY_CODE => {
+            let len = VInt::deserialize_u64(data)? as usize;
+            StoredValue::Array(StoredArray {
+                len,
+                data: read_values(data, len)?,
+            })
+        }
+        type_codes::OBJECT_CODE => {
+            let len = VInt::deserialize_u64(data)? as usize;
+            if len % 2 != 0 {
+                return Err(invalid_data(format!(
+                    "An object cannot have an odd number of keys and values ({len})"
+                )));
+            }
+            let start = *data;
+            for _ in 0..len / 2 {
+                if !matches!(read_value(data)?, StoredValue::Str(_)) {
+                    return Err(invalid_data(
+                        "The keys of an object must be text".to_string(),
+                    ));
+                }
+                read_value(data)?;
+            }
+            StoredValue::Object(StoredObject {
+                len: len / 2,
+                data: &start[..start.len() - data.len()],
+            })
+        }
+        _ => {
+            // Pre-tokenized strings and legacy JSON objects are stored as JSON, and cannot be
+            // borrowed.
+            return Err(invalid_data(format!(
+                "The stored value with type code {type_code} cannot be read without copying it, \
+                 use `StoreReader::get()` instead"
+            )));
+        }
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+
+    use super::StoredValue;
+    use crate::schema::{
+        Facet, FacetOptions, OwnedValue, Schema, FAST, INDEXED, STORED, STRING, TEXT,
+    };
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument};
+
+    #[test]
+    fn test_stored_document() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let tag = schema_builder.add_text_field("tag", STRING | STORED);
+        let count = schema_builder.add_u64_field("count", INDEXED | STORED);
+        let score = schema_builder.add_f64_field("score", STORED);
+        let data = schema_builder.add_bytes_field("data", STORED);
+        let ip = schema_builder.add_ip_addr_field("ip", FAST | STORED);
+        let facet = schema_builder.add_facet_field("facet", FacetOptions::default().set_stored());
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let not_stored = schema_builder.add_text_field("not_stored", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let attributes_value: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(r#"{"color": "red", "sizes": [1, 2]}"#)?;
+        index_writer.add_document(doc!(
+            title => "The Old Man and the Sea",
+            tag => "novel",
+            tag => "classic",
+            count => 3u64,
+            score => 1.5f64,
+            data => b"abc".as_slice(),
+            ip => Ipv6Addr::LOCALHOST,
+            facet => Facet::from("/category/fiction"),
+            attributes => attributes_value,
+            not_stored => "hidden",
+        ))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let doc_address = DocAddress::new(0, 0);
+        let stored_doc = searcher.stored_doc(doc_address)?;
+
+        assert_eq!(stored_doc.len(), 9);
+        assert_eq!(
+            stored_doc.get_first(title).and_then(|value| value.as_str()),
+            Some("The Old Man and the Sea")
+        );
+        let tags: Vec<&str> = stored_doc
+            .get_all(tag)
+            .filter_map(|value| value.as_str())
+            .collect();
+        assert_eq!(tags, ["novel", "classic"]);
+        assert_eq!(stored_doc.get_first(count), Some(StoredValue::U64(3)));
+        assert_eq!(
+            stored_doc.get_first(score).and_then(|value| value.as_f64()),
+            Some(1.5)
+        );
+        assert_eq!(
+            stored_doc
+                .get_first(data)
+                .and_then(|value| value.as_bytes()),
+            Some(b"abc".as_slice())
+        );
+        assert_eq!(
+            stored_doc.get_first(ip),
+            Some(StoredValue::IpAddr(Ipv6Addr::LOCALHOST))
+        );
+        assert_eq!(
+            stored_doc
+                .get_first(facet)
+                .map(|value| value.to_owned_value()),
+            Some(OwnedValue::Facet(Facet::from("/category/fiction")))
+        );
+        let Some(StoredValue::Object(object)) = stored_doc.get_first(attributes) else {
+            panic!("attributes should be an object");
+        };
+        assert_eq!(object.len(), 2);
+        assert_eq!(object.get("color"), Some(StoredValue::Str("red")));
+        let Some(StoredValue::Array(sizes)) = object.get("sizes") else {
+            panic!("sizes should be an array");
+        };
+        assert_eq!(
+            sizes.iter().collect::<Vec<_>>(),
+            [StoredValue::I64(1), StoredValue::I64(2)]
+        );
+        assert_eq!(stored_doc.get_first(not_stored), None);
+
+        // The borrowed values match the ones of the copied document.
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        let owned_values: Vec<_> = stored_doc
+            .field_values()
+            .map(|(field, value)| (field, value.to_owned_value()))
+            .collect();
+        let expected_values: Vec<_> = doc
+            .field_values()
+            .iter()
+            .map(|field_value| (field_value.field(), field_value.value().clone()))
+            .collect();
+        assert_eq!(owned_values, expected_values);
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common::OwnedBytes;
+
+use crate::schema::document::{
+    BinaryDocumentDeserializer, BinaryDocumentSerializer, Document, DocumentDeserialize, Value,
+};
+use crate::schema::{Field, OwnedValue, Schema, TantivyDocument};
+
+/// A hook transforming the values of a stored field, set on the index with
+/// [`Index::set_stored_value_transform()`](crate::Index::set_stored_value_transform).
+///
+/// The values are transformed by the index writer before they are written in the doc store,
+/// and by the searchers after they are read from the doc store. This makes it possible to strip
+/// personal data from the stored documents, or to resolve references to external data when they
+/// are retrieved, no matter how the documents were added or are read.
+///
+/// Merges copy the documents of the doc store as they are, so the values are transformed once
+/// before they are stored. The documents read through a [`StoreReader`](crate::store::StoreReader)
+/// opened directly from a [`SegmentReader`](crate::SegmentReader), or through a
+/// [`StoredDocument`](crate::store::StoredDocument), are returned as they are stored.
+pub trait StoredValueTransform: Send + Sync + 'static {
+    /// Transforms a value before it is written in the doc store.
+    ///
+    /// Returning `None` drops the value from the stored document. It is still indexed.
+    fn before_store(&self, value: OwnedValue) -> Option<OwnedValue> {
+        Some(value)
+    }
+
+    /// Transforms a value read from the doc store, before it is returned.
+    fn after_retrieve(&self, value: OwnedValue) -> OwnedValue {
+        value
+    }
+}
+
+/// The stored value transforms of an index, by field.
+#[derive(Clone)]
+pub(crate) struct StoredValueTransforms {
+    schema: Schema,
+    transforms: HashMap<Field, Arc<dyn StoredValueTransform>>,
+}
+
+impl StoredValueTransforms {
+    pub fn new(schema: Schema) -> StoredValueTransforms {
+        StoredValueTransforms {
+            schema,
+            transforms: HashMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    pub fn set(&mut self, field: Field, transform: Arc<dyn StoredValueTransform>) {
+        self.transforms.insert(field, transform);
+    }
+
+    pub fn remove(&mut self, field: Field) {
+        self.transforms.remove(&field);
+    }
+
+    /// Returns the stored values of `doc`, transformed before they are written in the doc store.
+    pub fn before_store<D: Document>(&self, doc: &D) -> TantivyDocument {
+        let mut transformed_doc = TantivyDocument::new();
+        for (field, value) in doc.iter_fields_and_values() {
+            if !self.schema.get_field_entry(field).is_stored() {
+                continue;
+            }
+            let value = OwnedValue::from(value.as_value());
+            let transformed_value = match self.transforms.get(&field) {
+                Some(transform) => transform.before_store(value),
+                None => Some(value),
+            };
+            if let Some(transformed_value) = transformed_value {
+                transformed_doc.add_field_value(field, transformed_value);
+            }
+        }
+        transformed_doc
+    }
+
+    /// Deserializes a document read from the doc store, transforming its values.
+    pub fn deserialize<D: DocumentDeserialize>(
+        &self,
+        mut doc_bytes: OwnedBytes,
+    ) -> crate::Result<D> {
+        let deserializer = BinaryDocumentDeserializer::from_reader(&mut doc_bytes)?;
+        if self.is_empty() {
+            return Ok(D::deserialize(deserializer)?);
+        }
+        let doc = TantivyDocument::deserialize(deserializer)?;
+        let mut transformed_doc = TantivyDocument::new();
+        for field_value in doc {
+            let value = match self.transforms.get(&field_value.field) {
+                Some(transform) => transform.after_retrieve(field_value.value),
+                None => field_value.value,
+            };
+            transformed_doc.add_field_value(field_value.field, value);
+        }
+        // The transformed document is serialized again, to be deserialized as a `D`.
+        let mut transformed_doc_bytes = Vec::new();
+        BinaryDocumentSerializer::new(&mut transformed_doc_bytes, &self.schema)
+            .serialize_doc(&transformed_doc)?;
+        let mut transformed_doc_reader = transformed_doc_bytes.as_slice();
+        let deserializer = BinaryDocumentDeserializer::from_reader(&mut transformed_doc_reader)?;
+        Ok(D::deserialize(deserializer)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StoredValueTransform;
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::document::Value;
+    use crate::schema::{IndexRecordOption, OwnedValue, Schema, STORED, STRING, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument, Term};
+
+    struct StripValue;
+
+    impl StoredValueTransform for StripValue {
+        fn before_store(&self, _value: OwnedValue) -> Option<OwnedValue> {
+            None
+        }
+    }
+
+    struct Uppercase;
+
+    impl StoredValueTransform for Uppercase {
+        fn after_retrieve(&self, value: OwnedValue) -> OwnedValue {
+            match value {
+                OwnedValue::Str(text) => OwnedValue::Str(text.to_uppercase()),
+                value => value,
+            }
+        }
+    }
+
+    #[test]
+    fn test_stored_value_transforms() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let name = schema_builder.add_text_field("name", TEXT | STORED);
+        let email = schema_builder.add_text_field("email", STRING | STORED);
+        let indexed_only = schema_builder.add_text_field("indexed_only", TEXT);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        assert!(index
+            .set_stored_value_transform(indexed_only, StripValue)
+            .is_err());
+        index.set_stored_value_transform(email, StripValue)?;
+        index.set_stored_value_transform(name, Uppercase)?;
+
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(name => "alice", email => "alice@example.com"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(name => "bob", email => "bob@example.com"))?;
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        // The stripped values are still indexed.
+        let query = TermQuery::new(
+            Term::from_field_text(email, "bob@example.com"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        let docs: Vec<TantivyDocument> = searcher
+            .segment_reader(0)
+            .get_store_reader(1)?
+            .iter(None)
+            .collect::<crate::Result<_>>()?;
+        assert_eq!(docs.len(), 2);
+        assert!(docs.iter().all(|doc| doc.get_first(email).is_none()));
+
+        let mut names: Vec<String> = (0..2)
+            .map(|doc_id| {
+                let doc: TantivyDocument = searcher.doc(DocAddress::new(0, doc_id))?;
+                Ok(doc
+                    .get_first(name)
+                    .and_then(|value| value.as_str())
+                    .unwrap()
+                    .to_string())
+            })
+            .collect::<crate::Result<_>>()?;
+        names.sort();
+        assert_eq!(names, vec!["ALICE".to_string(), "BOB".to_string()]);
+
+        index.clear_stored_value_transform(name);
+        let searcher = index.reader()?.searcher();
+        let doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        let name_value = doc
+            .get_first(name)
+            .and_then(|value| value.as_str())
+            .unwrap();
+        assert_eq!(name_value, name_value.to_lowercase());
+        Ok(())
+    }
+}
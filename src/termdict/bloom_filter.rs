@@ -0,0 +1,148 @@
+//! Bloom filter over the terms of a field.
+//!
+//! Fields acting as primary keys (`STRING` fields, tokenized with the `raw` tokenizer)
+//! typically hold one distinct term per document, and are mostly queried for terms that are
+//! absent from most segments: deleting or updating a document by id needs to visit every
+//! segment. The bloom filter makes it possible to skip the term dictionary lookup for
+//! the segments that definitely do not contain the term.
+//!
+//! The filter is serialized right after the term dictionary of the field, as
+//! the sub-index [`BLOOM_FILTER_IDX`] of the `.term` composite file.
+//! Segments written before the introduction of the filter simply do not have it.
+//!
+//! Format:
+//! * *BloomFilter* := *NumHashes*: u8 *Words*: (u64 little endian)^*NumWords*
+use std::hash::Hasher;
+use std::io::{self, Write};
+
+use byteorder::{ByteOrder, LittleEndian};
+use common::BinarySerializable;
+use fnv::FnvHasher;
+
+use crate::directory::{FileSlice, OwnedBytes};
+
+/// Sub-index of a field in the terms composite file, used to store its bloom filter.
+pub(crate) const BLOOM_FILTER_IDX: usize = 1;
+
+/// Number of bits allocated per term. With `NUM_HASHES` hashes,
+/// this yields a false positive rate close to 1%.
+const NUM_BITS_PER_TERM: usize = 10;
+const NUM_HASHES: u8 = 7;
+
+/// Hashes a term into the 64-bit value from which the bit positions are derived.
+pub(crate) fn hash_term(term: &[u8]) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(term);
+    // FNV does not mix its high bits well. We apply the splitmix64 finalizer.
+    let mut hash = hasher.finish();
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+/// Iterates through the bit positions associated with a term hash, using the
+/// Kirsch-Mitzenmacher double hashing scheme.
+fn bit_positions(term_hash: u64, num_hashes: u8, num_bits: u64) -> impl Iterator<Item = u64> {
+    let h1 = term_hash & 0xFFFF_FFFF;
+    let h2 = (term_hash >> 32) | 1;
+    (0..u64::from(num_hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+}
+
+/// Serializes a bloom filter for the given term hashes, as computed by [`hash_term`].
+pub(crate) fn serialize_bloom_filter<W: Write + ?Sized>(
+    term_hashes: &[u64],
+    write: &mut W,
+) -> io::Result<()> {
+    let num_words = (term_hashes.len() * NUM_BITS_PER_TERM + 63) / 64;
+    let num_words = num_words.max(1);
+    let num_bits = num_words as u64 * 64;
+    let mut words = vec![0u64; num_words];
+    for &term_hash in term_hashes {
+        for bit in bit_positions(term_hash, NUM_HASHES, num_bits) {
+            words[(bit / 64) as usize] |= 1u64 << (bit % 64);
+        }
+    }
+    NUM_HASHES.serialize(write)?;
+    for word in words {
+        write.write_all(&word.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Read-only bloom filter over the terms of a field in a segment.
+///
+/// `may_contain` never returns false for a term of the dictionary.
+#[derive(Clone)]
+pub(crate) struct TermBloomFilter {
+    num_hashes: u8,
+    words: OwnedBytes,
+}
+
+impl TermBloomFilter {
+    /// Opens a bloom filter serialized with [`serialize_bloom_filter`].
+    pub fn open(file: FileSlice) -> io::Result<TermBloomFilter> {
+        let mut bytes = file.read_bytes()?;
+        let num_hashes = u8::deserialize(&mut bytes)?;
+        if bytes.is_empty() || bytes.len() % 8 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid bloom filter length: {}", bytes.len()),
+            ));
+        }
+        Ok(TermBloomFilter {
+            num_hashes,
+            words: bytes,
+        })
+    }
+
+    /// Returns false if the term is definitely absent from the term dictionary.
+    pub fn may_contain(&self, term: &[u8]) -> bool {
+        let num_bits = self.words.len() as u64 * 8;
+        let words = self.words.as_slice();
+        bit_positions(hash_term(term), self.num_hashes, num_bits).all(|bit| {
+            let word_offset = (bit / 64) as usize * 8;
+            let word = LittleEndian::read_u64(&words[word_offset..word_offset + 8]);
+            word & (1u64 << (bit % 64)) != 0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_term, serialize_bloom_filter, TermBloomFilter};
+    use crate::directory::FileSlice;
+
+    fn build(terms: &[String]) -> TermBloomFilter {
+        let term_hashes: Vec<u64> = terms
+            .iter()
+            .map(|term| hash_term(term.as_bytes()))
+            .collect();
+        let mut buffer = Vec::new();
+        serialize_bloom_filter(&term_hashes, &mut buffer).unwrap();
+        TermBloomFilter::open(FileSlice::from(buffer)).unwrap()
+    }
+
+    #[test]
+    fn test_bloom_filter_empty() {
+        let bloom_filter = build(&[]);
+        assert!(!bloom_filter.may_contain(b"hello"));
+    }
+
+    #[test]
+    fn test_bloom_filter_no_false_negative_and_few_false_positives() {
+        let terms: Vec<String> = (0..10_000).map(|i| format!("id{i}")).collect();
+        let bloom_filter = build(&terms);
+        for term in &terms {
+            assert!(bloom_filter.may_contain(term.as_bytes()));
+        }
+        let num_false_positives = (0..10_000)
+            .filter(|i| bloom_filter.may_contain(format!("other{i}").as_bytes()))
+            .count();
+        assert!(num_false_positives < 300, "{num_false_positives}");
+    }
+
+    #[test]
+    fn test_bloom_filter_invalid_length() {
+        assert!(TermBloomFilter::open(FileSlice::from(vec![7u8, 0u8, 0u8])).is_err());
+    }
+}
@@ -18,6 +18,7 @@
 //!
 //! A second datastructure makes it possible to access a [`TermInfo`].
 
+mod bloom_filter;
 #[cfg(not(feature = "quickwit"))]
 mod fst_termdict;
 #[cfg(not(feature = "quickwit"))]
@@ -40,6 +41,9 @@ use common::file_slice::FileSlice;
 use common::BinarySerializable;
 use tantivy_fst::Automaton;
 
+pub(crate) use self::bloom_filter::{
+    hash_term, serialize_bloom_filter, TermBloomFilter, BLOOM_FILTER_IDX,
+};
 use self::termdict::{
     TermDictionary as InnerTermDict, TermDictionaryBuilder as InnerTermDictBuilder,
     TermStreamerBuilder,
@@ -131,6 +135,18 @@ impl TermDictionary {
         self.0.range()
     }
 
+    /// Returns a range builder, to stream all of the terms starting with
+    /// the given prefix.
+    ///
+    /// The returned builder can be further restricted, e.g. with `.lt(..)`.
+    pub fn prefix_range<K: AsRef<[u8]>>(&self, prefix: K) -> TermStreamerBuilder<'_> {
+        let range = self.0.range().ge(prefix.as_ref());
+        match prefix_end(prefix.as_ref()) {
+            Some(end) => range.lt(end),
+            None => range,
+        }
+    }
+
     /// A stream of all the sorted terms.
     pub fn stream(&self) -> io::Result<TermStreamer<'_>> {
         self.0.stream()
@@ -167,6 +183,22 @@ impl TermDictionary {
     }
 }
 
+/// Returns the smallest key greater than all of the keys starting with `prefix_start`, or
+/// `None` if there is none, i.e. if `prefix_start` only contains `0xFF` bytes.
+pub(crate) fn prefix_end(prefix_start: &[u8]) -> Option<Vec<u8>> {
+    let mut res = prefix_start.to_owned();
+    while !res.is_empty() {
+        let end = res.len() - 1;
+        if res[end] == u8::MAX {
+            res.pop();
+        } else {
+            res[end] += 1;
+            return Some(res);
+        }
+    }
+    None
+}
+
 /// A TermDictionaryBuilder wrapping either an FST or a SSTable dictionary builder.
 pub struct TermDictionaryBuilder<W: io::Write>(InnerTermDictBuilder<W>);
 
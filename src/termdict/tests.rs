@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 use std::{io, str};
 
-use super::{TermDictionary, TermDictionaryBuilder, TermStreamer};
+use super::{prefix_end, TermDictionary, TermDictionaryBuilder, TermStreamer};
 use crate::directory::{Directory, FileSlice, RamDirectory, TerminatingWrite};
 use crate::postings::TermInfo;
 
@@ -16,6 +16,14 @@ fn make_term_info(term_ord: u64) -> TermInfo {
     }
 }
 
+#[test]
+fn test_prefix_end() {
+    assert_eq!(prefix_end(b"aaa"), Some(b"aab".to_vec()));
+    assert_eq!(prefix_end(b"aa\xff"), Some(b"ab".to_vec()));
+    assert_eq!(prefix_end(b"a\xff\xff"), Some(b"b".to_vec()));
+    assert_eq!(prefix_end(b"\xff\xff\xff"), None);
+}
+
 #[test]
 fn test_empty_term_dictionary() {
     let empty = TermDictionary::empty();
@@ -429,3 +437,31 @@ fn test_automaton_search() -> crate::Result<()> {
     assert!(!range.advance());
     Ok(())
 }
+
+#[test]
+fn test_prefix_range() -> crate::Result<()> {
+    let mut term_dictionary_builder = TermDictionaryBuilder::create(Vec::new())?;
+    let terms: [&[u8]; 6] = [b"ab", b"abc", b"abd", b"ac", b"b", b"b\xff"];
+    for (term_ord, term) in terms.iter().enumerate() {
+        term_dictionary_builder.insert(term, &make_term_info(term_ord as u64))?;
+    }
+    let buffer = term_dictionary_builder.finish()?;
+    let term_dict = TermDictionary::open(FileSlice::from(buffer))?;
+    let terms = |prefix: &[u8]| -> io::Result<Vec<Vec<u8>>> {
+        let mut stream = term_dict.prefix_range(prefix).into_stream()?;
+        let mut terms = Vec::new();
+        while let Some((term, _)) = stream.next() {
+            terms.push(term.to_vec());
+        }
+        Ok(terms)
+    };
+    assert_eq!(
+        terms(b"ab")?,
+        vec![b"ab".to_vec(), b"abc".to_vec(), b"abd".to_vec()]
+    );
+    assert_eq!(terms(b"abc")?, vec![b"abc".to_vec()]);
+    assert_eq!(terms(b"b")?, vec![b"b".to_vec(), b"b\xff".to_vec()]);
+    assert!(terms(b"c")?.is_empty());
+    assert_eq!(terms(b"")?.len(), 6);
+    Ok(())
+}
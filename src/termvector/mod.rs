@@ -0,0 +1,200 @@
+//! Tantivy can (if instructed to do so in the schema) store the term vectors of a text field.
+//!
+//! The term vector of a document field lists the terms of the field, together with the
+//! position and the byte offsets of each of their occurrences. It is computed with the
+//! tokenizer of the field when the document is indexed, so that it matches the inverted index
+//! even if the tokenizer or the stored value change later on, and is available for fields that
+//! are not stored.
+//!
+//! Term vectors are enabled with
+//! [`TextFieldIndexing::set_term_vectors`](crate::schema::TextFieldIndexing::set_term_vectors),
+//! and read with [`Searcher::term_vector`](crate::Searcher::term_vector).
+//!
+//! The [term vectors](crate::SegmentComponent::TermVectors) file contains the term vectors of
+//! each document, one after the other, followed by a table of their offsets.
+//!
+//! More formally:
+//! * *TermVectors* := *DocTermVectors*^(*D*) *DocOffset*^(*D* + 1) *NumDocOffsets*
+//! * *DocTermVectors* := (*FieldId* *NumBytes* *TermVector*)*, for the fields of the document
+//!   with at least one term, encoded as variable byte integers and bytes.
+//! * *TermVector* := *NumTerms* (*Term* *NumOccurrences* (*Position* *ValueOrd* *Offset*
+//!   *OffsetLen*)^*NumOccurrences*)^*NumTerms*, encoded as variable byte integers, with the terms
+//!   in lexicographical order.
+//! * *DocOffset*, *NumDocOffsets* := u64
+//!
+//! If no document has any term vector, the offsets are omitted altogether.
+mod reader;
+mod serializer;
+mod term_vector;
+mod writer;
+
+pub use self::reader::TermVectorsReader;
+pub use self::serializer::TermVectorsSerializer;
+pub use self::term_vector::{TermOccurrence, TermVector};
+pub(crate) use self::writer::{fields_with_term_vectors, TermVectorsWriter};
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::TopDocs;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::PhraseQuery;
+    use crate::schema::{
+        Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, FAST, INDEXED, STORED,
+        STRING, TEXT,
+    };
+    use crate::{
+        DocAddress, Index, IndexSettings, IndexSortByField, IndexWriter, Order, TantivyError, Term,
+    };
+
+    fn text_with_term_vectors() -> TextOptions {
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_term_vectors(true),
+        )
+    }
+
+    #[test]
+    fn test_term_vector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", text_with_term_vectors());
+        let other = schema_builder.add_text_field("other", text_with_term_vectors());
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            id => "1",
+            text => "Hello happy world",
+            other => "something else",
+            text => "hello tantivy",
+        ))?;
+        index_writer.add_document(doc!(id => "2"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let doc_address = DocAddress::new(0, 0);
+
+        let term_vector = searcher.term_vector(doc_address, text)?;
+        assert_eq!(term_vector.num_terms(), 4);
+        assert_eq!(term_vector.term_freq("hello"), 2);
+        assert_eq!(term_vector.term_freq("happy"), 1);
+        assert_eq!(term_vector.term_freq("missing"), 0);
+        let hello = term_vector.occurrences("hello");
+        assert_eq!(hello[0].position, 0);
+        assert_eq!(hello[0].value_ord, 0);
+        assert_eq!(hello[0].offset, 0..5);
+        assert_eq!(hello[1].value_ord, 1);
+        assert_eq!(hello[1].offset, 0..5);
+        let terms: Vec<&str> = term_vector.iter().map(|(term, _)| term).collect();
+        assert_eq!(terms, vec!["happy", "hello", "tantivy", "world"]);
+        assert_eq!(searcher.term_vector(doc_address, other)?.num_terms(), 2);
+        assert!(searcher
+            .term_vector(DocAddress::new(0, 1), text)?
+            .is_empty());
+
+        // Positions match the ones of the inverted index.
+        let world = term_vector.occurrences("world")[0].position;
+        let hello = term_vector.occurrences("hello")[1].position;
+        assert_eq!(hello, world + 2);
+        let phrase_query = PhraseQuery::new_with_offset_and_slop(
+            vec![
+                (0, Term::from_field_text(text, "world")),
+                (2, Term::from_field_text(text, "hello")),
+            ],
+            0,
+        );
+        let top_docs = searcher.search(&phrase_query, &TopDocs::with_limit(1))?;
+        assert_eq!(top_docs.len(), 1);
+
+        // `id` is not indexed with term vectors.
+        assert!(matches!(
+            searcher.term_vector(doc_address, id),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_term_vector_not_enabled() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(matches!(
+            searcher.term_vector(DocAddress::new(0, 0), text),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+
+    fn terms_per_segment(index: &Index, field: Field) -> crate::Result<Vec<Vec<Vec<String>>>> {
+        let searcher = index.reader()?.searcher();
+        let mut terms_per_segment = Vec::new();
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let mut segment_terms = Vec::new();
+            for doc in 0..segment_reader.max_doc() {
+                let term_vector =
+                    searcher.term_vector(DocAddress::new(segment_ord as u32, doc), field)?;
+                segment_terms.push(
+                    term_vector
+                        .iter()
+                        .map(|(term, _)| term.to_string())
+                        .collect(),
+                );
+            }
+            terms_per_segment.push(segment_terms);
+        }
+        Ok(terms_per_segment)
+    }
+
+    #[test]
+    fn test_term_vector_merge_and_sort() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", text_with_term_vectors());
+        let rank = schema_builder.add_u64_field("rank", INDEXED | FAST);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "rank".to_string(),
+                    order: Order::Desc,
+                }),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(rank => 1u64, text => "one"))?;
+        index_writer.add_document(doc!(rank => 3u64, text => "three"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(rank => 2u64))?;
+        index_writer.add_document(doc!(rank => 4u64, text => "four"))?;
+        index_writer.commit()?;
+
+        let mut segment_terms = terms_per_segment(&index, text)?;
+        segment_terms.sort();
+        assert_eq!(
+            segment_terms,
+            vec![
+                vec![vec!["four".to_string()], vec![]],
+                vec![vec!["three".to_string()], vec!["one".to_string()]],
+            ]
+        );
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.commit()?;
+        assert_eq!(
+            terms_per_segment(&index, text)?,
+            vec![vec![
+                vec!["four".to_string()],
+                vec!["three".to_string()],
+                vec![],
+                vec!["one".to_string()],
+            ]]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use std::io;
+
+use common::{BinarySerializable, ByteCount, FixedSize, VInt};
+
+use super::TermVector;
+use crate::directory::{FileSlice, OwnedBytes};
+use crate::schema::Field;
+use crate::DocId;
+
+/// Reads the term vectors of the documents of a segment.
+///
+/// See [`TermVectorsSerializer`](super::TermVectorsSerializer) for the layout of the file.
+#[derive(Clone)]
+pub struct TermVectorsReader {
+    data: OwnedBytes,
+    doc_offsets: OwnedBytes,
+}
+
+fn corrupted_term_vectors() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Corrupted term vectors file.")
+}
+
+impl TermVectorsReader {
+    /// Opens the term vectors file of a segment.
+    pub fn open(file: FileSlice) -> io::Result<TermVectorsReader> {
+        let bytes = file.read_bytes()?;
+        let (body, num_doc_offsets_bytes) = bytes
+            .len()
+            .checked_sub(u64::SIZE_IN_BYTES)
+            .map(|split| bytes.split(split))
+            .ok_or_else(corrupted_term_vectors)?;
+        let num_doc_offsets = u64::deserialize(&mut num_doc_offsets_bytes.as_slice())? as usize;
+        let (data, doc_offsets) = num_doc_offsets
+            .checked_mul(u64::SIZE_IN_BYTES)
+            .and_then(|doc_offsets_len| body.len().checked_sub(doc_offsets_len))
+            .map(|split| body.split(split))
+            .ok_or_else(corrupted_term_vectors)?;
+        Ok(TermVectorsReader { data, doc_offsets })
+    }
+
+    /// Returns a reader without any term vectors, for the segments written without a term
+    /// vectors file.
+    pub fn empty() -> TermVectorsReader {
+        TermVectorsReader {
+            data: OwnedBytes::empty(),
+            doc_offsets: OwnedBytes::empty(),
+        }
+    }
+
+    fn doc_offset(&self, doc: usize) -> u64 {
+        let start = doc * u64::SIZE_IN_BYTES;
+        u64::deserialize(&mut &self.doc_offsets.as_slice()[start..start + u64::SIZE_IN_BYTES])
+            .expect("Reading from a slice of the right size cannot fail.")
+    }
+
+    /// Returns the serialized term vectors of a document.
+    pub(crate) fn doc_bytes(&self, doc: DocId) -> &[u8] {
+        if self.doc_offsets.is_empty() {
+            return &[];
+        }
+        let start = self.doc_offset(doc as usize) as usize;
+        let end = self.doc_offset(doc as usize + 1) as usize;
+        &self.data.as_slice()[start..end]
+    }
+
+    /// Returns the term vector of `field` in the document `doc`.
+    ///
+    /// The term vector is empty if the document has no terms in the field, or if the field is
+    /// not indexed with term vectors.
+    pub fn term_vector(&self, doc: DocId, field: Field) -> io::Result<TermVector> {
+        let mut doc_bytes = self.doc_bytes(doc);
+        while !doc_bytes.is_empty() {
+            let field_id = VInt::deserialize_u64(&mut doc_bytes)? as u32;
+            let num_bytes = VInt::deserialize_u64(&mut doc_bytes)? as usize;
+            if num_bytes > doc_bytes.len() {
+                return Err(corrupted_term_vectors());
+            }
+            let (field_bytes, other_field_bytes) = doc_bytes.split_at(num_bytes);
+            if field_id == field.field_id() {
+                return TermVector::deserialize(&mut &field_bytes[..]);
+            }
+            doc_bytes = other_field_bytes;
+        }
+        Ok(TermVector::default())
+    }
+
+    /// Returns the number of bytes of the term vectors.
+    pub fn space_usage(&self) -> ByteCount {
+        ByteCount::from(self.data.len() + self.doc_offsets.len())
+    }
+}
@@ -0,0 +1,48 @@
+use std::io;
+use std::io::Write;
+
+use common::{BinarySerializable, CountingWriter, TerminatingWrite};
+
+use crate::directory::WritePtr;
+
+/// The term vectors serializer writes the term vectors of the documents of a segment.
+///
+/// The term vectors of the documents are written one after the other, in the order of the
+/// documents, followed by the offset of each of them in the file, the end offset of the
+/// last one, and the number of offsets. If none of the documents have term vectors, there
+/// are no offsets at all.
+pub struct TermVectorsSerializer {
+    write: CountingWriter<WritePtr>,
+    doc_offsets: Vec<u64>,
+}
+
+impl TermVectorsSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> TermVectorsSerializer {
+        TermVectorsSerializer {
+            write: CountingWriter::wrap(write),
+            doc_offsets: Vec::new(),
+        }
+    }
+
+    /// Appends the serialized term vectors of the next document.
+    pub fn add_document_bytes(&mut self, doc_bytes: &[u8]) -> io::Result<()> {
+        self.doc_offsets.push(self.write.written_bytes());
+        self.write.write_all(doc_bytes)
+    }
+
+    /// Clean up / flush / close
+    pub fn close(mut self) -> io::Result<()> {
+        let data_len = self.write.written_bytes();
+        if data_len == 0 {
+            self.doc_offsets.clear();
+        } else {
+            self.doc_offsets.push(data_len);
+        }
+        for doc_offset in &self.doc_offsets {
+            doc_offset.serialize(&mut self.write)?;
+        }
+        (self.doc_offsets.len() as u64).serialize(&mut self.write)?;
+        self.write.terminate()
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::ops::Range;
+
+use common::{BinarySerializable, VInt};
+
+use crate::schema::document::{Document, Value};
+use crate::schema::Field;
+use crate::tokenizer::{
+    BoxTokenStream, PreTokenizedStream, TextAnalyzer, Token, TokenStream, MAX_TOKEN_LEN,
+};
+
+/// A single occurrence of a term within the values of a document field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermOccurrence {
+    /// Position of the token, consistent with the positions recorded in the inverted index.
+    pub position: u32,
+    /// Ordinal of the field value containing the token, for multivalued fields.
+    pub value_ord: usize,
+    /// Byte range of the token within its field value.
+    pub offset: Range<usize>,
+}
+
+/// The terms of a text field of a document, together with their positions and offsets.
+///
+/// A `TermVector` is obtained via [`Searcher::term_vector`](crate::Searcher::term_vector),
+/// for the fields indexed with
+/// [`TextFieldIndexing::set_term_vectors`](crate::schema::TextFieldIndexing::set_term_vectors).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TermVector {
+    terms: BTreeMap<String, Vec<TermOccurrence>>,
+}
+
+impl TermVector {
+    pub(crate) fn for_doc<D: Document>(
+        doc: &D,
+        field: Field,
+        text_analyzer: &mut TextAnalyzer,
+        position_gap: u32,
+    ) -> TermVector {
+        let mut term_vector = TermVector::default();
+        let mut end_position = 0u32;
+        let field_values = doc
+            .iter_fields_and_values()
+            .filter(|(value_field, _)| *value_field == field)
+            .map(|(_, value)| value);
+        for (value_ord, value) in field_values.enumerate() {
+            let value = value as D::Value<'_>;
+            let mut token_stream = if let Some(text) = value.as_str() {
+                text_analyzer.token_stream(text)
+            } else if let Some(tok_str) = value.as_pre_tokenized_text() {
+                BoxTokenStream::new(PreTokenizedStream::from(tok_str.clone()))
+            } else {
+                continue;
+            };
+            end_position = term_vector.record_tokens(&mut *token_stream, value_ord, end_position)
+                + position_gap;
+        }
+        term_vector
+    }
+
+    /// Records the tokens of a single value, and returns the position following its
+    /// last token.
+    ///
+    /// This follows the logic of the postings writer, so that positions can be
+    /// compared with those of the inverted index.
+    fn record_tokens(
+        &mut self,
+        token_stream: &mut dyn TokenStream,
+        value_ord: usize,
+        start_position: u32,
+    ) -> u32 {
+        let mut end_position = start_position;
+        token_stream.process(&mut |token: &Token| {
+            if token.text.len() > MAX_TOKEN_LEN {
+                return;
+            }
+            let position = start_position + token.position as u32;
+            end_position = end_position.max(position + token.position_length as u32);
+            let occurrence = TermOccurrence {
+                position,
+                value_ord,
+                offset: token.offset_from..token.offset_to,
+            };
+            if let Some(occurrences) = self.terms.get_mut(&token.text) {
+                occurrences.push(occurrence);
+            } else {
+                self.terms.insert(token.text.clone(), vec![occurrence]);
+            }
+        });
+        end_position
+    }
+
+    /// Serializes the term vector, as its terms in lexicographical order with their
+    /// occurrences.
+    pub(crate) fn serialize(&self, output: &mut Vec<u8>) {
+        VInt(self.terms.len() as u64).serialize_into_vec(output);
+        for (term, occurrences) in &self.terms {
+            VInt(term.len() as u64).serialize_into_vec(output);
+            output.extend_from_slice(term.as_bytes());
+            VInt(occurrences.len() as u64).serialize_into_vec(output);
+            for occurrence in occurrences {
+                VInt(occurrence.position as u64).serialize_into_vec(output);
+                VInt(occurrence.value_ord as u64).serialize_into_vec(output);
+                VInt(occurrence.offset.start as u64).serialize_into_vec(output);
+                VInt(occurrence.offset.len() as u64).serialize_into_vec(output);
+            }
+        }
+    }
+
+    pub(crate) fn deserialize(bytes: &mut &[u8]) -> io::Result<TermVector> {
+        let mut term_vector = TermVector::default();
+        let num_terms = VInt::deserialize_u64(bytes)?;
+        for _ in 0..num_terms {
+            let term = String::deserialize(bytes)?;
+            let num_occurrences = VInt::deserialize_u64(bytes)?;
+            let mut occurrences = Vec::with_capacity(num_occurrences as usize);
+            for _ in 0..num_occurrences {
+                let position = VInt::deserialize_u64(bytes)? as u32;
+                let value_ord = VInt::deserialize_u64(bytes)? as usize;
+                let offset_from = VInt::deserialize_u64(bytes)? as usize;
+                let offset_len = VInt::deserialize_u64(bytes)? as usize;
+                occurrences.push(TermOccurrence {
+                    position,
+                    value_ord,
+                    offset: offset_from..offset_from + offset_len,
+                });
+            }
+            term_vector.terms.insert(term, occurrences);
+        }
+        Ok(term_vector)
+    }
+
+    /// Returns true if the field has no terms in the document.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Returns the number of distinct terms.
+    pub fn num_terms(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Returns the number of occurrences of the given term.
+    pub fn term_freq(&self, term: &str) -> u32 {
+        self.occurrences(term).len() as u32
+    }
+
+    /// Returns the occurrences of the given term, ordered by position.
+    pub fn occurrences(&self, term: &str) -> &[TermOccurrence] {
+        self.terms
+            .get(term)
+            .map(|occurrences| &occurrences[..])
+            .unwrap_or(&[])
+    }
+
+    /// Iterates over the terms, in lexicographical order, with their occurrences.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[TermOccurrence])> {
+        self.terms
+            .iter()
+            .map(|(term, occurrences)| (term.as_str(), &occurrences[..]))
+    }
+}
@@ -0,0 +1,114 @@
+use std::io;
+
+use common::VInt;
+
+use super::{TermVector, TermVectorsSerializer};
+use crate::indexer::doc_id_mapping::DocIdMapping;
+use crate::schema::document::Document;
+use crate::schema::{Field, FieldType, Schema};
+use crate::tokenizer::TextAnalyzer;
+
+/// The `TermVectorsWriter` is in charge of computing the term vectors of the documents
+/// of a segment, for the text fields indexed with term vectors.
+///
+/// The term vectors are kept serialized in memory until the segment is written.
+pub(crate) struct TermVectorsWriter {
+    // The fields with term vectors, with their position gap.
+    fields: Vec<(Field, u32)>,
+    data: Vec<u8>,
+    doc_offsets: Vec<usize>,
+    buffer: Vec<u8>,
+}
+
+/// Returns the text fields that are indexed with term vectors, with their position gap.
+pub(crate) fn fields_with_term_vectors(schema: &Schema) -> Vec<(Field, u32)> {
+    schema
+        .fields()
+        .filter(|(_, field_entry)| field_entry.is_indexed() && !field_entry.is_ignored())
+        .filter_map(|(field, field_entry)| match field_entry.field_type() {
+            FieldType::Str(text_options) => text_options
+                .get_indexing_options()
+                .filter(|text_indexing| text_indexing.term_vectors())
+                .map(|text_indexing| (field, text_indexing.position_gap())),
+            _ => None,
+        })
+        .collect()
+}
+
+impl TermVectorsWriter {
+    pub(crate) fn for_schema(schema: &Schema) -> TermVectorsWriter {
+        TermVectorsWriter {
+            fields: fields_with_term_vectors(schema),
+            data: Vec::new(),
+            doc_offsets: Vec::new(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// The memory used inclusive childs
+    pub(crate) fn mem_usage(&self) -> usize {
+        self.data.capacity()
+            + self.doc_offsets.capacity() * std::mem::size_of::<usize>()
+            + self.buffer.capacity()
+    }
+
+    /// Computes and records the term vectors of the next document.
+    ///
+    /// `per_field_text_analyzers` are the analyzers used to index the document, so that
+    /// the term vectors match the inverted index.
+    pub(crate) fn add_document<D: Document>(
+        &mut self,
+        doc: &D,
+        per_field_text_analyzers: &mut [Option<TextAnalyzer>],
+    ) {
+        if self.fields.is_empty() {
+            return;
+        }
+        self.doc_offsets.push(self.data.len());
+        for &(field, position_gap) in &self.fields {
+            let text_analyzer = per_field_text_analyzers[field.field_id() as usize]
+                .as_mut()
+                .expect("Indexed fields have a text analyzer.");
+            let term_vector = TermVector::for_doc(doc, field, text_analyzer, position_gap);
+            if term_vector.is_empty() {
+                continue;
+            }
+            self.buffer.clear();
+            term_vector.serialize(&mut self.buffer);
+            VInt(field.field_id() as u64).serialize_into_vec(&mut self.data);
+            VInt(self.buffer.len() as u64).serialize_into_vec(&mut self.data);
+            self.data.extend_from_slice(&self.buffer);
+        }
+    }
+
+    fn doc_bytes(&self, doc: usize) -> &[u8] {
+        let start = self.doc_offsets[doc];
+        let end = self
+            .doc_offsets
+            .get(doc + 1)
+            .copied()
+            .unwrap_or(self.data.len());
+        &self.data[start..end]
+    }
+
+    /// Serialize the term vectors, in the order of the new doc ids if `doc_id_map` is given.
+    pub(crate) fn serialize(
+        &self,
+        serializer: &mut TermVectorsSerializer,
+        doc_id_map: Option<&DocIdMapping>,
+    ) -> io::Result<()> {
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+        if let Some(doc_id_map) = doc_id_map {
+            for old_doc_id in doc_id_map.iter_old_doc_ids() {
+                serializer.add_document_bytes(self.doc_bytes(old_doc_id as usize))?;
+            }
+        } else {
+            for doc in 0..self.doc_offsets.len() {
+                serializer.add_document_bytes(self.doc_bytes(doc))?;
+            }
+        }
+        Ok(())
+    }
+}
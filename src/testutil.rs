@@ -0,0 +1,277 @@
+//! Generation of random documents and indexes, for property-based tests.
+//!
+//! A [`DocumentGenerator`] generates random documents that are valid for a given [`Schema`]:
+//! each field gets values of its type. The values are drawn from a limited set of distinct
+//! values, following a [`ValueDistribution`], so that the generated documents share terms the
+//! way real documents do.
+//!
+//! The generation is deterministic for a given seed, which makes the failures of a test
+//! reproducible.
+
+use std::net::Ipv6Addr;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::indexer::NoMergePolicy;
+use crate::schema::{Facet, FieldType, OwnedValue, Schema, TantivyDocument};
+use crate::{DateTime, Index, IndexWriter};
+
+/// How a [`DocumentGenerator`] picks values among the distinct values of a field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueDistribution {
+    /// All of the distinct values are equally likely.
+    Uniform,
+    /// The `k`-th distinct value has a probability proportional to `1 / k^exponent`, like the
+    /// words of a natural language.
+    Zipf {
+        /// The exponent of the distribution. The higher, the more skewed.
+        exponent: f64,
+    },
+}
+
+/// Generates random documents for a [`Schema`].
+pub struct DocumentGenerator {
+    schema: Schema,
+    rng: StdRng,
+    presence_probability: f64,
+    max_values_per_field: usize,
+    max_words: usize,
+    num_distinct_values: usize,
+    distribution: ValueDistribution,
+    // Cumulative weights of the distinct values, computed from the distribution.
+    cumulative_weights: Vec<f64>,
+}
+
+impl DocumentGenerator {
+    /// Creates a generator of documents for `schema`, seeded with `seed`.
+    pub fn new(schema: Schema, seed: u64) -> DocumentGenerator {
+        DocumentGenerator {
+            schema,
+            rng: StdRng::seed_from_u64(seed),
+            presence_probability: 0.8,
+            max_values_per_field: 2,
+            max_words: 10,
+            num_distinct_values: 1_000,
+            distribution: ValueDistribution::Uniform,
+            cumulative_weights: Vec::new(),
+        }
+    }
+
+    /// Sets the probability for a field to have values in a document. Defaults to 0.8.
+    #[must_use]
+    pub fn presence_probability(mut self, presence_probability: f64) -> Self {
+        self.presence_probability = presence_probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the maximum number of values of a field in a document. Defaults to 2.
+    #[must_use]
+    pub fn max_values_per_field(mut self, max_values_per_field: usize) -> Self {
+        self.max_values_per_field = max_values_per_field.max(1);
+        self
+    }
+
+    /// Sets the maximum number of words of the values of the tokenized text fields.
+    /// Defaults to 10.
+    #[must_use]
+    pub fn max_words(mut self, max_words: usize) -> Self {
+        self.max_words = max_words.max(1);
+        self
+    }
+
+    /// Sets the number of distinct values of each field, words for the tokenized text fields.
+    /// Defaults to 1000.
+    #[must_use]
+    pub fn num_distinct_values(mut self, num_distinct_values: usize) -> Self {
+        self.num_distinct_values = num_distinct_values.max(1);
+        self.cumulative_weights.clear();
+        self
+    }
+
+    /// Sets the distribution of the values. Defaults to [`ValueDistribution::Uniform`].
+    #[must_use]
+    pub fn distribution(mut self, distribution: ValueDistribution) -> Self {
+        self.distribution = distribution;
+        self.cumulative_weights.clear();
+        self
+    }
+
+    /// Returns the schema of the generated documents.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Generates a document.
+    pub fn generate(&mut self) -> TantivyDocument {
+        let mut doc = TantivyDocument::new();
+        let schema = self.schema.clone();
+        for (field, field_entry) in schema.fields() {
+            if !self.rng.gen_bool(self.presence_probability) {
+                continue;
+            }
+            let num_values = self.rng.gen_range(1..=self.max_values_per_field);
+            for _ in 0..num_values {
+                let value = self.generate_value(field_entry.field_type());
+                doc.add_field_value(field, value);
+            }
+        }
+        doc
+    }
+
+    /// Creates an index in RAM holding `num_docs` generated documents, split into `num_segments`
+    /// segments.
+    pub fn create_index(&mut self, num_docs: usize, num_segments: usize) -> crate::Result<Index> {
+        let index = Index::create_in_ram(self.schema.clone());
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 15_000_000)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let num_segments = num_segments.max(1);
+        for segment_ord in 0..num_segments {
+            let num_segment_docs =
+                num_docs / num_segments + usize::from(segment_ord < num_docs % num_segments);
+            for _ in 0..num_segment_docs {
+                index_writer.add_document(self.generate())?;
+            }
+            index_writer.commit()?;
+        }
+        index_writer.wait_merging_threads()?;
+        Ok(index)
+    }
+
+    /// Picks the ordinal of one of the distinct values, following the distribution.
+    fn value_ord(&mut self) -> u64 {
+        let exponent = match self.distribution {
+            ValueDistribution::Uniform => {
+                return self.rng.gen_range(0..self.num_distinct_values as u64);
+            }
+            ValueDistribution::Zipf { exponent } => exponent,
+        };
+        if self.cumulative_weights.is_empty() {
+            let mut total_weight = 0.0;
+            self.cumulative_weights = (1..=self.num_distinct_values)
+                .map(|rank| {
+                    total_weight += 1.0 / (rank as f64).powf(exponent);
+                    total_weight
+                })
+                .collect();
+        }
+        let total_weight = *self.cumulative_weights.last().unwrap();
+        let target = self.rng.gen::<f64>() * total_weight;
+        let ord = self
+            .cumulative_weights
+            .partition_point(|&cumulative_weight| cumulative_weight <= target);
+        ord.min(self.num_distinct_values - 1) as u64
+    }
+
+    /// Returns a word identifying the ordinal of a distinct value.
+    fn word(&mut self) -> String {
+        let mut ord = self.value_ord();
+        let mut word = String::new();
+        loop {
+            word.push((b'a' + (ord % 26) as u8) as char);
+            ord /= 26;
+            if ord == 0 {
+                return word;
+            }
+        }
+    }
+
+    fn generate_value(&mut self, field_type: &FieldType) -> OwnedValue {
+        match field_type {
+            FieldType::Str(text_options) => {
+                let is_tokenized = text_options
+                    .get_indexing_options()
+                    .map(|indexing_options| indexing_options.tokenizer() != "raw")
+                    .unwrap_or(true);
+                let num_words = if is_tokenized {
+                    self.rng.gen_range(1..=self.max_words)
+                } else {
+                    1
+                };
+                let words: Vec<String> = (0..num_words).map(|_| self.word()).collect();
+                OwnedValue::Str(words.join(" "))
+            }
+            FieldType::U64(_) => OwnedValue::U64(self.value_ord()),
+            FieldType::I64(_) => {
+                OwnedValue::I64(self.value_ord() as i64 - self.num_distinct_values as i64 / 2)
+            }
+            FieldType::F64(_) | FieldType::ScaledF64(_) => {
+                OwnedValue::F64(self.value_ord() as f64 / 4.0)
+            }
+            FieldType::Bool(_) => OwnedValue::Bool(self.value_ord() % 2 == 0),
+            FieldType::Date(_) => {
+                // One distinct value per day, from 2020-01-01.
+                let seconds = 1_577_836_800 + self.value_ord() as i64 * 86_400;
+                OwnedValue::Date(DateTime::from_timestamp_secs(seconds))
+            }
+            FieldType::Facet(_) => {
+                let depth = self.rng.gen_range(1..=3);
+                let steps: Vec<String> = (0..depth).map(|_| self.word()).collect();
+                OwnedValue::Facet(Facet::from_path(steps))
+            }
+            FieldType::Bytes(_) => OwnedValue::Bytes(self.value_ord().to_le_bytes().to_vec()),
+            FieldType::JsonObject(_) => {
+                let num_keys = self.rng.gen_range(1..=3);
+                let object = (0..num_keys)
+                    .map(|key_ord| {
+                        let value = if self.rng.gen_bool(0.5) {
+                            OwnedValue::Str(self.word())
+                        } else {
+                            OwnedValue::U64(self.value_ord())
+                        };
+                        (format!("key{key_ord}"), value)
+                    })
+                    .collect();
+                OwnedValue::Object(object)
+            }
+            FieldType::IpAddr(_) => {
+                let ip_addr = Ipv6Addr::from(0xffff_0a00_0000u128 + u128::from(self.value_ord()));
+                OwnedValue::IpAddr(ip_addr)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DocumentGenerator, ValueDistribution};
+    use crate::schema::document::Document;
+    use crate::schema::{Schema, FAST, INDEXED, STORED, STRING, TEXT};
+
+    #[test]
+    fn test_document_generator() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("id", STRING | STORED);
+        schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_u64_field("count", INDEXED | FAST);
+        schema_builder.add_i64_field("delta", FAST);
+        schema_builder.add_f64_field("ratio", FAST);
+        schema_builder.add_bool_field("flag", INDEXED);
+        schema_builder.add_date_field("date", INDEXED | FAST);
+        schema_builder.add_facet_field("category", INDEXED);
+        schema_builder.add_bytes_field("hash", FAST);
+        schema_builder.add_json_field("attributes", TEXT);
+        schema_builder.add_ip_addr_field("ip", FAST);
+        let schema = schema_builder.build();
+
+        let generate = |seed: u64| {
+            let mut generator = DocumentGenerator::new(schema.clone(), seed)
+                .distribution(ValueDistribution::Zipf { exponent: 1.0 })
+                .num_distinct_values(10);
+            (0..10)
+                .map(|_| generator.generate().to_json(&schema))
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(generate(1), generate(1));
+        assert_ne!(generate(1), generate(2));
+
+        let mut generator = DocumentGenerator::new(schema, 3).presence_probability(1.0);
+        let doc = generator.generate();
+        assert!(doc.field_values().len() >= 11);
+        let index = generator.create_index(100, 3)?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 100);
+        assert_eq!(searcher.segment_readers().len(), 3);
+        Ok(())
+    }
+}
@@ -52,6 +52,17 @@
 //! remove their inflection. This tokenizer is slower than the default one,
 //! but is recommended to improve recall.
 //!
+//! ## `unicode`
+//!
+//! Like `default`, but chops your text on the word boundaries defined by
+//! [Unicode Standard Annex #29](https://www.unicode.org/reports/tr29/#Word_Boundaries),
+//! see [`UnicodeWordTokenizer`]. It keeps words like `don't` together, and handles
+//! non-Latin scripts better.
+//!
+//! It is not the `default` tokenizer, because the tokenizer of a field cannot change once
+//! documents are indexed: the terms of the new documents and of the queries would no longer
+//! match those of the existing ones. Switching a field to `unicode` requires a reindex.
+//!
 //! # Custom tokenizer Library
 //! Avoid using tantivy as dependency and prefer `tantivy-tokenizer-api` instead.
 //!
@@ -137,6 +148,7 @@ mod stop_word_filter;
 mod tokenized_string;
 mod tokenizer;
 mod tokenizer_manager;
+mod unicode_word_tokenizer;
 mod whitespace_tokenizer;
 
 pub use tokenizer_api::{BoxTokenStream, Token, TokenFilter, TokenStream, Tokenizer};
@@ -156,6 +168,7 @@ pub use self::stop_word_filter::StopWordFilter;
 pub use self::tokenized_string::{PreTokenizedStream, PreTokenizedString};
 pub use self::tokenizer::{TextAnalyzer, TextAnalyzerBuilder};
 pub use self::tokenizer_manager::TokenizerManager;
+pub use self::unicode_word_tokenizer::{UnicodeWordTokenStream, UnicodeWordTokenizer};
 pub use self::whitespace_tokenizer::WhitespaceTokenizer;
 
 /// Maximum authorized len (in bytes) for a token.
@@ -280,6 +293,26 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_unicode_tokenizer() {
+        let tokenizer_manager = TokenizerManager::default();
+        let mut unicode_tokenizer = tokenizer_manager.get("unicode").unwrap();
+        let mut tokens: Vec<Token> = vec![];
+        {
+            let mut add_token = |token: &Token| {
+                tokens.push(token.clone());
+            };
+            unicode_tokenizer
+                .token_stream("Don't panic, Ünïcödé!")
+                .process(&mut add_token);
+        }
+
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "don't", 0, 5);
+        assert_token(&tokens[1], 1, "panic", 6, 11);
+        assert_token(&tokens[2], 2, "ünïcödé", 13, 24);
+    }
+
     #[test]
     fn test_whitespace_tokenizer() {
         let tokenizer_manager = TokenizerManager::default();
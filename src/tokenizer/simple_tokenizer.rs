@@ -3,6 +3,12 @@ use std::str::CharIndices;
 use super::{Token, TokenStream, Tokenizer};
 
 /// Tokenize the text by splitting on whitespaces and punctuation.
+///
+/// This is the tokenizer of the `default` and `en_stem` analyzers. It splits on every character
+/// that is not alphanumeric, so words like `don't` give two tokens. It is kept as is, rather than
+/// replaced by the [`UnicodeWordTokenizer`](super::UnicodeWordTokenizer), so that the existing
+/// indexes keep producing the same terms without a reindex. The `unicode` analyzer uses the
+/// latter.
 #[derive(Clone, Default)]
 pub struct SimpleTokenizer {
     token: Token,
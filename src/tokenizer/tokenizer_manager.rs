@@ -4,7 +4,8 @@ use std::sync::{Arc, RwLock};
 use crate::tokenizer::stemmer::Language;
 use crate::tokenizer::tokenizer::TextAnalyzer;
 use crate::tokenizer::{
-    LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer, WhitespaceTokenizer,
+    LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer, UnicodeWordTokenizer,
+    WhitespaceTokenizer,
 };
 
 /// The tokenizer manager serves as a store for
@@ -20,6 +21,8 @@ use crate::tokenizer::{
 ///  resulting tokens. Stemming can improve the recall of your
 ///  search engine.
 /// * `whitespace` : Splits the text on whitespaces.
+/// * `unicode` : Like `default`, but splits the text on the Unicode word boundaries, see
+///   [`UnicodeWordTokenizer`].
 #[derive(Clone)]
 pub struct TokenizerManager {
     tokenizers: Arc<RwLock<HashMap<String, TextAnalyzer>>>,
@@ -75,6 +78,13 @@ impl Default for TokenizerManager {
                 .build(),
         );
         manager.register("whitespace", WhitespaceTokenizer::default());
+        manager.register(
+            "unicode",
+            TextAnalyzer::builder(UnicodeWordTokenizer::default())
+                .filter(RemoveLongFilter::limit(40))
+                .filter(LowerCaser)
+                .build(),
+        );
         manager
     }
 }
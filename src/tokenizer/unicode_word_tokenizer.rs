@@ -0,0 +1,149 @@
+use unicode_segmentation::{UWordBoundIndices, UnicodeSegmentation};
+
+use super::{Token, TokenStream, Tokenizer};
+
+/// Tokenize the text into words, following the word boundaries of
+/// [Unicode Standard Annex #29](https://www.unicode.org/reports/tr29/#Word_Boundaries).
+///
+/// Unlike the [`SimpleTokenizer`](super::SimpleTokenizer), which splits on every character
+/// that is not alphanumeric, this keeps words like `don't`, `e.g` or `1,000.50` together,
+/// emits ideographs one character at a time, and keeps combining marks attached to their base
+/// character. The segments without letters or digits, like whitespaces and punctuation, are
+/// skipped.
+///
+/// The rules are tailored to break words at colons, which UAX #29 keeps between letters, so
+/// that `title:rust` gives the tokens `title` and `rust`.
+#[derive(Clone, Default)]
+pub struct UnicodeWordTokenizer {
+    token: Token,
+}
+
+/// TokenStream produced by the `UnicodeWordTokenizer`.
+pub struct UnicodeWordTokenStream<'a> {
+    words: UWordBoundIndices<'a>,
+    // What remains of the current word after a colon, with its offset.
+    remainder: Option<(usize, &'a str)>,
+    token: &'a mut Token,
+}
+
+impl Tokenizer for UnicodeWordTokenizer {
+    type TokenStream<'a> = UnicodeWordTokenStream<'a>;
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> UnicodeWordTokenStream<'a> {
+        self.token.reset();
+        UnicodeWordTokenStream {
+            words: text.split_word_bound_indices(),
+            remainder: None,
+            token: &mut self.token,
+        }
+    }
+}
+
+impl<'a> TokenStream for UnicodeWordTokenStream<'a> {
+    fn advance(&mut self) -> bool {
+        self.token.text.clear();
+        self.token.position = self.token.position.wrapping_add(1);
+        loop {
+            let Some((offset_from, word)) = self.remainder.take().or_else(|| self.words.next())
+            else {
+                return false;
+            };
+            let word = match word.find(':') {
+                Some(colon_pos) => {
+                    self.remainder = Some((offset_from + colon_pos + 1, &word[colon_pos + 1..]));
+                    &word[..colon_pos]
+                }
+                None => word,
+            };
+            if word.chars().any(char::is_alphanumeric) {
+                self.token.offset_from = offset_from;
+                self.token.offset_to = offset_from + word.len();
+                self.token.text.push_str(word);
+                return true;
+            }
+        }
+    }
+
+    fn token(&self) -> &Token {
+        self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{TextAnalyzer, Token, UnicodeWordTokenizer};
+
+    #[test]
+    fn test_unicode_word_tokenizer() {
+        let tokens = token_stream_helper("Hello, happy tax payer!");
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "Hello", 0, 5);
+        assert_token(&tokens[1], 1, "happy", 7, 12);
+        assert_token(&tokens[2], 2, "tax", 13, 16);
+        assert_token(&tokens[3], 3, "payer", 17, 22);
+    }
+
+    #[test]
+    fn test_unicode_word_tokenizer_word_boundaries() {
+        let token_texts = |text: &str| -> Vec<String> {
+            token_stream_helper(text)
+                .into_iter()
+                .map(|token| token.text)
+                .collect()
+        };
+        assert_eq!(
+            token_texts("Don't pay $1,000.50 for e.g. the snake_case... ok?"),
+            [
+                "Don't",
+                "pay",
+                "1,000.50",
+                "for",
+                "e.g",
+                "the",
+                "snake_case",
+                "ok"
+            ]
+        );
+        assert_eq!(
+            token_texts("l\u{2019}été, a.. b,c 3..4 __ _x"),
+            ["l\u{2019}été", "a", "b", "c", "3", "4", "_x"]
+        );
+        // Combining marks stay in their word.
+        assert_eq!(token_texts("cafe\u{301} ok"), ["cafe\u{301}", "ok"]);
+        assert_eq!(
+            token_texts("Привет, мир! Γειά σου"),
+            ["Привет", "мир", "Γειά", "σου"]
+        );
+        let tokens = token_stream_helper("東京タワーへ行く");
+        let token_texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(token_texts, ["東", "京", "タワー", "へ", "行", "く"]);
+        assert_token(&tokens[2], 2, "タワー", 6, 15);
+    }
+
+    #[test]
+    fn test_unicode_word_tokenizer_colons() {
+        let tokens = token_stream_helper("title:rust key::value: 12:30");
+        assert_eq!(tokens.len(), 6);
+        assert_token(&tokens[0], 0, "title", 0, 5);
+        assert_token(&tokens[1], 1, "rust", 6, 10);
+        assert_token(&tokens[2], 2, "key", 11, 14);
+        assert_token(&tokens[3], 3, "value", 16, 21);
+        assert_token(&tokens[4], 4, "12", 23, 25);
+        assert_token(&tokens[5], 5, "30", 26, 28);
+    }
+
+    fn token_stream_helper(text: &str) -> Vec<Token> {
+        let mut a = TextAnalyzer::from(UnicodeWordTokenizer::default());
+        let mut token_stream = a.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+}